@@ -8,8 +8,8 @@ use heck::AsSnakeCase;
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::{
-    self, parse_macro_input, spanned::Spanned, Attribute, DeriveInput, Ident, ItemStruct, LitStr,
-    Result,
+    self, parse_macro_input, punctuated::Punctuated, spanned::Spanned, Attribute, DeriveInput,
+    Ident, ItemStruct, LitStr, Result, Token,
 };
 
 // Thanks to Yand!rs from Rust Community Discord server for this macro
@@ -114,6 +114,11 @@ fn impl_prototype_macro(ast: &syn::DeriveInput) -> TokenStream {
             fn name(&self) -> &String { &self.name }
             fn prototype_type(&self) -> PrototypeType { PrototypeType::#prot_type }
         }
+
+        impl #name {
+            /// Type of every prototype of this struct, same as [Prototype::prototype_type]
+            pub const PROTOTYPE_TYPE: PrototypeType = PrototypeType::#prot_type;
+        }
     };
     gen.into()
 }
@@ -146,7 +151,7 @@ pub fn prot_from_lua_blanket(input: TokenStream) -> TokenStream {
     let target_type = parse_macro_input!(input as syn::Type);
     let gen = quote! {
         impl<'lua> PrototypeFromLua<'lua> for #target_type {
-            fn prototype_from_lua(value: mlua::Value<'lua>, lua: &'lua mlua::Lua, data_table: &mut crate::prototypes::DataTable) -> mlua::prelude::LuaResult<Self> {
+            fn prototype_from_lua(value: crate::prototypes::lua_compat::LuaValue<'lua>, lua: &'lua mlua::Lua, data_table: &mut crate::prototypes::DataTable) -> mlua::prelude::LuaResult<Self> {
                 lua.unpack(value)
             }
         }
@@ -159,7 +164,7 @@ pub fn prot_from_str(input: TokenStream) -> TokenStream {
     let target_type = parse_macro_input!(input as syn::Type);
     let gen = quote! {
         impl<'lua> PrototypeFromLua<'lua> for #target_type {
-            fn prototype_from_lua(v: mlua::Value<'lua>, l: &'lua mlua::Lua, dt: &mut crate::prototypes::DataTable) -> mlua::prelude::LuaResult<Self> {
+            fn prototype_from_lua(v: crate::prototypes::lua_compat::LuaValue<'lua>, l: &'lua mlua::Lua, dt: &mut crate::prototypes::DataTable) -> mlua::prelude::LuaResult<Self> {
                 let s: String = l.unpack(v)?;
                 s.parse().map_err(mlua::prelude::LuaError::external)
             }
@@ -182,11 +187,21 @@ fn impl_data_table_accessable_macro(ast: &syn::DeriveInput) -> TokenStream {
     let gen = quote! {
         impl crate::prototypes::DataTableAccessable for #name {
             fn find<'a>(data_table: &'a crate::prototypes::DataTable, name: &str) -> Result<&'a Self, crate::prototypes::PrototypesErr> {
-                data_table.#attr.get(name).ok_or_else(|| crate::prototypes::PrototypesErr::PrototypeNotFound(name.into()))
+                data_table.#attr.get(name).map(|prototype| &**prototype).ok_or_else(|| crate::prototypes::PrototypesErr::PrototypeNotFound(name.into()))
+            }
+
+            fn category(data_table: &crate::prototypes::DataTable) -> Result<&crate::prototypes::PrototypeCategory<Self>, crate::prototypes::PrototypesErr> {
+                Ok(&data_table.#attr)
             }
 
             fn extend(self, data_table: &mut crate::prototypes::DataTable) -> Result<(), crate::prototypes::PrototypesErr> {
-                data_table.#attr.insert(self.name.clone(), self);
+                data_table.check_namespace(&self)?;
+                if let Some(old) = data_table.#attr.insert(self.name.clone(), std::sync::Arc::new(self)) {
+                    data_table.diagnostics.push(crate::prototypes::Diagnostic::Overwritten {
+                        ty: crate::prototypes::Prototype::prototype_type(&*old),
+                        name: old.name.clone(),
+                    });
+                }
                 Ok(())
             }
         }
@@ -200,12 +215,178 @@ fn parse_data_table_attribute(attr: &Attribute) -> Result<Ident> {
     Ok(ident.clone())
 }
 
+/// Walks every field (of every variant for enums) whose type implements `ResourceWalk`, other
+/// fields are skipped. Leaf types that actually hold resources (sprites, animations, sounds)
+/// implement the trait by hand.
+#[proc_macro_derive(ResourceWalk)]
+pub fn resource_walk_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    impl_resource_walk_macro(&ast)
+}
+
+fn impl_resource_walk_macro(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let body = resource_walk_body(&ast.data);
+    let gen = quote! {
+        impl #impl_generics crate::prototypes::ResourceWalk for #name #ty_generics #where_clause {
+            #[allow(clippy::needless_borrow)]
+            fn walk_resources(&self, records: &mut Vec<crate::prototypes::ResourceRecord>) {
+                #[allow(unused_imports)]
+                use crate::prototypes::resource_walk::{Skip as _, Walk as _};
+                #body
+            }
+        }
+    };
+    gen.into()
+}
+
+// Which of `Walk` and `Skip` is used is decided by autoref method resolution, `Walk` is
+// implemented only for fields that implement `ResourceWalk`
+fn resource_walk_field(binding: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        (&crate::prototypes::resource_walk::Field(#binding)).walk_field(records);
+    }
+}
+
+fn resource_walk_body(data: &syn::Data) -> proc_macro2::TokenStream {
+    fields_body(data, resource_walk_field)
+}
+
+/// Sums heap sizes of every field (of every variant for enums) whose type implements `HeapSize`,
+/// other fields count as 0. Meant for coarse estimates, see `MemoryReport`.
+#[proc_macro_derive(HeapSize)]
+pub fn heap_size_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    impl_heap_size_macro(&ast)
+}
+
+fn impl_heap_size_macro(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let body = fields_body(&ast.data, heap_size_field);
+    let gen = quote! {
+        impl #impl_generics crate::prototypes::HeapSize for #name #ty_generics #where_clause {
+            #[allow(clippy::needless_borrow)]
+            fn heap_size(&self) -> usize {
+                #[allow(unused_imports)]
+                use crate::prototypes::memory_report::heap_size::{Measure as _, Skip as _};
+                #[allow(unused_mut)]
+                let mut size = 0;
+                #body
+                size
+            }
+        }
+    };
+    gen.into()
+}
+
+// Same autoref specialization as `resource_walk_field`, `Measure` is implemented only for fields
+// that implement `HeapSize`
+fn heap_size_field(binding: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        size += (&crate::prototypes::memory_report::heap_size::Field(#binding)).field_heap_size();
+    }
+}
+
+// Destructures `self` and runs `field` statements for each field, of each variant for enums
+fn fields_body(
+    data: &syn::Data,
+    field: fn(proc_macro2::TokenStream) -> proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    // Bindings for destructuring fields and statements for each of them
+    let destructure =
+        |fields: &syn::Fields| -> (proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>) {
+            match fields {
+                syn::Fields::Named(f) => {
+                    let idents = f.named.iter().map(|f| &f.ident).collect::<Vec<_>>();
+                    (
+                        quote! { { #(#idents),* } },
+                        idents.iter().map(|i| field(quote! { #i })).collect(),
+                    )
+                }
+                syn::Fields::Unnamed(f) => {
+                    let idents = (0..f.unnamed.len())
+                        .map(|i| format_ident!("field_{}", i))
+                        .collect::<Vec<_>>();
+                    (
+                        quote! { ( #(#idents),* ) },
+                        idents.iter().map(|i| field(quote! { #i })).collect(),
+                    )
+                }
+                syn::Fields::Unit => (quote! {}, vec![]),
+            }
+        };
+    match data {
+        syn::Data::Struct(d) => {
+            let (pattern, walks) = destructure(&d.fields);
+            quote! {
+                let Self #pattern = self;
+                #(#walks)*
+            }
+        }
+        syn::Data::Enum(d) => {
+            let arms = d.variants.iter().map(|v| {
+                let variant = &v.ident;
+                let (pattern, walks) = destructure(&v.fields);
+                quote! { Self::#variant #pattern => { #(#walks)* } }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        syn::Data::Union(_) => panic!("expected struct or enum"),
+    }
+}
+
+#[test]
+fn resource_walk_fields() {
+    let count = |ast: DeriveInput| {
+        resource_walk_body(&ast.data)
+            .to_string()
+            .matches("walk_field")
+            .count()
+    };
+    let named: DeriveInput = syn::parse_quote! {
+        struct S { a: Sprite, b: Option<u32> }
+    };
+    let tuple: DeriveInput = syn::parse_quote! {
+        struct S(Sprite);
+    };
+    let unit: DeriveInput = syn::parse_quote! {
+        struct S;
+    };
+    let variants: DeriveInput = syn::parse_quote! {
+        enum E { A(Animation, Sprite), B { sound: Sound }, C }
+    };
+    assert_eq!(count(named), 2);
+    assert_eq!(count(tuple), 1);
+    assert_eq!(count(unit), 0);
+    assert_eq!(count(variants), 3);
+}
+
+#[test]
+fn heap_size_fields() {
+    let ast: DeriveInput = syn::parse_quote! {
+        enum E { A(String, u32), B { name: String }, C }
+    };
+    let body = fields_body(&ast.data, heap_size_field).to_string();
+    assert_eq!(body.matches("field_heap_size").count(), 3);
+}
+
 /// Attributes on fields
 ///
 /// `#[default(expr)]` - `expr` is default value, which is used in case value of thsi field is None
 /// after extraction
 /// Incompatible with: `use_self`, `use_self_vec`, `use_self_forced`
 ///
+/// Fields are extracted in declaration order, each into a local variable with the name of the
+/// field, so `expr` of `default` and `fallback` can use fields declared above it (for example,
+/// `#[default(collision_box)]`) and `prot_table`. Using a field declared below or the field itself
+/// is a compile error.
+///
 /// `#[use_self]` - use the table which is used for constructing current prototype for property if
 /// corresponding field does not exist in the table
 /// Incompatible with: `default`, `from_str`, `use_self_vec`, `use_self_forced`, `resource`, `mandatory_if`
@@ -214,6 +395,8 @@ fn parse_data_table_attribute(attr: &Attribute) -> Result<Ident> {
 /// Incompatible with: `default`, `from_str`, `use_self`, `use_self_forced`, `resource`, `mandatory_if`
 ///
 /// `#[use_self_forced]` - same as `use_self`, but forced instead of defaulting in case of failure.
+/// Can be combined with `loaded_if` for optional properties that are the table itself, such as
+/// icons.
 /// Incompatible with: `default`, `from_str`, `use_self`, `use_self_vec`, `resource`, `mandatory_if`
 ///
 /// `#[resource]` - this field is a resource record (sound only, textures should be done in post-extraction)
@@ -228,11 +411,23 @@ fn parse_data_table_attribute(attr: &Attribute) -> Result<Ident> {
 /// Use only on Option<>
 /// Incompatible with: `use_self`, `use_self_vec`, `use_self_forced`
 ///
+/// `#[loaded_if(expr)]` - expr is a condition, the field is only extracted if it results in
+/// `true`, otherwise it is `None` and the key is left unread, without being reported as unknown
+/// (see `DataTable::report_unknown_keys`). Like `default`, `expr` can use fields
+/// declared above it. Use only on Option<>
+/// Incompatible with: `default`, `use_self`, `use_self_vec`
+///
 /// `#[required]` - use only with fallback. Yes, this is still a hack but a better one.
 ///
 /// `#[rename(str)]` - str is a string supposed to be used for extracting field from table in case
 /// name in table differs from name of this struct field
 ///
+/// `#[rename(str, str, ...)]` - multiple candidate names, the first one that is present
+/// (not `nil`) in the table is used. If none are present, the first name is used, so the error
+/// for a missing mandatory field names it. With `use_self` or `use_self_vec` all names are
+/// tried before falling back to the table itself.
+/// Incompatible with: `use_self_forced`
+///
 /// Attributes on container
 ///
 /// `#[post_extr_fn(path)]` - path is a path to a function that needs to be executed after
@@ -246,6 +441,7 @@ fn parse_data_table_attribute(attr: &Attribute) -> Result<Ident> {
         use_self_forced,
         resource,
         mandatory_if,
+        loaded_if,
         post_extr_fn,
         fallback,
         rename,
@@ -272,6 +468,9 @@ fn impl_prototype_from_lua_macro(ast: &syn::DeriveInput) -> TokenStream {
             _ => panic!("expected named fields"),
         }
     };
+    if let Err(e) = check_field_references(&fields.clone().collect::<Vec<_>>()) {
+        return e.to_compile_error().into();
+    }
     let (parsed_fields, mut mandatory_exprs): (
         Vec<proc_macro2::TokenStream>,
         Vec<Option<proc_macro2::TokenStream>>,
@@ -303,18 +502,24 @@ fn impl_prototype_from_lua_macro(ast: &syn::DeriveInput) -> TokenStream {
     };
     let gen = quote! {
         impl<'lua> crate::prototypes::PrototypeFromLua<'lua> for #name {
-            fn prototype_from_lua(value: mlua::Value<'lua>, lua: &'lua mlua::Lua, data_table: &mut crate::prototypes::DataTable) -> mlua::prelude::LuaResult<Self> {
+            fn prototype_from_lua(value: crate::prototypes::lua_compat::LuaValue<'lua>, lua: &'lua mlua::Lua, data_table: &mut crate::prototypes::DataTable) -> mlua::prelude::LuaResult<Self> {
                 let str_name = #str_name;
                 if let mlua::Value::Table(ref prot_table) = value {
-                    #(#parsed_fields)*
-                    #(#mandatory_exprs)*
-                    let mut result = Self{#(#field_names),*};
-                    #[allow(unnecessary_mut_passed)]
-                    #post_extr
-                    Ok(result)
+                    data_table.enter_table(prot_table);
+                    // Closure, so that the table is exited on errors too
+                    let extracted = (|| -> mlua::prelude::LuaResult<Self> {
+                        #(#parsed_fields)*
+                        #(#mandatory_exprs)*
+                        let mut result = Self{#(#field_names),*};
+                        #[allow(unnecessary_mut_passed)]
+                        #post_extr
+                        Ok(result)
+                    })();
+                    data_table.exit_table(prot_table, str_name);
+                    extracted
                 } else {
-                    Err(mlua::Error::FromLuaConversionError{from: value.type_name(), to: str_name,
-                    message: Some("Expected Table".into())})
+                    Err(crate::prototypes::lua_compat::conversion_error(value.type_name(), str_name,
+                    Some("Expected Table".into())))
                 }
             }
         }
@@ -326,8 +531,9 @@ fn impl_prototype_from_lua_macro(ast: &syn::DeriveInput) -> TokenStream {
 struct PrototypeFromLuaFieldAttrArgs {
     default_value: Option<proc_macro2::TokenStream>, // Incompatible with: use_self, use_self_vec
     mandatory_if: Option<proc_macro2::TokenStream>, // Incompatible with: default, use_self, use_self_vec
+    loaded_if: Option<proc_macro2::TokenStream>, // Incompatible with: default, use_self, use_self_vec
     fallbacks: Vec<proc_macro2::TokenStream>,
-    rename: Option<String>,
+    rename: Vec<String>,
     required: bool,
     // use_self* is incompatible with default and mandatory_if
     // Only 1 can be used:
@@ -374,6 +580,8 @@ impl PrototypeFromLuaFieldAttrArgs {
             ("mandatory_if", self.mandatory_if.is_some()),
             ("resource", self.is_resource),
             ("fallback", !self.fallbacks.is_empty()),
+            ("rename", !self.rename.is_empty()),
+            ("loaded_if", self.loaded_if.is_some()),
         );
         vec![
             (
@@ -384,6 +592,7 @@ impl PrototypeFromLuaFieldAttrArgs {
                 },
                 vec![
                     ("mandatory_if", self.mandatory_if.is_some()),
+                    oth.5,
                     sel.0,
                     sel.1,
                     sel.2,
@@ -402,6 +611,14 @@ impl PrototypeFromLuaFieldAttrArgs {
                     sel.2,
                 ],
             ),
+            (
+                "loaded_if",
+                |s, a| {
+                    s.loaded_if = Some(a.tokens.clone());
+                    Ok(())
+                },
+                vec![oth.0, sel.0, sel.1],
+            ),
             (
                 "resource",
                 |s, _| {
@@ -416,7 +633,7 @@ impl PrototypeFromLuaFieldAttrArgs {
                     s.use_self = true;
                     Ok(())
                 },
-                vec![oth.0, oth.1, oth.2, oth.3, sel.1, sel.2],
+                vec![oth.0, oth.1, oth.2, oth.3, oth.5, sel.1, sel.2],
             ),
             (
                 "use_self_vec",
@@ -424,7 +641,7 @@ impl PrototypeFromLuaFieldAttrArgs {
                     s.use_self_vec = true;
                     Ok(())
                 },
-                vec![oth.0, oth.1, oth.2, oth.3, sel.0, sel.2],
+                vec![oth.0, oth.1, oth.2, oth.3, oth.5, sel.0, sel.2],
             ),
             (
                 "use_self_forced",
@@ -432,7 +649,7 @@ impl PrototypeFromLuaFieldAttrArgs {
                     s.use_self_forced = true;
                     Ok(())
                 },
-                vec![oth.0, oth.1, oth.2, oth.3, sel.0, sel.1],
+                vec![oth.0, oth.1, oth.2, oth.3, oth.4, sel.0, sel.1],
             ),
             (
                 "fallback",
@@ -445,10 +662,15 @@ impl PrototypeFromLuaFieldAttrArgs {
             (
                 "rename",
                 |s, a| {
-                    s.rename = Some(a.parse_args::<LitStr>()?.value());
+                    let names =
+                        a.parse_args_with(Punctuated::<LitStr, Token![,]>::parse_terminated)?;
+                    if names.is_empty() {
+                        return Self::attr_error(a, "`rename` requires at least one name");
+                    }
+                    s.rename = names.iter().map(LitStr::value).collect();
                     Ok(())
                 },
-                vec![],
+                vec![sel.2],
             ),
             (
                 "required",
@@ -479,6 +701,126 @@ impl PrototypeFromLuaFieldAttrArgs {
     }
 }
 
+// Fields are extracted in declaration order, so `default`, `fallback` and `loaded_if` expressions
+// can only use fields that are declared before the field they are on
+fn check_field_references(fields: &[&syn::Field]) -> Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        let field_attrs = PrototypeFromLuaFieldAttrArgs::from_attrs(&field.attrs)?;
+        for expr in field_attrs
+            .default_value
+            .iter()
+            .chain(field_attrs.fallbacks.iter())
+            .chain(field_attrs.loaded_if.iter())
+        {
+            for ident in expr_idents(expr.clone()) {
+                if fields[i..].iter().any(|f| f.ident.as_ref() == Some(&ident)) {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!(
+                            "`{}` is not extracted yet when extracting `{}`, fields are extracted in declaration order",
+                            ident,
+                            field.ident.as_ref().unwrap()
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Identifiers that can refer to local variables, so not method names, field accesses or path
+// segments after `::`
+fn expr_idents(tokens: proc_macro2::TokenStream) -> Vec<Ident> {
+    let mut result = vec![];
+    let mut prev_punct = None;
+    for tt in tokens {
+        match tt {
+            proc_macro2::TokenTree::Ident(ident) => {
+                if !matches!(prev_punct, Some('.') | Some(':')) {
+                    result.push(ident)
+                }
+                prev_punct = None
+            }
+            proc_macro2::TokenTree::Group(group) => {
+                result.extend(expr_idents(group.stream()));
+                prev_punct = None
+            }
+            proc_macro2::TokenTree::Punct(punct) => prev_punct = Some(punct.as_char()),
+            proc_macro2::TokenTree::Literal(_) => prev_punct = None,
+        }
+    }
+    result
+}
+
+#[test]
+fn default_field_references() {
+    let backward: ItemStruct = syn::parse_quote! {
+        struct S { a: u32, #[default(a.max(1))] b: u32 }
+    };
+    let forward: ItemStruct = syn::parse_quote! {
+        struct S { #[default(b)] a: u32, b: u32 }
+    };
+    let own: ItemStruct = syn::parse_quote! {
+        struct S { #[fallback(Some(a))] a: Option<u32> }
+    };
+    assert!(check_field_references(&backward.fields.iter().collect::<Vec<_>>()).is_ok());
+    assert!(check_field_references(&forward.fields.iter().collect::<Vec<_>>()).is_err());
+    assert!(check_field_references(&own.fields.iter().collect::<Vec<_>>()).is_err());
+}
+
+#[test]
+fn loaded_if_field() {
+    let field = |s: ItemStruct| {
+        let fields = s.fields.iter().collect::<Vec<_>>();
+        check_field_references(&fields)?;
+        PrototypeFromLuaFieldAttrArgs::from_attrs(&fields[fields.len() - 1].attrs)
+    };
+    let backward: ItemStruct = syn::parse_quote! {
+        struct S { a: Option<u32>, #[loaded_if(a.is_none())] b: Option<u32> }
+    };
+    let forward: ItemStruct = syn::parse_quote! {
+        struct S { #[loaded_if(b.is_none())] a: Option<u32>, b: Option<u32> }
+    };
+    let with_default: ItemStruct = syn::parse_quote! {
+        struct S { #[loaded_if(true)] #[default(0)] a: Option<u32> }
+    };
+    let with_use_self: ItemStruct = syn::parse_quote! {
+        struct S { #[use_self] #[loaded_if(true)] a: Option<u32> }
+    };
+    let with_use_self_forced: ItemStruct = syn::parse_quote! {
+        struct S { #[use_self_forced] #[loaded_if(true)] a: Option<u32> }
+    };
+    assert!(field(backward).unwrap().loaded_if.is_some());
+    assert!(field(forward).is_err());
+    assert!(field(with_default).is_err());
+    assert!(field(with_use_self).is_err());
+    assert!(field(with_use_self_forced).unwrap().use_self_forced);
+}
+
+#[test]
+fn rename_names() {
+    let field = |s: ItemStruct| {
+        PrototypeFromLuaFieldAttrArgs::from_attrs(&s.fields.iter().next().unwrap().attrs)
+    };
+    let single: ItemStruct = syn::parse_quote! {
+        struct S { #[rename("type")] a: u32 }
+    };
+    let multiple: ItemStruct = syn::parse_quote! {
+        struct S { #[use_self_vec] #[rename("layers", "frames")] a: Vec<u32> }
+    };
+    let empty: ItemStruct = syn::parse_quote! {
+        struct S { #[rename()] a: u32 }
+    };
+    let forced: ItemStruct = syn::parse_quote! {
+        struct S { #[rename("a", "b")] #[use_self_forced] a: u32 }
+    };
+    assert_eq!(field(single).unwrap().rename, ["type"]);
+    assert_eq!(field(multiple).unwrap().rename, ["layers", "frames"]);
+    assert!(field(empty).is_err());
+    assert!(field(forced).is_err());
+}
+
 fn prot_from_lua_field(
     field: &syn::Field,
 ) -> Result<(proc_macro2::TokenStream, Option<proc_macro2::TokenStream>)> {
@@ -486,9 +828,23 @@ fn prot_from_lua_field(
     let ident = &field.ident;
     let field_type = &field.ty;
     let prototype_field_attrs = PrototypeFromLuaFieldAttrArgs::from_attrs(&field.attrs)?;
-    let str_field = prototype_field_attrs
-        .rename
-        .unwrap_or_else(|| ident.as_ref().unwrap().to_string());
+    let str_field = match prototype_field_attrs.rename.as_slice() {
+        [] => {
+            let name = ident.as_ref().unwrap().to_string();
+            quote! { #name }
+        }
+        [name] => quote! { #name },
+        names => quote! {
+            {
+                let names: &[&'static str] = &[#(#names),*];
+                names
+                    .iter()
+                    .copied()
+                    .find(|n| matches!(crate::prototypes::lua_compat::TableGetCompat::get_as::<Option<mlua::Value>>(prot_table, *n), Ok(Some(_))))
+                    .unwrap_or(names[0])
+            }
+        },
+    };
     let mut field_extr_type = if prototype_field_attrs.is_resource {
         quote! { String }
     } else {
@@ -538,14 +894,27 @@ fn prot_from_lua_field(
         let err_str = format!("{} is required", ident.clone().unwrap());
         Some(quote! {
             if #mandatory_if && #ident.is_none() {
-                return Err(mlua::Error::FromLuaConversionError{from: value.type_name(), to: str_name, message: Some(#err_str.into())})
+                return Err(crate::prototypes::lua_compat::conversion_error(value.type_name(), str_name, Some(#err_str.into())))
             };
         })
     } else {
         None
     };
-    let gen = quote! {
-        let #ident: #field_type = #get_expr
+    let gen = if let Some(loaded_if) = prototype_field_attrs.loaded_if {
+        quote! {
+            let #ident: #field_type = if #loaded_if {
+                let #ident: #field_type = #get_expr
+                #ident
+            } else {
+                // Skipped on purpose, so not reported as unknown
+                data_table.use_key(prot_table, #str_field);
+                None
+            };
+        }
+    } else {
+        quote! {
+            let #ident: #field_type = #get_expr
+        }
     };
     Ok((gen, mand_expr))
 }
@@ -0,0 +1,10 @@
+use factorio_prototypes_macros::PrototypeFromLua;
+
+#[derive(PrototypeFromLua)]
+struct Sprite {
+    #[default(height)]
+    width: u32,
+    height: u32,
+}
+
+fn main() {}
@@ -0,0 +1,56 @@
+//! Times [DataTable::validate_all] on a synthetic table of stub prototypes, some of which have
+//! dangling references so that there are errors to collect and sort.
+//!
+//! Run with `cargo bench --bench validate_all`, add `--features rayon` to run the passes in
+//! parallel.
+
+use factorio_prototypes::DataTable;
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::Instant;
+
+/// Number of prototypes of each stubbed type
+const PROTOTYPES: usize = 10_000;
+const ITERATIONS: u32 = 20;
+
+fn synthetic_table() -> DataTable {
+    let mut list = String::new();
+    for i in 0..PROTOTYPES {
+        // Every tenth subgroup belongs to a group that doesn't exist
+        let group = if i % 10 == 0 {
+            "missing-group"
+        } else {
+            "other"
+        };
+        writeln!(list, "item-subgroup subgroup-{i} {group}").unwrap();
+        writeln!(list, "item item-{i}").unwrap();
+        writeln!(list, "fluid fluid-{i}").unwrap();
+        writeln!(list, "fuel-category fuel-{i}").unwrap();
+    }
+    let mut data_table = DataTable::with_base_game_stubs();
+    data_table
+        .add_stubs(&list)
+        .expect("synthetic stubs are valid");
+    data_table
+}
+
+fn time(name: &str, validate: impl Fn() -> usize) {
+    let errors = validate();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(validate());
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+    println!("{name}: {elapsed:?} per run, {errors} errors");
+}
+
+fn main() {
+    let data_table = synthetic_table();
+    time("DataTable::validate_all", || {
+        black_box(&data_table)
+            .validate_all()
+            .err()
+            .unwrap_or_default()
+            .len()
+    });
+}
@@ -1,4 +1,9 @@
-use crate::prototypes::{DataTable, DataTableAccessable, Prototype, PrototypeType, PrototypesErr};
+use crate::prototypes::{
+    data_table_categories, DataTable, DataTableAccessable, Prototype, PrototypeCategory,
+    PrototypeType, PrototypesErr,
+};
+use std::borrow::Cow;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct AbstractEntity {
@@ -16,26 +21,34 @@ impl Prototype for AbstractEntity {
     }
 }
 
-// Executes get() on every specified prototype category, returning the first match. Errors if no
-// prototypes with this name found  in corresponding category.
-macro_rules! abstract_prototype_get {(
-    $data_table:ident, $name:ident,
-    $( $ptype:ident $(,)? )*
-    ) => (
-    $( if let Some(prototype) = $data_table.$ptype.get($name) {
-        return Ok(Self{name: $name.into(), prototype_type: prototype.prototype_type()})
-    } )*
-    return Err(PrototypesErr::PrototypeNotFound($name.into()))
-)}
-
 impl DataTableAccessable for AbstractEntity {
     fn find<'a>(_data_table: &'a DataTable, _name: &str) -> Result<&'a Self, PrototypesErr> {
         Err(PrototypesErr::AbstractFind)
     }
 
+    fn category(_data_table: &DataTable) -> Result<&PrototypeCategory<Self>, PrototypesErr> {
+        Err(PrototypesErr::AbstractFind)
+    }
+
     fn find_cloned(data_table: &DataTable, name: &str) -> Result<Self, PrototypesErr> {
-        // TODO: add other types
-        abstract_prototype_get!(data_table, name, arrow, artillery_flare);
+        // Executes get() on every entity category, returning the first match
+        macro_rules! abstract_prototype_get {
+            (other $other:tt entities { $($category:ident: $prototype:ty,)* } $($rest:tt)*) => {
+                $( if let Some(prototype) = data_table.$category.get(name) {
+                    return Ok(Self { name: name.into(), prototype_type: prototype.prototype_type() })
+                } )*
+            };
+        }
+        data_table_categories!(abstract_prototype_get);
+        Err(PrototypesErr::PrototypeNotFound(name.into()))
+    }
+
+    fn find_cow<'a>(data_table: &'a DataTable, name: &str) -> Result<Cow<'a, Self>, PrototypesErr> {
+        Ok(Cow::Owned(Self::find_cloned(data_table, name)?))
+    }
+
+    fn find_shared(data_table: &DataTable, name: &str) -> Result<Arc<Self>, PrototypesErr> {
+        Ok(Arc::new(Self::find_cloned(data_table, name)?))
     }
 
     fn extend(self, _data_table: &mut DataTable) -> Result<(), PrototypesErr> {
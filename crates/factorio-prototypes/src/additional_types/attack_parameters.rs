@@ -4,14 +4,17 @@ use super::{
     AmmoType, CircularParticleCreationSpecification, CircularProjectileCreationSpecification,
     Factorio2DVector, RangeMode,
 };
+use crate::prototypes::ResourceWalk;
 use crate::util::defaults::*;
 use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 use strum_macros::{AsRefStr, EnumString};
 
 // ========== // AttackParameters // ========== //
 
 /// <https://wiki.factorio.com/Types/AttackParameters>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum AttackParameters {
     // Depends on `type` key
@@ -20,7 +23,29 @@ pub enum AttackParameters {
     Stream(StreamAttackParameters),         // "stream"
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl AttackParameters {
+    pub fn base(&self) -> &BaseAttackParameters {
+        match self {
+            Self::Projectile(p) => &p.base,
+            Self::Beam(p) => &p.base,
+            Self::Stream(p) => &p.base,
+        }
+    }
+
+    /// Names of entities created by the ammo action of these attack parameters
+    pub fn referenced_entities(&self) -> Vec<&str> {
+        match &self.base().ammo_categories {
+            AmmoCategories::Many { ammo_type, .. } => ammo_type
+                .action
+                .as_ref()
+                .map(|action| action.referenced_entities())
+                .unwrap_or_default(),
+            AmmoCategories::Single { .. } => vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct BaseAttackParameters {
     #[serde(flatten)]
     pub range: AttackParametersRange,
@@ -72,6 +97,15 @@ pub enum AttackParametersRange {
     },
 }
 
+impl AttackParametersRange {
+    /// Maximum range of the attack
+    pub fn range(&self) -> f32 {
+        match self {
+            Self::Both { range, .. } | Self::Single { range } => *range,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum AttackParametersCooldown {
@@ -92,7 +126,7 @@ const fn default_activation_type_shoot() -> ActivationType {
     ActivationType::Shoot
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 #[serde(untagged)]
 pub enum AmmoCategories {
     Many {
@@ -105,7 +139,7 @@ pub enum AmmoCategories {
 }
 
 /// <https://wiki.factorio.com/Types/ProjectileAttackParameters>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct ProjectileAttackParameters {
     #[serde(flatten)]
     pub base: BaseAttackParameters,
@@ -120,7 +154,7 @@ pub struct ProjectileAttackParameters {
 }
 
 /// <https://wiki.factorio.com/Types/BeamAttackParameters>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct BeamAttackParameters {
     #[serde(flatten)]
     pub base: BaseAttackParameters,
@@ -130,7 +164,7 @@ pub struct BeamAttackParameters {
 }
 
 /// <https://wiki.factorio.com/Types/StreamAttackParameters>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct StreamAttackParameters {
     #[serde(flatten)]
     base: BaseAttackParameters,
@@ -147,6 +181,7 @@ pub struct StreamAttackParameters {
 
 /// <https://wiki.factorio.com/Types/BaseAttackParameters#activation_type>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum ActivationType {
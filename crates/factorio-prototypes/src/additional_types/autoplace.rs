@@ -1,11 +1,11 @@
 use crate::util::defaults::*;
-use mlua::ToLua;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::convert::AsRef;
 use strum::IntoEnumIterator;
 use strum::{AsRefStr, EnumDiscriminants, EnumIter, EnumString};
 
+use crate::prototypes::lua_compat::{conversion_error, LuaValue, TableGetCompat};
 use crate::prototypes::{GetPrototype, PrototypeFromLua};
 
 pub type NoiseExpression = String;
@@ -152,7 +152,7 @@ pub struct Dimensions(Vec<Dimension>);
 
 impl<'lua> PrototypeFromLua<'lua> for Dimensions {
     fn prototype_from_lua(
-        value: mlua::Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua mlua::Lua,
         data_table: &mut crate::prototypes::DataTable,
     ) -> mlua::Result<Self> {
@@ -161,28 +161,30 @@ impl<'lua> PrototypeFromLua<'lua> for Dimensions {
             for dimension in DimensionDiscriminants::iter() {
                 let dim_table = lua.create_table()?;
                 if let Some(optimal) =
-                    table.get::<_, Option<f64>>(format!("{}_optimal", dimension.as_ref()))?
+                    table.get_as::<Option<f64>>(format!("{}_optimal", dimension.as_ref()))?
                 {
                     dim_table.set("optimal", optimal)?;
                 }
                 if let Some(range) =
-                    table.get::<_, Option<f64>>(format!("{}_range", dimension.as_ref()))?
+                    table.get_as::<Option<f64>>(format!("{}_range", dimension.as_ref()))?
                 {
                     dim_table.set("range", range)?;
                 }
                 if let Some(max_range) =
-                    table.get::<_, Option<f64>>(format!("{}_max_range", dimension.as_ref()))?
+                    table.get_as::<Option<f64>>(format!("{}_max_range", dimension.as_ref()))?
                 {
                     dim_table.set("max_range", max_range)?;
                 }
                 if let Some(top_property_limit) = table
-                    .get::<_, Option<f64>>(format!("{}_top_property_limit", dimension.as_ref()))?
+                    .get_as::<Option<f64>>(format!("{}_top_property_limit", dimension.as_ref()))?
                 {
                     dim_table.set("top_property_limit", top_property_limit)?;
                 }
-                if let Ok(dimension_val) =
-                    DimensionSpec::prototype_from_lua(dim_table.to_lua(lua)?, lua, data_table)
-                {
+                if let Ok(dimension_val) = DimensionSpec::prototype_from_lua(
+                    mlua::Value::Table(dim_table),
+                    lua,
+                    data_table,
+                ) {
                     result.push(match dimension {
                         DimensionDiscriminants::StartingAreaWeight => {
                             Dimension::StartingAreaWeight(dimension_val)
@@ -202,11 +204,11 @@ impl<'lua> PrototypeFromLua<'lua> for Dimensions {
             }
             Ok(Self(result))
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "Dimensions",
-                message: Some("expected table. You shouldn't be able to get this error".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "Dimensions",
+                Some("expected table. You shouldn't be able to get this error".into()),
+            ))
         }
     }
 }
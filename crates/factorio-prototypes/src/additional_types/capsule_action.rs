@@ -1,9 +1,10 @@
 use super::AttackParameters;
+use crate::prototypes::ResourceWalk;
 use crate::util::defaults::*;
 use serde::Deserialize;
 
 /// <https://wiki.factorio.com/Types/CapsuleAction>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum CapsuleAction {
     Throw(ThrowCapsuleAction),
@@ -13,8 +14,22 @@ pub enum CapsuleAction {
     DestroyCliffs(DestroyCliffsCapsuleAction),
 }
 
+impl CapsuleAction {
+    /// Names of entities this capsule action can create or target, such as the explosion of a
+    /// grenade or the flare of an artillery remote. Equipment is not an entity and is not included.
+    pub fn referenced_entities(&self) -> Vec<&str> {
+        match self {
+            Self::Throw(ca) => ca.attack_parameters.referenced_entities(),
+            Self::EquipmentRemote(_) => vec![],
+            Self::UseOnSelf(ca) => ca.attack_parameters.referenced_entities(),
+            Self::ArtilleryRemote(ca) => vec![ca.flare.as_str()],
+            Self::DestroyCliffs(ca) => ca.attack_parameters.referenced_entities(),
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Types/ThrowCapsuleAction>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct ThrowCapsuleAction {
     pub attack_parameters: AttackParameters,
     #[serde(default = "default_bool::<true>")]
@@ -28,7 +43,7 @@ pub struct ActivateEquipmentCapsuleAction {
 }
 
 /// <https://wiki.factorio.com/Types/UseOnSelfCapsuleAction>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct UseOnSelfCapsuleAction {
     pub attack_parameters: AttackParameters,
     #[serde(default = "default_bool::<true>")]
@@ -44,7 +59,7 @@ pub struct ArtilleryRemoteCapsuleAction {
 }
 
 /// <https://wiki.factorio.com/Types/DestroyCliffsCapsuleAction>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct DestroyCliffsCapsuleAction {
     pub attack_parameters: AttackParameters,
     pub radius: f32,
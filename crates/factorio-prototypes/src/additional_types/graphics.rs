@@ -2,11 +2,15 @@ use super::{
     BoundingBox, Color, CreateParticleTriggerEffectItem, Factorio2DVector, FileName,
     RealOrientation,
 };
-use super::{DataTable, GetPrototype, PrototypeFromLua};
+use super::{DataTable, Direction, GetPrototype, PrototypeFromLua};
+use crate::prototypes::lua_compat::{conversion_error, LuaValue, TableGetCompat};
+use crate::prototypes::{ResourceRecord, ResourceType, ResourceWalk};
 use crate::util::defaults::*;
 use factorio_lib_rs_derive::prot_from_str;
 use mlua::{prelude::*, Value};
 use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 use std::iter::{FromIterator, Iterator};
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
 use strum_macros::{AsRefStr, EnumString};
@@ -33,6 +37,7 @@ pub type SpriteSizeType = i16;
 
 /// <https://wiki.factorio.com/Types/WorkingVisualisation#apply_recipe_tint>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum ApplyRecipeTint {
@@ -45,6 +50,7 @@ pub enum ApplyRecipeTint {
 
 /// <https://wiki.factorio.com/Types/WorkingVisualisation#apply_tint>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum ApplyTint {
@@ -57,6 +63,7 @@ pub enum ApplyTint {
 
 /// <https://wiki.factorio.com/Types/BeaconGraphicsSet#apply_module_tint>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum ApplyModuleTint {
@@ -69,6 +76,7 @@ pub enum ApplyModuleTint {
 
 /// <https://wiki.factorio.com/Types/BeaconGraphicsSet#module_tint_mode>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum ModuleTintMode {
@@ -78,6 +86,7 @@ pub enum ModuleTintMode {
 
 /// <https://wiki.factorio.com/Types/LightDefinition#type>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum LightDefinitionType {
@@ -87,6 +96,7 @@ pub enum LightDefinitionType {
 
 /// <https://wiki.factorio.com/Types/BaseAttackParameters#range_mode>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum RangeMode {
@@ -96,6 +106,7 @@ pub enum RangeMode {
 
 /// <https://wiki.factorio.com/Prototype/Lamp#glow_render_mode>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Hash, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum GlowRenderMode {
@@ -105,6 +116,7 @@ pub enum GlowRenderMode {
 
 /// <https://wiki.factorio.com/Types/RenderLayer>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum RenderLayer {
@@ -153,6 +165,13 @@ pub enum RenderLayer {
     Cursor,
 }
 
+impl RenderLayer {
+    /// Whether this is the "decals" layer, which makes decoratives use `tile_layer`
+    pub fn is_decal(&self) -> bool {
+        matches!(self, Self::Decals)
+    }
+}
+
 /// <https://wiki.factorio.com/Types/Sprite#draw_as_shadow>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Deserialize)]
 #[serde(from = "DrawAsIntermediate")]
@@ -202,6 +221,7 @@ impl From<DrawAsIntermediate> for DrawAs {
 
 /// <https://wiki.factorio.com/Types/Sprite#blend_mode>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum BlendMode {
@@ -214,6 +234,7 @@ pub enum BlendMode {
 
 /// <https://wiki.factorio.com/Types/Animation#run_mode>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum RunMode {
@@ -238,14 +259,14 @@ pub struct SecondaryDrawOrders {
 }
 
 /// <https://wiki.factorio.com/Types/LightDefinition>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 #[serde(untagged)]
 pub enum LightDefinition {
     Single(LightDefinitionProperties),
     Multiple(Vec<LightDefinitionProperties>),
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum LightDefinitionProperties {
     Basic(BasicLightDefinition),
@@ -266,7 +287,7 @@ pub struct BasicLightDefinition {
     pub minimum_darkness: f32,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct OrientedLightDefinition {
     #[serde(flatten)]
     pub base: BasicLightDefinition,
@@ -314,30 +335,50 @@ const fn default_f32_0_5() -> f32 {
     0.5
 }
 const fn default_color_white() -> Color {
-    Color {
-        r: 1.0,
-        g: 1.0,
-        b: 1.0,
-        a: 1.0,
-    }
+    Color::WHITE
 }
 
 /// <https://wiki.factorio.com/Prototype/CraftingMachine#default_recipe_tint>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, PrototypeFromLua)]
 pub struct RecipeTint {
     // All default to (1, 1, 1, 1), except special cases
     #[serde(default = "default_color_white")]
+    #[default(Color::WHITE)]
     pub primary: Color,
     #[serde(default = "default_color_white")]
+    #[default(Color::WHITE)]
     pub secondary: Color,
     #[serde(default = "default_color_white")]
+    #[default(Color::WHITE)]
     pub tertiary: Color,
     #[serde(default = "default_color_white")]
+    #[default(Color::WHITE)]
     pub quaternary: Color,
 }
 
+impl RecipeTint {
+    /// Tint used when the whole table is not specified, like in `Recipe::crafting_machine_tint`
+    pub const ZERO: Self = Self {
+        primary: Color::TRANSPARENT,
+        secondary: Color::TRANSPARENT,
+        tertiary: Color::TRANSPARENT,
+        quaternary: Color::TRANSPARENT,
+    };
+
+    /// Color selected by `apply_recipe_tint` of a [WorkingVisualisation]
+    pub fn get(&self, apply: ApplyRecipeTint) -> Option<&Color> {
+        match apply {
+            ApplyRecipeTint::Primary => Some(&self.primary),
+            ApplyRecipeTint::Secondary => Some(&self.secondary),
+            ApplyRecipeTint::Tertiary => Some(&self.tertiary),
+            ApplyRecipeTint::Quaternary => Some(&self.quaternary),
+            ApplyRecipeTint::None => None,
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/CraftingMachine#shift_animation_waypoints>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, PrototypeFromLua)]
 pub struct ShiftAnimationWaypoints {
     pub north: Option<Vec<Factorio2DVector>>,
     pub east: Option<Vec<Factorio2DVector>>,
@@ -345,11 +386,99 @@ pub struct ShiftAnimationWaypoints {
     pub west: Option<Vec<Factorio2DVector>>,
 }
 
+/// Shift animation of crafting machines and mining drills, read from the prototype table.
+///
+/// `shift_animation_waypoints` are only loaded if `shift_animation_waypoint_stop_duration` or
+/// `shift_animation_transition_duration` is not 0, and the durations are only loaded if
+/// waypoints are present. Durations are read first, then waypoints if any duration is not 0.
+/// Without waypoints, both durations are 0.
+#[derive(Debug, Clone, Default)]
+pub struct ShiftAnimation {
+    pub waypoints: Option<ShiftAnimationWaypoints>,
+    pub waypoint_stop_duration: u16, // Default: 0
+    pub transition_duration: u16,    // Default: 0
+}
+
+impl ShiftAnimation {
+    fn resolve(
+        waypoint_stop_duration: u16,
+        transition_duration: u16,
+        waypoints: impl FnOnce() -> LuaResult<Option<ShiftAnimationWaypoints>>,
+    ) -> LuaResult<Self> {
+        if waypoint_stop_duration == 0 && transition_duration == 0 {
+            return Ok(Self::default());
+        }
+        Ok(match waypoints()? {
+            Some(waypoints) => Self {
+                waypoints: Some(waypoints),
+                waypoint_stop_duration,
+                transition_duration,
+            },
+            None => Self::default(),
+        })
+    }
+}
+
+impl<'lua> PrototypeFromLua<'lua> for ShiftAnimation {
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua mlua::Lua,
+        data_table: &mut DataTable,
+    ) -> mlua::Result<Self> {
+        if let mlua::Value::Table(p_table) = &value {
+            let mut duration = |key: &str| -> LuaResult<u16> {
+                Ok(p_table
+                    .get_prot::<_, Option<u16>>(key, lua, data_table)?
+                    .unwrap_or(0))
+            };
+            let waypoint_stop_duration = duration("shift_animation_waypoint_stop_duration")?;
+            let transition_duration = duration("shift_animation_transition_duration")?;
+            // Waypoints are ignored without durations, which is not an unknown key
+            data_table.use_key(p_table, "shift_animation_waypoints");
+            Self::resolve(waypoint_stop_duration, transition_duration, || {
+                p_table.get_prot("shift_animation_waypoints", lua, data_table)
+            })
+        } else {
+            Err(conversion_error(
+                value.type_name(),
+                "ShiftAnimation",
+                Some("Expected table".into()),
+            ))
+        }
+    }
+}
+
+#[test]
+fn shift_animation_conditional_load() {
+    let waypoints = || {
+        Ok(Some(ShiftAnimationWaypoints {
+            north: Some(vec![Factorio2DVector(0.0, 0.5)]),
+            east: None,
+            south: None,
+            west: None,
+        }))
+    };
+    // Waypoints are present, but not loaded without durations
+    let sa = ShiftAnimation::resolve(0, 0, || -> LuaResult<_> { panic!("waypoints were read") })
+        .unwrap();
+    assert!(sa.waypoints.is_none());
+    // No waypoints and no durations
+    let sa = ShiftAnimation::resolve(0, 0, || Ok(None)).unwrap();
+    assert!(sa.waypoints.is_none());
+    // Durations without waypoints are reset to 0
+    let sa = ShiftAnimation::resolve(10, 5, || Ok(None)).unwrap();
+    assert_eq!((sa.waypoint_stop_duration, sa.transition_duration), (0, 0));
+    // Both are loaded
+    let sa = ShiftAnimation::resolve(0, 5, waypoints).unwrap();
+    assert!(sa.waypoints.is_some());
+    assert_eq!((sa.waypoint_stop_duration, sa.transition_duration), (0, 5));
+}
+
 // TODO
 /// <https://wiki.factorio.com/Prototype/CraftingMachine#status_colors>
 #[derive(Debug, Clone, PrototypeFromLua)]
 pub struct StatusColors {
-    #[default(Color::new_rgb(1.0, 1.0, 1.0))]
+    #[default(Color::WHITE)]
     pub idle: Color, // Default: White (1, 1, 1)
     #[default(idle.clone())]
     pub no_minable_resources: Color, // Default: `idle`
@@ -359,14 +488,30 @@ pub struct StatusColors {
     pub insufficient_output: Color, // Default: `idle`
     #[default(idle.clone())]
     pub disabled: Color, // Default: `idle`
-    #[default(Color(0.0, 0.0, 0.0, 0.0))]
+    #[default(Color::TRANSPARENT)]
     pub no_power: Color, // Default: No color
-    #[default(Color::new_rgb(1.0, 1.0, 1.0))]
+    #[default(Color::WHITE)]
     pub working: Color, // Default: White (1, 1, 1)
     #[default(working.clone())]
     pub low_power: Color, // Default: `working`
 }
 
+impl Default for StatusColors {
+    /// Colors used when `status_colors` is not specified, same as defaults of the fields
+    fn default() -> Self {
+        Self {
+            idle: Color::WHITE,
+            no_minable_resources: Color::WHITE,
+            full_output: Color::WHITE,
+            insufficient_output: Color::WHITE,
+            disabled: Color::WHITE,
+            no_power: Color::TRANSPARENT,
+            working: Color::WHITE,
+            low_power: Color::WHITE,
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Types/MiningDrillGraphicsSet#circuit_connector_secondary_draw_order>
 #[derive(Debug, Clone, Deserialize)]
 #[serde(from = "CircuitConnectorSecondaryDrawOrderIntermediate")]
@@ -426,7 +571,7 @@ impl From<CircuitConnectorSecondaryDrawOrderIntermediate> for CircuitConnectorSe
 // TODO
 /// <https://wiki.factorio.com/Prototype/Entity#radius_visualisation_specification>
 /// <https://wiki.factorio.com/Types/RadiusVisualisationSpecification>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct RadiusVisualizationSpecification {
     pub sprite: Option<Sprite>,
@@ -440,24 +585,32 @@ pub struct RadiusVisualizationSpecification {
 }
 
 impl RadiusVisualizationSpecification {
+    /// Radius of the drawn visualization, `None` if it has no sprite or zero `distance`
+    pub fn radius(&self) -> Option<f64> {
+        self.sprite
+            .as_ref()
+            .filter(|_| self.distance > 0.0)
+            .map(|_| self.distance)
+    }
+
     fn post_extr_fn(
         &mut self,
         _lua: &mlua::Lua,
         _data_table: &DataTable,
     ) -> mlua::prelude::LuaResult<()> {
         if self.distance.is_sign_negative() {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "RadiusVisualizationSpecification",
-                message: Some("`distance` must be positive (>= 0)".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "RadiusVisualizationSpecification",
+                Some("`distance` must be positive (>= 0)".into()),
+            ));
         }
         Ok(())
     }
 }
 
 /// <https://wiki.factorio.com/Types/WaterReflectionDefinition>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct WaterReflectionDefinition {
     pictures: Option<Vec<SpriteVariation>>,
     #[serde(default)]
@@ -510,7 +663,7 @@ pub enum SliceOrDiceNonSquare {
 // ============= // Animations // ============= //
 
 /// <https://wiki.factorio.com/Types/Animation#layers>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub enum Animation {
     Layers(Vec<Animation>),
     Single(Box<AnimationBase>),
@@ -529,17 +682,25 @@ impl Animation {
             Self::Single(ab) => ab.check_stripes(),
         }
     }
+
+    /// Frame count of the animation, or of the first layer
+    pub fn frame_count(&self) -> u32 {
+        match self {
+            Self::Layers(layers) => layers.first().map_or(1, Self::frame_count),
+            Self::Single(ab) => ab.regular.frame_count,
+        }
+    }
 }
 
 impl<'lua> PrototypeFromLua<'lua> for Animation {
     fn prototype_from_lua(
-        value: mlua::Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua mlua::Lua,
         data_table: &mut DataTable,
     ) -> mlua::Result<Self> {
         let type_name = &value.type_name();
         if let mlua::Value::Table(p_table) = value {
-            let layers = p_table.get::<_, Option<Vec<mlua::Value>>>("layers")?;
+            let layers = p_table.get_as::<Option<Vec<mlua::Value>>>("layers")?;
             let result = if let Some(actual_layers) = layers {
                 Self::Layers(
                     actual_layers
@@ -549,31 +710,31 @@ impl<'lua> PrototypeFromLua<'lua> for Animation {
                 )
             } else {
                 Self::Single(Box::new(AnimationBase::prototype_from_lua(
-                    p_table.to_lua(lua)?,
+                    mlua::Value::Table(p_table),
                     lua,
                     data_table,
                 )?))
             };
             if result.check_stripes() {
-                return Err(mlua::Error::FromLuaConversionError {
-                    from: type_name,
-                    to: "Animation",
-                    message: Some("`height_in_frames` in stripes is mandatory".into()),
-                });
+                return Err(conversion_error(
+                    type_name,
+                    "Animation",
+                    Some("`height_in_frames` in stripes is mandatory".into()),
+                ));
             };
             Ok(result)
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: type_name,
-                to: "Animation",
-                message: Some("Expected table".into()),
-            })
+            Err(conversion_error(
+                type_name,
+                "Animation",
+                Some("Expected table".into()),
+            ))
         }
     }
 }
 
 /// <https://wiki.factorio.com/Types/Animation#hr_version>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct AnimationBase {
     #[use_self_forced]
     pub regular: AnimationSpec,
@@ -622,12 +783,63 @@ impl AnimationSpec {
     fn register_resources(
         &self,
         _lua: &mlua::Lua,
-        _data_table: &mut DataTable,
+        data_table: &mut DataTable,
     ) -> mlua::prelude::LuaResult<()> {
-        todo!() // TODO
-                // List of things to do:
-                // Rename this function to a more fitting name
-                // check data: `frame_count`, `repeat_count`
+        if self.frame_count == 0 || self.repeat_count == 0 {
+            return Err(conversion_error(
+                "table",
+                "Animation",
+                Some("`frame_count` and `repeat_count` can't be 0".into()),
+            ));
+        }
+        for record in self.resource_records() {
+            data_table.register_resource(record)
+        }
+        Ok(())
+    }
+
+    // Images of the filename sheet and stripes, with dimensions needed for all frames
+    fn resource_records(&self) -> Vec<ResourceRecord> {
+        let mut records = vec![];
+        if self.frame_count == 0 {
+            return records;
+        }
+        let SpriteSize(width, height) = self.sprite.size;
+        if let Some(filename) = &self.filename {
+            let per_line = match self.line_length {
+                0 => self.frame_count,
+                line_length => line_length.min(self.frame_count),
+            };
+            let lines = (self.frame_count + per_line - 1) / per_line;
+            records.push(match self.sprite.position.unwrap_or(SpritePosition(0, 0)) {
+                SpritePosition(0, 0) => {
+                    frame_sheet_record(filename, self.sprite.size, per_line, lines)
+                }
+                SpritePosition(x, y) => image_record(
+                    filename,
+                    x as u32 + width as u32 * per_line,
+                    y as u32 + height as u32 * lines,
+                ),
+            })
+        }
+        for stripe in self.stripes.iter().flatten() {
+            let lines = stripe.height_in_frames.unwrap_or(1);
+            records.push(if stripe.x == 0 && stripe.y == 0 {
+                frame_sheet_record(
+                    &stripe.filename,
+                    self.sprite.size,
+                    stripe.width_in_frames,
+                    lines,
+                )
+            } else {
+                image_record(
+                    &stripe.filename,
+                    stripe.x + width as u32 * stripe.width_in_frames,
+                    stripe.y + height as u32 * lines,
+                )
+            })
+        }
+        records
     }
 
     fn check_stripes(&self) -> bool {
@@ -641,6 +853,12 @@ impl AnimationSpec {
     }
 }
 
+impl ResourceWalk for AnimationSpec {
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>) {
+        records.extend(self.resource_records())
+    }
+}
+
 /// <https://wiki.factorio.com/Types/Stripe>
 #[derive(Debug, Clone, PrototypeFromLua)]
 pub struct Stripe {
@@ -653,11 +871,43 @@ pub struct Stripe {
     pub y: u32, // Default: 0
 }
 
+/// Graphics made of a number of variations, like [AnimationVariations] and [SpriteVariations]
+pub trait Variations {
+    /// Total number of variations
+    fn variation_count(&self) -> usize;
+
+    /// Errors if number of variations is not within `min..=max`. `type_name` and `field` are
+    /// used in the error message.
+    fn require_variation_count(
+        &self,
+        min: usize,
+        max: usize,
+        type_name: &'static str,
+        field: &str,
+    ) -> LuaResult<()> {
+        let count = self.variation_count();
+        let message = if count < min {
+            format!("`{field}` must have at least {min} variations, got {count}")
+        } else if count > max {
+            format!("`{field}` can have at most {max} variations, got {count}")
+        } else {
+            return Ok(());
+        };
+        Err(conversion_error("table", type_name, Some(message)))
+    }
+}
+
 /// <https://wiki.factorio.com/Types/AnimationVariations>
 pub type AnimationVariations = Vec<AnimationVariation>;
 
+impl Variations for AnimationVariations {
+    fn variation_count(&self) -> usize {
+        self.iter().map(|av| av.variation_count as usize).sum()
+    }
+}
+
 /// <https://wiki.factorio.com/Types/AnimationVariations>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct AnimationVariation {
     #[use_self_forced]
     pub animation: AnimationBase, // Filename is mandatory
@@ -669,21 +919,21 @@ pub struct AnimationVariation {
 }
 
 /// <https://wiki.factorio.com/Types/Animation4Way>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct Animation4Way {
     // All fancy shenanigans are omitted, this program/library behaves like a game
     #[use_self]
     pub north: Animation,
-    #[mandatory_if(prot_table.get::<_, Option<Value>>("north")?.is_some())]
+    #[mandatory_if(prot_table.get_as::<Option<Value>>("north")?.is_some())]
     pub east: Option<Animation>,
-    #[mandatory_if(prot_table.get::<_, Option<Value>>("north")?.is_some())]
+    #[mandatory_if(prot_table.get_as::<Option<Value>>("north")?.is_some())]
     pub south: Option<Animation>,
-    #[mandatory_if(prot_table.get::<_, Option<Value>>("north")?.is_some())]
+    #[mandatory_if(prot_table.get_as::<Option<Value>>("north")?.is_some())]
     pub west: Option<Animation>,
 }
 
 /// <https://wiki.factorio.com/Types/AnimationElement>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct AnimationElement {
     #[default(RenderLayer::Object)]
     pub render_layer: RenderLayer, // Default: "object"
@@ -700,7 +950,7 @@ pub struct AnimationElement {
 }
 
 /// <https://wiki.factorio.com/Types/RotatedAnimation>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct RotatedAnimation {
     #[use_self_forced]
     pub regular: RotatedAnimationSpec,
@@ -710,7 +960,7 @@ pub struct RotatedAnimation {
 // A: "Are you sure this will work?"; Me: "I have no idea!"
 // Don't forget to check Stripes to set `height_in_frames` to `direction_count` if it's None
 /// <https://wiki.factorio.com/Types/RotatedAnimation>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct RotatedAnimationSpec {
     pub direction_count: u32,
     #[default(0_u32)]
@@ -733,7 +983,7 @@ pub struct RotatedAnimationSpec {
 pub type RotatedAnimationVariations = Vec<RotatedAnimationVariation>;
 
 /// <https://wiki.factorio.com/Types/RotatedAnimationVariations>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub enum RotatedAnimationVariation {
     Layers(Vec<RotatedAnimationVariation>),
     Single(Box<RotatedAnimation>),
@@ -741,15 +991,15 @@ pub enum RotatedAnimationVariation {
 
 impl<'lua> PrototypeFromLua<'lua> for RotatedAnimationVariation {
     fn prototype_from_lua(
-        value: mlua::Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua mlua::Lua,
         data_table: &mut DataTable,
     ) -> mlua::Result<Self> {
         let type_name = value.type_name();
         if let Value::Table(t) = value {
-            if t.get::<_, Option<String>>("direction_count")?.is_some() {
+            if t.get_as::<Option<String>>("direction_count")?.is_some() {
                 Ok(Self::Single(Box::new(
-                    RotatedAnimation::prototype_from_lua(t.to_lua(lua)?, lua, data_table)?,
+                    RotatedAnimation::prototype_from_lua(Value::Table(t), lua, data_table)?,
                 )))
             } else {
                 Ok(Self::Layers(
@@ -759,17 +1009,17 @@ impl<'lua> PrototypeFromLua<'lua> for RotatedAnimationVariation {
                 ))
             }
         } else {
-            Err(LuaError::FromLuaConversionError {
-                from: type_name,
-                to: "RotatedAnimationVariation",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                type_name,
+                "RotatedAnimationVariation",
+                Some("expected table".into()),
+            ))
         }
     }
 }
 
 /// <https://wiki.factorio.com/Types/RotatedAnimation4Way>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct RotatedAnimation4Way {
     north: RotatedAnimation,
     east: RotatedAnimation,
@@ -779,7 +1029,7 @@ pub struct RotatedAnimation4Way {
 }
 
 /// <https://wiki.factorio.com/Prototype/Beam#light_animations>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct LightAnimations {
     pub start: Option<Animation>,
     pub ending: Option<Animation>,
@@ -791,14 +1041,14 @@ pub struct LightAnimations {
 // ============== // Sprites // ==============  //
 
 /// <https://wiki.factorio.com/Types/Sprite>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct Sprite {
     #[use_self_vec]
     pub layers: Vec<SpriteLayer>,
 }
 
 /// <https://wiki.factorio.com/Types/Sprite>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct SpriteLayer {
     #[use_self_forced]
     pub regular: SpriteSpec,
@@ -807,12 +1057,75 @@ pub struct SpriteLayer {
 
 /// <https://wiki.factorio.com/Types/Sprite>
 #[derive(Debug, Clone, PrototypeFromLua)]
+#[post_extr_fn(Self::register_resources)]
 pub struct SpriteSpec {
     pub filename: FileName, // Mandatory in some cases
     #[use_self_forced]
     pub body: SpriteSpecWithoutFilename,
 }
 
+impl SpriteSpec {
+    fn register_resources(&self, _lua: &Lua, data_table: &mut DataTable) -> LuaResult<()> {
+        data_table.register_resource(self.resource_record());
+        Ok(())
+    }
+
+    fn resource_record(&self) -> ResourceRecord {
+        let SpriteSize(width, height) = self.body.size;
+        let SpritePosition(x, y) = self.body.position.unwrap_or(SpritePosition(0, 0));
+        image_record(
+            &self.filename,
+            x as u32 + width as u32,
+            y as u32 + height as u32,
+        )
+    }
+}
+
+impl ResourceWalk for SpriteSpec {
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>) {
+        records.push(self.resource_record())
+    }
+}
+
+/// Record image file with minimal required dimensions
+pub(super) fn register_image(
+    data_table: &mut DataTable,
+    filename: &FileName,
+    width: u32,
+    height: u32,
+) {
+    data_table.register_resource(image_record(filename, width, height))
+}
+
+fn clamp_size(size: u32) -> SpriteSizeType {
+    size.min(SpriteSizeType::MAX as u32) as SpriteSizeType
+}
+
+fn image_record(filename: &FileName, width: u32, height: u32) -> ResourceRecord {
+    ResourceRecord {
+        path: filename.name.clone(),
+        resource_type: ResourceType::Image(clamp_size(width), clamp_size(height), None),
+    }
+}
+
+/// Image file that is a sheet of `columns` by `lines` frames of given size, starting at top left
+/// corner
+fn frame_sheet_record(
+    filename: &FileName,
+    SpriteSize(width, height): SpriteSize,
+    columns: u32,
+    lines: u32,
+) -> ResourceRecord {
+    ResourceRecord {
+        path: filename.name.clone(),
+        resource_type: ResourceType::Image(
+            clamp_size(width as u32 * columns),
+            clamp_size(height as u32 * lines),
+            Some((width, height)),
+        ),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SpriteSpecWithoutFilename {
     pub dice: Option<SliceOrDice>, // AKA slice // _y and _x are converted into this
@@ -842,23 +1155,23 @@ pub struct SpriteSpecWithoutFilename {
 
 impl<'lua> PrototypeFromLua<'lua> for SpriteSpecWithoutFilename {
     fn prototype_from_lua(
-        value: mlua::Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua mlua::Lua,
         _data_table: &mut DataTable,
     ) -> mlua::Result<Self> {
         if let mlua::Value::Table(p_table) = value {
             let dice: Option<Dice> = {
                 let dice_gen_opt: Option<i16> = p_table
-                    .get::<_, Option<i16>>("dice")?
+                    .get_as::<Option<i16>>("dice")?
                     .or_else(|| p_table.get("slice").ok());
                 if let Some(dice_gen) = dice_gen_opt {
                     Some(Dice::new(dice_gen))
                 } else {
                     let x: Option<i16> = p_table
-                        .get::<_, Option<i16>>("dice_x")?
+                        .get_as::<Option<i16>>("dice_x")?
                         .or_else(|| p_table.get("slice_x").ok());
                     let y: Option<i16> = p_table
-                        .get::<_, Option<i16>>("dice_y")?
+                        .get_as::<Option<i16>>("dice_y")?
                         .or_else(|| p_table.get("slice_y").ok());
                     if let (Some(ax), Some(ay)) = (x, y) {
                         Some(Dice(ax, ay))
@@ -868,24 +1181,22 @@ impl<'lua> PrototypeFromLua<'lua> for SpriteSpecWithoutFilename {
                 }
             };
             let priority: SpritePriority = p_table
-                .get::<_, Option<String>>("priority")?
+                .get_as::<Option<String>>("priority")?
                 .unwrap_or_else(|| "medium".into())
                 .parse()
-                .map_err(|_| mlua::Error::FromLuaConversionError {
-                    from: "String",
-                    to: "SpritePriority",
-                    message: Some("invalid value".into()),
+                .map_err(|_| {
+                    conversion_error("String", "SpritePriority", Some("invalid value".into()))
                 })?;
             let flags = p_table
-                .get::<_, Option<Vec<String>>>("flags")?
+                .get_as::<Option<Vec<String>>>("flags")?
                 .map(SpriteFlags::from_iter);
             let size = {
-                let error = Err(mlua::Error::FromLuaConversionError {
-                    from: "integer",
-                    to: "SpriteSizeType",
-                    message: Some("value must be in range 0-8192".into()),
-                });
-                if let Some(s_value) = p_table.get::<_, Option<mlua::Value>>("size")? {
+                let error = Err(conversion_error(
+                    "integer",
+                    "SpriteSizeType",
+                    Some("value must be in range 0-8192".into()),
+                ));
+                if let Some(s_value) = p_table.get_as::<Option<mlua::Value>>("size")? {
                     match lua.unpack::<i16>(s_value.clone()) {
                         Ok(size) => {
                             if (0..=8192_i16).contains(&size) {
@@ -916,56 +1227,54 @@ impl<'lua> PrototypeFromLua<'lua> for SpriteSpecWithoutFilename {
                 }
             };
             let position = {
-                let x = p_table.get::<_, Option<SpriteSizeType>>("x")?.unwrap_or(0);
-                let y = p_table.get::<_, Option<SpriteSizeType>>("y")?.unwrap_or(0);
+                let x = p_table.get_as::<Option<SpriteSizeType>>("x")?.unwrap_or(0);
+                let y = p_table.get_as::<Option<SpriteSizeType>>("y")?.unwrap_or(0);
                 if x != 0 || y != 0 {
                     Some(SpritePosition(x, y))
                 } else {
                     p_table
-                        .get::<_, Option<[SpriteSizeType; 2]>>("position")?
+                        .get_as::<Option<[SpriteSizeType; 2]>>("position")?
                         .map(|pos| SpritePosition(pos[0], pos[1]))
                 }
             };
             let shift = p_table
-                .get::<_, Option<Factorio2DVector>>("shift")?
+                .get_as::<Option<Factorio2DVector>>("shift")?
                 .unwrap_or(Factorio2DVector(0.0, 0.0));
-            let scale = p_table.get::<_, Option<f64>>("scale")?.unwrap_or(1.0);
+            let scale = p_table.get_as::<Option<f64>>("scale")?.unwrap_or(1.0);
             let draw_as = {
                 let draw_as_shadow = p_table
-                    .get::<_, Option<bool>>("draw_as_shadow")?
+                    .get_as::<Option<bool>>("draw_as_shadow")?
                     .unwrap_or(false);
                 let draw_as_glow = p_table
-                    .get::<_, Option<bool>>("draw_as_glow")?
+                    .get_as::<Option<bool>>("draw_as_glow")?
                     .unwrap_or(false);
                 let draw_as_light = p_table
-                    .get::<_, Option<bool>>("draw_as_light")?
+                    .get_as::<Option<bool>>("draw_as_light")?
                     .unwrap_or(false);
                 DrawAs::new(draw_as_shadow, draw_as_glow, draw_as_light)
             };
-            let mipmap_count = p_table.get::<_, Option<u8>>("mipmap_count")?.unwrap_or(0);
+            let mipmap_count = p_table.get_as::<Option<u8>>("mipmap_count")?.unwrap_or(0);
             let apply_runtime_tint = p_table
-                .get::<_, Option<bool>>("apply_runtime_tint")?
+                .get_as::<Option<bool>>("apply_runtime_tint")?
                 .unwrap_or(false);
             let tint = p_table
-                .get::<_, Option<Color>>("tint")?
-                .unwrap_or(Color(1.0, 1.0, 1.0, 1.0));
+                .get_as::<Option<Color>>("tint")?
+                .unwrap_or(Color::WHITE);
             let blend_mode: BlendMode = p_table
-                .get::<_, Option<String>>("blend_mode")?
+                .get_as::<Option<String>>("blend_mode")?
                 .unwrap_or_else(|| "normal".into())
                 .parse()
-                .map_err(|_| mlua::Error::FromLuaConversionError {
-                    from: "string",
-                    to: "BlendMode",
-                    message: Some("Invalid variant".into()),
+                .map_err(|_| {
+                    conversion_error("string", "BlendMode", Some("Invalid variant".into()))
                 })?;
             let load_in_minimal_mode = p_table
-                .get::<_, Option<bool>>("load_in_minimal_mode")?
+                .get_as::<Option<bool>>("load_in_minimal_mode")?
                 .unwrap_or(false);
             let premul_alpha = p_table
-                .get::<_, Option<bool>>("premul_alpha")?
+                .get_as::<Option<bool>>("premul_alpha")?
                 .unwrap_or(true);
             let generate_sfd = p_table
-                .get::<_, Option<bool>>("generate_sfd")?
+                .get_as::<Option<bool>>("generate_sfd")?
                 .unwrap_or(false);
             Ok(Self {
                 dice,
@@ -985,11 +1294,11 @@ impl<'lua> PrototypeFromLua<'lua> for SpriteSpecWithoutFilename {
                 generate_sfd,
             })
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "SpriteSpec",
-                message: Some("Expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "SpriteSpec",
+                Some("Expected table".into()),
+            ))
         }
     }
 }
@@ -1003,27 +1312,27 @@ pub struct SpriteNWaySheet {
 
 impl SpriteNWaySheet {
     fn new<'lua>(
-        value: mlua::Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua Lua,
         data_table: &mut DataTable,
         frames: u32,
     ) -> LuaResult<Self> {
         if let mlua::Value::Table(t) = &value {
-            let frames = t.get::<_, Option<u32>>("frames")?.unwrap_or(frames);
+            let frames = t.get_as::<Option<u32>>("frames")?.unwrap_or(frames);
             let sprite = SpriteSpec::prototype_from_lua(value, lua, data_table)?;
             Ok(Self { sprite, frames })
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "SpriteNWaySheet",
-                message: Some("Expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "SpriteNWaySheet",
+                Some("Expected table".into()),
+            ))
         }
     }
 }
 
 /// <https://wiki.factorio.com/Types/Sprite4Way>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct Sprite4Way(pub DirectionalSprite);
 
 impl<'lua> PrototypeFromLua<'lua> for Sprite4Way {
@@ -1034,13 +1343,13 @@ impl<'lua> PrototypeFromLua<'lua> for Sprite4Way {
     ) -> LuaResult<Self> {
         let type_name = value.type_name();
         if let LuaValue::Table(t) = &value {
-            if let Some(sheets) = t.get::<_, Option<Vec<Value>>>("sheets")? {
+            if let Some(sheets) = t.get_as::<Option<Vec<Value>>>("sheets")? {
                 let sheets = sheets
                     .into_iter()
                     .map(|v| SpriteNWaySheet::new(v, lua, data_table, 4))
                     .collect::<LuaResult<Vec<SpriteNWaySheet>>>()?;
                 Ok(Self(sheets.into()))
-            } else if let Some(sheet) = t.get::<_, Option<Value>>("sheet")? {
+            } else if let Some(sheet) = t.get_as::<Option<Value>>("sheet")? {
                 let sheets = vec![SpriteNWaySheet::new(sheet, lua, data_table, 4)?];
                 Ok(Self(sheets.into()))
             } else {
@@ -1049,17 +1358,17 @@ impl<'lua> PrototypeFromLua<'lua> for Sprite4Way {
                 )))
             }
         } else {
-            Err(LuaError::FromLuaConversionError {
-                from: type_name,
-                to: "Sprite8Way",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                type_name,
+                "Sprite8Way",
+                Some("expected table".into()),
+            ))
         }
     }
 }
 
 /// <https://wiki.factorio.com/Types/Sprite8Way>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct Sprite8Way(pub DirectionalSprite);
 
 impl<'lua> PrototypeFromLua<'lua> for Sprite8Way {
@@ -1070,13 +1379,13 @@ impl<'lua> PrototypeFromLua<'lua> for Sprite8Way {
     ) -> LuaResult<Self> {
         let type_name = value.type_name();
         if let LuaValue::Table(t) = &value {
-            if let Some(sheets) = t.get::<_, Option<Vec<Value>>>("sheets")? {
+            if let Some(sheets) = t.get_as::<Option<Vec<Value>>>("sheets")? {
                 let sheets = sheets
                     .into_iter()
                     .map(|v| SpriteNWaySheet::new(v, lua, data_table, 8))
                     .collect::<LuaResult<Vec<SpriteNWaySheet>>>()?;
                 Ok(Self(sheets.into()))
-            } else if let Some(sheet) = t.get::<_, Option<Value>>("sheet")? {
+            } else if let Some(sheet) = t.get_as::<Option<Value>>("sheet")? {
                 let sheets = vec![SpriteNWaySheet::new(sheet, lua, data_table, 8)?];
                 Ok(Self(sheets.into()))
             } else {
@@ -1085,28 +1394,41 @@ impl<'lua> PrototypeFromLua<'lua> for Sprite8Way {
                 )))
             }
         } else {
-            Err(LuaError::FromLuaConversionError {
-                from: type_name,
-                to: "Sprite8Way",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                type_name,
+                "Sprite8Way",
+                Some("expected table".into()),
+            ))
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub enum DirectionalSprite {
     Sheets(Vec<SpriteNWaySheet>),
     Directions(SpriteDirections),
 }
 
+impl ResourceWalk for SpriteNWaySheet {
+    // Directions are laid out horizontally
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>) {
+        let SpriteSize(width, height) = self.sprite.body.size;
+        let SpritePosition(x, y) = self.sprite.body.position.unwrap_or(SpritePosition(0, 0));
+        records.push(image_record(
+            &self.sprite.filename,
+            x as u32 + width as u32 * self.frames,
+            y as u32 + height as u32,
+        ))
+    }
+}
+
 impl From<Vec<SpriteNWaySheet>> for DirectionalSprite {
     fn from(sheets: Vec<SpriteNWaySheet>) -> Self {
         Self::Sheets(sheets)
     }
 }
 
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct SpriteDirections {
     pub north: Option<Sprite>,
     pub north_east: Option<Sprite>,
@@ -1119,14 +1441,14 @@ pub struct SpriteDirections {
 }
 
 /// <https://wiki.factorio.com/Types/RotatedSprite#layers>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct RotatedSprite {
     #[use_self_vec]
     pub layers: Vec<RotatedSpriteLayer>,
 }
 
 /// <https://wiki.factorio.com/Types/RotatedSprite>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct RotatedSpriteLayer {
     #[use_self_forced]
     pub regular: RotatedSpriteSpec,
@@ -1134,7 +1456,7 @@ pub struct RotatedSpriteLayer {
 }
 
 /// <https://wiki.factorio.com/Types/RotatedSprite>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct RotatedSpriteSpec {
     pub sprites: Vec<SpriteSpec>, // If `filenames` is set, copy all properties to each object for each filename // FIXME
     pub direction_count: u16,
@@ -1143,15 +1465,27 @@ pub struct RotatedSpriteSpec {
 /// <https://wiki.factorio.com/Types/SpriteVariations>
 pub type SpriteVariations = Vec<SpriteVariation>;
 
+impl Variations for SpriteVariations {
+    fn variation_count(&self) -> usize {
+        self.iter()
+            .map(|sv| {
+                sv.layers
+                    .first()
+                    .map_or(0, |l| l.regular.variation_count as usize)
+            })
+            .sum()
+    }
+}
+
 /// <https://wiki.factorio.com/Types/SpriteVariations>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct SpriteVariation {
     #[use_self_vec]
     pub layers: Vec<SpriteVariationLayer>,
 }
 
 /// <https://wiki.factorio.com/Types/SpriteVariations>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct SpriteVariationLayer {
     #[use_self_forced]
     pub regular: SpriteVariationSpec,
@@ -1161,6 +1495,7 @@ pub struct SpriteVariationLayer {
 /// Extension of SpriteSpec, ignores dice and slice
 /// <https://wiki.factorio.com/Types/SpriteVariations>
 #[derive(Debug, Clone, PrototypeFromLua)]
+#[post_extr_fn(Self::register_resources)]
 pub struct SpriteVariationSpec {
     #[use_self_forced]
     pub sprite: SpriteSpec,
@@ -1172,6 +1507,44 @@ pub struct SpriteVariationSpec {
     pub line_length: u32, // Default: value of `variation_count`
 }
 
+impl SpriteVariationSpec {
+    // SpriteSpec only records the first variation
+    fn register_resources(&self, _lua: &Lua, data_table: &mut DataTable) -> LuaResult<()> {
+        if self.variation_count == 0 {
+            return Err(conversion_error(
+                "table",
+                "SpriteVariations",
+                Some("`variation_count` can't be 0".into()),
+            ));
+        }
+        data_table.register_resource(self.resource_record());
+        Ok(())
+    }
+
+    // Whole sheet of variations
+    fn resource_record(&self) -> ResourceRecord {
+        let per_line = match self.line_length {
+            0 => self.variation_count,
+            line_length => line_length.min(self.variation_count),
+        }
+        .max(1);
+        let lines = (self.variation_count + per_line - 1) / per_line;
+        let SpriteSize(width, height) = self.sprite.body.size;
+        let SpritePosition(x, y) = self.sprite.body.position.unwrap_or(SpritePosition(0, 0));
+        image_record(
+            &self.sprite.filename,
+            x as u32 + width as u32 * per_line,
+            y as u32 + height as u32 * lines,
+        )
+    }
+}
+
+impl ResourceWalk for SpriteVariationSpec {
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>) {
+        records.push(self.resource_record())
+    }
+}
+
 /// <https://wiki.factorio.com/Types/SpriteFlags>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Hash)]
 pub struct SpriteFlags(u32);
@@ -1368,7 +1741,7 @@ pub enum SpritePriority {
 // ===== // Graphics Sets and Pictures // ===== //
 
 /// <https://wiki.factorio.com/Prototype/Rail#pictures>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct RailPictures {
     pub straight_rail_horizontal: RailPieceLayers,
     pub straight_rail_vertical: RailPieceLayers,
@@ -1388,7 +1761,7 @@ pub struct RailPictures {
 }
 
 /// <https://wiki.factorio.com/Types/RailPieceLayers>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct RailPieceLayers {
     // TODO: checks
     pub metals: Vec<SpriteVariation>,
@@ -1404,7 +1777,7 @@ pub struct RailPieceLayers {
 }
 
 /// <https://wiki.factorio.com/Types/CircuitConnectorSprites>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct CircuitConnectorSprites {
     pub led_red: Sprite,
     pub led_green: Sprite,
@@ -1420,7 +1793,7 @@ pub struct CircuitConnectorSprites {
 }
 
 /// <https://wiki.factorio.com/Types/BeaconGraphicsSet>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct BeaconGraphicsSet {
     #[default(true)]
     pub draw_animation_when_idle: bool, //Default: true
@@ -1446,7 +1819,7 @@ pub struct BeaconGraphicsSet {
     pub apply_module_tint: ApplyModuleTint, // Default: "none"
     #[default(ApplyModuleTint::None)]
     pub apply_module_tint_to_light: ApplyModuleTint, // Default: "none"
-    #[default(Color(0.0, 0.0, 0.0, 1.0))]
+    #[default(Color::BLACK)]
     pub no_modules_tint: Color, //Default: no color
     pub animation_list: Option<Vec<AnimationElement>>,
     pub light: Option<LightDefinition>,
@@ -1456,7 +1829,7 @@ pub struct BeaconGraphicsSet {
 }
 
 /// <https://wiki.factorio.com/Types/BeaconModuleVisualizations>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct BeaconModuleVisualizations {
     pub art_style: String,
     #[default(false)]
@@ -1467,7 +1840,7 @@ pub struct BeaconModuleVisualizations {
 }
 
 /// <https://wiki.factorio.com/Types/BeaconModuleVisualization>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct BeaconModuleVisualization {
     #[default(false)]
     pub has_empty_slot: bool, // Default: false
@@ -1485,7 +1858,7 @@ pub struct BeaconModuleVisualization {
 }
 
 /// <https://wiki.factorio.com/Types/CharacterArmorAnimation>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct CharacterArmorAnimation {
     pub idle: RotatedAnimation,
     pub idle_with_gun: RotatedAnimation,
@@ -1497,7 +1870,7 @@ pub struct CharacterArmorAnimation {
 }
 
 /// <https://wiki.factorio.com/Types/WorkingVisualisation>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct WorkingVisualisation {
     #[default(RenderLayer::Object)]
     pub render_layer: RenderLayer, // Default: "object"
@@ -1533,6 +1906,29 @@ pub struct WorkingVisualisation {
     pub east_position: Option<Factorio2DVector>,
 }
 
+impl WorkingVisualisation {
+    pub fn render_layer(&self) -> RenderLayer {
+        self.render_layer
+    }
+
+    /// Animation drawn when machine is facing `direction`, `animation` is used if there is no
+    /// animation specific to the direction. Diagonal directions only use `animation`.
+    pub fn animation(&self, direction: Direction) -> Option<&Animation> {
+        let directional = match direction {
+            Direction::North => &self.north_animation,
+            Direction::East => &self.east_animation,
+            Direction::South => &self.south_animation,
+            Direction::West => &self.west_animation,
+            _ => &None,
+        };
+        directional.as_ref().or(self.animation.as_ref())
+    }
+
+    pub fn light(&self) -> Option<&LightDefinition> {
+        self.light.as_ref()
+    }
+}
+
 /// <https://wiki.factorio.com/Types/WorkingVisualisation#effect>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
@@ -1554,7 +1950,8 @@ impl<'lua> PrototypeFromLua<'lua> for WorkingVisualisationEffect {
 }
 
 /// <https://wiki.factorio.com/Types/ConnectableEntityGraphics>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct ConnectableEntityGraphics {
     pub single: Vec<SpriteVariation>,
     pub straight_vertical: Vec<SpriteVariation>,
@@ -1574,8 +1971,35 @@ pub struct ConnectableEntityGraphics {
     pub cross: Vec<SpriteVariation>,
 }
 
+impl ConnectableEntityGraphics {
+    // Every connection shape must have graphics, an empty table would leave it invisible
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        for (field, variations) in [
+            ("single", &self.single),
+            ("straight_vertical", &self.straight_vertical),
+            ("straight_horizontal", &self.straight_horizontal),
+            ("corner_right_down", &self.corner_right_down),
+            ("corner_left_down", &self.corner_left_down),
+            ("corner_right_up", &self.corner_right_up),
+            ("corner_left_up", &self.corner_left_up),
+            ("t_up", &self.t_up),
+            ("t_right", &self.t_right),
+            ("t_down", &self.t_down),
+            ("t_left", &self.t_left),
+            ("ending_up", &self.ending_up),
+            ("ending_right", &self.ending_right),
+            ("ending_down", &self.ending_down),
+            ("ending_left", &self.ending_left),
+            ("cross", &self.cross),
+        ] {
+            variations.require_variation_count(1, usize::MAX, "ConnectableEntityGraphics", field)?
+        }
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Types/MiningDrillGraphicsSet>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct MiningDrillGraphicsSet {
     pub animation: Option<Animation4Way>,
     pub idle_animation: Option<Animation4Way>,
@@ -1583,12 +2007,8 @@ pub struct MiningDrillGraphicsSet {
     pub always_draw_idle_animation: bool, // Default: false
     pub default_recipe_tint: Option<RecipeTint>,
     pub working_visualisations: Option<WorkingVisualisation>,
-    /// Only loaded if `shift_animation_waypoint_stop_duration` or `shift_animation_transition_duration` is not 0 // TODO
-    pub shift_animation_waypoints: Option<ShiftAnimationWaypoints>,
-    #[default(0_u16)]
-    pub shift_animation_waypoint_stop_duration: u16, // Default: 0
-    #[default(0_u16)]
-    pub shift_animation_transition_duration: u16, // Default: 0
+    #[use_self_forced]
+    pub shift_animation: ShiftAnimation,
     pub status_colors: Option<StatusColors>,
     #[default(0_u16)]
     pub drilling_vertical_movement_duration: u16, // Default: 0
@@ -1623,7 +2043,7 @@ impl CircuitConnectorRenderLayers {
 }
 
 /// <https://wiki.factorio.com/Prototype/OffshorePump#graphics_set>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct OffshorePumpGraphicsSet {
     pub animation: Animation4Way,
     #[default(RenderLayer::GroundPatch)]
@@ -1637,7 +2057,7 @@ pub struct OffshorePumpGraphicsSet {
 }
 
 /// <https://wiki.factorio.com/Prototype/Pipe#pictures>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct PipePictures {
     straight_vertical_single: Sprite,
     straight_vertical: Sprite,
@@ -1667,23 +2087,23 @@ pub struct PipePictures {
 }
 
 /// <https://wiki.factorio.com/Prototype/PipeToGround#pictures>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct PipeToGroundPictures {
     down: Sprite,
     up: Sprite,
     left: Sprite,
-    ritgh: Sprite,
+    right: Sprite,
 }
 
 /// <https://wiki.factorio.com/Prototype/Pump#fluid_wagon_connector_graphics>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct PumpConnectorGraphicsFluidWagon {
     load_animations: PumpConnectorGraphics,
     unload_animations: PumpConnectorGraphics,
 }
 
 /// <https://wiki.factorio.com/Types/PumpConnectorGraphics>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct PumpConnectorGraphics {
     north: Vec<PumpConnectorGraphicsMapping>,
     east: Vec<PumpConnectorGraphicsMapping>,
@@ -1692,7 +2112,7 @@ pub struct PumpConnectorGraphics {
 }
 
 /// <https://wiki.factorio.com/Types/PumpConnectorGraphics>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct PumpConnectorGraphicsMapping {
     standup_base: Option<Animation>,
     standup_top: Option<Animation>,
@@ -1702,23 +2122,101 @@ pub struct PumpConnectorGraphicsMapping {
 }
 
 /// <https://wiki.factorio.com/Prototype/SimpleEntity#pictures>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub enum SimpleEntityVisuals {
     Pictures(SpriteVariations),
     Picture(Sprite),
     Animations(AnimationVariations),
 }
 
+impl<'lua> PrototypeFromLua<'lua> for SimpleEntityVisuals {
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<Self> {
+        if let LuaValue::Table(t) = &value {
+            if let Some(visuals) =
+                t.get_prot::<_, Option<SpriteVariations>>("pictures", lua, data_table)?
+            {
+                Ok(Self::Pictures(visuals))
+            } else if let Some(visuals) =
+                t.get_prot::<_, Option<Sprite>>("picture", lua, data_table)?
+            {
+                Ok(Self::Picture(visuals))
+            } else if let Some(visuals) =
+                t.get_prot::<_, Option<AnimationVariations>>("animations", lua, data_table)?
+            {
+                Ok(Self::Animations(visuals))
+            } else {
+                Err(conversion_error(
+                    value.type_name(),
+                    "SimpleEntityVisuals",
+                    Some(
+                        "One of these properties has to exist: `pictures`, `picture`, `animations`"
+                            .into(),
+                    ),
+                ))
+            }
+        } else {
+            Err(conversion_error(
+                value.type_name(),
+                "SimpleEntityVisuals",
+                Some("expected table".into()),
+            ))
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/SimpleEntityWithOwner#pictures>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub enum SimpleEntityWithOwnerVisuals {
     Pictires(SpriteVariations),
     Pictire(Sprite4Way),
     Animations(AnimationVariations),
 }
 
+impl<'lua> PrototypeFromLua<'lua> for SimpleEntityWithOwnerVisuals {
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<Self> {
+        if let LuaValue::Table(t) = &value {
+            if let Some(visuals) =
+                t.get_prot::<_, Option<SpriteVariations>>("pictures", lua, data_table)?
+            {
+                Ok(Self::Pictires(visuals))
+            } else if let Some(visuals) =
+                t.get_prot::<_, Option<Sprite4Way>>("picture", lua, data_table)?
+            {
+                Ok(Self::Pictire(visuals))
+            } else if let Some(visuals) =
+                t.get_prot::<_, Option<AnimationVariations>>("animations", lua, data_table)?
+            {
+                Ok(Self::Animations(visuals))
+            } else {
+                Err(conversion_error(
+                    value.type_name(),
+                    "SimpleEntityWithOwnerVisuals",
+                    Some(
+                        "One of these properties has to exist: `pictures`, `picture`, `animations`"
+                            .into(),
+                    ),
+                ))
+            }
+        } else {
+            Err(conversion_error(
+                value.type_name(),
+                "SimpleEntityWithOwnerVisuals",
+                Some("expected table".into()),
+            ))
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Types/SpiderLegGraphicsSet>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct SpiderLegGraphicsSet {
     joint_turn_offset: f32, // Default: 0
     joint: Option<Sprite>,
@@ -1731,8 +2229,25 @@ pub struct SpiderLegGraphicsSet {
     lower_part_water_reflection: Option<SpiderLegPart>,
 }
 
+impl Default for SpiderLegGraphicsSet {
+    /// Graphics set without any sprites, same as an empty table
+    fn default() -> Self {
+        Self {
+            joint_turn_offset: 0.0,
+            joint: None,
+            joint_shadow: None,
+            upper_part: None,
+            lower_part: None,
+            upper_part_shadow: None,
+            lower_part_shadow: None,
+            upper_part_water_reflection: None,
+            lower_part_water_reflection: None,
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Types/SpiderLegPart>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct SpiderLegPart {
     top_end: Option<Sprite>,
     middle: Option<Sprite>,
@@ -1744,7 +2259,7 @@ pub struct SpiderLegPart {
 }
 
 /// <https://wiki.factorio.com/Prototype/StorageTank#pictures>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct StorageTankPictures {
     picture: Sprite4Way,
     window_background: Sprite,
@@ -1754,7 +2269,7 @@ pub struct StorageTankPictures {
 }
 
 /// <https://wiki.factorio.com/Prototype/TrainStop#light1>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct TrainStopLight {
     sprite: Sprite4Way,
     red_picture: Sprite4Way,
@@ -1771,7 +2286,7 @@ pub struct TrainStopDrawingBoxes {
 }
 
 /// <https://wiki.factorio.com/Prototype/TransportBeltConnectable#belt_animation_set>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct BeltAnimationSet {
     animation_set: RotatedAnimation,
     east_index: u8,           // Default: 1
@@ -1791,7 +2306,7 @@ pub struct BeltAnimationSet {
 }
 
 /// <https://wiki.factorio.com/Prototype/TransportBeltConnectable#belt_horizontal>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct BeltGraphicsSet {
     belt_horizontal: Animation,
     belt_vertical: Animation,
@@ -1806,7 +2321,7 @@ pub struct BeltGraphicsSet {
 }
 
 /// <https://wiki.factorio.com/Prototype/Loader1x1#structure>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct BeltStructure {
     direction_in: Sprite4Way,
     direction_out: Sprite4Way,
@@ -1815,7 +2330,7 @@ pub struct BeltStructure {
 }
 
 /// <https://wiki.factorio.com/Prototype/LinkedBelt#structure>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct BeltStructureWithSideLoading {
     base_structure: BeltStructure,
     direction_in_side_loading: Option<Sprite4Way>,
@@ -1823,7 +2338,7 @@ pub struct BeltStructureWithSideLoading {
 }
 
 /// <https://wiki.factorio.com/Types/TransportBeltConnectorFrame>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct TransportBeltConnectorFrame {
     frame_main: AnimationVariations,
     frame_shadow: AnimationVariations,
@@ -1865,27 +2380,74 @@ pub struct BeltAnimationSetIndexes {
 }
 
 /// <https://wiki.factorio.com/Prototype/Tree#pictures>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub enum TreeVisuals {
     Pictures(TreePictures),
     Variations(Vec<TreePrototypeVariation>), // Non-empty array
 }
 
+impl TreeVisuals {
+    /// Number of visual variations, which `variation_weights` of the tree have to match
+    pub fn variation_count(&self) -> usize {
+        match self {
+            Self::Pictures(tp) => tp.pictures.variation_count(),
+            Self::Variations(variations) => variations.len(),
+        }
+    }
+}
+
+impl<'lua> PrototypeFromLua<'lua> for TreeVisuals {
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua mlua::Lua,
+        data_table: &mut DataTable,
+    ) -> mlua::Result<Self> {
+        let type_name = value.type_name();
+        if let mlua::Value::Table(p_table) = &value {
+            if let Some(variations) = p_table.get_as::<Option<mlua::Value>>("variations")? {
+                let variations =
+                    Vec::<TreePrototypeVariation>::prototype_from_lua(variations, lua, data_table)?;
+                if variations.is_empty() {
+                    return Err(conversion_error(
+                        type_name,
+                        "TreeVisuals",
+                        Some("`variations` can't be empty".into()),
+                    ));
+                }
+                Ok(Self::Variations(variations))
+            } else {
+                Ok(Self::Pictures(TreePictures::prototype_from_lua(
+                    value, lua, data_table,
+                )?))
+            }
+        } else {
+            Err(conversion_error(
+                type_name,
+                "TreeVisuals",
+                Some("Expected table".into()),
+            ))
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Tree#pictures>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct TreePictures {
     pictures: SpriteVariations,
+    #[rename("colors")]
+    #[default(vec![])]
     color: Vec<Color>,
 }
 
 /// <https://wiki.factorio.com/Prototype/Tree#variations>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct TreePrototypeVariation {
     trunk: Animation,
     leaves: Animation,
     leaf_generation: CreateParticleTriggerEffectItem,
     branch_generation: CreateParticleTriggerEffectItem,
     shadow: Option<Animation>,
+    #[default(shadow.as_ref().map_or(0, |s| s.frame_count().saturating_sub(1)))]
     disable_shadow_distortion_beginning_at_frame: u32, // Default: shadow.frame_count - 1
     normal: Option<Animation>,
     overlay: Option<Animation>,
@@ -1893,7 +2455,7 @@ pub struct TreePrototypeVariation {
 }
 
 /// <https://wiki.factorio.com/Types/SpiderVehicleGraphicsSet>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct SpiderVehicleGraphicsSet {
     base_animation: Option<RotatedAnimation>,
     shadow_base_animation: Option<RotatedAnimation>,
@@ -1913,8 +2475,32 @@ pub struct SpiderVehicleGraphicsSet {
     light_positions: Vec<Vec<Factorio2DVector>>,
 }
 
+impl Default for SpiderVehicleGraphicsSet {
+    /// Graphics set without any animations or lights, same as an empty table
+    fn default() -> Self {
+        Self {
+            base_animation: None,
+            shadow_base_animation: None,
+            animation: None,
+            shadow_animation: None,
+            base_render_layer: RenderLayer::HigherObjectUnder,
+            render_layer: RenderLayer::WiresAbove,
+            autopilot_destination_visualisation_render_layer: RenderLayer::Object,
+            light: None,
+            eye_light: None,
+            autopilot_destination_on_map_visualisation: None,
+            autopilot_destination_queue_on_map_visualisation: None,
+            autopilot_destination_visualisation: None,
+            autopilot_destination_queue_visualisation: None,
+            autopilot_path_visualisation_line_width: 0.125,
+            autopilot_path_visualisation_on_map_line_width: 2.0,
+            light_positions: vec![],
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Wall#pictures>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct WallPictures {
     single: SpriteVariations,
     straight_vertical: SpriteVariations,
@@ -21,23 +21,30 @@ pub use tile_transitions::*;
 pub use tip_trigger::*;
 pub use trigger::*;
 
-use super::{Base, DataTable, LocalisedString, PrototypeFromLua};
-use crate::prototypes::{GetPrototype, PrototypesErr};
+use super::{Base, DataTable, LocalisedString, Prototype, PrototypeFromLua};
+use crate::prototypes::lua_compat::{
+    conversion_error, impl_from_lua, impl_into_lua, LuaValue, TableGetCompat,
+};
+use crate::prototypes::{GetPrototype, PrototypesErr, ResourceRecord, ResourceType, ResourceWalk};
 use crate::util::fixed_serde;
 use factorio_lib_rs_derive::prot_from_str;
 use fixed::types::I24F8;
-use mlua::{prelude::*, FromLua, Lua, ToLua, Value};
+use mlua::{prelude::*, Lua, Value};
 use serde::{Deserialize, Deserializer};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
 use std::collections::HashMap;
 use std::convert::From;
+#[cfg(feature = "std")]
 use std::ffi::OsStr;
 use std::fmt::{self, Display};
 use std::iter::FromIterator;
 use std::marker::PhantomData;
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
+#[cfg(feature = "std")]
 use std::path::Path;
 use std::str::FromStr;
-use strum_macros::{AsRefStr, EnumDiscriminants, EnumString};
+use strum_macros::{AsRefStr, EnumDiscriminants, EnumIter, EnumString};
 
 /// <https://wiki.factorio.com/Types/FileName>
 #[derive(Debug, Clone, Deserialize)]
@@ -47,16 +54,28 @@ pub struct FileName<FT: FileType = ImageFileType> {
     phantom: PhantomData<FT>,
 }
 
+impl<FT: FileType> FileName<FT> {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            phantom: PhantomData,
+        }
+    }
+}
+
 pub trait FileType: Copy {
     fn validate_extension(ext: &str) -> bool;
+    #[cfg(feature = "std")]
     fn validate_extension_osstr(ext: &OsStr) -> bool {
         ext.to_str()
             .map(|extstr| Self::validate_extension(extstr))
             .unwrap_or(false)
     }
+    #[cfg(feature = "std")]
     fn validate_path(path: &Path) -> Option<bool> {
         Some(Self::validate_extension_osstr(path.extension()?))
     }
+    #[cfg(feature = "std")]
     fn validate_filename_osstr(filename: &OsStr) -> Option<bool> {
         Self::validate_filename(filename.to_str()?)
     }
@@ -120,6 +139,17 @@ pub struct Factorio3DVector(pub f32, pub f32, pub f32);
 #[serde(transparent)]
 pub struct KeySequence(pub String);
 
+impl KeySequence {
+    /// Empty sequence means that the input is unassigned
+    pub fn is_empty(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+}
+
+impl_from_lua!(KeySequence, |lua_value, lua| {
+    Ok(Self(lua.unpack(lua_value)?))
+});
+
 /// <https://wiki.factorio.com/Types/BoundingBox>
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
 pub struct BoundingBox(pub Position, pub Position);
@@ -248,12 +278,7 @@ pub struct Color {
 
 impl Default for Color {
     fn default() -> Self {
-        Self {
-            r: 0.0,
-            g: 0.0,
-            b: 0.0,
-            a: 1.0,
-        }
+        Self::BLACK
     }
 }
 
@@ -283,7 +308,16 @@ fn default_alpha() -> f32 {
 }
 
 impl Color {
-    pub fn new_rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+    pub const WHITE: Self = Self::new_rgba(1.0, 1.0, 1.0, 1.0);
+    pub const BLACK: Self = Self::new_rgba(0.0, 0.0, 0.0, 1.0);
+    pub const TRANSPARENT: Self = Self::new_rgba(0.0, 0.0, 0.0, 0.0);
+    pub const RED: Self = Self::new_rgba(1.0, 0.0, 0.0, 1.0);
+    pub const GREEN: Self = Self::new_rgba(0.0, 1.0, 0.0, 1.0);
+    pub const BLUE: Self = Self::new_rgba(0.0, 0.0, 1.0, 1.0);
+    /// Default color of smoke prototypes
+    pub const SMOKE: Self = Self::new_rgba(0.375, 0.375, 0.375, 0.375);
+
+    pub const fn new_rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
         Self { r, g, b, a }
     }
 
@@ -295,14 +329,30 @@ impl Color {
         Self { r, g, b, a }
     }
 
-    pub fn new_rgb(r: f32, g: f32, b: f32) -> Self {
+    pub const fn new_rgb(r: f32, g: f32, b: f32) -> Self {
         // r, g, b default is 0
         Self { r, g, b, a: 1.0 }
     }
+
+    /// Same color with all components clamped to `[0, 1]`
+    pub fn clamped(self) -> Self {
+        Self {
+            r: self.r.clamp(0.0, 1.0),
+            g: self.g.clamp(0.0, 1.0),
+            b: self.b.clamp(0.0, 1.0),
+            a: self.a.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Same color with alpha replaced by `a`
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
 }
 
 /// <https://lua-api.factorio.com/latest/defines.html#defines.difficulty_settings>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum DifficultySetting {
@@ -312,6 +362,7 @@ pub enum DifficultySetting {
 
 /// <https://wiki.factorio.com/Prototype/MapSettings#difficulty_settings>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum ResearchQueueSetting {
@@ -322,6 +373,7 @@ pub enum ResearchQueueSetting {
 
 /// <https://wiki.factorio.com/Tutorial:Mod_settings#The_setting_type_property>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum ModSettingType {
@@ -330,6 +382,40 @@ pub enum ModSettingType {
     RuntimePerUser,
 }
 
+#[test]
+fn string_enums_deserialize() {
+    assert_eq!(
+        serde_json::from_str::<ModSettingType>(r#""runtime-per-user""#).unwrap(),
+        ModSettingType::RuntimePerUser
+    );
+    assert_eq!(
+        serde_json::from_str::<RenderLayer>(r#""higher-object-under""#).unwrap(),
+        RenderLayer::HigherObjectUnder
+    );
+    assert!(serde_json::from_str::<ModSettingType>(r#""per-user""#).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn string_enums_serde_roundtrip() {
+    fn roundtrip<T>(value: T, json: &str)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + PartialEq + std::fmt::Debug,
+    {
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert_eq!(serde_json::from_str::<T>(json).unwrap(), value);
+    }
+    roundtrip(ModSettingType::Startup, r#""startup""#);
+    roundtrip(ModSettingType::RuntimePerUser, r#""runtime-per-user""#);
+    roundtrip(RenderLayer::HigherObjectUnder, r#""higher-object-under""#);
+    roundtrip(BlendMode::AdditiveSoft, r#""additive-soft""#);
+    roundtrip(ForceCondition::NotSame, r#""not-same""#);
+    roundtrip(
+        crate::prototypes::PrototypeType::AssemblingMachine,
+        r#""assembling-machine""#,
+    );
+}
+
 /// <https://wiki.factorio.com/Types/MapGenPreset>
 #[derive(Debug, Clone, Deserialize)]
 #[serde(from = "MapGenPresetIntermediate")]
@@ -778,6 +864,7 @@ pub enum MouseCursorType {
 
 /// <https://wiki.factorio.com/Prototype/MouseCursor#system_cursor>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum SystemCursor {
@@ -807,6 +894,92 @@ pub enum IconSpecification {
     Icon(IconSpec),
 }
 
+impl IconSpecification {
+    // Icon file, size and mipmap count of each layer
+    fn layers(&self) -> Vec<(&FileName, i16, u8)> {
+        match self {
+            Self::Icon(icon) => vec![(&icon.icon, icon.icon_size, icon.icon_mipmaps)],
+            Self::Icons(icons) => icons
+                .icons
+                .iter()
+                .map(|data| (&data.icon, data.icon_size, data.icon_mipmaps))
+                .collect(),
+        }
+    }
+
+    // Size, mipmap count, shift and scale of each layer
+    fn layout(&self) -> Vec<(i16, u8, Factorio2DVector, f64)> {
+        match self {
+            Self::Icon(icon) => vec![(
+                icon.icon_size,
+                icon.icon_mipmaps,
+                Factorio2DVector(0.0, 0.0),
+                1.0,
+            )],
+            Self::Icons(icons) => icons
+                .icons
+                .iter()
+                .map(|data| (data.icon_size, data.icon_mipmaps, data.shift, data.scale))
+                .collect(),
+        }
+    }
+
+    /// Whether both icons have the same number of layers with the same sizes, shifts and scales,
+    /// so that one can be drawn over the other as a mask
+    pub fn matches_layout(&self, other: &IconSpecification) -> bool {
+        self.layout() == other.layout()
+    }
+}
+
+#[test]
+fn icon_layout_match() {
+    let icon = |name: &str, icon_size| {
+        IconSpecification::Icon(IconSpec {
+            icon: FileName::new(name.into()),
+            icon_size,
+            icon_mipmaps: 4,
+        })
+    };
+    let layer = |name: &str, scale| IconData {
+        icon: FileName::new(name.into()),
+        icon_size: 64,
+        tint: Color::default(),
+        shift: Factorio2DVector(0.0, 0.0),
+        scale,
+        icon_mipmaps: 4,
+    };
+    let two_layers = |scale| {
+        IconSpecification::Icons(IconsSpec {
+            icons: vec![layer("base", 1.0), layer("overlay", scale)],
+            icon_mipmaps: 4,
+        })
+    };
+    assert!(icon("tintable", 64).matches_layout(&icon("mask", 64)));
+    assert!(!icon("tintable", 64).matches_layout(&icon("mask", 32)));
+    assert!(two_layers(0.5).matches_layout(&two_layers(0.5)));
+    assert!(!two_layers(0.5).matches_layout(&two_layers(1.0)));
+    assert!(!icon("base", 64).matches_layout(&two_layers(0.5)));
+}
+
+impl ResourceWalk for IconSpecification {
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>) {
+        for (icon, size, mipmaps) in self.layers() {
+            // Mipmaps are placed to the right of the icon, each one half the size of previous one
+            let width: u32 = (0..mipmaps.max(1) as u32)
+                .map(|level| size as u32 >> level)
+                .sum();
+            records.push(ResourceRecord {
+                path: icon.name.clone(),
+                resource_type: ResourceType::Image(
+                    width.min(SpriteSizeType::MAX as u32) as SpriteSizeType,
+                    size,
+                    None,
+                ),
+            })
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Types/IconSpecification#Prototype_properties:_Option_2>
 #[derive(Debug, Clone, Deserialize)]
 pub struct IconSpec {
@@ -984,9 +1157,43 @@ fn energy_parse() {
     assert!(Energy::from_str("").is_err())
 }
 
+/// Duration in game ticks, the game runs at 60 ticks per second
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Ticks(pub u32);
+
+impl Ticks {
+    pub const PER_SECOND: u32 = 60;
+
+    /// Duration closest to given amount of seconds, rounded to whole ticks
+    pub fn from_seconds(seconds: f64) -> Self {
+        Self((seconds * Self::PER_SECOND as f64).round() as u32)
+    }
+
+    pub fn as_seconds(&self) -> f64 {
+        self.0 as f64 / Self::PER_SECOND as f64
+    }
+}
+
+impl Display for Ticks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ticks ({}s)", self.0, self.as_seconds())
+    }
+}
+
+impl_from_lua!(Ticks, |lua_value, lua| { Ok(Self(lua.unpack(lua_value)?)) });
+
+#[test]
+fn ticks_seconds() {
+    assert_eq!(Ticks(900).as_seconds(), 15.0);
+    assert_eq!(Ticks::from_seconds(3.0), Ticks(180));
+    assert_eq!(Ticks::from_seconds(0.01), Ticks(1));
+    assert_eq!(Ticks(30).to_string(), "30 ticks (0.5s)");
+}
+
 /// <https://wiki.factorio.com/Prototype/ProduceAchievement#item_product>
 /// <https://wiki.factorio.com/Prototype/ProducePerHourAchievement#item_product>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
 pub enum ProductType {
     #[serde(rename = "item_product")]
     Item(String),
@@ -994,6 +1201,15 @@ pub enum ProductType {
     Fluid(String),
 }
 
+impl ProductType {
+    /// Name of the item or fluid
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Item(name) | Self::Fluid(name) => name,
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/ResearchAchievement>
 #[derive(Debug, Clone, Deserialize)]
 #[serde(from = "ResearchTargetIntermediate")]
@@ -1021,7 +1237,8 @@ impl From<ResearchTargetIntermediate> for ResearchTarget {
 }
 
 /// <https://wiki.factorio.com/Prototype/AutoplaceControl#category>
-#[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Copy, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum AutoplaceControlCategory {
@@ -1030,8 +1247,33 @@ pub enum AutoplaceControlCategory {
     Enemy,
 }
 
+impl FromStr for AutoplaceControlCategory {
+    type Err = PrototypesErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "resource" => Ok(Self::Resource),
+            "terrain" => Ok(Self::Terrain),
+            "enemy" => Ok(Self::Enemy),
+            _ => Err(PrototypesErr::InvalidTypeStr(
+                "AutoplaceControlCategory".into(),
+                s.into(),
+            )),
+        }
+    }
+}
+
+impl Display for AutoplaceControlCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+prot_from_str!(AutoplaceControlCategory);
+
 /// <https://wiki.factorio.com/Prototype/CustomInput#consuming>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum ConsumingType {
@@ -1039,8 +1281,17 @@ pub enum ConsumingType {
     GameOnly,
 }
 
+impl Display for ConsumingType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+prot_from_str!(ConsumingType);
+
 /// <https://wiki.factorio.com/Prototype/CustomInput#action>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum CustomInputAction {
@@ -1051,6 +1302,133 @@ pub enum CustomInputAction {
     ToggleEquipmentMovementBonus,
 }
 
+impl Display for CustomInputAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+prot_from_str!(CustomInputAction);
+
+#[test]
+fn custom_input_enums_round_trip() {
+    for action in [
+        CustomInputAction::Lua,
+        CustomInputAction::SpawnItem,
+        CustomInputAction::TogglePersonalLogisticRequests,
+    ] {
+        assert_eq!(action.to_string().parse::<CustomInputAction>(), Ok(action))
+    }
+    assert_eq!(ConsumingType::GameOnly.to_string(), "game-only");
+    assert!("game".parse::<ConsumingType>().is_err());
+}
+
+/// Names of built-in controls that `linked_game_control` of
+/// [CustomInput](crate::prototypes::CustomInput) can refer to, as shown in the controls settings
+/// of the game
+pub const GAME_CONTROLS: &[&str] = &[
+    "move-up",
+    "move-down",
+    "move-left",
+    "move-right",
+    "open-character-gui",
+    "open-technology-gui",
+    "close-gui",
+    "toggle-map",
+    "toggle-console",
+    "confirm-gui",
+    "toggle-menu",
+    "pause-game",
+    "toggle-driving",
+    "shoot-enemy",
+    "shoot-selected",
+    "next-weapon",
+    "pick-items",
+    "pick-item",
+    "rotate",
+    "reverse-rotate",
+    "flip-blueprint-horizontal",
+    "flip-blueprint-vertical",
+    "mine",
+    "build",
+    "build-ghost",
+    "build-with-obstacle-avoidance",
+    "drop-cursor",
+    "clear-cursor",
+    "cursor-split",
+    "cursor-transfer",
+    "inventory-split",
+    "inventory-transfer",
+    "stack-split",
+    "stack-transfer",
+    "fast-entity-split",
+    "fast-entity-transfer",
+    "open-gui",
+    "open-item",
+    "copy-entity-settings",
+    "paste-entity-settings",
+    "smart-pipette",
+    "toggle-filter",
+    "show-info",
+    "toggle-entity",
+    "zoom-in",
+    "zoom-out",
+    "alt-zoom-in",
+    "alt-zoom-out",
+    "larger-terrain-building-area",
+    "smaller-terrain-building-area",
+    "select-for-blueprint",
+    "select-for-cancel-deconstruct",
+    "reverse-select",
+    "undo",
+    "copy",
+    "cut",
+    "paste",
+    "cycle-clipboard-forwards",
+    "cycle-clipboard-backwards",
+    "import-string",
+    "place-in-chat",
+    "focus-search",
+    "connect-train",
+    "disconnect-train",
+    "toggle-blueprint-library",
+    "toggle-production-gui",
+    "toggle-logistic-networks",
+    "toggle-personal-roboport",
+    "toggle-personal-logistic-requests",
+    "toggle-equipment-movement-bonus",
+    "quick-bar-button-1",
+    "quick-bar-button-2",
+    "quick-bar-button-3",
+    "quick-bar-button-4",
+    "quick-bar-button-5",
+    "quick-bar-button-6",
+    "quick-bar-button-7",
+    "quick-bar-button-8",
+    "quick-bar-button-9",
+    "quick-bar-button-10",
+    "action-bar-select-page-1",
+    "action-bar-select-page-2",
+    "action-bar-select-page-3",
+    "action-bar-select-page-4",
+    "action-bar-select-page-5",
+    "action-bar-select-page-6",
+    "action-bar-select-page-7",
+    "action-bar-select-page-8",
+    "action-bar-select-page-9",
+    "action-bar-select-page-10",
+    "rotate-active-quick-bars",
+    "next-active-quick-bar",
+    "previous-active-quick-bar",
+    "activate-tooltip",
+    "toggle-gui-debug",
+    "toggle-gui-style-view",
+    "toggle-gui-shadows",
+    "toggle-gui-glows",
+    "open-prototypes-gui",
+    "open-prototype-explorer-gui",
+];
+
 /// <https://wiki.factorio.com/Types/CollisionMask>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Hash, Default)]
 pub struct CollisionMask(pub(crate) u64);
@@ -1131,9 +1509,113 @@ impl<T: AsRef<str>> FromIterator<T> for CollisionMask {
 }
 
 impl CollisionMask {
+    /// Names of layers and flags, in order of their bits
+    const LAYER_NAMES: [&'static str; 58] = [
+        "ground-tile",
+        "water-tile",
+        "resource-layer",
+        "doodad-layer",
+        "floor-layer",
+        "item-layer",
+        "ghost-layer",
+        "object-layer",
+        "player-layer",
+        "train-layer",
+        "rail-layer",
+        "transport-belt-layer",
+        "layer-13",
+        "layer-14",
+        "layer-15",
+        "layer-16",
+        "layer-17",
+        "layer-18",
+        "layer-19",
+        "layer-20",
+        "layer-21",
+        "layer-22",
+        "layer-23",
+        "layer-24",
+        "layer-25",
+        "layer-26",
+        "layer-27",
+        "layer-28",
+        "layer-29",
+        "layer-30",
+        "layer-31",
+        "layer-32",
+        "layer-33",
+        "layer-34",
+        "layer-35",
+        "layer-36",
+        "layer-37",
+        "layer-38",
+        "layer-39",
+        "layer-40",
+        "layer-41",
+        "layer-42",
+        "layer-43",
+        "layer-44",
+        "layer-45",
+        "layer-46",
+        "layer-47",
+        "layer-48",
+        "layer-49",
+        "layer-50",
+        "layer-51",
+        "layer-52",
+        "layer-53",
+        "layer-54",
+        "layer-55",
+        "not-colliding-with-itself",
+        "consider-tile-transitions",
+        "colliding-with-tiles-only",
+    ];
+
     pub fn without_flags(&self) -> Self {
         Self(self.0 & (Self::NOT_COLLIDING_WITH_ITSELF.0 - 1))
     }
+
+    /// Names of layers and flags in this mask, as used in `collision_mask` of prototypes
+    pub fn layers(&self) -> Vec<&'static str> {
+        Self::LAYER_NAMES
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.0 & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+
+    /// Whether two masks share a layer, meaning prototypes with them collide. Flags like
+    /// `not-colliding-with-itself` are ignored.
+    pub fn intersects(&self, other: Self) -> bool {
+        let layers = Self::NOT_COLLIDING_WITH_ITSELF.0 - 1;
+        self.0 & other.0 & layers != 0
+    }
+}
+
+#[test]
+fn collision_mask_intersects() {
+    let water = CollisionMask::WATER_TILE
+        | CollisionMask::ITEM_LAYER
+        | CollisionMask::RESOURCE_LAYER
+        | CollisionMask::PLAYER_LAYER
+        | CollisionMask::DOODAD_LAYER;
+    let building = CollisionMask::ITEM_LAYER
+        | CollisionMask::OBJECT_LAYER
+        | CollisionMask::PLAYER_LAYER
+        | CollisionMask::WATER_TILE;
+    let offshore_pump = CollisionMask::OBJECT_LAYER | CollisionMask::TRAIN_LAYER;
+    assert!(building.intersects(water));
+    assert!(!offshore_pump.intersects(water));
+    assert!(offshore_pump.intersects(building));
+    assert!(!CollisionMask::NOT_COLLIDING_WITH_ITSELF
+        .intersects(CollisionMask::NOT_COLLIDING_WITH_ITSELF));
+}
+
+impl Display for CollisionMask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.layers().join(", "))
+    }
 }
 
 #[derive(Debug, Clone, Error)]
@@ -1214,186 +1696,184 @@ impl FromStr for CollisionMask {
     }
 }
 
-impl<'lua> ToLua<'lua> for CollisionMask {
-    fn to_lua(self, lua: &'lua Lua) -> LuaResult<Value<'lua>> {
-        let mut result: HashMap<String, bool> = HashMap::new();
-        if (self & Self::GROUND_TILE).0 > 0 {
-            result.insert("ground-tile".into(), true);
-        };
-        if (self & Self::WATER_TILE).0 > 0 {
-            result.insert("water-tile".into(), true);
-        };
-        if (self & Self::RESOURCE_LAYER).0 > 0 {
-            result.insert("resource-layer".into(), true);
-        };
-        if (self & Self::DOODAD_LAYER).0 > 0 {
-            result.insert("doodad-layer".into(), true);
-        };
-        if (self & Self::FLOOR_LAYER).0 > 0 {
-            result.insert("floor-layer".into(), true);
-        };
-        if (self & Self::ITEM_LAYER).0 > 0 {
-            result.insert("item-layer".into(), true);
-        };
-        if (self & Self::GHOST_LAYER).0 > 0 {
-            result.insert("ghost-layer".into(), true);
-        };
-        if (self & Self::OBJECT_LAYER).0 > 0 {
-            result.insert("object-layer".into(), true);
-        };
-        if (self & Self::PLAYER_LAYER).0 > 0 {
-            result.insert("player-layer".into(), true);
-        };
-        if (self & Self::TRAIN_LAYER).0 > 0 {
-            result.insert("train-layer".into(), true);
-        };
-        if (self & Self::RAIL_LAYER).0 > 0 {
-            result.insert("rail-layer".into(), true);
-        };
-        if (self & Self::TRANSPORT_BELT_LAYER).0 > 0 {
-            result.insert("transport-belt-layer".into(), true);
-        };
-        if (self & Self::NOT_COLLIDING_WITH_ITSELF).0 > 0 {
-            result.insert("not-colliding-with-itself".into(), true);
-        };
-        if (self & Self::CONSIDER_TILE_TRANSITIONS).0 > 0 {
-            result.insert("consider-tile-transitions".into(), true);
-        };
-        if (self & Self::COLLIDING_WITH_TILES_ONLY).0 > 0 {
-            result.insert("colliding-with-tiles-only".into(), true);
-        };
-        if (self & Self::LAYER_13).0 > 0 {
-            result.insert("layer-13".into(), true);
-        };
-        if (self & Self::LAYER_14).0 > 0 {
-            result.insert("layer-14".into(), true);
-        };
-        if (self & Self::LAYER_15).0 > 0 {
-            result.insert("layer-15".into(), true);
-        };
-        if (self & Self::LAYER_16).0 > 0 {
-            result.insert("layer-16".into(), true);
-        };
-        if (self & Self::LAYER_17).0 > 0 {
-            result.insert("layer-17".into(), true);
-        };
-        if (self & Self::LAYER_18).0 > 0 {
-            result.insert("layer-18".into(), true);
-        };
-        if (self & Self::LAYER_19).0 > 0 {
-            result.insert("layer-19".into(), true);
-        };
-        if (self & Self::LAYER_20).0 > 0 {
-            result.insert("layer-20".into(), true);
-        };
-        if (self & Self::LAYER_21).0 > 0 {
-            result.insert("layer-21".into(), true);
-        };
-        if (self & Self::LAYER_22).0 > 0 {
-            result.insert("layer-22".into(), true);
-        };
-        if (self & Self::LAYER_23).0 > 0 {
-            result.insert("layer-23".into(), true);
-        };
-        if (self & Self::LAYER_24).0 > 0 {
-            result.insert("layer-24".into(), true);
-        };
-        if (self & Self::LAYER_25).0 > 0 {
-            result.insert("layer-25".into(), true);
-        };
-        if (self & Self::LAYER_26).0 > 0 {
-            result.insert("layer-26".into(), true);
-        };
-        if (self & Self::LAYER_27).0 > 0 {
-            result.insert("layer-27".into(), true);
-        };
-        if (self & Self::LAYER_28).0 > 0 {
-            result.insert("layer-28".into(), true);
-        };
-        if (self & Self::LAYER_29).0 > 0 {
-            result.insert("layer-29".into(), true);
-        };
-        if (self & Self::LAYER_30).0 > 0 {
-            result.insert("layer-30".into(), true);
-        };
-        if (self & Self::LAYER_31).0 > 0 {
-            result.insert("layer-31".into(), true);
-        };
-        if (self & Self::LAYER_32).0 > 0 {
-            result.insert("layer-32".into(), true);
-        };
-        if (self & Self::LAYER_33).0 > 0 {
-            result.insert("layer-33".into(), true);
-        };
-        if (self & Self::LAYER_34).0 > 0 {
-            result.insert("layer-34".into(), true);
-        };
-        if (self & Self::LAYER_35).0 > 0 {
-            result.insert("layer-35".into(), true);
-        };
-        if (self & Self::LAYER_36).0 > 0 {
-            result.insert("layer-36".into(), true);
-        };
-        if (self & Self::LAYER_37).0 > 0 {
-            result.insert("layer-37".into(), true);
-        };
-        if (self & Self::LAYER_38).0 > 0 {
-            result.insert("layer-38".into(), true);
-        };
-        if (self & Self::LAYER_39).0 > 0 {
-            result.insert("layer-39".into(), true);
-        };
-        if (self & Self::LAYER_40).0 > 0 {
-            result.insert("layer-40".into(), true);
-        };
-        if (self & Self::LAYER_41).0 > 0 {
-            result.insert("layer-41".into(), true);
-        };
-        if (self & Self::LAYER_42).0 > 0 {
-            result.insert("layer-42".into(), true);
-        };
-        if (self & Self::LAYER_43).0 > 0 {
-            result.insert("layer-43".into(), true);
-        };
-        if (self & Self::LAYER_44).0 > 0 {
-            result.insert("layer-44".into(), true);
-        };
-        if (self & Self::LAYER_45).0 > 0 {
-            result.insert("layer-45".into(), true);
-        };
-        if (self & Self::LAYER_46).0 > 0 {
-            result.insert("layer-46".into(), true);
-        };
-        if (self & Self::LAYER_47).0 > 0 {
-            result.insert("layer-47".into(), true);
-        };
-        if (self & Self::LAYER_48).0 > 0 {
-            result.insert("layer-48".into(), true);
-        };
-        if (self & Self::LAYER_49).0 > 0 {
-            result.insert("layer-49".into(), true);
-        };
-        if (self & Self::LAYER_50).0 > 0 {
-            result.insert("layer-50".into(), true);
-        };
-        if (self & Self::LAYER_51).0 > 0 {
-            result.insert("layer-51".into(), true);
-        };
-        if (self & Self::LAYER_52).0 > 0 {
-            result.insert("layer-52".into(), true);
-        };
-        if (self & Self::LAYER_53).0 > 0 {
-            result.insert("layer-53".into(), true);
-        };
-        if (self & Self::LAYER_54).0 > 0 {
-            result.insert("layer-54".into(), true);
-        };
-        if (self & Self::LAYER_55).0 > 0 {
-            result.insert("layer-55".into(), true);
-        };
-        result.to_lua(lua)
-    }
-}
+impl_into_lua!(CollisionMask, |self, lua| {
+    let mut result: HashMap<String, bool> = HashMap::new();
+    if (self & Self::GROUND_TILE).0 > 0 {
+        result.insert("ground-tile".into(), true);
+    };
+    if (self & Self::WATER_TILE).0 > 0 {
+        result.insert("water-tile".into(), true);
+    };
+    if (self & Self::RESOURCE_LAYER).0 > 0 {
+        result.insert("resource-layer".into(), true);
+    };
+    if (self & Self::DOODAD_LAYER).0 > 0 {
+        result.insert("doodad-layer".into(), true);
+    };
+    if (self & Self::FLOOR_LAYER).0 > 0 {
+        result.insert("floor-layer".into(), true);
+    };
+    if (self & Self::ITEM_LAYER).0 > 0 {
+        result.insert("item-layer".into(), true);
+    };
+    if (self & Self::GHOST_LAYER).0 > 0 {
+        result.insert("ghost-layer".into(), true);
+    };
+    if (self & Self::OBJECT_LAYER).0 > 0 {
+        result.insert("object-layer".into(), true);
+    };
+    if (self & Self::PLAYER_LAYER).0 > 0 {
+        result.insert("player-layer".into(), true);
+    };
+    if (self & Self::TRAIN_LAYER).0 > 0 {
+        result.insert("train-layer".into(), true);
+    };
+    if (self & Self::RAIL_LAYER).0 > 0 {
+        result.insert("rail-layer".into(), true);
+    };
+    if (self & Self::TRANSPORT_BELT_LAYER).0 > 0 {
+        result.insert("transport-belt-layer".into(), true);
+    };
+    if (self & Self::NOT_COLLIDING_WITH_ITSELF).0 > 0 {
+        result.insert("not-colliding-with-itself".into(), true);
+    };
+    if (self & Self::CONSIDER_TILE_TRANSITIONS).0 > 0 {
+        result.insert("consider-tile-transitions".into(), true);
+    };
+    if (self & Self::COLLIDING_WITH_TILES_ONLY).0 > 0 {
+        result.insert("colliding-with-tiles-only".into(), true);
+    };
+    if (self & Self::LAYER_13).0 > 0 {
+        result.insert("layer-13".into(), true);
+    };
+    if (self & Self::LAYER_14).0 > 0 {
+        result.insert("layer-14".into(), true);
+    };
+    if (self & Self::LAYER_15).0 > 0 {
+        result.insert("layer-15".into(), true);
+    };
+    if (self & Self::LAYER_16).0 > 0 {
+        result.insert("layer-16".into(), true);
+    };
+    if (self & Self::LAYER_17).0 > 0 {
+        result.insert("layer-17".into(), true);
+    };
+    if (self & Self::LAYER_18).0 > 0 {
+        result.insert("layer-18".into(), true);
+    };
+    if (self & Self::LAYER_19).0 > 0 {
+        result.insert("layer-19".into(), true);
+    };
+    if (self & Self::LAYER_20).0 > 0 {
+        result.insert("layer-20".into(), true);
+    };
+    if (self & Self::LAYER_21).0 > 0 {
+        result.insert("layer-21".into(), true);
+    };
+    if (self & Self::LAYER_22).0 > 0 {
+        result.insert("layer-22".into(), true);
+    };
+    if (self & Self::LAYER_23).0 > 0 {
+        result.insert("layer-23".into(), true);
+    };
+    if (self & Self::LAYER_24).0 > 0 {
+        result.insert("layer-24".into(), true);
+    };
+    if (self & Self::LAYER_25).0 > 0 {
+        result.insert("layer-25".into(), true);
+    };
+    if (self & Self::LAYER_26).0 > 0 {
+        result.insert("layer-26".into(), true);
+    };
+    if (self & Self::LAYER_27).0 > 0 {
+        result.insert("layer-27".into(), true);
+    };
+    if (self & Self::LAYER_28).0 > 0 {
+        result.insert("layer-28".into(), true);
+    };
+    if (self & Self::LAYER_29).0 > 0 {
+        result.insert("layer-29".into(), true);
+    };
+    if (self & Self::LAYER_30).0 > 0 {
+        result.insert("layer-30".into(), true);
+    };
+    if (self & Self::LAYER_31).0 > 0 {
+        result.insert("layer-31".into(), true);
+    };
+    if (self & Self::LAYER_32).0 > 0 {
+        result.insert("layer-32".into(), true);
+    };
+    if (self & Self::LAYER_33).0 > 0 {
+        result.insert("layer-33".into(), true);
+    };
+    if (self & Self::LAYER_34).0 > 0 {
+        result.insert("layer-34".into(), true);
+    };
+    if (self & Self::LAYER_35).0 > 0 {
+        result.insert("layer-35".into(), true);
+    };
+    if (self & Self::LAYER_36).0 > 0 {
+        result.insert("layer-36".into(), true);
+    };
+    if (self & Self::LAYER_37).0 > 0 {
+        result.insert("layer-37".into(), true);
+    };
+    if (self & Self::LAYER_38).0 > 0 {
+        result.insert("layer-38".into(), true);
+    };
+    if (self & Self::LAYER_39).0 > 0 {
+        result.insert("layer-39".into(), true);
+    };
+    if (self & Self::LAYER_40).0 > 0 {
+        result.insert("layer-40".into(), true);
+    };
+    if (self & Self::LAYER_41).0 > 0 {
+        result.insert("layer-41".into(), true);
+    };
+    if (self & Self::LAYER_42).0 > 0 {
+        result.insert("layer-42".into(), true);
+    };
+    if (self & Self::LAYER_43).0 > 0 {
+        result.insert("layer-43".into(), true);
+    };
+    if (self & Self::LAYER_44).0 > 0 {
+        result.insert("layer-44".into(), true);
+    };
+    if (self & Self::LAYER_45).0 > 0 {
+        result.insert("layer-45".into(), true);
+    };
+    if (self & Self::LAYER_46).0 > 0 {
+        result.insert("layer-46".into(), true);
+    };
+    if (self & Self::LAYER_47).0 > 0 {
+        result.insert("layer-47".into(), true);
+    };
+    if (self & Self::LAYER_48).0 > 0 {
+        result.insert("layer-48".into(), true);
+    };
+    if (self & Self::LAYER_49).0 > 0 {
+        result.insert("layer-49".into(), true);
+    };
+    if (self & Self::LAYER_50).0 > 0 {
+        result.insert("layer-50".into(), true);
+    };
+    if (self & Self::LAYER_51).0 > 0 {
+        result.insert("layer-51".into(), true);
+    };
+    if (self & Self::LAYER_52).0 > 0 {
+        result.insert("layer-52".into(), true);
+    };
+    if (self & Self::LAYER_53).0 > 0 {
+        result.insert("layer-53".into(), true);
+    };
+    if (self & Self::LAYER_54).0 > 0 {
+        result.insert("layer-54".into(), true);
+    };
+    if (self & Self::LAYER_55).0 > 0 {
+        result.insert("layer-55".into(), true);
+    };
+    lua.pack(result)
+});
 
 impl BitAnd for CollisionMask {
     type Output = Self;
@@ -1469,6 +1949,53 @@ impl<'de> Deserialize<'de> for CollisionMask {
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for CollisionMask {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.layers())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn collision_mask_serde_roundtrip() {
+    let mask = CollisionMask::WATER_TILE
+        | CollisionMask::OBJECT_LAYER
+        | CollisionMask::LAYER_13
+        | CollisionMask::NOT_COLLIDING_WITH_ITSELF
+        | CollisionMask::COLLIDING_WITH_TILES_ONLY;
+    let json = serde_json::to_string(&mask).unwrap();
+    assert_eq!(
+        json,
+        r#"["water-tile","object-layer","layer-13","not-colliding-with-itself","colliding-with-tiles-only"]"#
+    );
+    assert_eq!(serde_json::from_str::<CollisionMask>(&json).unwrap(), mask);
+}
+
+#[test]
+fn collision_mask_display() {
+    let mask = CollisionMask::WATER_TILE
+        | CollisionMask::OBJECT_LAYER
+        | CollisionMask::LAYER_13
+        | CollisionMask::NOT_COLLIDING_WITH_ITSELF
+        | CollisionMask::COLLIDING_WITH_TILES_ONLY;
+    assert_eq!(
+        serde_json::from_str::<CollisionMask>(
+            r#"["water-tile","object-layer","layer-13","not-colliding-with-itself","colliding-with-tiles-only"]"#
+        )
+        .unwrap(),
+        mask
+    );
+    assert_eq!(
+        mask.to_string(),
+        "water-tile, object-layer, layer-13, not-colliding-with-itself, colliding-with-tiles-only"
+    );
+    assert_eq!(mask.layers().into_iter().collect::<CollisionMask>(), mask);
+}
+
 /// <https://wiki.factorio.com/Types/EntityPrototypeFlags>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Hash, Default)]
 pub struct EntityPrototypeFlags(pub(crate) u32);
@@ -1656,7 +2183,8 @@ pub struct DamageTypeFilters {
 }
 
 /// <https://wiki.factorio.com/Types/ForceCondition>
-#[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Copy, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum ForceCondition {
@@ -1669,8 +2197,111 @@ pub enum ForceCondition {
     NotSame,
 }
 
+impl ForceCondition {
+    /// Whether a force with given relation to the attacker force passes the condition
+    pub fn matches(&self, relation: ForceRelation) -> bool {
+        let friend = matches!(relation, ForceRelation::Same | ForceRelation::Friend);
+        match self {
+            Self::All => true,
+            Self::Enemy => relation == ForceRelation::Enemy,
+            Self::Ally => relation != ForceRelation::Enemy,
+            Self::Friend => friend,
+            Self::NotFriend => !friend,
+            Self::Same => relation == ForceRelation::Same,
+            Self::NotSame => relation != ForceRelation::Same,
+        }
+    }
+}
+
+impl FromStr for ForceCondition {
+    type Err = PrototypesErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "enemy" => Ok(Self::Enemy),
+            "ally" => Ok(Self::Ally),
+            "friend" => Ok(Self::Friend),
+            "not-friend" => Ok(Self::NotFriend),
+            "same" => Ok(Self::Same),
+            "not-same" => Ok(Self::NotSame),
+            _ => Err(PrototypesErr::InvalidTypeStr(
+                "ForceCondition".into(),
+                s.into(),
+            )),
+        }
+    }
+}
+
+impl Display for ForceCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+prot_from_str!(ForceCondition);
+
+/// Relation of a target force to an attacker force, see [ForceCondition::matches]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ForceRelation {
+    Same,
+    Friend,
+    /// Not a friend, but has cease fire with the attacker, so it's not attacked
+    CeaseFire,
+    Enemy,
+}
+
+impl ForceRelation {
+    /// Relation of `target_force` to `attacker_force`. `friend` and `cease_fire` are the
+    /// attacker's diplomacy settings towards the target and are ignored for the same force.
+    pub fn new(attacker_force: &str, target_force: &str, friend: bool, cease_fire: bool) -> Self {
+        if attacker_force == target_force {
+            Self::Same
+        } else if friend {
+            Self::Friend
+        } else if cease_fire {
+            Self::CeaseFire
+        } else {
+            Self::Enemy
+        }
+    }
+}
+
+#[test]
+fn force_condition_matches() {
+    let same = ForceRelation::new("player", "player", false, false);
+    let friend = ForceRelation::new("player", "neutral", true, true);
+    let cease_fire = ForceRelation::new("player", "neutral", false, true);
+    let enemy = ForceRelation::new("player", "enemy", false, false);
+    let passing = |condition: ForceCondition| {
+        [same, friend, cease_fire, enemy]
+            .into_iter()
+            .filter(|r| condition.matches(*r))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(passing(ForceCondition::All).len(), 4);
+    assert_eq!(passing(ForceCondition::Enemy), vec![enemy]);
+    assert_eq!(
+        passing(ForceCondition::Ally),
+        vec![same, friend, cease_fire]
+    );
+    assert_eq!(passing(ForceCondition::Friend), vec![same, friend]);
+    assert_eq!(passing(ForceCondition::NotFriend), vec![cease_fire, enemy]);
+    assert_eq!(passing(ForceCondition::Same), vec![same]);
+    assert_eq!(
+        passing(ForceCondition::NotSame),
+        vec![friend, cease_fire, enemy]
+    );
+    assert_eq!(
+        "not-friend".parse::<ForceCondition>().unwrap(),
+        ForceCondition::NotFriend
+    );
+    assert_eq!(ForceCondition::NotSame.to_string(), "not-same");
+}
+
 /// <https://wiki.factorio.com/Types/AreaTriggerItem#collision_mode>
 #[derive(Debug, Clone, Copy, Eq, PartialEq, EnumString, AsRefStr, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum CollisionMode {
@@ -1679,7 +2310,7 @@ pub enum CollisionMode {
 }
 
 /// <https://wiki.factorio.com/Types/MinableProperties>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct MinableProperties {
     pub mining_time: f64,
     pub results: ProductResults,
@@ -1694,6 +2325,16 @@ pub struct MinableProperties {
     pub mining_trigger: Option<Trigger>,
 }
 
+impl MinableProperties {
+    /// Items and fluids obtained by mining
+    pub fn products(&self) -> &[ProductPrototype] {
+        match &self.results {
+            ProductResults::Result(product) => std::slice::from_ref(product),
+            ProductResults::Results { results } => results,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum ProductResults {
@@ -1714,19 +2355,19 @@ pub enum ProductPrototype {
 
 impl<'lua> PrototypeFromLua<'lua> for ProductPrototype {
     fn prototype_from_lua(
-        value: Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua Lua,
         data_table: &mut DataTable,
     ) -> LuaResult<Self> {
         if let mlua::Value::Table(table) = &value {
-            if table.get::<_, Option<f64>>("mining_time")?.is_some() {
+            if table.get_as::<Option<f64>>("mining_time")?.is_some() {
                 // this means that we are in MinableProperties definition
-                let name = table.get::<_, String>("result")?;
-                let amount = table.get::<_, Option<u16>>("count")?.unwrap_or(1);
+                let name = table.get_as::<String>("result")?;
+                let amount = table.get_as::<Option<u16>>("count")?.unwrap_or(1);
                 Ok(Self::Item(ItemProductPrototype::name_and_amount(
                     name, amount,
                 )))
-            } else if let Some(pp_type) = table.get::<_, Option<String>>("type")? {
+            } else if let Some(pp_type) = table.get_as::<Option<String>>("type")? {
                 match pp_type.as_ref() {
                     "item" => Ok(Self::Item(ItemProductPrototype::prototype_from_lua(
                         value, lua, data_table,
@@ -1734,11 +2375,11 @@ impl<'lua> PrototypeFromLua<'lua> for ProductPrototype {
                     "fluid" => Ok(Self::Fluid(FluidProductPrototype::prototype_from_lua(
                         value, lua, data_table,
                     )?)),
-                    _ => Err(mlua::Error::FromLuaConversionError {
-                        from: value.type_name(),
-                        to: "ProductPrototype",
-                        message: Some("Invalid `type`".into()),
-                    }),
+                    _ => Err(conversion_error(
+                        value.type_name(),
+                        "ProductPrototype",
+                        Some("Invalid `type`".into()),
+                    )),
                 }
             } else {
                 Ok(Self::Item(ItemProductPrototype::from_sequence(
@@ -1746,13 +2387,49 @@ impl<'lua> PrototypeFromLua<'lua> for ProductPrototype {
                 )?))
             }
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "ProductPrototype",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "ProductPrototype",
+                Some("expected table".into()),
+            ))
+        }
+    }
+}
+
+impl ProductPrototype {
+    /// Name of produced item or fluid
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Item(ipp) => &ipp.name,
+            Self::Fluid(fpp) => &fpp.name,
+        }
+    }
+
+    /// Produced item or fluid
+    pub fn product(&self) -> ProductType {
+        match self {
+            Self::Item(ipp) => ProductType::Item(ipp.name.clone()),
+            Self::Fluid(fpp) => ProductType::Fluid(fpp.name.clone()),
         }
     }
+
+    /// Average amount produced per craft, taking `probability` and `amount_min`/`amount_max`
+    /// into account
+    pub fn expected_amount(&self) -> f64 {
+        let (amount, amount_min, amount_max, probability) = match self {
+            Self::Item(ipp) => (
+                ipp.amount.map(f64::from),
+                ipp.amount_min.map(f64::from),
+                ipp.amount_max.map(f64::from),
+                ipp.probability,
+            ),
+            Self::Fluid(fpp) => (fpp.amount, fpp.amount_min, fpp.amount_max, fpp.probability),
+        };
+        let amount = amount.unwrap_or_else(|| {
+            (amount_min.unwrap_or_default() + amount_max.unwrap_or_default()) / 2.0
+        });
+        amount * probability
+    }
 }
 
 /// Either a sequence or a table, first item stands for name and second for amount
@@ -1777,11 +2454,11 @@ pub struct ItemProductPrototype {
 impl ItemProductPrototype {
     fn post_extr_fn(&mut self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.probability < 0.0 || self.probability > 1.0 {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "ItemProductPrototype",
-                message: Some("`probability` must be in a range of [0; 1]".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "ItemProductPrototype",
+                Some("`probability` must be in a range of [0; 1]".into()),
+            ));
         }
         if let Some(amount_min) = self.amount_min {
             if let Some(amount_max) = self.amount_max {
@@ -1796,20 +2473,20 @@ impl ItemProductPrototype {
 
 impl<'lua> ItemProductPrototype {
     fn from_sequence(
-        value: Value<'lua>,
+        value: LuaValue<'lua>,
         _lua: &'lua Lua,
         _data_table: &mut DataTable,
     ) -> LuaResult<Self> {
         if let Value::Table(t) = &value {
-            let name = t.get::<_, String>(1)?;
-            let amount = t.get::<_, u16>(2)?;
+            let name = t.get_as::<String>(1)?;
+            let amount = t.get_as::<u16>(2)?;
             Ok(Self::name_and_amount(name, amount))
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "ItemProductPrototype",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "ItemProductPrototype",
+                Some("expected table".into()),
+            ))
         }
     }
 
@@ -1850,28 +2527,28 @@ pub struct FluidProductPrototype {
 impl FluidProductPrototype {
     fn post_extr_fn(&mut self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.probability < 0.0 || self.probability > 1.0 {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "FluidProductPrototype",
-                message: Some("`probability` must be in a range of [0; 1]".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "FluidProductPrototype",
+                Some("`probability` must be in a range of [0; 1]".into()),
+            ));
         }
         if let Some(amount) = self.amount {
             if amount.is_sign_negative() {
-                return Err(mlua::Error::FromLuaConversionError {
-                    from: "table",
-                    to: "FluidProductPrototype",
-                    message: Some("`amount` can't be negative".into()),
-                });
+                return Err(conversion_error(
+                    "table",
+                    "FluidProductPrototype",
+                    Some("`amount` can't be negative".into()),
+                ));
             }
         }
         if let Some(amount_min) = self.amount_min {
             if amount_min.is_sign_negative() {
-                return Err(mlua::Error::FromLuaConversionError {
-                    from: "table",
-                    to: "FluidProductPrototype",
-                    message: Some("`amount_min` can't be negative".into()),
-                });
+                return Err(conversion_error(
+                    "table",
+                    "FluidProductPrototype",
+                    Some("`amount_min` can't be negative".into()),
+                ));
             }
             if let Some(amount_max) = self.amount_max {
                 if amount_max < amount_min {
@@ -1900,7 +2577,7 @@ pub struct ItemsToPlace(pub Vec<ItemToPlace>);
 
 impl<'lua> PrototypeFromLua<'lua> for ItemsToPlace {
     fn prototype_from_lua(
-        value: Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua Lua,
         data_table: &mut DataTable,
     ) -> LuaResult<Self> {
@@ -1910,11 +2587,11 @@ impl<'lua> PrototypeFromLua<'lua> for ItemsToPlace {
         } else if let Ok(v) = <Vec<ItemToPlace>>::prototype_from_lua(value, lua, data_table) {
             Ok(Self(v))
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: type_name,
-                to: "ItemsToPlace",
-                message: Some("expected ItemToPlace or array of ItemToPlace".into()),
-            })
+            Err(conversion_error(
+                type_name,
+                "ItemsToPlace",
+                Some("expected ItemToPlace or array of ItemToPlace".into()),
+            ))
         }
     }
 }
@@ -1927,7 +2604,7 @@ pub struct ItemToPlace {
 }
 
 /// <https://wiki.factorio.com/Prototype/Cliff#orientations>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct OrientedCliffPrototypes {
     pub west_to_east: OrientedCliffPrototype,
     pub north_to_south: OrientedCliffPrototype,
@@ -1952,13 +2629,21 @@ pub struct OrientedCliffPrototypes {
 }
 
 /// <https://wiki.factorio.com/Types/OrientedCliffPrototype>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct OrientedCliffPrototype {
     pub collision_bounding_box: BoundingBox,
     pub pictures: Vec<SpriteVariation>,
     pub fill_volume: u32,
 }
 
+impl OrientedCliffPrototype {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        self.pictures
+            .require_variation_count(1, usize::MAX, "OrientedCliffPrototype", "pictures")
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/RailRemnants#bending_type>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
@@ -2003,18 +2688,18 @@ pub struct Loot {
 impl Loot {
     fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.count_max <= 0.0 {
-            return Err(LuaError::FromLuaConversionError {
-                from: "table",
-                to: "Loot",
-                message: Some("`count_max` must be > 0".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "Loot",
+                Some("`count_max` must be > 0".into()),
+            ));
         }
         Ok(())
     }
 }
 
 /// <https://wiki.factorio.com/Types/AttackReactionItem>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct AttackReactionItem {
     pub range: f32,
     pub action: Option<Trigger>,
@@ -2035,8 +2720,8 @@ pub struct EnergySourceBase {
 }
 
 /// <https://wiki.factorio.com/Types/EnergySource>
-#[derive(Debug, Clone, EnumDiscriminants)]
-#[strum_discriminants(derive(EnumString), strum(serialize_all = "kebab-case"))]
+#[derive(Debug, Clone, EnumDiscriminants, ResourceWalk)]
+#[strum_discriminants(derive(EnumString, AsRefStr), strum(serialize_all = "kebab-case"))]
 pub enum EnergySource {
     /// <https://wiki.factorio.com/Types/EnergySource#Electric_energy_source>
     Electric(ElectricEnergySource),
@@ -2050,16 +2735,66 @@ pub enum EnergySource {
     Void,
 }
 
+impl EnergySource {
+    pub fn kind(&self) -> EnergySourceDiscriminants {
+        self.into()
+    }
+
+    /// Fuel categories accepted by a burner energy source, `None` for other kinds
+    pub fn fuel_categories(&self) -> Option<&[String]> {
+        match self {
+            Self::Burner(burner) => Some(&burner.fuel_categories),
+            _ => None,
+        }
+    }
+
+    /// Smoke of a burner or fluid energy source, empty for other kinds
+    pub fn smoke(&self) -> &[SmokeSource] {
+        let smoke = match self {
+            Self::Burner(burner) => &burner.smoke,
+            Self::Fluid(fluid) => &fluid.smoke,
+            _ => return &[],
+        };
+        smoke.as_deref().unwrap_or_default()
+    }
+
+    /// Errors if kind of this energy source is not one of `allowed`. `type_name` and `field` are
+    /// used in the error message.
+    pub fn require_kind(
+        &self,
+        allowed: &[EnergySourceDiscriminants],
+        type_name: &'static str,
+        field: &str,
+    ) -> LuaResult<()> {
+        if allowed.contains(&self.kind()) {
+            return Ok(());
+        }
+        let allowed = allowed
+            .iter()
+            .map(|k| k.as_ref())
+            .collect::<Vec<_>>()
+            .join(" or ");
+        Err(conversion_error(
+            "table",
+            type_name,
+            Some(format!(
+                "`{field}` must be {allowed} energy source, got {}",
+                self.kind().as_ref()
+            )),
+        ))
+    }
+}
+
 impl<'lua> PrototypeFromLua<'lua> for EnergySource {
     fn prototype_from_lua(
-        value: Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua Lua,
         data_table: &mut DataTable,
     ) -> LuaResult<Self> {
         if let Value::Table(t) = &value {
             Ok(
                 match t
-                    .get::<_, String>("type")?
+                    .get_as::<String>("type")?
                     .parse::<EnergySourceDiscriminants>()
                     .map_err(LuaError::external)?
                 {
@@ -2079,11 +2814,11 @@ impl<'lua> PrototypeFromLua<'lua> for EnergySource {
                 },
             )
         } else {
-            Err(LuaError::FromLuaConversionError {
-                from: value.type_name(),
-                to: "EnergySource",
-                message: Some("Expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "EnergySource",
+                Some("Expected table".into()),
+            ))
         }
     }
 }
@@ -2119,7 +2854,7 @@ pub struct BurnerEnergySource {
 }
 
 /// <https://wiki.factorio.com/Types/EnergySource#Heat_energy_source>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct HeatEnergySource {
     #[use_self_forced]
@@ -2145,33 +2880,33 @@ pub struct HeatEnergySource {
 impl HeatEnergySource {
     fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.max_temperature < self.default_temperature {
-            return Err(LuaError::FromLuaConversionError {
-                from: "table",
-                to: "HeatEnergySource",
-                message: Some("`max_temperature` must be >= `default_temperature`".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "HeatEnergySource",
+                Some("`max_temperature` must be >= `default_temperature`".into()),
+            ));
         }
         if self.min_working_temperature < self.default_temperature {
-            return Err(LuaError::FromLuaConversionError {
-                from: "table",
-                to: "HeatEnergySource",
-                message: Some("`min_working_temperature` must be >= `default_temperature`".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "HeatEnergySource",
+                Some("`min_working_temperature` must be >= `default_temperature`".into()),
+            ));
         }
         if self.min_working_temperature > self.max_temperature {
-            return Err(LuaError::FromLuaConversionError {
-                from: "table",
-                to: "HeatEnergySource",
-                message: Some("`min_working_temperature` must be <= `max_temperature`".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "HeatEnergySource",
+                Some("`min_working_temperature` must be <= `max_temperature`".into()),
+            ));
         }
         if let Some(connections) = &self.connections {
             if connections.len() > 32 {
-                return Err(LuaError::FromLuaConversionError {
-                    from: "table",
-                    to: "HeatEnergySource",
-                    message: Some("`connections` amount must be <= 32".into()),
-                });
+                return Err(conversion_error(
+                    "table",
+                    "HeatEnergySource",
+                    Some("`connections` amount must be <= 32".into()),
+                ));
             }
         }
         Ok(())
@@ -2179,7 +2914,7 @@ impl HeatEnergySource {
 }
 
 /// <https://wiki.factorio.com/Types/EnergySource#Fluid_energy_source>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct FluidEnergySource {
     #[use_self_forced]
     pub base: EnergySourceBase,
@@ -2254,16 +2989,21 @@ pub struct SmokeSource {
 }
 
 impl SmokeSource {
+    /// Name of TrivialSmoke prototype
+    pub fn smoke_name(&self) -> &str {
+        &self.name
+    }
+
     fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.frequency.is_sign_negative()
             || self.frequency.is_nan()
             || self.frequency.is_infinite()
         {
-            return Err(LuaError::FromLuaConversionError {
-                from: "table",
-                to: "SmokeSource",
-                message: Some("`frequency` can't be negative, NaN or infinite".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "SmokeSource",
+                Some("`frequency` can't be negative, NaN or infinite".into()),
+            ));
         }
         Ok(())
     }
@@ -2277,7 +3017,7 @@ pub struct HeatConnection {
 }
 
 /// <https://wiki.factorio.com/Types/FluidBox>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct FluidBox {
     pub pipe_connections: Vec<PipeConnectionDefinition>, // Max: 256
@@ -2300,17 +3040,30 @@ pub struct FluidBox {
 }
 
 impl FluidBox {
-    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
-        if self.pipe_connections.len() > 255 {
-            return Err(LuaError::FromLuaConversionError {
-                from: "table",
-                to: "FluidBox",
-                message: Some("no more than 255 `pipe_connections` are allowed".into()),
-            });
-        }
-        Ok(())
+    /// Name of the fluid this fluid box is filtered to
+    pub fn filter(&self) -> Option<&str> {
+        self.filter.as_deref()
     }
-}
+
+    pub fn connections(&self) -> &[PipeConnectionDefinition] {
+        &self.pipe_connections
+    }
+
+    pub fn production_type(&self) -> ProductionType {
+        self.production_type
+    }
+
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        if self.pipe_connections.len() > 255 {
+            return Err(conversion_error(
+                "table",
+                "FluidBox",
+                Some("no more than 255 `pipe_connections` are allowed".into()),
+            ));
+        }
+        Ok(())
+    }
+}
 
 /// <https://wiki.factorio.com/Types/PipeConnectionDefinition>
 #[derive(Debug, Clone, PrototypeFromLua)]
@@ -2337,27 +3090,25 @@ pub enum Direction {
     Northwest = 7,
 }
 
-impl<'lua> FromLua<'lua> for Direction {
-    fn from_lua(lua_value: Value<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
-        Ok(match lua.unpack::<u8>(lua_value)? {
-            0 => Self::North,
-            1 => Self::Northeast,
-            2 => Self::East,
-            3 => Self::Southeast,
-            4 => Self::South,
-            5 => Self::Southwest,
-            6 => Self::West,
-            7 => Self::Northwest,
-            _ => {
-                return Err(LuaError::FromLuaConversionError {
-                    from: "u8",
-                    to: "Direction",
-                    message: Some("Value must be in range [0; 7]".into()),
-                })
-            }
-        })
-    }
-}
+impl_from_lua!(Direction, |lua_value, lua| {
+    Ok(match lua.unpack::<u8>(lua_value)? {
+        0 => Self::North,
+        1 => Self::Northeast,
+        2 => Self::East,
+        3 => Self::Southeast,
+        4 => Self::South,
+        5 => Self::Southwest,
+        6 => Self::West,
+        7 => Self::Northwest,
+        _ => {
+            return Err(conversion_error(
+                "u8",
+                "Direction",
+                Some("Value must be in range [0; 7]".into()),
+            ))
+        }
+    })
+});
 
 /// <https://wiki.factorio.com/Types/FluidBox#production_type>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr)]
@@ -2394,6 +3145,61 @@ pub struct SignalIDConnector {
     pub name: String, // Name of a circuit network signal
 }
 
+impl SignalIDConnector {
+    /// Item, fluid or virtual signal prototype this signal refers to
+    pub fn resolve<'a>(&self, data_table: &'a DataTable) -> Option<&'a dyn Prototype> {
+        match self.signal_type {
+            SignalType::Item => data_table.find_item(&self.name),
+            SignalType::Fluid => data_table
+                .fluid
+                .get(&self.name)
+                .map(|f| f as &dyn Prototype),
+            SignalType::Virtual => data_table
+                .virtual_signal
+                .get(&self.name)
+                .map(|s| s as &dyn Prototype),
+        }
+    }
+}
+
+/// Circuit network signal, identified by its source and name
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SignalId {
+    /// Name of Item
+    Item(String),
+    /// Name of Fluid
+    Fluid(String),
+    /// Name of VirtualSignal
+    Virtual(String),
+}
+
+impl SignalId {
+    pub fn kind(&self) -> SignalType {
+        match self {
+            Self::Item(_) => SignalType::Item,
+            Self::Fluid(_) => SignalType::Fluid,
+            Self::Virtual(_) => SignalType::Virtual,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Item(name) | Self::Fluid(name) | Self::Virtual(name) => name,
+        }
+    }
+}
+
+impl From<&SignalIDConnector> for SignalId {
+    fn from(signal: &SignalIDConnector) -> Self {
+        let name = signal.name.clone();
+        match signal.signal_type {
+            SignalType::Item => Self::Item(name),
+            SignalType::Fluid => Self::Fluid(name),
+            SignalType::Virtual => Self::Virtual(name),
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Types/ModuleSpecification>
 #[derive(Debug, Clone, PrototypeFromLua)]
 pub struct ModuleSpecification {
@@ -2417,31 +3223,155 @@ impl EffectTypeLimitation {
     pub const POLLUTION: Self = Self(1 << 3);
     pub const NONE: Self = Self(0);
     pub const ALL: Self = Self((1 << 4) - 1);
+
+    /// Names of effects, in order of their bits
+    const EFFECT_NAMES: [&'static str; 4] = ["speed", "productivity", "consumption", "pollution"];
+
+    /// Names of effects in this limitation, as used in `allowed_effects` of prototypes
+    pub fn effects(&self) -> Vec<&'static str> {
+        Self::EFFECT_NAMES
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.0 & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+
+    /// Limitation with only the effect of given name
+    fn from_effect_name(name: &str) -> Option<Self> {
+        Self::EFFECT_NAMES
+            .iter()
+            .position(|n| *n == name)
+            .map(|bit| Self(1 << bit))
+    }
+}
+
+impl Display for EffectTypeLimitation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.effects().join(", "))
+    }
 }
 
 impl<T: AsRef<str>> FromIterator<T> for EffectTypeLimitation {
     fn from_iter<I: IntoIterator<Item = T>>(in_arr: I) -> Self {
-        let mut result = Self(0);
-        for item in in_arr {
-            match item.as_ref() {
-                "speed" => result |= Self::SPEED,
-                "productivity" => result |= Self::PRODUCTIVITY,
-                "consumption" => result |= Self::CONSUMPTION,
-                "pollution" => result |= Self::POLLUTION,
-                _ => {}
-            }
+        // Unknown names are ignored
+        in_arr
+            .into_iter()
+            .filter_map(|i| Self::from_effect_name(i.as_ref()))
+            .fold(Self::NONE, |result, effect| result | effect)
+    }
+}
+
+impl_from_lua!(EffectTypeLimitation, |lua_value, lua| {
+    let arr: Vec<String> = lua.unpack(lua_value)?;
+    Ok(Self::from_iter(arr))
+});
+
+struct EffectTypeLimitationVisitor;
+
+impl<'de> Visitor<'de> for EffectTypeLimitationVisitor {
+    type Value = EffectTypeLimitation;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("A string or a sequence of strings")
+    }
+
+    // Unlike `FromIterator`, unknown effect names are an error
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match v {
+            "ALL" => Ok(EffectTypeLimitation::ALL),
+            "NONE" => Ok(EffectTypeLimitation::NONE),
+            name => EffectTypeLimitation::from_effect_name(name)
+                .ok_or_else(|| E::unknown_variant(name, &EffectTypeLimitation::EFFECT_NAMES)),
         }
-        result
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut result = EffectTypeLimitation::NONE;
+        while let Some(effect) = seq.next_element::<String>()? {
+            result |= EffectTypeLimitation::from_effect_name(&effect).ok_or_else(|| {
+                de::Error::unknown_variant(&effect, &EffectTypeLimitation::EFFECT_NAMES)
+            })?
+        }
+        Ok(result)
     }
 }
 
-impl<'lua> FromLua<'lua> for EffectTypeLimitation {
-    fn from_lua(lua_value: Value<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
-        let arr: Vec<String> = lua.unpack(lua_value)?;
-        Ok(Self::from_iter(arr))
+impl<'de> Deserialize<'de> for EffectTypeLimitation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(EffectTypeLimitationVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for EffectTypeLimitation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.effects())
     }
 }
 
+#[cfg(feature = "serde")]
+#[test]
+fn effect_type_limitation_serde_roundtrip() {
+    let roundtrip = |effects: EffectTypeLimitation, json: &str| {
+        assert_eq!(serde_json::to_string(&effects).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<EffectTypeLimitation>(json).unwrap(),
+            effects
+        );
+    };
+    roundtrip(
+        EffectTypeLimitation::ALL,
+        r#"["speed","productivity","consumption","pollution"]"#,
+    );
+    roundtrip(EffectTypeLimitation::NONE, "[]");
+    roundtrip(
+        EffectTypeLimitation::SPEED | EffectTypeLimitation::POLLUTION,
+        r#"["speed","pollution"]"#,
+    );
+}
+
+#[test]
+fn effect_type_limitation_deserialize() {
+    assert_eq!(
+        serde_json::from_str::<EffectTypeLimitation>(r#"["speed","pollution"]"#).unwrap(),
+        EffectTypeLimitation::SPEED | EffectTypeLimitation::POLLUTION
+    );
+    // Single effect can be specified without an array
+    assert_eq!(
+        serde_json::from_str::<EffectTypeLimitation>(r#""productivity""#).unwrap(),
+        EffectTypeLimitation::PRODUCTIVITY
+    );
+    // Shorthands for all and no effects
+    assert_eq!(
+        serde_json::from_str::<EffectTypeLimitation>(r#""ALL""#).unwrap(),
+        EffectTypeLimitation::ALL
+    );
+    assert_eq!(
+        serde_json::from_str::<EffectTypeLimitation>(r#""NONE""#).unwrap(),
+        EffectTypeLimitation::NONE
+    );
+    // Unknown names
+    assert!(serde_json::from_str::<EffectTypeLimitation>(r#""speeed""#).is_err());
+    assert!(serde_json::from_str::<EffectTypeLimitation>(r#"["speed","quality"]"#).is_err());
+    assert_eq!(
+        EffectTypeLimitation::ALL.to_string(),
+        "speed, productivity, consumption, pollution"
+    );
+}
+
 impl BitAnd for EffectTypeLimitation {
     type Output = Self;
     fn bitand(self, rhs: Self) -> Self::Output {
@@ -2482,13 +3412,31 @@ impl BitXorAssign for EffectTypeLimitation {
 }
 
 /// <https://wiki.factorio.com/Prototype/Boiler#mode>
-#[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr)]
+#[derive(Debug, Clone, Eq, PartialEq, Copy, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
 pub enum BoilerMode {
     HeatWaterInside,
     OutputToSeparatePipe,
 }
 
+impl FromStr for BoilerMode {
+    type Err = PrototypesErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "heat-water-inside" => Ok(Self::HeatWaterInside),
+            "output-to-separate-pipe" => Ok(Self::OutputToSeparatePipe),
+            _ => Err(PrototypesErr::InvalidTypeStr("BoilerMode".into(), s.into())),
+        }
+    }
+}
+
+impl Display for BoilerMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
 prot_from_str!(BoilerMode);
 
 /// <https://wiki.factorio.com/Types/FootprintParticle>
@@ -2501,7 +3449,7 @@ pub struct FootprintParticle {
 }
 
 /// <https://wiki.factorio.com/Prototype/LogisticContainer#logistic_mode>
-#[derive(Debug, Clone, Eq, PartialEq, Copy, EnumString, AsRefStr)]
+#[derive(Debug, Clone, Eq, PartialEq, Copy, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
 pub enum LogisticMode {
     PassiveProvider,
@@ -2511,6 +3459,37 @@ pub enum LogisticMode {
     Requester,
 }
 
+impl LogisticMode {
+    /// Modes with request slots, which need `max_logistic_slots` to be > 0 if it's specified
+    pub fn requires_positive_slots(&self) -> bool {
+        matches!(self, Self::Requester | Self::Buffer)
+    }
+}
+
+impl FromStr for LogisticMode {
+    type Err = PrototypesErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "passive-provider" => Ok(Self::PassiveProvider),
+            "active-provider" => Ok(Self::ActiveProvider),
+            "storage" => Ok(Self::Storage),
+            "buffer" => Ok(Self::Buffer),
+            "requester" => Ok(Self::Requester),
+            _ => Err(PrototypesErr::InvalidTypeStr(
+                "LogisticMode".into(),
+                s.into(),
+            )),
+        }
+    }
+}
+
+impl Display for LogisticMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
 prot_from_str!(LogisticMode);
 
 /// Used in many places, specified as string
@@ -2533,6 +3512,16 @@ pub struct UnitSpawnDefinition {
     pub spawn_points: Vec<SpawnPoint>, // `evolution_factor` must be ascending from entry to entry
 }
 
+impl UnitSpawnDefinition {
+    /// Spawn curve as pairs of evolution factor and spawn weight
+    pub fn weights(&self) -> Vec<(f64, f64)> {
+        self.spawn_points
+            .iter()
+            .map(|sp| (sp.evolution_factor, sp.spawn_height))
+            .collect()
+    }
+}
+
 // Can also be converted from array
 /// <https://wiki.factorio.com/Types/SpawnPoint>
 #[derive(Debug, Clone, PrototypeFromLua)]
@@ -2542,7 +3531,7 @@ pub struct SpawnPoint {
 }
 
 /// <https://wiki.factorio.com/Types/AmmoType>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct AmmoType {
     pub category: String, // Name of AmmoCategory
@@ -2587,7 +3576,7 @@ pub struct CircularProjectileCreationSpecification(
 
 impl<'lua> PrototypeFromLua<'lua> for CircularProjectileCreationSpecification {
     fn prototype_from_lua(
-        value: Value<'lua>,
+        value: LuaValue<'lua>,
         _lua: &'lua Lua,
         _data_table: &mut DataTable,
     ) -> LuaResult<Self> {
@@ -2595,11 +3584,11 @@ impl<'lua> PrototypeFromLua<'lua> for CircularProjectileCreationSpecification {
         if let Value::Table(t) = value {
             Ok(Self(t.sequence_values().collect::<LuaResult<_>>()?))
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: type_name,
-                to: "CircularProjectileCreationSpecification",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                type_name,
+                "CircularProjectileCreationSpecification",
+                Some("expected table".into()),
+            ))
         }
     }
 }
@@ -2607,21 +3596,22 @@ impl<'lua> PrototypeFromLua<'lua> for CircularProjectileCreationSpecification {
 #[derive(Debug, Clone)]
 pub struct CircularProjectileCreationSpecificationItem(pub RealOrientation, pub Factorio2DVector);
 
-impl<'lua> FromLua<'lua> for CircularProjectileCreationSpecificationItem {
-    fn from_lua(value: Value<'lua>, _lua: &'lua Lua) -> LuaResult<Self> {
+impl_from_lua!(
+    CircularProjectileCreationSpecificationItem,
+    |value, _lua| {
         if let Value::Table(t) = &value {
             let orientation: RealOrientation = t.get(1)?;
             let vector: Factorio2DVector = t.get(2)?;
             Ok(Self(orientation, vector))
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "CircularProjectileCreationSpecification item",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "CircularProjectileCreationSpecification item",
+                Some("expected table".into()),
+            ))
         }
     }
-}
+);
 
 /// <https://wiki.factorio.com/Types/CircularParticleCreationSpecification>
 #[derive(Debug, Clone, PrototypeFromLua)]
@@ -2656,7 +3646,7 @@ pub struct CircularParticleCreationSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/HeatBuffer>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct HeatBuffer {
     pub max_temperature: f64, // Must be >= `default_temperature` // TODO
     pub specific_heat: Energy,
@@ -2697,19 +3687,50 @@ pub enum SignalType {
 prot_from_str!(SignalType);
 
 /// <https://wiki.factorio.com/Prototype/ProgrammableSpeaker#instruments>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct Instrument {
     name: String,
     notes: Vec<Note>,
 }
 
+impl Instrument {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// Note with given name
+    pub fn note(&self, name: &str) -> Option<&Note> {
+        self.notes.iter().find(|n| n.name == name)
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/ProgrammableSpeaker#instruments>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
+#[post_extr_fn(Self::register_resources)]
 pub struct Note {
     name: String,
     sound: Sound,
 }
 
+impl Note {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn sound(&self) -> &Sound {
+        &self.sound
+    }
+
+    fn register_resources(&self, _lua: &Lua, data_table: &mut DataTable) -> LuaResult<()> {
+        self.sound.register_resources(data_table);
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Types/AnimatedVector>
 #[derive(Debug, Clone)]
 pub struct AnimatedVector {
@@ -2718,15 +3739,80 @@ pub struct AnimatedVector {
     direction_shift: Option<AnimatedVectorDirectionShift>,
 }
 
+impl AnimatedVector {
+    pub fn rotations(&self) -> &[AnimatedVectorRotation] {
+        &self.rotations
+    }
+
+    pub fn direction_shift(&self) -> Option<&AnimatedVectorDirectionShift> {
+        self.direction_shift.as_ref()
+    }
+}
+
+impl<'lua> PrototypeFromLua<'lua> for AnimatedVector {
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<Self> {
+        if let Value::Table(t) = &value {
+            let render_layer: Option<RenderLayer> = t.get_prot("render_layer", lua, data_table)?;
+            let rotations = t
+                .get_as::<LuaTable>("rotations")?
+                .sequence_values::<LuaTable>()
+                .map(|rotation| {
+                    let rotation = rotation?;
+                    let own_layer: Option<RenderLayer> =
+                        rotation.get_prot("render_layer", lua, data_table)?;
+                    Ok(AnimatedVectorRotation {
+                        frames: rotation.get_prot("frames", lua, data_table)?,
+                        render_layer: own_layer.or(render_layer),
+                    })
+                })
+                .collect::<LuaResult<Vec<AnimatedVectorRotation>>>()?;
+            if rotations
+                .windows(2)
+                .any(|pair| pair[0].frames.len() != pair[1].frames.len())
+            {
+                return Err(conversion_error(
+                    value.type_name(),
+                    "AnimatedVector",
+                    Some("`frames` of all rotations must have the same size".into()),
+                ));
+            }
+            Ok(Self {
+                rotations,
+                direction_shift: t.get_prot("direction_shift", lua, data_table)?,
+            })
+        } else {
+            Err(conversion_error(
+                value.type_name(),
+                "AnimatedVector",
+                Some("expected table".into()),
+            ))
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Types/AnimatedVector#rotations>
 #[derive(Debug, Clone)]
 pub struct AnimatedVectorRotation {
     frames: Vec<Factorio2DVector>, // Sizes of all arrays must be the same
-    render_layer: RenderLayer,
+    render_layer: Option<RenderLayer>, // Default: `render_layer` of AnimatedVector
+}
+
+impl AnimatedVectorRotation {
+    pub fn frames(&self) -> &[Factorio2DVector] {
+        &self.frames
+    }
+
+    pub fn render_layer(&self) -> Option<RenderLayer> {
+        self.render_layer
+    }
 }
 
 /// <https://wiki.factorio.com/Types/AnimatedVector#direction_shift>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua)]
 pub struct AnimatedVectorDirectionShift {
     north: Option<Factorio2DVector>,
     east: Option<Factorio2DVector>,
@@ -2734,6 +3820,67 @@ pub struct AnimatedVectorDirectionShift {
     west: Option<Factorio2DVector>,
 }
 
+impl AnimatedVectorDirectionShift {
+    /// Shift for given direction, only the four cardinal directions have one
+    pub fn shift(&self, direction: Direction) -> Option<Factorio2DVector> {
+        match direction {
+            Direction::North => self.north,
+            Direction::East => self.east,
+            Direction::South => self.south,
+            Direction::West => self.west,
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn animated_vector_load() {
+    let lua = Lua::new();
+    let mut data_table = DataTable::default();
+    let mut load = |source: &str| {
+        let value: Value = lua.load(source).eval().unwrap();
+        AnimatedVector::prototype_from_lua(value, &lua, &mut data_table)
+    };
+    let vector = load(
+        r#"{
+            render_layer = "object",
+            rotations = {
+                {frames = {{0, 1}, {0, 2}}},
+                {frames = {{1, 0}, {2, 0}}, render_layer = "higher-object-above"},
+            },
+            direction_shift = {north = {0, -1}},
+        }"#,
+    )
+    .unwrap();
+    let rotations = vector.rotations();
+    assert_eq!(rotations.len(), 2);
+    assert_eq!(rotations[0].frames().len(), 2);
+    // Layer of the vector is used for rotations without their own
+    assert_eq!(rotations[0].render_layer(), Some(RenderLayer::Object));
+    assert_eq!(
+        rotations[1].render_layer(),
+        Some(RenderLayer::HigherObjectAbove)
+    );
+    let shift = vector.direction_shift().unwrap();
+    assert!(shift.shift(Direction::North).is_some());
+    assert!(shift.shift(Direction::East).is_none());
+    // Rotations with different frame counts
+    assert!(load(r#"{rotations = {{frames = {{0, 1}}}, {frames = {{0, 1}, {0, 2}}}}}"#).is_err());
+}
+
+/// Phase of turret state, each has its own animation
+/// <https://wiki.factorio.com/Prototype/Turret#folded_animation>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum TurretPhase {
+    Folded,
+    Preparing,
+    Prepared,
+    StartingAttack,
+    Attacking,
+    EndingAttack,
+    Folding,
+}
+
 /// <https://wiki.factorio.com/Types/UnitAISettings>
 #[derive(Debug, Clone)]
 pub struct UnitAISettings {
@@ -2760,14 +3907,27 @@ pub struct UnitAlternativeAttackingFrameSequence {
 }
 
 /// <https://wiki.factorio.com/Types/SpiderEnginePrototype>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct SpiderEnginePrototype {
     military_target: bool, // Converted from Option<String>. if Some(_), set to true. If None, set to false
     legs: Vec<SpiderLegSpecification>, // Single leg is converted to Vec with one leg
 }
 
+impl SpiderEnginePrototype {
+    pub fn new(military_target: bool, legs: Vec<SpiderLegSpecification>) -> Self {
+        Self {
+            military_target,
+            legs,
+        }
+    }
+
+    pub fn legs(&self) -> &[SpiderLegSpecification] {
+        &self.legs
+    }
+}
+
 /// <https://wiki.factorio.com/Types/SpiderLegSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct SpiderLegSpecification {
     leg: String, // Name of SpiderLeg
     mount_position: Factorio2DVector,
@@ -2776,6 +3936,28 @@ pub struct SpiderLegSpecification {
     leg_hit_the_ground_trigger: Option<TriggerEffect>,
 }
 
+impl SpiderLegSpecification {
+    /// Leg that doesn't block other legs and has no trigger when it hits the ground
+    pub fn new(
+        leg: String,
+        mount_position: Factorio2DVector,
+        ground_position: Factorio2DVector,
+    ) -> Self {
+        Self {
+            leg,
+            mount_position,
+            ground_position,
+            blocking_legs: vec![],
+            leg_hit_the_ground_trigger: None,
+        }
+    }
+
+    /// Name of the SpiderLeg entity
+    pub fn leg(&self) -> &str {
+        &self.leg
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/FireFlame#burnt_patch_alpha_variations>
 #[derive(Debug, Clone)]
 pub struct FireFlameBurntPatchAlphaVariation {
@@ -2793,7 +3975,7 @@ pub enum TextAlignment {
 }
 
 /// <https://wiki.factorio.com/Types/CursorBoxType>
-#[derive(Debug, Clone, Eq, PartialEq, Copy, Hash, EnumString, AsRefStr)]
+#[derive(Debug, Clone, Eq, PartialEq, Copy, Hash, AsRefStr, EnumIter)]
 #[strum(serialize_all = "kebab-case")]
 pub enum CursorBoxType {
     Entity,
@@ -2806,6 +3988,51 @@ pub enum CursorBoxType {
     BlueprintSnapRectangle,
 }
 
+impl FromStr for CursorBoxType {
+    type Err = PrototypesErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "entity" => Ok(Self::Entity),
+            "not-allowed" => Ok(Self::NotAllowed),
+            "electricity" => Ok(Self::Electricity),
+            "pair" => Ok(Self::Pair),
+            "copy" => Ok(Self::Copy),
+            "train-visualization" => Ok(Self::TrainVisualization),
+            "logistics" => Ok(Self::Logistics),
+            "blueprint-snap-rectangle" => Ok(Self::BlueprintSnapRectangle),
+            _ => Err(PrototypesErr::InvalidTypeStr(
+                "CursorBoxType".into(),
+                s.into(),
+            )),
+        }
+    }
+}
+
+impl Display for CursorBoxType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+prot_from_str!(CursorBoxType);
+
+#[test]
+fn cursor_box_type_roundtrip() {
+    use strum::IntoEnumIterator;
+
+    for cursor_box_type in CursorBoxType::iter() {
+        assert_eq!(
+            cursor_box_type
+                .to_string()
+                .parse::<CursorBoxType>()
+                .unwrap(),
+            cursor_box_type
+        );
+    }
+    assert!("blueprint".parse::<CursorBoxType>().is_err());
+}
+
 /// <https://wiki.factorio.com/Types/EquipmentShape>
 #[derive(Debug, Clone)]
 pub struct EquipmentShape {
@@ -2815,6 +4042,51 @@ pub struct EquipmentShape {
     points: Option<EquipmentShapePoints>, // Mandatory if type is manual
 }
 
+impl<'lua> PrototypeFromLua<'lua> for EquipmentShape {
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<Self> {
+        if let Value::Table(t) = &value {
+            let width: u32 = t.get_prot("width", lua, data_table)?;
+            let height: u32 = t.get_prot("height", lua, data_table)?;
+            let shape_type: EquipmentShapeType = t.get_prot("type", lua, data_table)?;
+            let points = if shape_type == EquipmentShapeType::Manual {
+                let points: Vec<Vec<u32>> = t.get_prot("points", lua, data_table)?;
+                if points
+                    .iter()
+                    .any(|p| p.len() != 2 || p[0] >= width || p[1] >= height)
+                {
+                    return Err(conversion_error(
+                        value.type_name(),
+                        "EquipmentShape",
+                        Some(format!(
+                            "`points` must be pairs of coordinates within {width}x{height}"
+                        )),
+                    ));
+                }
+                Some(EquipmentShapePoints(points))
+            } else {
+                data_table.use_key(t, "points");
+                None
+            };
+            Ok(Self {
+                width,
+                height,
+                shape_type,
+                points,
+            })
+        } else {
+            Err(conversion_error(
+                value.type_name(),
+                "EquipmentShape",
+                Some("expected table".into()),
+            ))
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Types/EquipmentShape#type>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Hash, EnumString, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
@@ -2823,6 +4095,8 @@ pub enum EquipmentShapeType {
     Manual,
 }
 
+prot_from_str!(EquipmentShapeType);
+
 // Constructor should accept width and height, as points can't exceed them.
 /// <https://wiki.factorio.com/Types/EquipmentShape#points>
 #[derive(Debug, Clone)]
@@ -2836,18 +4110,18 @@ pub struct DaytimeColorLookupTableItem(pub f64, pub ColorLookupTable);
 
 impl<'lua> PrototypeFromLua<'lua> for DaytimeColorLookupTableItem {
     fn prototype_from_lua(
-        value: Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua Lua,
         data_table: &mut DataTable,
     ) -> LuaResult<Self> {
         if let Value::Table(t) = &value {
             Ok(Self(t.get(1_isize)?, t.get_prot(2, lua, data_table)?))
         } else {
-            Err(LuaError::FromLuaConversionError {
-                from: value.type_name(),
-                to: "DaytimeColorLookupTable item",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "DaytimeColorLookupTable item",
+                Some("expected table".into()),
+            ))
         }
     }
 }
@@ -2859,25 +4133,35 @@ pub enum ColorLookupTable {
     Filename(FileName),
 }
 
+impl ColorLookupTable {
+    /// Size of color lookup table image
+    pub const IMAGE_SIZE: (SpriteSizeType, SpriteSizeType) = (256, 16);
+}
+
 impl<'lua> PrototypeFromLua<'lua> for ColorLookupTable {
     fn prototype_from_lua(
-        value: Value<'lua>,
+        value: LuaValue<'lua>,
         _lua: &'lua Lua,
-        _data_table: &mut DataTable,
+        data_table: &mut DataTable,
     ) -> LuaResult<Self> {
         if let Value::String(s) = &value {
             let s = s.to_str()?;
             if s == "identity" {
                 Ok(Self::Identity)
             } else {
+                let (width, height) = Self::IMAGE_SIZE;
+                data_table.register_resource(crate::prototypes::ResourceRecord {
+                    path: s.into(),
+                    resource_type: crate::prototypes::ResourceType::Image(width, height, None),
+                });
                 Ok(Self::Filename(s.into()))
             }
         } else {
-            Err(LuaError::FromLuaConversionError {
-                from: value.type_name(),
-                to: "ColorLookupTable",
-                message: Some("expected string".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "ColorLookupTable",
+                Some("expected string".into()),
+            ))
         }
     }
 }
@@ -2971,7 +4255,7 @@ impl BitXorAssign for ItemPrototypeFlags {
 }
 
 /// <https://wiki.factorio.com/Prototype/AmmoItem#ammo_type>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct AmmoItemAmmoType {
     ammo_type: AmmoType,
     source_type: AmmoSourceType,
@@ -2995,6 +4279,8 @@ pub enum FilterMode {
     Blacklist,
 }
 
+prot_from_str!(FilterMode);
+
 /// <https://wiki.factorio.com/Prototype/ItemWithInventory#insertion_priority_mode>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Hash, EnumString, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
@@ -3033,39 +4319,106 @@ impl SelectionMode {
     pub const AVOID_ROLLING_STOCK: Self = Self(1 << 20);
 }
 
+impl SelectionMode {
+    /// Names of modes, in order of their bits
+    const MODE_NAMES: [&'static str; 21] = [
+        "blueprint",
+        "deconstruct",
+        "cancel-deconstruct",
+        "items",
+        "trees",
+        "buildable-type",
+        "nothing",
+        "items-to-place",
+        "any-entity",
+        "any-tile",
+        "same-force",
+        "not-same-force",
+        "friend",
+        "enemy",
+        "upgrade",
+        "cancel-upgrade",
+        "downgrade",
+        "entity-with-health",
+        "entity-with-force",
+        "entity-with-owner",
+        "avoid-rolling-stock",
+    ];
+
+    /// Names of modes in this set, as used in `selection_mode` of prototypes
+    pub fn modes(&self) -> Vec<&'static str> {
+        Self::MODE_NAMES
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.0 & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl FromStr for SelectionMode {
+    type Err = PrototypesErr;
+
+    /// Parse a single mode
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::MODE_NAMES
+            .iter()
+            .position(|name| *name == s)
+            .map(|bit| Self(1 << bit))
+            .ok_or_else(|| PrototypesErr::InvalidTypeStr("SelectionMode".into(), s.into()))
+    }
+}
+
+impl Display for SelectionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.modes().join(", "))
+    }
+}
+
+impl<'lua> PrototypeFromLua<'lua> for SelectionMode {
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        _data_table: &mut DataTable,
+    ) -> LuaResult<Self> {
+        let modes = if let Value::String(s) = &value {
+            vec![s.to_str()?.to_owned()]
+        } else {
+            lua.unpack::<Vec<String>>(value)?
+        };
+        let mut result = Self(0);
+        for mode in modes {
+            result |= mode.parse::<Self>().map_err(LuaError::external)?;
+        }
+        Ok(result)
+    }
+}
+
 impl<T: AsRef<str>> FromIterator<T> for SelectionMode {
     fn from_iter<I: IntoIterator<Item = T>>(in_arr: I) -> Self {
         let mut result = Self(0);
         for item in in_arr {
-            match item.as_ref() {
-                "blueprint" => result |= Self::BLUEPRINT,
-                "deconstruct" => result |= Self::DECONSTRUCT,
-                "cancel-deconstruct" => result |= Self::CANCEL_DECONSTRUCT,
-                "items" => result |= Self::ITEMS,
-                "trees" => result |= Self::TREES,
-                "buildable-type" => result |= Self::BUILDABLE_TYPE,
-                "nothing" => result |= Self::NOTHING,
-                "items-to-place" => result |= Self::ITEMS_TO_PLACE,
-                "any-entity" => result |= Self::ANY_ENTITY,
-                "any-tile" => result |= Self::ANY_TILE,
-                "same-force" => result |= Self::SAME_FORCE,
-                "not-same-force" => result |= Self::NOT_SAME_FORCE,
-                "friend" => result |= Self::FRIEND,
-                "enemy" => result |= Self::ENEMY,
-                "upgrade" => result |= Self::UPGRADE,
-                "cancel-upgrade" => result |= Self::CANCEL_UPGRADE,
-                "downgrade" => result |= Self::DOWNGRADE,
-                "entity-with-health" => result |= Self::ENTITY_WITH_HEALTH,
-                "entity-with-force" => result |= Self::ENTITY_WITH_FORCE,
-                "entity-with-owner" => result |= Self::ENTITY_WITH_OWNER,
-                "avoid-rolling-stock" => result |= Self::AVOID_ROLLING_STOCK,
-                _ => {}
+            if let Ok(mode) = item.as_ref().parse::<Self>() {
+                result |= mode;
             }
         }
         result
     }
 }
 
+#[test]
+fn selection_mode_roundtrip() {
+    for (bit, name) in SelectionMode::MODE_NAMES.iter().enumerate() {
+        let mode: SelectionMode = name.parse().unwrap();
+        assert_eq!(mode, SelectionMode(1 << bit));
+        assert_eq!(mode.to_string(), *name);
+    }
+    let modes = SelectionMode::BLUEPRINT | SelectionMode::SAME_FORCE | SelectionMode::UPGRADE;
+    assert_eq!(modes.to_string(), "blueprint, same-force, upgrade");
+    assert_eq!(modes.modes().into_iter().collect::<SelectionMode>(), modes);
+    assert!("blueprints".parse::<SelectionMode>().is_err());
+}
+
 impl BitAnd for SelectionMode {
     type Output = Self;
     fn bitand(self, rhs: Self) -> Self::Output {
@@ -3122,6 +4475,141 @@ pub struct EffectSpec(f64); // `bonus` key // Pecision is ignored beyond two dec
 #[derive(Debug, Clone)]
 pub struct Resistances(Vec<Resistance>);
 
+/// Difficulty data that can be disabled, used in [DifficultyPair]
+pub trait DifficultyData: Clone {
+    fn set_enabled(&mut self, enabled: bool);
+}
+
+/// Value of `normal` or `expensive` field of a prototype
+#[derive(Debug, Clone)]
+pub enum DifficultyValue<T> {
+    /// Not set (`nil`)
+    Undefined,
+    /// Set to `false`
+    Disabled,
+    Defined(T),
+}
+
+/// `normal` and `expensive` difficulty data of Recipe and Technology prototypes.
+///
+/// If one difficulty is not defined, it uses the other difficulty's data. If one is defined and
+/// the other is set to `false`, the other copies data of the first one with `enabled = false`.
+/// If data is defined directly in prototype body, without `normal` and `expensive`, it's used
+/// for both.
+#[derive(Debug, Clone)]
+pub struct DifficultyPair<T> {
+    normal: T,
+    expensive: Option<T>,
+}
+
+impl<T: DifficultyData> DifficultyPair<T> {
+    /// Resolves the fallback rules. `None` if neither difficulty is defined.
+    pub fn new(normal: DifficultyValue<T>, expensive: DifficultyValue<T>) -> Option<Self> {
+        let disabled = |data: &T| {
+            let mut data = data.clone();
+            data.set_enabled(false);
+            data
+        };
+        Some(match (normal, expensive) {
+            (DifficultyValue::Defined(normal), DifficultyValue::Defined(expensive)) => Self {
+                normal,
+                expensive: Some(expensive),
+            },
+            (DifficultyValue::Defined(normal), DifficultyValue::Undefined) => Self {
+                normal,
+                expensive: None,
+            },
+            (DifficultyValue::Defined(normal), DifficultyValue::Disabled) => Self {
+                expensive: Some(disabled(&normal)),
+                normal,
+            },
+            (DifficultyValue::Undefined, DifficultyValue::Defined(expensive)) => Self {
+                normal: expensive,
+                expensive: None,
+            },
+            (DifficultyValue::Disabled, DifficultyValue::Defined(expensive)) => Self {
+                normal: disabled(&expensive),
+                expensive: Some(expensive),
+            },
+            _ => return None,
+        })
+    }
+
+    pub fn normal(&self) -> &T {
+        &self.normal
+    }
+
+    /// Expensive difficulty data, falls back to normal
+    pub fn expensive(&self) -> &T {
+        self.expensive.as_ref().unwrap_or(&self.normal)
+    }
+}
+
+#[test]
+fn difficulty_pair_fallback() {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Data(u8, bool);
+    impl DifficultyData for Data {
+        fn set_enabled(&mut self, enabled: bool) {
+            self.1 = enabled
+        }
+    }
+    use DifficultyValue::*;
+    let pair = DifficultyPair::new(Defined(Data(1, true)), Undefined).unwrap();
+    assert_eq!(pair.expensive(), &Data(1, true));
+    let pair = DifficultyPair::new(Undefined, Defined(Data(2, true))).unwrap();
+    assert_eq!(pair.normal(), &Data(2, true));
+    let pair = DifficultyPair::new(Defined(Data(1, true)), Disabled).unwrap();
+    assert_eq!(pair.expensive(), &Data(1, false));
+    let pair = DifficultyPair::new(Disabled, Defined(Data(2, true))).unwrap();
+    assert_eq!(pair.normal(), &Data(2, false));
+    assert_eq!(pair.expensive(), &Data(2, true));
+    assert!(DifficultyPair::<Data>::new(Disabled, Disabled).is_none());
+}
+
+impl<'lua, T: PrototypeFromLua<'lua> + DifficultyData> PrototypeFromLua<'lua>
+    for DifficultyPair<T>
+{
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<Self> {
+        let type_name = value.type_name();
+        if let Value::Table(p_table) = &value {
+            let mut difficulty = |key: &str| -> LuaResult<DifficultyValue<T>> {
+                Ok(match p_table.get_as::<Value>(key)? {
+                    Value::Nil => DifficultyValue::Undefined,
+                    Value::Boolean(false) => DifficultyValue::Disabled,
+                    v => DifficultyValue::Defined(T::prototype_from_lua(v, lua, data_table)?),
+                })
+            };
+            let normal = difficulty("normal")?;
+            let expensive = difficulty("expensive")?;
+            let (normal, expensive) =
+                if let (DifficultyValue::Undefined, DifficultyValue::Undefined) =
+                    (&normal, &expensive)
+                {
+                    let body = T::prototype_from_lua(value.clone(), lua, data_table)?;
+                    (DifficultyValue::Defined(body), DifficultyValue::Undefined)
+                } else {
+                    (normal, expensive)
+                };
+            Self::new(normal, expensive).ok_or(conversion_error(
+                type_name,
+                "DifficultyPair",
+                Some("`normal` and `expensive` can't both be false".into()),
+            ))
+        } else {
+            Err(conversion_error(
+                type_name,
+                "DifficultyPair",
+                Some("Expected table".into()),
+            ))
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Recipe#Recipe_data>
 #[derive(Debug, Clone)]
 pub struct RecipeData {
@@ -3147,6 +4635,96 @@ pub struct RecipeData {
     main_product: Option<String>,    // Name (not prototype reference) of recipe from `results`
 }
 
+impl RecipeData {
+    pub fn ingredients(&self) -> &[IngredientPrototype] {
+        &self.ingredients
+    }
+
+    pub fn results(&self) -> &[ProductPrototype] {
+        &self.results
+    }
+
+    pub fn energy_required(&self) -> f64 {
+        self.energy_required
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl DifficultyData for RecipeData {
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled
+    }
+}
+
+#[cfg(test)]
+impl RecipeData {
+    /// Data with given ingredients and results and default values of other properties
+    pub(crate) fn stub(ingredients: &[(ProductType, f64)], results: &[(ProductType, f64)]) -> Self {
+        let ingredients = ingredients.iter().map(|(product, amount)| match product {
+            ProductType::Item(name) => IngredientPrototype::Item(ItemIngredientPrototype {
+                name: name.clone(),
+                amount: *amount as u16,
+                catalyst_amount: 0,
+            }),
+            ProductType::Fluid(name) => IngredientPrototype::Fluid(FluidIngredientPrototype {
+                name: name.clone(),
+                amount: *amount,
+                temperature: None,
+                minimum_temperature: None,
+                maximum_temperature: None,
+                catalyst_amount: 0.0,
+                fluidbox_index: 0,
+            }),
+        });
+        let results = results.iter().map(|(product, amount)| match product {
+            ProductType::Item(name) => ProductPrototype::Item(ItemProductPrototype {
+                name: name.clone(),
+                show_details_in_recipe_tooltip: true,
+                amount: Some(*amount as u16),
+                probability: 1.0,
+                amount_min: None,
+                amount_max: None,
+                catalyst_amount: 0,
+            }),
+            ProductType::Fluid(name) => ProductPrototype::Fluid(FluidProductPrototype {
+                name: name.clone(),
+                show_details_in_recipe_tooltip: true,
+                probability: 1.0,
+                amount: Some(*amount),
+                amount_min: None,
+                amount_max: None,
+                temperature: None,
+                catalyst_amount: 0.0,
+                fuildbox_index: 0,
+            }),
+        });
+        Self {
+            ingredients: ingredients.collect(),
+            results: results.collect(),
+            energy_required: 0.5,
+            emissions_multiplier: 1.0,
+            requester_paste_multiplier: 30,
+            overload_multiplier: 0,
+            allow_inserter_overload: true,
+            enabled: true,
+            hidden: false,
+            hide_from_stats: false,
+            hide_from_player_crafting: false,
+            allow_decomposition: true,
+            allow_as_intermediate: true,
+            allow_intermediates: true,
+            always_show_made_in: false,
+            show_amount_in_title: true,
+            always_show_products: false,
+            unlock_results: true,
+            main_product: None,
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Types/IngredientPrototype>
 #[derive(Debug, Clone)]
 pub enum IngredientPrototype {
@@ -3155,6 +4733,31 @@ pub enum IngredientPrototype {
     Fluid(FluidIngredientPrototype),
 }
 
+impl IngredientPrototype {
+    /// Name of required item or fluid
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Item(iip) => &iip.name,
+            Self::Fluid(fip) => &fip.name,
+        }
+    }
+
+    /// Required item or fluid
+    pub fn product(&self) -> ProductType {
+        match self {
+            Self::Item(iip) => ProductType::Item(iip.name.clone()),
+            Self::Fluid(fip) => ProductType::Fluid(fip.name.clone()),
+        }
+    }
+
+    pub fn amount(&self) -> f64 {
+        match self {
+            Self::Item(iip) => iip.amount.into(),
+            Self::Fluid(fip) => fip.amount,
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Types/ItemIngredientPrototype>
 #[derive(Debug, Clone)]
 pub struct ItemIngredientPrototype {
@@ -3202,7 +4805,7 @@ pub enum ShortcutStyle {
 }
 
 /// <https://wiki.factorio.com/Prototype/Technology#Technology_data>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct TechnologyData {
     upgrade: bool,                     // Default: false
     enabled: bool,                     // Default: true
@@ -3215,6 +4818,74 @@ pub struct TechnologyData {
     effects: Vec<ModifierPrototype>,
 }
 
+impl TechnologyData {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn prerequisites(&self) -> &[String] {
+        &self.prerequisites
+    }
+
+    /// Names of recipes unlocked by `unlock-recipe` effects
+    pub fn unlocked_recipes(&self) -> Vec<&str> {
+        self.effects
+            .iter()
+            .filter_map(|e| match &e.body {
+                ModifierPrototypeBody::UnlockRecipe(ur) => Some(ur.recipe.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Names of items given by `give-item` effects
+    pub fn given_items(&self) -> Vec<&str> {
+        self.effects
+            .iter()
+            .filter_map(|e| match &e.body {
+                ModifierPrototypeBody::GiveItem(gi) => Some(gi.item.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn effects(&self) -> &[ModifierPrototype] {
+        &self.effects
+    }
+
+    /// Names and amounts of science packs required per research unit
+    pub fn science_packs(&self) -> Vec<(&str, u32)> {
+        self.unit
+            .ingredients
+            .iter()
+            .filter_map(|i| match i {
+                IngredientPrototype::Item(iip) => Some((iip.name.as_str(), iip.amount.into())),
+                IngredientPrototype::Fluid(_) => None,
+            })
+            .collect()
+    }
+
+    /// Number of research units for given level. `count` takes priority over `count_formula`.
+    /// `None` if the formula is invalid or doesn't evaluate to a positive number.
+    pub fn research_count(&self, level: u32) -> Option<u64> {
+        if let Some(count) = self.unit.count {
+            return Some(count);
+        }
+        let count = eval_count_formula(self.unit.count_formula.as_ref()?, level).ok()?;
+        if count.is_finite() && count >= 1.0 {
+            Some(count as u64)
+        } else {
+            None
+        }
+    }
+}
+
+impl DifficultyData for TechnologyData {
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Technology#unit>
 #[derive(Debug, Clone)]
 pub struct TechnologyUnit {
@@ -3225,6 +4896,141 @@ pub struct TechnologyUnit {
     ingredients: Vec<IngredientPrototype>, // All Items must be tools
 }
 
+/// Error in `count_formula` of [TechnologyUnit]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FormulaError {
+    #[error("unexpected `{0}` at position {1}")]
+    UnexpectedChar(char, usize),
+    #[error("unexpected end of formula")]
+    UnexpectedEnd,
+    #[error("invalid number `{0}`")]
+    InvalidNumber(String),
+}
+
+/// Evaluate `count_formula` of [TechnologyUnit] for given level. Supports numbers, `L` or `l`
+/// for the level, `+`, `-`, `*`, `/`, `^` and parentheses.
+pub fn eval_count_formula(formula: &str, level: u32) -> Result<f64, FormulaError> {
+    struct Parser<'a> {
+        chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+        level: f64,
+    }
+
+    impl Parser<'_> {
+        fn skip_whitespace(&mut self) {
+            while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        }
+
+        fn next_if(&mut self, c: char) -> bool {
+            self.skip_whitespace();
+            self.chars.next_if(|(_, next)| *next == c).is_some()
+        }
+
+        fn unexpected(&mut self) -> FormulaError {
+            match self.chars.next() {
+                Some((i, c)) => FormulaError::UnexpectedChar(c, i),
+                None => FormulaError::UnexpectedEnd,
+            }
+        }
+
+        fn expression(&mut self) -> Result<f64, FormulaError> {
+            let mut result = self.term()?;
+            loop {
+                if self.next_if('+') {
+                    result += self.term()?
+                } else if self.next_if('-') {
+                    result -= self.term()?
+                } else {
+                    return Ok(result);
+                }
+            }
+        }
+
+        fn term(&mut self) -> Result<f64, FormulaError> {
+            let mut result = self.power()?;
+            loop {
+                if self.next_if('*') {
+                    result *= self.power()?
+                } else if self.next_if('/') {
+                    result /= self.power()?
+                } else {
+                    return Ok(result);
+                }
+            }
+        }
+
+        // Right-associative
+        fn power(&mut self) -> Result<f64, FormulaError> {
+            let base = self.operand()?;
+            if self.next_if('^') {
+                Ok(base.powf(self.power()?))
+            } else {
+                Ok(base)
+            }
+        }
+
+        fn operand(&mut self) -> Result<f64, FormulaError> {
+            if self.next_if('-') {
+                return Ok(-self.power()?);
+            }
+            if self.next_if('(') {
+                let result = self.expression()?;
+                return if self.next_if(')') {
+                    Ok(result)
+                } else {
+                    Err(self.unexpected())
+                };
+            }
+            if self.next_if('L') || self.next_if('l') {
+                return Ok(self.level);
+            }
+            let mut number = String::new();
+            while let Some((_, c)) = self.chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.') {
+                number.push(c)
+            }
+            if number.is_empty() {
+                return Err(self.unexpected());
+            }
+            number
+                .parse()
+                .map_err(|_| FormulaError::InvalidNumber(number))
+        }
+    }
+
+    let mut parser = Parser {
+        chars: formula.char_indices().peekable(),
+        level: level.into(),
+    };
+    let result = parser.expression()?;
+    parser.skip_whitespace();
+    match parser.chars.next() {
+        None => Ok(result),
+        Some((i, c)) => Err(FormulaError::UnexpectedChar(c, i)),
+    }
+}
+
+#[test]
+fn count_formula() {
+    assert_eq!(eval_count_formula("2^(L-6)*1000", 8), Ok(4000.0));
+    assert_eq!(eval_count_formula("1000 * (l - 6)", 8), Ok(2000.0));
+    assert_eq!(eval_count_formula("50*L^2-L", 2), Ok(198.0));
+    assert_eq!(eval_count_formula("-2^2", 1), Ok(-4.0));
+    assert_eq!(eval_count_formula("2^3^2", 1), Ok(512.0));
+    assert_eq!(eval_count_formula("8/2/2", 1), Ok(2.0));
+    assert_eq!(
+        eval_count_formula("(L", 1),
+        Err(FormulaError::UnexpectedEnd)
+    );
+    assert_eq!(
+        eval_count_formula("L x", 1),
+        Err(FormulaError::UnexpectedChar('x', 2))
+    );
+    assert_eq!(
+        eval_count_formula("1.2.3", 1),
+        Err(FormulaError::InvalidNumber("1.2.3".into()))
+    );
+    assert_eq!(eval_count_formula("", 1), Err(FormulaError::UnexpectedEnd));
+}
+
 /// <https://wiki.factorio.com/Prototype/Technology#max_level>
 #[derive(Debug, Clone)]
 pub enum TechnologyMaxLevel {
@@ -3233,7 +5039,7 @@ pub enum TechnologyMaxLevel {
 }
 
 /// <https://wiki.factorio.com/Types/ModifierPrototype>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct ModifierPrototype {
     mp_type: ModifierPrototypeType,
     body: ModifierPrototypeBody,
@@ -3242,6 +5048,16 @@ pub struct ModifierPrototype {
     use_icon_overlay_constant: Option<bool>, // This is stupid
 }
 
+impl ModifierPrototype {
+    pub fn mp_type(&self) -> ModifierPrototypeType {
+        self.mp_type
+    }
+
+    pub fn body(&self) -> &ModifierPrototypeBody {
+        &self.body
+    }
+}
+
 /// <https://wiki.factorio.com/Types/ModifierPrototype>
 #[derive(Debug, Clone)]
 pub enum ModifierPrototypeBody {
@@ -3331,6 +5147,16 @@ pub struct GiveItemModifierPrototype {
     count: ItemCountType, // Default: 1 // Must be > 0
 }
 
+impl GiveItemModifierPrototype {
+    pub fn item(&self) -> &str {
+        &self.item
+    }
+
+    pub fn count(&self) -> ItemCountType {
+        self.count
+    }
+}
+
 /// <https://wiki.factorio.com/Types/GunSpeedModifierPrototype>
 #[derive(Debug, Clone)]
 pub struct GunSpeedModifierPrototype {
@@ -3344,6 +5170,12 @@ pub struct UnlockRecipeModifierPrototype {
     recipe: String, // Name of the recipe
 }
 
+impl UnlockRecipeModifierPrototype {
+    pub fn recipe(&self) -> &str {
+        &self.recipe
+    }
+}
+
 /// <https://wiki.factorio.com/Types/BoolModifierPrototype>
 #[derive(Debug, Clone)]
 pub struct BoolModifierPrototype {
@@ -3426,6 +5258,82 @@ pub struct SimulationDefinition {
     pub override_volume: bool, // Default: false // default not confirmed
 }
 
+impl SimulationDefinition {
+    /// Scripts that are run, `init_file` and `update_file` replace inline `init` and `update`
+    fn inline_scripts(&self) -> impl Iterator<Item = &str> {
+        [
+            (&self.init_file, &self.init),
+            (&self.update_file, &self.update),
+        ]
+        .into_iter()
+        .filter(|(file, _)| file.is_none())
+        .map(|(_, script)| script.as_str())
+    }
+
+    /// Blueprint strings embedded as string literals in inline scripts, usually passed to
+    /// `create_entities_from_blueprint_string`. Scripts loaded from files are not inspected.
+    pub fn blueprint_strings(&self) -> Vec<&str> {
+        self.inline_scripts()
+            .flat_map(|script| {
+                script
+                    .split(['"', '\'', '[', ']'])
+                    .map(str::trim)
+                    .filter(|s| s.len() > 1 && s.starts_with('0'))
+                    .filter(|s| {
+                        s.bytes()
+                            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'='))
+                    })
+            })
+            .collect()
+    }
+}
+
+impl ResourceWalk for SimulationDefinition {
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>) {
+        records.extend(
+            [&self.init_file, &self.update_file]
+                .into_iter()
+                .flatten()
+                .map(|file| ResourceRecord {
+                    path: file.name.clone(),
+                    resource_type: ResourceType::Script,
+                }),
+        )
+    }
+}
+
+#[test]
+fn simulation_blueprint_strings() {
+    let simulation = SimulationDefinition {
+        save: None,
+        init_file: None,
+        init: r#"
+            game.surfaces[1].create_entities_from_blueprint_string
+            {
+              string = "0eNqVkMEKwjAQRP9lzltoU1pp"
+            }
+            game.camera_position = {0, 0.5}
+        "#
+        .into(),
+        update_file: Some(FileName::new("__base__/script/update.lua".into())),
+        update: "local bp = [[0eNpVjsEKgjAQRP9lznuI]]".into(),
+        init_update_count: 0,
+        length: 0,
+        generate_map: false,
+        checkboard: true,
+        volume_modifier: None,
+        override_volume: false,
+    };
+    assert_eq!(
+        simulation.blueprint_strings(),
+        vec!["0eNqVkMEKwjAQRP9lzltoU1pp"]
+    );
+    let mut records = vec![];
+    simulation.walk_resources(&mut records);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].path, "__base__/script/update.lua");
+}
+
 /// <https://wiki.factorio.com/Types/TipStatus>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Hash, EnumString, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
@@ -3440,8 +5348,16 @@ pub enum TipStatus {
     Completed,
 }
 
+impl Display for TipStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+prot_from_str!(TipStatus);
+
 /// <https://wiki.factorio.com/Types/BoxSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct BoxSpecification {
     pub sprite: Sprite,
     pub dimension_spec: BoxSpecificationDimensionSpec,
@@ -3449,7 +5365,7 @@ pub struct BoxSpecification {
 
 impl<'lua> PrototypeFromLua<'lua> for BoxSpecification {
     fn prototype_from_lua(
-        value: Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua Lua,
         data_table: &mut DataTable,
     ) -> LuaResult<Self> {
@@ -3469,11 +5385,11 @@ impl<'lua> PrototypeFromLua<'lua> for BoxSpecification {
                 dimension_spec,
             })
         } else {
-            Err(LuaError::FromLuaConversionError {
-                from: value.type_name(),
-                to: "BoxSpecification",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "BoxSpecification",
+                Some("expected table".into()),
+            ))
         }
     }
 }
@@ -3484,6 +5400,7 @@ pub enum BoxSpecificationDimensionSpec {
     NotWholeBox(f64),   // `is_whole_box` = false (Default); `max_side_length`
 }
 
+/// <https://wiki.factorio.com/Prototype/AmbientSound#track_type>
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Hash, EnumString, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
 pub enum TrackType {
@@ -3494,4 +5411,10 @@ pub enum TrackType {
     MenuTrack,
 }
 
+impl Display for TrackType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
 prot_from_str!(TrackType);
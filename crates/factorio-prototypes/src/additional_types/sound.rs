@@ -4,9 +4,10 @@ use serde_with::serde_as;
 use thiserror::Error;
 
 use super::{FileName, SoundFileType};
+use crate::prototypes::{DataTable, ResourceRecord, ResourceType, ResourceWalk};
 
 /// <https://wiki.factorio.com/Types/LayeredSound>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 #[serde(from = "LayeredSoundIntermediate")]
 pub struct LayeredSound {
     pub layers: Vec<Sound>,
@@ -38,10 +39,40 @@ pub enum Sound {
     Variations(Vec<SoundVariation>),
 }
 
+impl Sound {
+    /// Sound files of all variations
+    pub fn filenames(&self) -> Vec<&FileName<SoundFileType>> {
+        match self {
+            Self::AsTable(table) => table.variations().iter().map(|v| &v.filename).collect(),
+            Self::Variations(variations) => variations.iter().map(|v| &v.filename).collect(),
+        }
+    }
+
+    /// Record sound files of all variations as resources
+    pub(crate) fn register_resources(&self, data_table: &mut DataTable) {
+        let mut records = vec![];
+        self.walk_resources(&mut records);
+        for record in records {
+            data_table.register_resource(record)
+        }
+    }
+}
+
+impl ResourceWalk for Sound {
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>) {
+        records.extend(self.filenames().into_iter().map(|filename| ResourceRecord {
+            path: filename.name.clone(),
+            resource_type: ResourceType::Sound,
+        }))
+    }
+}
+
 /// <https://wiki.factorio.com/Types/Sound>
 #[serde_as]
 #[derive(Debug, Clone, Deserialize)]
 pub struct SoundAsTable {
+    #[serde(flatten)]
+    pub sound: SoundAsTableVariations,
     pub aggregation: Option<SoundAggregation>,
     #[serde(default = "default_bool::<false>")]
     pub allow_random_repeat: bool,
@@ -49,6 +80,24 @@ pub struct SoundAsTable {
     pub audible_distance_modifier: f64,
 }
 
+impl SoundAsTable {
+    /// All variations, a single one if it's defined in the table itself
+    pub fn variations(&self) -> &[SoundVariation] {
+        match &self.sound {
+            SoundAsTableVariations::Variations { variations } => variations,
+            SoundAsTableVariations::Single(variation) => std::slice::from_ref(variation),
+        }
+    }
+}
+
+/// <https://wiki.factorio.com/Types/Sound#variations>
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SoundAsTableVariations {
+    Variations { variations: Vec<SoundVariation> },
+    Single(SoundVariation), // `filename` and other properties of variation in the table itself
+}
+
 /// <https://wiki.factorio.com/Types/Sound#aggregation>
 #[derive(Debug, Clone, Deserialize)]
 pub struct SoundAggregation {
@@ -145,8 +194,38 @@ impl TryFrom<SoundVariationIntermediate> for SoundVariation {
     }
 }
 
+#[test]
+fn sound_as_table_filenames() {
+    let filenames = |json: &str| -> Vec<String> {
+        let sound: Sound = serde_json::from_str(json).unwrap();
+        let mut records = vec![];
+        sound.walk_resources(&mut records);
+        records.into_iter().map(|r| r.path).collect()
+    };
+    assert_eq!(
+        filenames(r#"{"filename": "__base__/sound/a.ogg", "volume": 0.5}"#),
+        ["__base__/sound/a.ogg"]
+    );
+    assert_eq!(
+        filenames(
+            r#"{
+                "variations": [
+                    {"filename": "__base__/sound/a.ogg"},
+                    {"filename": "__base__/sound/b.ogg"}
+                ],
+                "allow_random_repeat": true
+            }"#
+        ),
+        ["__base__/sound/a.ogg", "__base__/sound/b.ogg"]
+    );
+    assert_eq!(
+        filenames(r#"[{"filename": "__base__/sound/a.ogg"}]"#),
+        ["__base__/sound/a.ogg"]
+    );
+}
+
 /// <https://wiki.factorio.com/Types/WorkingSound>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct WorkingSound {
     #[serde(flatten)]
     pub sound: WorkingSoundSoundDef, // If property not present, Sound is constructed from WorkingSound fields
@@ -176,7 +255,7 @@ pub struct WorkingSound {
     pub deactivate_sound: Option<Sound>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 #[serde(untagged)]
 pub enum WorkingSoundSoundDef {
     Separate { sound: Sound },
@@ -184,7 +263,7 @@ pub enum WorkingSoundSoundDef {
 }
 
 /// <https://wiki.factorio.com/Types/InterruptibleSound>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct InterruptibleSound {
     pub sound: Sound,
     #[serde(default = "default_u32::<0>")]
@@ -192,7 +271,7 @@ pub struct InterruptibleSound {
 }
 
 /// <https://wiki.factorio.com/Types/CyclicSound>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct CyclicSound {
     pub begin_sound: Option<Sound>,
     pub middle_sound: Option<Sound>,
@@ -200,7 +279,7 @@ pub struct CyclicSound {
 }
 
 /// <https://wiki.factorio.com/Prototype/Tile#build_sound>
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 #[serde(untagged)]
 pub enum TileBuildSound {
     Single(Sound),
@@ -1,5 +1,6 @@
 use super::LocalisedString;
 use super::{Color, FileName, Position, Sound, Sprite, SpriteSizeType};
+use crate::prototypes::ResourceWalk;
 use strum_macros::{AsRefStr, EnumString};
 
 // FIXME?
@@ -11,7 +12,7 @@ use strum_macros::{AsRefStr, EnumString};
 // without Option<> wrap.
 
 /// <https://wiki.factorio.com/Types/StyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub enum StyleSpecification {
     ActivityBar(ActivityBarStyleSpecification),
     Camera(CameraStyleSpecification),
@@ -96,7 +97,7 @@ pub enum StretchRule {
 }
 
 /// <https://wiki.factorio.com/Types/ActivityBarStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct ActivityBarStyleSpecification {
     base: StyleSpecificationBase,
     speed: f32,
@@ -108,7 +109,7 @@ pub struct ActivityBarStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/EmptyWidgetStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub enum EmptyWidgetStyleSpecification {
     Camera(CameraStyleSpecification),
     Minimap(MinimapStyleSpecification),
@@ -116,7 +117,7 @@ pub enum EmptyWidgetStyleSpecification {
 
 /// <https://wiki.factorio.com/Types/EmptyWidgetStyleSpecification>
 /// <https://wiki.factorio.com/Types/CameraStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct CameraStyleSpecification {
     base: StyleSpecificationBase,
     graphical_set: ElementImageSet,
@@ -124,14 +125,14 @@ pub struct CameraStyleSpecification {
 
 /// <https://wiki.factorio.com/Types/EmptyWidgetStyleSpecification>
 /// <https://wiki.factorio.com/Types/MinimapStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct MinimapStyleSpecification {
     base: StyleSpecificationBase,
     graphical_set: ElementImageSet,
 }
 
 /// <https://wiki.factorio.com/Types/ElementImageSet>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct ElementImageSet {
     base: ElementImageSetLayer,
     shadow: Option<ElementImageSetLayer>,
@@ -139,7 +140,7 @@ pub struct ElementImageSet {
 }
 
 /// <https://wiki.factorio.com/Types/ElementImageSetLayer>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct ElementImageSetLayer {
     draw_type: DrawType,                 // Default: "inner"
     eisl_type: ElementImageSetLayerType, // Default: "none" if there are no other properties, "composition" otherwise
@@ -209,7 +210,7 @@ pub enum ElementImageSetLayerType {
 }
 
 /// <https://wiki.factorio.com/Types/DropDownStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct DropDownStyleSpecification {
     base: StyleSpecificationBase,
     button_style: Option<ButtonStyleSpecification>,
@@ -230,7 +231,7 @@ pub struct FlowStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/FrameStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct FrameStyleSpecification {
     base: StyleSpecificationBase,
     graphical_set: Option<ElementImageSet>,
@@ -248,7 +249,7 @@ pub struct FrameStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/BorderImageSet>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct BorderImageSet {
     scale: f64,        // Default: 1
     border_width: u32, // Default: 0
@@ -270,7 +271,7 @@ pub struct BorderImageSet {
 }
 
 /// <https://wiki.factorio.com/Types/GlowStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct GlowStyleSpecification {
     base: StyleSpecificationBase,
     image_set: Option<ElementImageSet>,
@@ -311,7 +312,7 @@ pub struct VerticalFlowStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/ImageStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct ImageStyleSpecification {
     base: StyleSpecificationBase,
     graphical_set: Option<ElementImageSet>,
@@ -345,14 +346,14 @@ pub enum RichTextSetting {
 }
 
 /// <https://wiki.factorio.com/Types/LineStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct LineStyleSpecification {
     base: StyleSpecificationBase,
     border: Option<BorderImageSet>,
 }
 
 /// <https://wiki.factorio.com/Types/ListBoxStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct ListBoxStyleSpecification {
     base: StyleSpecificationBase,
     item_style: Option<ButtonStyleSpecification>,
@@ -360,7 +361,7 @@ pub struct ListBoxStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/ProgressBarStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct ProgressBarStyleSpecification {
     base: StyleSpecificationBase,
     bar_width: u32,
@@ -375,7 +376,7 @@ pub struct ProgressBarStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/ProgressBarStyleSpecification#other_colors>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct OtherColor {
     less_than: f64,
     color: Option<Color>,
@@ -383,7 +384,7 @@ pub struct OtherColor {
 }
 
 /// <https://wiki.factorio.com/Types/HorizontalScrollBarStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct HorizontalScrollBarStyleSpecification {
     base: StyleSpecificationBase,
     background_graphical_set: Option<ElementImageSet>,
@@ -391,7 +392,7 @@ pub struct HorizontalScrollBarStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/VerticalScrollBarStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct VerticalScrollBarStyleSpecification {
     base: StyleSpecificationBase,
     background_graphical_set: Option<ElementImageSet>,
@@ -399,7 +400,7 @@ pub struct VerticalScrollBarStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/ScrollPaneStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct ScrollPaneStyleSpecification {
     base: StyleSpecificationBase,
     vertical_flow_style: Option<VerticalFlowStyleSpecification>,
@@ -413,7 +414,7 @@ pub struct ScrollPaneStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/SliderStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct SliderStyleSpecification {
     base: StyleSpecificationBase,
     full_bar: Option<ElementImageSet>,
@@ -427,7 +428,7 @@ pub struct SliderStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/DoubleSliderStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct DoubleSliderStyleSpecification {
     base: StyleSpecificationBase,
     full_bar: Option<ElementImageSet>,
@@ -441,7 +442,7 @@ pub struct DoubleSliderStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/SpeechBubbleStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct SpeechBubbleStyleSpecification {
     base: StyleSpecificationBase,
     frame_style: Option<FrameStyleSpecification>,
@@ -453,7 +454,7 @@ pub struct SpeechBubbleStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/ButtonStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct ButtonStyleSpecification {
     base: StyleSpecificationBase,
     default_graphical_set: Option<ElementImageSet>,
@@ -481,7 +482,7 @@ pub struct ButtonStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/TechnologySlotStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct TechnologySlotStyleSpecification {
     base: StyleSpecificationBase,
     default_graphical_set: Option<ElementImageSet>,
@@ -542,7 +543,7 @@ pub struct TechnologySlotStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/CheckBoxStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct CheckBoxStyleSpecification {
     base: StyleSpecificationBase,
     default_graphical_set: Option<ElementImageSet>,
@@ -562,7 +563,7 @@ pub struct CheckBoxStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/RadioButtonStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct RadioButtonStyleSpecification {
     base: StyleSpecificationBase,
     default_graphical_set: Option<ElementImageSet>,
@@ -579,7 +580,7 @@ pub struct RadioButtonStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/SwitchStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct SwitchStyleSpecification {
     base: StyleSpecificationBase,
     left_button_position: u32,
@@ -594,7 +595,7 @@ pub struct SwitchStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/TabbedPaneStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct TabbedPaneStyleSpecification {
     base: StyleSpecificationBase,
     vertical_spacing: u32,
@@ -603,7 +604,7 @@ pub struct TabbedPaneStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/TableStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct TableStyleSpecification {
     base: StyleSpecificationBase,
     horizontal_spacing: i32,
@@ -665,7 +666,7 @@ pub enum ColumnAlignmentStr {
 }
 
 /// <https://wiki.factorio.com/Types/TabStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct TabStyleSpecification {
     base: StyleSpecificationBase,
     font: Option<String>,       // Name of Font prototype
@@ -695,7 +696,7 @@ pub struct TabStyleSpecification {
 }
 
 /// <https://wiki.factorio.com/Types/TextBoxStyleSpecification>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub struct TextBoxStyleSpecification {
     base: StyleSpecificationBase,
     font: Option<String>, // Name of Font prototype
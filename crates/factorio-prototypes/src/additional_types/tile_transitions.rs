@@ -1,15 +1,24 @@
+use super::graphics::register_image;
 use super::{Animation, FileName, Sprite, SpriteSizeType};
+use super::{DataTable, GetPrototype, PrototypeFromLua, ResourceWalk};
+use crate::prototypes::lua_compat::conversion_error;
+use factorio_lib_rs_derive::prot_from_str;
+use mlua::prelude::*;
 use strum_macros::{AsRefStr, EnumString};
 
 /// <https://wiki.factorio.com/Types/TileTransitions>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct TileTransitionsBase {
     // 3 properties need to be specified if `empty_transitions` is false
     // They also correspond to *_mask names
+    #[mandatory_if(!empty_transitions)]
     pub side: Option<TileTransitionSprite>,
+    #[mandatory_if(!empty_transitions)]
     pub inner_corner: Option<TileTransitionSprite>,
+    #[mandatory_if(!empty_transitions)]
     pub outer_corner: Option<TileTransitionSprite>,
-    pub empty_transitions: bool,                       // default: false
+    #[default(false)]
+    pub empty_transitions: bool, // default: false
     pub side_background: Option<TileTransitionSprite>, // And _mask
     pub side_effect_map: Option<TileTransitionSprite>,
     pub side_weights: Option<Vec<f32>>,
@@ -32,10 +41,14 @@ pub struct TileTransitionsBase {
     pub overlay_layer_group: Option<LayerGroup>,
     pub background_layer_group: Option<LayerGroup>,
     pub overlay_layer_offset: Option<i8>,
+    #[default(0_i8)]
     pub masked_overlay_layer_offset: i8, // Default: 0
-    pub background_layer_offset: i8,     // Default: 0
+    #[default(0_i8)]
+    pub background_layer_offset: i8, // Default: 0
     pub masked_background_layer_offset: Option<i8>,
+    #[default(false)]
     pub apply_effect_color_to_overlay: bool, // Default: false
+    #[default(false)]
     pub offset_background_layer_by_tile_layer: bool, // Default: false
 }
 
@@ -50,78 +63,186 @@ pub enum LayerGroup {
     Top,
 }
 
+prot_from_str!(LayerGroup);
+
 /// <https://wiki.factorio.com/Types/TileTransitionSprite>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua)]
 pub struct TileTransitionSprite {
+    #[use_self_forced]
     regular: TileTransitionSpriteSpec,
     hr_version: Option<TileTransitionSpriteSpec>,
 }
 
 /// <https://wiki.factorio.com/Types/TileTransitionSprite>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua)]
+#[post_extr_fn(Self::register_resources)]
 pub struct TileTransitionSpriteSpec {
     count: u32,
     picture: FileName,
-    tall: bool,        // Default: false
-    scale: f32,        // Default: 1
+    #[default(false)]
+    tall: bool, // Default: false
+    #[default(1.0_f32)]
+    scale: f32, // Default: 1
+    #[default(0_i16)]
     x: SpriteSizeType, // Default: 0
+    #[default(0_i16)]
     y: SpriteSizeType, // Default: 0
 }
 
+impl TileTransitionSpriteSpec {
+    // Only the first row of variations is accounted for
+    fn register_resources(&self, _lua: &Lua, data_table: &mut DataTable) -> LuaResult<()> {
+        let tile = tile_size(self.scale, 1, "TileTransitionSprite")?;
+        let rows = if self.tall { 2 } else { 1 };
+        register_image(
+            data_table,
+            &self.picture,
+            self.x as u32 + tile * self.count,
+            self.y as u32 + tile * rows,
+        );
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Tile#variants>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct MainTileTransitions {
+    #[use_self_forced]
     base: TileTransitionsBase,
     main: Vec<MainTileSprite>,
     material_background: Option<TileSprite>,
 }
 
 /// <https://wiki.factorio.com/Types/TileSprite>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua)]
+#[post_extr_fn(Self::register_resources)]
 pub struct TileSprite {
+    #[use_self_forced]
     regular: TileSpriteSpec,
     hr_version: Option<TileSpriteSpec>,
 }
 
+impl TileSprite {
+    fn register_resources(&self, _lua: &Lua, data_table: &mut DataTable) -> LuaResult<()> {
+        self.regular.register_image(data_table, 1)?;
+        if let Some(hr_version) = &self.hr_version {
+            hr_version.register_image(data_table, 1)?;
+        }
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Types/TileSprite>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct TileSpriteSpec {
     count: u32,
     picture: FileName,
-    scale: f32,        // Default: 1
+    #[default(1.0_f32)]
+    scale: f32, // Default: 1
+    #[default(0_i16)]
     x: SpriteSizeType, // Default: 0
+    #[default(0_i16)]
     y: SpriteSizeType, // Default: 0
     line_length: Option<u32>,
 }
 
+impl TileSpriteSpec {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        if self.count == 0 {
+            return Err(conversion_error(
+                "table",
+                "TileSprite",
+                Some("`count` can't be 0".into()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Record the picture, `size` is the side of a variation in tiles
+    fn register_image(&self, data_table: &mut DataTable, size: u32) -> LuaResult<()> {
+        let tile = tile_size(self.scale, size, "TileSprite")?;
+        let per_line = match self.line_length {
+            None | Some(0) => self.count,
+            Some(line_length) => line_length.min(self.count),
+        };
+        let lines = (self.count + per_line - 1) / per_line;
+        register_image(
+            data_table,
+            &self.picture,
+            self.x as u32 + tile * per_line,
+            self.y as u32 + tile * lines,
+        );
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Tile#variants>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua)]
 pub struct MainTileSprite {
+    #[use_self_forced]
     regular: MainTileSpriteSpec,
     hr_version: Option<MainTileSpriteSpec>,
 }
 
 /// <https://wiki.factorio.com/Prototype/Tile#variants>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct MainTileSpriteSpec {
+    #[use_self_forced]
     base: TileSpriteSpec,
     size: u32, // Only power of 2 from 1 to 128 are accepted // So whitelist: [1, 2, 4, 8, 16, 32, 64, 128]
+    #[default(1.0_f64)]
     probability: f64, // Default: 1
     weights: Option<Vec<f64>>,
 }
 
+impl MainTileSpriteSpec {
+    fn post_extr_fn(&self, _lua: &Lua, data_table: &mut DataTable) -> LuaResult<()> {
+        if !self.size.is_power_of_two() || self.size > 128 {
+            return Err(conversion_error(
+                "table",
+                "MainTileSprite",
+                Some("`size` must be a power of 2 from 1 to 128".into()),
+            ));
+        }
+        self.base.register_image(data_table, self.size)
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Tile#transitions>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct ExtraTileTransitions {
+    #[use_self_forced]
     base: TileTransitionsBase,
-    to_tiles: String, // Name of a Tile
+    to_tiles: Vec<String>, // (Names) Name of a Tile
     transition_group: u8,
 }
 
+impl ExtraTileTransitions {
+    /// Names of tiles these transitions are drawn to
+    pub fn to_tiles(&self) -> &[String] {
+        &self.to_tiles
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Tile#transitions_between_transitions>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct BetweenTileTransitions {
+    #[use_self_forced]
     base: TileTransitionsBase,
     transition_group1: u8,
     transition_group2: u8,
 }
+
+/// Side of a `size` tiles square in pixels at given `scale`
+fn tile_size(scale: f32, size: u32, type_name: &'static str) -> LuaResult<u32> {
+    if scale <= 0.0 {
+        return Err(conversion_error(
+            "table",
+            type_name,
+            Some("`scale` must be positive".into()),
+        ));
+    }
+    Ok((32.0 / scale).round() as u32 * size)
+}
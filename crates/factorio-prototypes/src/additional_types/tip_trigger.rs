@@ -1,7 +1,9 @@
-use strum_macros::{AsRefStr, EnumString};
+use std::fmt::{self, Display};
+use strum_macros::{AsRefStr, EnumDiscriminants, EnumString};
 
 /// <https://wiki.factorio.com/Types/TipTrigger>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, EnumDiscriminants)]
+#[strum_discriminants(derive(EnumString, AsRefStr), strum(serialize_all = "kebab-case"))]
 pub enum TipTrigger {
     Or(OrTipTrigger),
     And(AndTipTrigger),
@@ -33,6 +35,37 @@ pub enum TipTrigger {
     ManualWireDrag(ManualWireDragTipTrigger),
 }
 
+impl TipTrigger {
+    pub fn kind(&self) -> TipTriggerDiscriminants {
+        self.into()
+    }
+}
+
+/// Writes the `type` of the trigger
+impl Display for TipTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind().fmt(f)
+    }
+}
+
+impl Display for TipTriggerDiscriminants {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+#[test]
+fn tip_trigger_type_round_trip() {
+    for name in ["or", "dependencies-met", "research", "gate-over-rail-build"] {
+        let kind: TipTriggerDiscriminants = name.parse().unwrap();
+        assert_eq!(kind.to_string(), name)
+    }
+    assert_eq!(
+        "dependencies-met".parse::<TipTriggerDiscriminants>().ok(),
+        Some(TipTrigger::DependenciesMet(DependenciesMetTipTrigger).kind())
+    );
+}
+
 /// <https://wiki.factorio.com/Types/TipTrigger#OrTipTrigger>
 #[derive(Debug, Clone)]
 pub struct OrTipTrigger {
@@ -4,7 +4,8 @@ use super::{
     Base, BoundingBox, CollisionMask, CollisionMode, DamagePrototype, DamageTypeFilters,
     EntityPrototypeFlags, Factorio2DVector, ForceCondition,
 };
-use crate::prototypes::{DataTable, GetPrototype, PrototypeFromLua};
+use crate::prototypes::lua_compat::{conversion_error, LuaValue, TableGetCompat};
+use crate::prototypes::{DataTable, GetPrototype, PrototypeFromLua, ResourceWalk};
 use std::cmp;
 use strum::{EnumDiscriminants, EnumString};
 
@@ -12,7 +13,7 @@ use strum::{EnumDiscriminants, EnumString};
 
 // Reminder: check the wiki again to see if anything changed
 /// <https://wiki.factorio.com/Types/Trigger>
-#[derive(Debug, Clone, EnumDiscriminants)]
+#[derive(Debug, Clone, EnumDiscriminants, ResourceWalk)]
 #[strum_discriminants(derive(EnumString), strum(serialize_all = "kebab-case"))]
 pub enum Trigger {
     Direct(DirectTriggerItem),
@@ -23,14 +24,14 @@ pub enum Trigger {
 
 impl<'lua> PrototypeFromLua<'lua> for Trigger {
     fn prototype_from_lua(
-        value: mlua::Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua mlua::Lua,
         data_table: &mut DataTable,
     ) -> mlua::Result<Self> {
         if let mlua::Value::Table(table) = &value {
             Ok(
                 match table
-                    .get::<_, String>("type")?
+                    .get_as::<String>("type")?
                     .parse::<TriggerDiscriminants>()
                     .map_err(mlua::Error::external)?
                 {
@@ -49,17 +50,45 @@ impl<'lua> PrototypeFromLua<'lua> for Trigger {
                 },
             )
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "Trigger",
-                message: Some("Expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "Trigger",
+                Some("Expected table".into()),
+            ))
         }
     }
 }
 
+impl Trigger {
+    pub fn trigger_item(&self) -> &TriggerItemBase {
+        match self {
+            Self::Direct(t) => &t.trigger_item,
+            Self::Area(t) => &t.trigger_item,
+            Self::Line(t) => &t.trigger_item,
+            Self::Cluster(t) => &t.trigger_item,
+        }
+    }
+
+    /// Names of entities that can be created by this trigger, including nested triggers
+    pub fn referenced_entities(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = self
+            .trigger_item()
+            .action_delivery
+            .iter()
+            .flatten()
+            .flat_map(TriggerDelivery::referenced_entities)
+            .collect();
+        if let Self::Line(t) = self {
+            if let Some(range_effects) = &t.range_effects {
+                result.extend(range_effects.referenced_entities())
+            }
+        }
+        result
+    }
+}
+
 /// <https://wiki.factorio.com/Types/TriggerItem>
-#[derive(Debug, Clone, PrototypeFromLua, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, ResourceWalk)]
 pub struct TriggerItemBase {
     #[default(EntityPrototypeFlags::ALL)]
     pub entity_flags: EntityPrototypeFlags, // Default: all flags
@@ -79,7 +108,7 @@ pub struct TriggerItemBase {
 }
 
 /// <https://wiki.factorio.com/Types/DirectTriggerItem>
-#[derive(Debug, Clone, TriggerItem!, PrototypeFromLua)]
+#[derive(Debug, Clone, TriggerItem!, PrototypeFromLua, ResourceWalk)]
 pub struct DirectTriggerItem {
     #[use_self_forced]
     pub trigger_item: TriggerItemBase,
@@ -88,7 +117,7 @@ pub struct DirectTriggerItem {
 }
 
 /// <https://wiki.factorio.com/Types/AreaTriggerItem>
-#[derive(Debug, Clone, TriggerItem!, PrototypeFromLua)]
+#[derive(Debug, Clone, TriggerItem!, PrototypeFromLua, ResourceWalk)]
 pub struct AreaTriggerItem {
     #[use_self_forced]
     pub trigger_item: TriggerItemBase,
@@ -104,7 +133,7 @@ pub struct AreaTriggerItem {
 }
 
 /// <https://wiki.factorio.com/Types/LineTriggerItem>
-#[derive(Debug, Clone, TriggerItem!, PrototypeFromLua)]
+#[derive(Debug, Clone, TriggerItem!, PrototypeFromLua, ResourceWalk)]
 pub struct LineTriggerItem {
     #[use_self_forced]
     pub trigger_item: TriggerItemBase,
@@ -114,7 +143,7 @@ pub struct LineTriggerItem {
 }
 
 /// <https://wiki.factorio.com/Types/ClusterTriggerItem>
-#[derive(Debug, Clone, TriggerItem!, PrototypeFromLua)]
+#[derive(Debug, Clone, TriggerItem!, PrototypeFromLua, ResourceWalk)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct ClusterTriggerItem {
     #[use_self_forced]
@@ -132,11 +161,11 @@ impl ClusterTriggerItem {
         _data_table: &DataTable,
     ) -> mlua::prelude::LuaResult<()> {
         if self.cluster_count < 2.0 {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "ClusterTriggerItem",
-                message: Some("`cluster_count` must be at least 2".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "ClusterTriggerItem",
+                Some("`cluster_count` must be at least 2".into()),
+            ));
         }
         Ok(())
     }
@@ -145,7 +174,7 @@ impl ClusterTriggerItem {
 // ========== // TriggerDelivery // =========== //
 
 /// <https://wiki.factorio.com/Types/TriggerDelivery>
-#[derive(Debug, Clone, EnumDiscriminants)]
+#[derive(Debug, Clone, EnumDiscriminants, ResourceWalk)]
 #[strum_discriminants(derive(EnumString), strum(serialize_all = "kebab-case"))]
 pub enum TriggerDelivery {
     Instant(InstantTriggerDelivery),
@@ -158,14 +187,14 @@ pub enum TriggerDelivery {
 
 impl<'lua> PrototypeFromLua<'lua> for TriggerDelivery {
     fn prototype_from_lua(
-        value: mlua::Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua mlua::Lua,
         data_table: &mut DataTable,
     ) -> mlua::Result<Self> {
         if let mlua::Value::Table(table) = &value {
             Ok(
                 match table
-                    .get::<_, String>("type")?
+                    .get_as::<String>("type")?
                     .parse::<TriggerDeliveryDiscriminants>()
                     .map_err(mlua::Error::external)?
                 {
@@ -192,24 +221,48 @@ impl<'lua> PrototypeFromLua<'lua> for TriggerDelivery {
                 },
             )
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "TriggerDelivery",
-                message: Some("Expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "TriggerDelivery",
+                Some("Expected table".into()),
+            ))
         }
     }
 }
 
+impl TriggerDelivery {
+    /// Names of entities referenced by this delivery and its source and target effects
+    pub fn referenced_entities(&self) -> Vec<&str> {
+        let (source_effects, target_effects, delivered) = match self {
+            Self::Instant(d) => (&d.source_effects, &d.target_effects, None),
+            Self::Projectile(d) => (&d.source_effects, &d.target_effects, Some(&d.projectile)),
+            Self::FlameThrower(d) => (&d.source_effects, &d.target_effects, Some(&d.explosion)),
+            Self::Beam(d) => (&d.source_effects, &d.target_effects, Some(&d.beam)),
+            Self::Stream(d) => (&d.source_effects, &d.target_effects, Some(&d.stream)),
+            Self::Artillery(d) => (&d.source_effects, &d.target_effects, Some(&d.projectile)),
+        };
+        delivered
+            .map(String::as_str)
+            .into_iter()
+            .chain(
+                source_effects
+                    .iter()
+                    .chain(target_effects)
+                    .flat_map(TriggerEffect::referenced_entities),
+            )
+            .collect()
+    }
+}
+
 /// <https://wiki.factorio.com/Types/InstantTriggerDelivery>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct InstantTriggerDelivery {
     pub source_effects: Option<TriggerEffect>,
     pub target_effects: Option<TriggerEffect>,
 }
 
 /// <https://wiki.factorio.com/Types/ProjectileTriggerDelivery>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct ProjectileTriggerDelivery {
     pub source_effects: Option<TriggerEffect>,
     pub target_effects: Option<TriggerEffect>,
@@ -228,7 +281,7 @@ pub struct ProjectileTriggerDelivery {
 }
 
 /// <https://wiki.factorio.com/Types/FlameThrowerExplosionTriggerDelivery>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct FlameThrowerExplosionTriggerDelivery {
     pub source_effects: Option<TriggerEffect>,
     pub target_effects: Option<TriggerEffect>,
@@ -245,7 +298,7 @@ pub struct FlameThrowerExplosionTriggerDelivery {
 }
 
 /// <https://wiki.factorio.com/Types/BeamTriggerDelivery>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct BeamTriggerDelivery {
     pub source_effects: Option<TriggerEffect>,
     pub target_effects: Option<TriggerEffect>,
@@ -260,7 +313,7 @@ pub struct BeamTriggerDelivery {
 }
 
 /// <https://wiki.factorio.com/Types/StreamTriggerDelivery>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct StreamTriggerDelivery {
     pub source_effects: Option<TriggerEffect>,
     pub target_effects: Option<TriggerEffect>,
@@ -269,7 +322,7 @@ pub struct StreamTriggerDelivery {
 }
 
 /// <https://wiki.factorio.com/Types/ArtilleryTriggerDelivery>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk)]
 pub struct ArtilleryTriggerDelivery {
     pub source_effects: Option<TriggerEffect>,
     pub target_effects: Option<TriggerEffect>,
@@ -288,7 +341,7 @@ pub struct ArtilleryTriggerDelivery {
 // ============ // TriggerEffect // =========== //
 
 /// <https://wiki.factorio.com/Types/TriggerEffect>
-#[derive(Debug, Clone, EnumDiscriminants)]
+#[derive(Debug, Clone, EnumDiscriminants, ResourceWalk)]
 #[strum_discriminants(derive(EnumString), strum(serialize_all = "kebab-case"))]
 pub enum TriggerEffect {
     Damage(DamageTriggerEffectItem),
@@ -315,12 +368,12 @@ pub enum TriggerEffect {
 
 impl<'lua> PrototypeFromLua<'lua> for TriggerEffect {
     fn prototype_from_lua(
-        value: mlua::Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua mlua::Lua,
         data_table: &mut crate::prototypes::DataTable,
     ) -> mlua::Result<Self> {
         if let mlua::Value::Table(table) = &value {
-            if let Some(te_type) = table.get::<_, Option<String>>("type")? {
+            if let Some(te_type) = table.get_as::<Option<String>>("type")? {
                 Ok(
                     match te_type
                         .parse::<TriggerEffectDiscriminants>()
@@ -433,18 +486,36 @@ impl<'lua> PrototypeFromLua<'lua> for TriggerEffect {
                     },
                 )
             } else {
-                Err(mlua::Error::FromLuaConversionError {
-                    from: value.type_name(),
-                    to: "TriggerEffect",
-                    message: Some("Expected `type` field".into()),
-                })
+                Err(conversion_error(
+                    value.type_name(),
+                    "TriggerEffect",
+                    Some("Expected `type` field".into()),
+                ))
             }
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "TriggerEffect",
-                message: Some("Expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "TriggerEffect",
+                Some("Expected table".into()),
+            ))
+        }
+    }
+}
+
+impl TriggerEffect {
+    /// Names of entities created by this effect, including effects of nested triggers
+    pub fn referenced_entities(&self) -> Vec<&str> {
+        match self {
+            Self::CreateEntity(te) => vec![te.create_entity_trigger_effect.entity_name.as_str()],
+            Self::CreateExplosion(te) => {
+                vec![te.create_entity_trigger_effect.entity_name.as_str()]
+            }
+            Self::CreateFire(te) => vec![te.create_entity_trigger_effect.entity_name.as_str()],
+            Self::CreateSmoke(te) => vec![te.create_entity_trigger_effect.entity_name.as_str()],
+            Self::CreateSticker(te) => vec![te.stricker.as_str()],
+            Self::DestroyCliffs(te) => te.explosion.iter().map(String::as_str).collect(),
+            Self::NestedResult(te) => te.action.referenced_entities(),
+            _ => vec![],
         }
     }
 }
@@ -461,7 +532,7 @@ pub struct TriggerEffectItemBase {
     #[default(false)]
     pub affects_target: bool, // Default: false
     // We can safely use `.ok()?` because type was found to be a string earlier
-    #[default({let te_type = prot_table.get::<_, String>("type").ok()?; !["create-entity", "create-explosion", "create-fire", "create-smoke", "create-particle", "create-sticker"].contains(&te_type.as_ref())})]
+    #[default({let te_type = prot_table.get_as::<String>("type").ok()?; !["create-entity", "create-explosion", "create-fire", "create-smoke", "create-particle", "create-sticker"].contains(&te_type.as_ref())})]
     pub show_in_tooltip: bool, // Default: true // Default: false in some cases
     pub damage_type_filters: Option<DamageTypeFilters>,
 }
@@ -683,18 +754,18 @@ impl CreateDecorativesTriggerEffectItem {
         _data_table: &DataTable,
     ) -> mlua::prelude::LuaResult<()> {
         if self.spawn_max_radius >= 24.0 {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "CreateDecorativesTriggerEffectItem",
-                message: Some("`spawn_max_radius` must be < 24.0".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "CreateDecorativesTriggerEffectItem",
+                Some("`spawn_max_radius` must be < 24.0".into()),
+            ));
         }
         Ok(())
     }
 }
 
 /// <https://wiki.factorio.com/Types/NestedTriggerEffectItem>
-#[derive(Debug, Clone, TriggerEffectItem!, PrototypeFromLua)]
+#[derive(Debug, Clone, TriggerEffectItem!, PrototypeFromLua, ResourceWalk)]
 pub struct NestedTriggerEffectItem {
     #[use_self_forced]
     pub trigger_effect_item: TriggerEffectItemBase,
@@ -702,7 +773,7 @@ pub struct NestedTriggerEffectItem {
 }
 
 /// <https://wiki.factorio.com/Types/PlaySoundTriggerEffectItem>
-#[derive(Debug, Clone, TriggerEffectItem!, PrototypeFromLua)]
+#[derive(Debug, Clone, TriggerEffectItem!, PrototypeFromLua, ResourceWalk)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct PlaySoundTriggerEffectItem {
     #[use_self_forced]
@@ -872,7 +943,7 @@ pub enum TriggerTargetMask {
 
 impl<'lua> PrototypeFromLua<'lua> for TriggerTargetMask {
     fn prototype_from_lua(
-        value: mlua::Value<'lua>,
+        value: LuaValue<'lua>,
         lua: &'lua mlua::Lua,
         _data_table: &mut DataTable,
     ) -> mlua::Result<Self> {
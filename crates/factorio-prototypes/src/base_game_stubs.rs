@@ -0,0 +1,259 @@
+use mlua::prelude::*;
+
+use super::additional_types::{Color, Energy, FileName, IconSpec, IconSpecification};
+use super::prototype_type::PrototypeType;
+use super::{
+    AmmoCategory, AssemblingMachine, Container, DataTable, EquipmentCategory, Fluid, FuelCategory,
+    Furnace, ItemBase, ItemGroup, ItemPrototype, ItemSubGroup, MiningDrill, ModuleCategory,
+    PrototypeBaseSpec, PrototypeFromLua, PrototypesErr, RecipeCategory, ResourceCategory, Tool,
+    VirtualSignal,
+};
+
+/// Bundled list of stubs for [DataTable::with_base_game_stubs], see the file for the format
+pub const BASE_GAME_STUBS: &str = include_str!("base_game_stubs.txt");
+
+impl DataTable {
+    /// Table seeded with stubs of commonly referenced base game prototypes, from
+    /// [BASE_GAME_STUBS].
+    ///
+    /// This is a convenience for validating a mod without the base game, so that references to
+    /// names like `"iron-plate"` or `"crafting"` don't show up as missing. It's not an import of
+    /// the base game: stubs only have a name and placeholder values for mandatory properties,
+    /// only a few entity types are included and recipes and technologies are not.
+    pub fn with_base_game_stubs() -> Self {
+        let mut data_table = Self::default();
+        data_table
+            .add_stubs(BASE_GAME_STUBS)
+            .expect("bundled base game stubs are valid");
+        data_table
+    }
+
+    /// Add stub prototypes from a list in the format of [BASE_GAME_STUBS]. Prototypes that are
+    /// already present are kept.
+    ///
+    /// Supported types are `item`, `tool`, `fluid`, `virtual-signal`, `item-group`,
+    /// `item-subgroup`, the category prototypes and the `furnace`, `assembling-machine`,
+    /// `mining-drill` and `container` entities.
+    pub fn add_stubs(&mut self, list: &str) -> Result<(), PrototypesErr> {
+        let lua = Lua::new();
+        for line in list.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut columns = line.split_whitespace();
+            let ty_str = columns.next().unwrap_or_default();
+            let name = columns
+                .next()
+                .ok_or_else(|| PrototypesErr::FieldRequired(format!("name of {ty_str} stub")))?;
+            let ty: PrototypeType = ty_str.parse().map_err(|_| PrototypesErr::UnknownType {
+                category: ty_str.into(),
+                name: name.into(),
+                ty: ty_str.into(),
+            })?;
+            if self.contains(ty, name) {
+                continue;
+            }
+            let name = name.to_string();
+            let prototype_base = PrototypeBaseSpec {
+                localised_description: None,
+                localised_name: None,
+                order: String::new(),
+            };
+            match ty {
+                PrototypeType::ItemPrototype => self.extend(ItemPrototype {
+                    name,
+                    prototype_base,
+                    item: stub_item_base(),
+                })?,
+                PrototypeType::Tool => self.extend(Tool {
+                    name,
+                    prototype_base,
+                    item: stub_item_base(),
+                    durability: Some(1.0),
+                    durability_description_key: "description.durability-key".into(),
+                    durability_description_value: "description.durability-value".into(),
+                    infinite: false,
+                })?,
+                PrototypeType::Fluid => self.extend(Fluid {
+                    name,
+                    prototype_base,
+                    icon: stub_icon(),
+                    default_temperature: 15.0,
+                    base_color: Color::WHITE,
+                    flow_color: Color::WHITE,
+                    max_temperature: 15.0,
+                    heat_capacity: Energy(1000.0),
+                    fuel_value: Energy(0.0),
+                    emissions_multiplier: 1.0,
+                    subgroup: "fluid".into(),
+                    gas_temperature: f64::MAX,
+                    hidden: false,
+                })?,
+                PrototypeType::VirtualSignal => self.extend(VirtualSignal {
+                    name,
+                    prototype_base,
+                    icon: stub_icon(),
+                    subgroup: "virtual-signal".into(),
+                })?,
+                PrototypeType::ItemGroup => self.extend(ItemGroup {
+                    name,
+                    prototype_base,
+                    icon: stub_icon(),
+                    order_in_recipe: String::new(),
+                })?,
+                PrototypeType::ItemSubGroup => {
+                    let group = columns.next().ok_or_else(|| {
+                        PrototypesErr::FieldRequired(format!("group of {name} stub"))
+                    })?;
+                    self.extend(ItemSubGroup {
+                        name,
+                        prototype_base,
+                        group: group.into(),
+                    })?
+                }
+                PrototypeType::AmmoCategory => self.extend(AmmoCategory {
+                    name,
+                    prototype_base,
+                    bonus_gui_order: String::new(),
+                })?,
+                PrototypeType::EquipmentCategory => self.extend(EquipmentCategory {
+                    name,
+                    prototype_base,
+                })?,
+                PrototypeType::FuelCategory => self.extend(FuelCategory {
+                    name,
+                    prototype_base,
+                })?,
+                PrototypeType::ModuleCategory => self.extend(ModuleCategory {
+                    name,
+                    prototype_base,
+                })?,
+                PrototypeType::RecipeCategory => self.extend(RecipeCategory {
+                    name,
+                    prototype_base,
+                })?,
+                PrototypeType::ResourceCategory => self.extend(ResourceCategory {
+                    name,
+                    prototype_base,
+                })?,
+                PrototypeType::Furnace => {
+                    self.extend(load_entity_stub::<Furnace>(&lua, ty, name)?)?
+                }
+                PrototypeType::AssemblingMachine => {
+                    self.extend(load_entity_stub::<AssemblingMachine>(&lua, ty, name)?)?
+                }
+                PrototypeType::MiningDrill => {
+                    self.extend(load_entity_stub::<MiningDrill>(&lua, ty, name)?)?
+                }
+                PrototypeType::Container => {
+                    self.extend(load_entity_stub::<Container>(&lua, ty, name)?)?
+                }
+                _ => return Err(PrototypesErr::InvalidPrototypeType(ty_str.into())),
+            }
+        }
+        Ok(())
+    }
+}
+
+// Properties of entity stubs, in Lua. Mandatory properties of every entity come first, then the
+// ones of each type.
+const ENTITY_STUB: &str = r#"
+    map_generator_bounding_box = {{-0.5, -0.5}, {0.5, 0.5}},
+    repair_sound = {filename = "__core__/sound/silence-1sec.ogg"},
+    integration_patch = {},
+"#;
+const CRAFTING_MACHINE_STUB: &str = r#"
+    energy_usage = "1kW",
+    crafting_speed = 1,
+    crafting_categories = {},
+    energy_source = {type = "void"},
+"#;
+const FURNACE_STUB: &str = "result_inventory_size = 1, source_inventory_size = 1,";
+const MINING_DRILL_STUB: &str = r#"
+    vector_to_place_result = {0, 0},
+    resource_searching_radius = 0.49,
+    energy_usage = "1kW",
+    mining_speed = 1,
+    energy_source = {type = "void"},
+    resource_categories = {},
+    base_productivity = 0,
+    circuit_wire_connection_points = {},
+    circuit_connector_sprites = {},
+"#;
+const CONTAINER_STUB: &str = r#"
+    inventory_size = 1,
+    picture = {filename = "__core__/graphics/empty.png", size = 1},
+"#;
+
+// Entity stub of type `T` from the properties of its type. Resources and references of the stub are
+// placeholders, so they are recorded in a scratch table instead of the one the stub goes to.
+fn load_entity_stub<'lua, T: PrototypeFromLua<'lua>>(
+    lua: &'lua Lua,
+    ty: PrototypeType,
+    name: String,
+) -> Result<T, PrototypesErr> {
+    let properties = match ty {
+        PrototypeType::Furnace => format!("{CRAFTING_MACHINE_STUB}{FURNACE_STUB}"),
+        PrototypeType::AssemblingMachine => CRAFTING_MACHINE_STUB.to_string(),
+        PrototypeType::MiningDrill => MINING_DRILL_STUB.to_string(),
+        PrototypeType::Container => CONTAINER_STUB.to_string(),
+        _ => String::new(),
+    };
+    let load = || {
+        let table: LuaTable = lua.load(&format!("{{{ENTITY_STUB}{properties}}}")).eval()?;
+        table.set("type", ty.as_ref())?;
+        table.set("name", name.as_str())?;
+        T::prototype_from_lua(LuaValue::Table(table), lua, &mut DataTable::default())
+    };
+    load().map_err(|error| PrototypesErr::InvalidStub { ty, name, error })
+}
+
+pub(crate) fn stub_icon() -> IconSpecification {
+    IconSpecification::Icon(IconSpec {
+        icon: FileName::new("__core__/graphics/icons/unknown.png".into()),
+        icon_size: 64,
+        icon_mipmaps: 0,
+    })
+}
+
+fn stub_item_base() -> ItemBase {
+    ItemBase {
+        icon: Some(stub_icon()),
+        stack_size: 100,
+        place_result: String::new(),
+        placed_as_equipment_result: String::new(),
+        subgroup: "other".into(),
+        fuel_category: String::new(),
+        burnt_result: String::new(),
+        place_as_tile: None,
+        pictures: None,
+        flags: None,
+        default_request_amount: 100,
+        wire_count: 0,
+        fuel_value: Energy(0.0),
+        fuel_acceleration_multiplier: 1.0,
+        fuel_top_speed_multiplier: 1.0,
+        fuel_emissions_multiplier: 1.0,
+        fuel_glow_color: Color::BLACK,
+        open_sound: None,
+        close_sound: None,
+        dark_background_icon: None,
+        rocket_launch_products: None,
+        rocket_launch_product: None,
+    }
+}
+
+#[test]
+fn base_game_stubs_load() {
+    let data_table = DataTable::with_base_game_stubs();
+    assert!(data_table.find_item("iron-plate").is_some());
+    assert!(data_table.tool.contains_key("automation-science-pack"));
+    assert!(data_table.recipe_category.contains_key("crafting"));
+    assert!(data_table.contains(PrototypeType::Furnace, "stone-furnace"));
+    assert!(data_table.contains(PrototypeType::Container, "iron-chest"));
+    assert!(data_table.validate_subgroups_and_groups().is_ok());
+    assert!(matches!(
+        DataTable::default().add_stubs("recipe iron-plate"),
+        Err(PrototypesErr::InvalidPrototypeType(_))
+    ));
+}
@@ -0,0 +1,244 @@
+use super::{collect_errors, DataTable, Prototype, PrototypesErr, SimulationDefinition};
+use base64::Engine;
+use flate2::read::ZlibDecoder;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::io::Read;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum BlueprintError {
+    #[error("Unsupported blueprint string version: {0:?}")]
+    UnsupportedVersion(Option<char>),
+    #[error("Invalid base64 in blueprint string: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("Failed to decompress blueprint string: {0}")]
+    Decompress(#[from] std::io::Error),
+    #[error("Invalid blueprint JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Decode blueprint string, as exported from the game, into its JSON representation.
+///
+/// The string is a version byte (only `0` exists) followed by base64 of zlib-compressed JSON.
+pub fn decode_blueprint_string(s: &str) -> Result<Value, BlueprintError> {
+    let s = s.trim();
+    let mut chars = s.chars();
+    match chars.next() {
+        Some('0') => {}
+        version => return Err(BlueprintError::UnsupportedVersion(version)),
+    }
+    let compressed = base64::engine::general_purpose::STANDARD.decode(chars.as_str())?;
+    let mut json = String::new();
+    ZlibDecoder::new(compressed.as_slice()).read_to_string(&mut json)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Kind of prototype a name used in a blueprint refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BlueprintReference {
+    Entity,
+    Recipe,
+    Item,
+    Tile,
+    Fluid,
+    VirtualSignal,
+}
+
+/// Names of entities, items, recipes, tiles and icon signals used in a blueprint or blueprint
+/// book, with the kind of prototype each one refers to
+pub fn blueprint_references(bp: &Value) -> BTreeSet<(BlueprintReference, &str)> {
+    let mut references = BTreeSet::new();
+    let mut stack: Vec<&Value> = vec![bp];
+    while let Some(value) = stack.pop() {
+        if let Some(book) = value.get("blueprint_book") {
+            stack.extend(value_array(book, "blueprints"));
+            icon_references(book, &mut references);
+            continue;
+        }
+        let blueprint = match value.get("blueprint") {
+            Some(blueprint) => blueprint,
+            None => continue,
+        };
+        for entity in value_array(blueprint, "entities") {
+            if let Some(name) = value_str(entity, "name") {
+                references.insert((BlueprintReference::Entity, name));
+            }
+            if let Some(recipe) = value_str(entity, "recipe") {
+                references.insert((BlueprintReference::Recipe, recipe));
+            }
+            if let Some(items) = entity.get("items").and_then(Value::as_object) {
+                references.extend(
+                    items
+                        .keys()
+                        .map(|item| (BlueprintReference::Item, item.as_str())),
+                );
+            }
+        }
+        for tile in value_array(blueprint, "tiles") {
+            if let Some(name) = value_str(tile, "name") {
+                references.insert((BlueprintReference::Tile, name));
+            }
+        }
+        icon_references(blueprint, &mut references);
+    }
+    references
+}
+
+/// Names referenced by blueprints embedded in inline scripts of a simulation, see
+/// [SimulationDefinition::blueprint_strings]
+pub fn simulation_references(
+    simulation: &SimulationDefinition,
+) -> Result<BTreeSet<(BlueprintReference, String)>, BlueprintError> {
+    let mut references = BTreeSet::new();
+    for bp_string in simulation.blueprint_strings() {
+        let bp = decode_blueprint_string(bp_string)?;
+        references.extend(
+            blueprint_references(&bp)
+                .into_iter()
+                .map(|(kind, name)| (kind, name.to_string())),
+        );
+    }
+    Ok(references)
+}
+
+impl DataTable {
+    /// Whether prototype referenced from a blueprint exists
+    pub fn blueprint_reference_exists(&self, kind: BlueprintReference, name: &str) -> bool {
+        match kind {
+            BlueprintReference::Entity => self.entities().iter().any(|(p, _)| p.name() == name),
+            BlueprintReference::Recipe => self.recipe.contains_key(name),
+            BlueprintReference::Item => self.find_item(name).is_some(),
+            BlueprintReference::Tile => self.tile.contains_key(name),
+            BlueprintReference::Fluid => self.fluid.contains_key(name),
+            BlueprintReference::VirtualSignal => self.virtual_signal.contains_key(name),
+        }
+    }
+
+    /// Names used in a blueprint or blueprint book that don't refer to an existing prototype
+    fn missing_blueprint_references<'a>(&self, bp: &'a Value) -> BTreeSet<&'a str> {
+        let entities: BTreeSet<&str> = self
+            .entities()
+            .into_iter()
+            .map(|(p, _)| p.name().as_str())
+            .collect();
+        blueprint_references(bp)
+            .into_iter()
+            .filter(|(kind, name)| match kind {
+                BlueprintReference::Entity => !entities.contains(name),
+                _ => !self.blueprint_reference_exists(*kind, name),
+            })
+            .map(|(_, name)| name)
+            .collect()
+    }
+
+    /// Checks that every entity, item, recipe, tile and icon signal used in a blueprint or
+    /// blueprint book exists, meaning that the blueprint can be built with this set of prototypes.
+    /// Each missing name is reported once.
+    pub fn validate_blueprint(&self, bp: &Value) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .missing_blueprint_references(bp)
+            .into_iter()
+            .map(|name| PrototypesErr::PrototypeNotFound(name.into()))
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Checks blueprints embedded in simulations of tips and tricks items, so that the
+    /// prototypes they build exist. Missing prototypes and blueprint strings that can't be decoded
+    /// are reported as invalid `simulation` of the tip.
+    pub fn validate_tip_simulations(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut tips: Vec<_> = self.tips_and_tricks_item.values().collect();
+        tips.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut errors = vec![];
+        for tip in tips {
+            let simulation = match &tip.simulation {
+                Some(simulation) => simulation,
+                None => continue,
+            };
+            for bp_string in simulation.blueprint_strings() {
+                match decode_blueprint_string(bp_string) {
+                    Ok(bp) => errors.extend(
+                        self.missing_blueprint_references(&bp)
+                            .into_iter()
+                            .map(|name| PrototypesErr::missing_reference(tip, "simulation", name)),
+                    ),
+                    Err(_) => errors.push(PrototypesErr::invalid_field(
+                        tip,
+                        "simulation",
+                        "contains a blueprint string that can't be decoded",
+                    )),
+                }
+            }
+        }
+        collect_errors(errors)
+    }
+}
+
+fn icon_references<'a>(value: &'a Value, references: &mut BTreeSet<(BlueprintReference, &'a str)>) {
+    for icon in value_array(value, "icons") {
+        let signal = icon.get("signal");
+        let name = match signal.and_then(|s| value_str(s, "name")) {
+            Some(name) => name,
+            None => continue,
+        };
+        let kind = match signal.and_then(|s| value_str(s, "type")) {
+            Some("item") => BlueprintReference::Item,
+            Some("fluid") => BlueprintReference::Fluid,
+            Some("virtual") => BlueprintReference::VirtualSignal,
+            _ => continue,
+        };
+        references.insert((kind, name));
+    }
+}
+
+fn value_array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map_or(&[], Vec::as_slice)
+}
+
+fn value_str<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+#[test]
+fn decode_blueprint_string_roundtrip() {
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    let json = r#"{"blueprint":{"item":"blueprint","entities":[{"name":"inserter"}]}}"#;
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(json.as_bytes()).unwrap();
+    let encoded = base64::engine::general_purpose::STANDARD.encode(encoder.finish().unwrap());
+    let decoded = decode_blueprint_string(&format!("0{encoded}\n")).unwrap();
+    assert_eq!(decoded, serde_json::from_str::<Value>(json).unwrap());
+    assert!(matches!(
+        decode_blueprint_string(&format!("1{encoded}")),
+        Err(BlueprintError::UnsupportedVersion(Some('1')))
+    ));
+}
+
+#[test]
+fn blueprint_references_in_book() {
+    let bp: Value = serde_json::from_str(
+        r#"{"blueprint_book":{"blueprints":[{"blueprint":{
+            "entities":[{"name":"assembling-machine-1","recipe":"gear-wheel","items":{"speed-module":1}}],
+            "tiles":[{"name":"stone-path"}],
+            "icons":[{"signal":{"type":"virtual","name":"signal-A"}},{"signal":{"type":"unknown","name":"x"}}]
+        }}]}}"#,
+    )
+    .unwrap();
+    let references: Vec<_> = blueprint_references(&bp).into_iter().collect();
+    assert_eq!(
+        references,
+        vec![
+            (BlueprintReference::Entity, "assembling-machine-1"),
+            (BlueprintReference::Recipe, "gear-wheel"),
+            (BlueprintReference::Item, "speed-module"),
+            (BlueprintReference::Tile, "stone-path"),
+            (BlueprintReference::VirtualSignal, "signal-A"),
+        ]
+    );
+}
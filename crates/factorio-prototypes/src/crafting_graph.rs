@@ -0,0 +1,398 @@
+use super::{graph, DataTable};
+use crate::additional_types::ProductType;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Crafting dependencies between items and fluids.
+///
+/// Built from `normal` difficulty data of recipes and technologies with
+/// [DataTable::crafting_graph].
+#[derive(Debug, Clone, Default)]
+pub struct CraftingGraph {
+    /// All items and fluids
+    pub nodes: BTreeSet<ProductType>,
+    /// Recipes, by name
+    pub recipes: BTreeMap<String, RecipeEdge>,
+    /// Items and fluids mined from resources or pumped by offshore pumps
+    pub resources: BTreeSet<ProductType>,
+}
+
+/// Recipe in a [CraftingGraph], connecting its ingredients to its products
+#[derive(Debug, Clone)]
+pub struct RecipeEdge {
+    pub name: String,
+    /// Name of RecipeCategory
+    pub category: String,
+    /// Names of entities that can craft this recipe
+    pub machines: Vec<String>,
+    /// Ingredients and amounts consumed per craft
+    pub ingredients: Vec<(ProductType, f64)>,
+    /// Products and average amounts produced per craft
+    pub products: Vec<(ProductType, f64)>,
+    pub energy_required: f64,
+    /// Whether the recipe is available without research
+    pub enabled: bool,
+    /// Names of technologies that unlock this recipe
+    pub unlocked_by: Vec<String>,
+}
+
+impl RecipeEdge {
+    /// Whether the recipe is enabled from the start or unlocked by one of `researched`
+    /// technologies
+    pub fn is_unlocked(&self, researched: &BTreeSet<String>) -> bool {
+        self.enabled || self.unlocked_by.iter().any(|t| researched.contains(t))
+    }
+}
+
+impl CraftingGraph {
+    pub(crate) fn new(data_table: &DataTable) -> Self {
+        let mut nodes: BTreeSet<ProductType> = data_table
+            .item_subgroups()
+            .into_iter()
+            .map(|(p, _)| ProductType::Item(p.name().clone()))
+            .collect();
+        nodes.extend(data_table.fluid.keys().cloned().map(ProductType::Fluid));
+        let mut resources: BTreeSet<ProductType> = data_table
+            .resource
+            .values()
+            .filter_map(|p| p.entity.minable.as_ref())
+            .flat_map(|minable| minable.products().iter().map(|p| p.product()))
+            .collect();
+        resources.extend(
+            data_table
+                .offshore_pump
+                .values()
+                .map(|p| ProductType::Fluid(p.fluid.clone())),
+        );
+        let mut machines: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        let crafting_machines = data_table
+            .assembling_machine
+            .values()
+            .map(|p| (&p.name, &p.crafting_machine.crafting_categories))
+            .chain(
+                data_table
+                    .furnace
+                    .values()
+                    .map(|p| (&p.name, &p.crafting_machine.crafting_categories)),
+            )
+            .chain(
+                data_table
+                    .rocket_silo
+                    .values()
+                    .map(|p| (&p.name, &p.crafting_machine.crafting_categories)),
+            );
+        let characters = data_table
+            .character
+            .values()
+            .filter_map(|p| Some((&p.name, p.crafting_categories.as_ref()?)));
+        for (name, categories) in crafting_machines.chain(characters) {
+            for category in categories {
+                machines.entry(category).or_default().push(name.clone())
+            }
+        }
+        let mut unlocked_by: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for technology in data_table.technology.values() {
+            for recipe in technology.difficulty.normal().unlocked_recipes() {
+                unlocked_by
+                    .entry(recipe)
+                    .or_default()
+                    .push(technology.name.clone())
+            }
+        }
+        let recipes = data_table
+            .recipe
+            .values()
+            .map(|recipe| {
+                let edge = RecipeEdge {
+                    name: recipe.name.clone(),
+                    category: recipe.category.clone(),
+                    machines: machines
+                        .get(recipe.category.as_str())
+                        .cloned()
+                        .unwrap_or_default(),
+                    ingredients: recipe
+                        .difficulty
+                        .normal()
+                        .ingredients()
+                        .iter()
+                        .map(|i| (i.product(), i.amount()))
+                        .collect(),
+                    products: recipe
+                        .difficulty
+                        .normal()
+                        .results()
+                        .iter()
+                        .map(|p| (p.product(), p.expected_amount()))
+                        .collect(),
+                    energy_required: recipe.difficulty.normal().energy_required(),
+                    enabled: recipe.difficulty.normal().enabled(),
+                    unlocked_by: unlocked_by
+                        .get(recipe.name.as_str())
+                        .cloned()
+                        .unwrap_or_default(),
+                };
+                (recipe.name.clone(), edge)
+            })
+            .collect();
+        Self {
+            nodes,
+            recipes,
+            resources,
+        }
+    }
+
+    /// Recipes that have `product` as one of the products
+    pub fn recipes_producing(&self, product: &ProductType) -> Vec<&RecipeEdge> {
+        self.recipes
+            .values()
+            .filter(|r| r.products.iter().any(|(p, _)| p == product))
+            .collect()
+    }
+
+    /// Recipes that have `ingredient` as one of the ingredients
+    pub fn recipes_using(&self, ingredient: &ProductType) -> Vec<&RecipeEdge> {
+        self.recipes
+            .values()
+            .filter(|r| r.ingredients.iter().any(|(i, _)| i == ingredient))
+            .collect()
+    }
+
+    /// Everything that can be crafted, directly or through intermediates, from given inputs.
+    /// Inputs themselves are not included unless they can also be crafted from other inputs.
+    ///
+    /// Only recipes unlocked with `researched` technologies are used, see
+    /// [RecipeEdge::is_unlocked]. With `None`, research is not taken into account and all
+    /// recipes are used.
+    pub fn craftable_from(
+        &self,
+        inputs: &[ProductType],
+        researched: Option<&BTreeSet<String>>,
+    ) -> BTreeSet<&ProductType> {
+        let mut available: BTreeSet<&ProductType> = inputs.iter().collect();
+        let mut result = BTreeSet::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for recipe in self.recipes.values() {
+                if researched.is_none_or(|r| recipe.is_unlocked(r))
+                    && recipe
+                        .ingredients
+                        .iter()
+                        .all(|(i, _)| available.contains(i))
+                {
+                    for (product, _) in &recipe.products {
+                        changed |= available.insert(product);
+                        result.insert(product);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Items and fluids that have to be gathered to craft `product`: [resources](Self::resources),
+    /// ones no recipe produces and ones only produced by recipes that need them back, like water
+    /// from emptying a barrel of water. Ingredients of all the other recipes producing an item are
+    /// followed, alternatives are not chosen between. If `product` itself is raw, it's the only
+    /// raw input.
+    pub fn raw_inputs<'a>(&'a self, product: &'a ProductType) -> BTreeSet<&'a ProductType> {
+        let mut result = BTreeSet::new();
+        let mut visited: BTreeSet<&ProductType> = BTreeSet::new();
+        let mut stack: Vec<&ProductType> = vec![product];
+        while let Some(current) = stack.pop() {
+            if !visited.insert(current) {
+                continue;
+            }
+            let producing: Vec<&RecipeEdge> = if self.resources.contains(current) {
+                vec![]
+            } else {
+                self.recipes_producing(current)
+                    .into_iter()
+                    .filter(|recipe| !self.requires(recipe, current))
+                    .collect()
+            };
+            if producing.is_empty() {
+                result.insert(current);
+                continue;
+            }
+            for recipe in producing {
+                stack.extend(recipe.ingredients.iter().map(|(i, _)| i))
+            }
+        }
+        result
+    }
+
+    // Whether crafting `recipe` needs `product`, as an ingredient or to craft one of them.
+    // Resources are not crafted.
+    fn requires(&self, recipe: &RecipeEdge, product: &ProductType) -> bool {
+        let mut visited: BTreeSet<&ProductType> = BTreeSet::new();
+        let mut stack: Vec<&ProductType> = recipe.ingredients.iter().map(|(i, _)| i).collect();
+        while let Some(current) = stack.pop() {
+            if current == product {
+                return true;
+            }
+            if self.resources.contains(current) || !visited.insert(current) {
+                continue;
+            }
+            for producing in self.recipes_producing(current) {
+                stack.extend(producing.ingredients.iter().map(|(i, _)| i))
+            }
+        }
+        false
+    }
+
+    /// Groups of recipes that depend on each other's products, as recipe names. A recipe that
+    /// uses its own product is a cycle too.
+    pub fn find_cycles(&self) -> Vec<Vec<String>> {
+        let mut consumers: BTreeMap<&ProductType, Vec<&str>> = BTreeMap::new();
+        for recipe in self.recipes.values() {
+            for (ingredient, _) in &recipe.ingredients {
+                consumers.entry(ingredient).or_default().push(&recipe.name)
+            }
+        }
+        // Edges go from recipe to recipes consuming its products
+        let successors: BTreeMap<&str, Vec<&str>> = self
+            .recipes
+            .values()
+            .map(|recipe| {
+                let next = recipe
+                    .products
+                    .iter()
+                    .filter_map(|(p, _)| consumers.get(p))
+                    .flatten()
+                    .copied()
+                    .collect();
+                (recipe.name.as_str(), next)
+            })
+            .collect();
+        graph::find_cycles(&successors)
+            .into_iter()
+            .map(|cycle| cycle.into_iter().map(String::from).collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+fn item(name: &str) -> ProductType {
+    ProductType::Item(name.into())
+}
+
+// Iron plates smelted from ore, barrels made from plates and water barreled and unbarreled
+#[cfg(test)]
+fn barrel_table() -> DataTable {
+    use super::Recipe;
+    let water = ProductType::Fluid("water".into());
+    let mut data_table = DataTable::default();
+    let recipes = [
+        Recipe::stub(
+            "iron-plate",
+            &[(item("iron-ore"), 1.0)],
+            &[(item("iron-plate"), 1.0)],
+        ),
+        Recipe::stub(
+            "empty-barrel",
+            &[(item("iron-plate"), 1.0)],
+            &[(item("empty-barrel"), 1.0)],
+        ),
+        Recipe::stub(
+            "fill-water-barrel",
+            &[(water.clone(), 50.0), (item("empty-barrel"), 1.0)],
+            &[(item("water-barrel"), 1.0)],
+        ),
+        Recipe::stub(
+            "empty-water-barrel",
+            &[(item("water-barrel"), 1.0)],
+            &[(water, 50.0), (item("empty-barrel"), 1.0)],
+        ),
+    ];
+    for recipe in recipes {
+        data_table.extend(recipe).unwrap();
+    }
+    data_table
+}
+
+#[test]
+fn recipes_producing() {
+    let graph = barrel_table().crafting_graph();
+    let names = |recipes: Vec<&RecipeEdge>| -> Vec<String> {
+        recipes.into_iter().map(|r| r.name.clone()).collect()
+    };
+    assert_eq!(
+        names(graph.recipes_producing(&item("empty-barrel"))),
+        ["empty-barrel", "empty-water-barrel"]
+    );
+    assert_eq!(
+        names(graph.recipes_using(&item("water-barrel"))),
+        ["empty-water-barrel"]
+    );
+    assert!(graph.recipes_producing(&item("iron-ore")).is_empty());
+}
+
+#[test]
+fn craftable_from() {
+    let mut graph = barrel_table().crafting_graph();
+    let ore = [item("iron-ore")];
+    assert_eq!(
+        graph.craftable_from(&ore, None),
+        BTreeSet::from([&item("iron-plate"), &item("empty-barrel")])
+    );
+    let water = ProductType::Fluid("water".into());
+    let ore_and_water = [item("iron-ore"), water.clone()];
+    assert_eq!(graph.craftable_from(&ore_and_water, None).len(), 5);
+    let barrel = graph.recipes.get_mut("empty-barrel").unwrap();
+    barrel.enabled = false;
+    barrel.unlocked_by = vec!["fluid-handling".into()];
+    assert_eq!(
+        graph.craftable_from(&ore_and_water, Some(&BTreeSet::new())),
+        BTreeSet::from([&item("iron-plate")])
+    );
+    let researched = BTreeSet::from(["fluid-handling".to_string()]);
+    assert_eq!(
+        graph.craftable_from(&ore_and_water, Some(&researched)),
+        BTreeSet::from([
+            &item("iron-plate"),
+            &item("empty-barrel"),
+            &item("water-barrel"),
+            &water
+        ])
+    );
+}
+
+#[test]
+fn raw_inputs() {
+    let mut graph = barrel_table().crafting_graph();
+    let water = ProductType::Fluid("water".into());
+    // Water is only produced by emptying a barrel, which needs water
+    assert_eq!(graph.raw_inputs(&water), BTreeSet::from([&water]));
+    assert_eq!(
+        graph.raw_inputs(&item("empty-barrel")),
+        BTreeSet::from([&item("iron-ore")])
+    );
+    graph.resources = BTreeSet::from([item("iron-ore"), water.clone()]);
+    assert_eq!(
+        graph.raw_inputs(&item("water-barrel")),
+        BTreeSet::from([&water, &item("iron-ore")])
+    );
+    assert_eq!(
+        graph.raw_inputs(&item("iron-ore")),
+        BTreeSet::from([&item("iron-ore")])
+    );
+}
+
+#[test]
+fn find_cycles() {
+    let mut data_table = barrel_table();
+    data_table
+        .extend(super::Recipe::stub(
+            "kovarex",
+            &[(item("uranium-235"), 40.0), (item("uranium-238"), 5.0)],
+            &[(item("uranium-235"), 41.0), (item("uranium-238"), 2.0)],
+        ))
+        .unwrap();
+    assert_eq!(
+        data_table.crafting_graph().find_cycles(),
+        vec![
+            vec!["empty-water-barrel".to_string(), "fill-water-barrel".into()],
+            vec!["kovarex".into()]
+        ]
+    );
+}
@@ -0,0 +1,234 @@
+use super::DataTable;
+use mlua::prelude::*;
+use serde_json::{json, Map, Value};
+use std::ffi::c_void;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Version of this crate, cache files written by other versions are not used
+const CACHE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("Failed to load prototypes from Lua: {0}")]
+    Lua(#[from] LuaError),
+    #[error("Failed to write cache file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+// FNV-1a, so that the hash and file names stay the same between Rust versions
+fn source_hash(source: &[u8]) -> u64 {
+    source.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn cache_path(cache_dir: &Path, hash: u64) -> PathBuf {
+    cache_dir.join(format!("data-table-{hash:016x}.json"))
+}
+
+// `data.raw` JSON of the cache file, if it was written by this version for the same source
+fn read_cache(path: &Path, hash: u64) -> Option<Value> {
+    let mut cache: Value = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+    let same_source = cache["source_hash"] == format!("{hash:016x}");
+    (cache["version"] == CACHE_VERSION && same_source).then(|| cache["data_raw"].take())
+}
+
+// JSON of a Lua value that `json_to_lua` turns back into an equal value. Tables with keys
+// `1..=n` are arrays and tables with string keys are objects. `None` if the value has anything
+// JSON can't hold: functions, userdata, non-finite numbers, strings that aren't UTF-8, tables
+// with other keys and tables that contain themselves.
+fn lua_to_json(value: LuaValue, parents: &mut Vec<*const c_void>) -> Option<Value> {
+    let pointer = value.to_pointer();
+    Some(match value {
+        LuaValue::Boolean(value) => value.into(),
+        LuaValue::Integer(value) => value.into(),
+        LuaValue::Number(value) => serde_json::Number::from_f64(value)?.into(),
+        LuaValue::String(value) => value.to_str().ok()?.into(),
+        LuaValue::Table(table) => {
+            if parents.contains(&pointer) {
+                return None;
+            }
+            parents.push(pointer);
+            let len = table.raw_len();
+            let mut object = Map::new();
+            let mut array = vec![];
+            for pair in table.pairs::<LuaValue, LuaValue>() {
+                let (key, value) = pair.ok()?;
+                let value = lua_to_json(value, parents)?;
+                match key {
+                    LuaValue::String(key) => {
+                        object.insert(key.to_str().ok()?.into(), value);
+                    }
+                    LuaValue::Integer(index) if (1..=len).contains(&index) => {
+                        array.push((index, value))
+                    }
+                    _ => return None,
+                }
+            }
+            parents.pop();
+            match (object.is_empty(), array.is_empty()) {
+                (_, true) => Value::Object(object),
+                (true, false) if array.len() as LuaInteger == len => {
+                    array.sort_by_key(|(index, _)| *index);
+                    array.into_iter().map(|(_, value)| value).collect()
+                }
+                _ => return None,
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn json_to_lua<'lua>(lua: &'lua Lua, value: &Value) -> LuaResult<LuaValue<'lua>> {
+    Ok(match value {
+        Value::Null => LuaValue::Nil,
+        Value::Bool(value) => LuaValue::Boolean(*value),
+        Value::Number(value) => match value.as_i64().and_then(|v| v.try_into().ok()) {
+            Some(value) => LuaValue::Integer(value),
+            None => LuaValue::Number(value.as_f64().unwrap_or_default()),
+        },
+        Value::String(value) => LuaValue::String(lua.create_string(value)?),
+        Value::Array(values) => {
+            let values = values.iter().map(|value| json_to_lua(lua, value));
+            LuaValue::Table(lua.create_sequence_from(values.collect::<LuaResult<Vec<_>>>()?)?)
+        }
+        Value::Object(entries) => {
+            let table = lua.create_table()?;
+            for (key, value) in entries {
+                table.raw_set(key.as_str(), json_to_lua(lua, value)?)?;
+            }
+            LuaValue::Table(table)
+        }
+    })
+}
+
+impl DataTable {
+    /// Table of prototypes in Lua `source`, which evaluates to a table in the shape of
+    /// `data.raw`. The evaluated table is cached in `cache_dir` and loaded from there instead of
+    /// running the same source again.
+    ///
+    /// Cache files are named by a hash of `source` and store the version of this crate. A cache
+    /// file is only used if both match, otherwise the source is run and the file is written
+    /// again. Nothing else is tracked, so files that `source` loads with `require` have to be
+    /// included in it for changes to them to invalidate the cache.
+    ///
+    /// The cache holds `data.raw` itself, which is parsed with
+    /// [extend_from_lua](Self::extend_from_lua) both on a hit and on a miss, so the table,
+    /// including recorded resources and diagnostics, is the same either way. Tables that JSON
+    /// can't hold, like ones with functions, are not cached and the source is run on every call.
+    pub fn load_cached(source: &[u8], cache_dir: &Path) -> Result<Self, CacheError> {
+        let hash = source_hash(source);
+        let path = cache_path(cache_dir, hash);
+        let lua = Lua::new();
+        let data_raw = match read_cache(&path, hash) {
+            Some(data_raw) => json_to_lua(&lua, &data_raw)?,
+            None => {
+                let data_raw: LuaValue = lua.load(source).eval()?;
+                if let Some(data_raw) = lua_to_json(data_raw.clone(), &mut vec![]) {
+                    let cache = json!({
+                        "version": CACHE_VERSION,
+                        "source_hash": format!("{hash:016x}"),
+                        "data_raw": data_raw,
+                    });
+                    fs::create_dir_all(cache_dir)?;
+                    fs::write(path, cache.to_string())?;
+                }
+                data_raw
+            }
+        };
+        let mut data_table = Self::default();
+        data_table.extend_from_lua(&lua, lua.unpack(data_raw)?)?;
+        Ok(data_table)
+    }
+}
+
+#[test]
+fn load_cached() {
+    let cache_dir =
+        std::env::temp_dir().join(format!("factorio-prototypes-cache-{}", std::process::id()));
+    let source = br#"return {
+        ["recipe-category"] = {crafting = {type = "recipe-category", name = "crafting"}},
+        ["item-subgroup"] = {belt = {type = "item-subgroup", name = "belt", group = "logistics"}},
+    }"#;
+    let data_table = DataTable::load_cached(source, &cache_dir).unwrap();
+    assert!(data_table.recipe_category.contains_key("crafting"));
+    let path = cache_path(&cache_dir, source_hash(source));
+    let mut cache: Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+    assert_eq!(cache["version"], CACHE_VERSION);
+    // Second load uses the cache file, not the source
+    cache["data_raw"]["item-subgroup"]["belt"]["group"] = "cached".into();
+    fs::write(&path, cache.to_string()).unwrap();
+    let data_table = DataTable::load_cached(source, &cache_dir).unwrap();
+    assert_eq!(data_table.item_subgroup["belt"].group, "cached");
+    // Cache of another version is parsed again and overwritten
+    cache["version"] = "0.0.0".into();
+    fs::write(&path, cache.to_string()).unwrap();
+    let data_table = DataTable::load_cached(source, &cache_dir).unwrap();
+    assert_eq!(data_table.item_subgroup["belt"].group, "logistics");
+    assert_eq!(
+        read_cache(&path, source_hash(source)).unwrap()["item-subgroup"]["belt"]["group"],
+        "logistics"
+    );
+    // Changed source has another cache file
+    let changed =
+        br#"return {["fuel-category"] = {chemical = {type = "fuel-category", name = "chemical"}}}"#;
+    let data_table = DataTable::load_cached(changed, &cache_dir).unwrap();
+    assert!(data_table.fuel_category.contains_key("chemical"));
+    assert!(data_table.recipe_category.is_empty());
+    // Tables that JSON can't hold are run on every load
+    let unsupported = br#"return {["recipe-category"] = {crafting = {
+        type = "recipe-category", name = "crafting", callback = function() end,
+    }}}"#;
+    let data_table = DataTable::load_cached(unsupported, &cache_dir).unwrap();
+    assert!(data_table.recipe_category.contains_key("crafting"));
+    assert!(!cache_path(&cache_dir, source_hash(unsupported)).exists());
+    fs::remove_dir_all(&cache_dir).unwrap();
+}
+
+#[test]
+fn load_cached_matches_parsed() {
+    let cache_dir = std::env::temp_dir().join(format!(
+        "factorio-prototypes-cache-parsed-{}",
+        std::process::id()
+    ));
+    let source = br#"return {
+        container = {["wooden-chest"] = {
+            type = "container", name = "wooden-chest",
+            icon = "__base__/graphics/icons/wooden-chest.png", icon_size = 64, icon_mipmaps = 4,
+            flags = {"placeable-neutral", "player-creation"},
+            minable = {mining_time = 0.1, result = "wooden-chest"},
+            max_health = 100,
+            collision_box = {{-0.35, -0.35}, {0.35, 0.35}},
+            selection_box = {{-0.5, -0.5}, {0.5, 0.5}},
+            inventory_size = 16,
+            picture = {
+                filename = "__base__/graphics/entity/wooden-chest/wooden-chest.png",
+                priority = "extra-high", width = 32, height = 36, shift = {0.015625, -0.0625},
+            },
+        }},
+        recipe = {["wooden-chest"] = {
+            type = "recipe", name = "wooden-chest",
+            ingredients = {{"wood", 2}}, result = "wooden-chest",
+        }},
+    }"#;
+    let parsed = DataTable::load_cached(source, &cache_dir).unwrap();
+    assert!(cache_path(&cache_dir, source_hash(source)).exists());
+    let cached = DataTable::load_cached(source, &cache_dir).unwrap();
+    assert_eq!(
+        format!("{:?}", cached.container["wooden-chest"]),
+        format!("{:?}", parsed.container["wooden-chest"])
+    );
+    assert!(!parsed.unique_resource_paths().is_empty());
+    assert_eq!(
+        cached.unique_resource_paths(),
+        parsed.unique_resource_paths()
+    );
+    assert!(!parsed.diagnostics().is_empty());
+    assert_eq!(
+        format!("{:?}", cached.diagnostics()),
+        format!("{:?}", parsed.diagnostics())
+    );
+    fs::remove_dir_all(&cache_dir).unwrap();
+}
@@ -0,0 +1,182 @@
+use super::{
+    collect_errors, ResourceError, ResourceRecord, ResourceType, ResourceValidator, SpriteSizeType,
+};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+
+/// [ResourceValidator] that looks up resources on the filesystem.
+///
+/// Paths like `__base__/graphics/icons/iron-plate.png` are resolved using the registered mod
+/// directories. Image dimensions are read from PNG header.
+#[derive(Debug, Clone, Default)]
+pub struct FileSystemResourceValidator {
+    mods: HashMap<String, PathBuf>,
+}
+
+impl FileSystemResourceValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register directory of a mod, used to resolve `__mod_name__` paths
+    pub fn add_mod(&mut self, mod_name: impl Into<String>, directory: impl Into<PathBuf>) {
+        self.mods.insert(mod_name.into(), directory.into());
+    }
+
+    /// Resolve Factorio resource path to a filesystem path
+    pub fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let rest = path.strip_prefix("__")?;
+        let (mod_name, rest) = rest.split_once("__/")?;
+        Some(self.mods.get(mod_name)?.join(rest))
+    }
+
+    fn png_size(path: &PathBuf) -> Option<(SpriteSizeType, SpriteSizeType)> {
+        let mut header = [0_u8; 24];
+        File::open(path).ok()?.read_exact(&mut header).ok()?;
+        if &header[1..4] != b"PNG" || &header[12..16] != b"IHDR" {
+            return None;
+        }
+        let width = u32::from_be_bytes(header[16..20].try_into().ok()?);
+        let height = u32::from_be_bytes(header[20..24].try_into().ok()?);
+        Some((width.try_into().ok()?, height.try_into().ok()?))
+    }
+
+    fn validate_record(&self, record: &ResourceRecord) -> Result<(), ResourceError> {
+        let path = self
+            .resolve(&record.path)
+            .filter(|p| p.is_file())
+            .ok_or_else(|| ResourceError::FileNotFound(record.path.clone()))?;
+        if let ResourceType::Image(width, height, frame) = record.resource_type {
+            let (actual_width, actual_height) = Self::png_size(&path)
+                .ok_or_else(|| ResourceError::ImageUnreadable(record.path.clone()))?;
+            if actual_width < width || actual_height < height {
+                return Err(ResourceError::ImageSizeIncorrect(
+                    record.path.clone(),
+                    width,
+                    height,
+                    actual_width,
+                    actual_height,
+                ));
+            }
+            if let Some((frame_width, frame_height)) = frame {
+                if frame_width > 0
+                    && frame_height > 0
+                    && (actual_width % frame_width != 0 || actual_height % frame_height != 0)
+                {
+                    return Err(ResourceError::FrameGridMismatch(
+                        record.path.clone(),
+                        frame_width,
+                        frame_height,
+                        actual_width,
+                        actual_height,
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ResourceValidator for FileSystemResourceValidator {
+    fn validate(&self, resources: &[ResourceRecord]) -> Result<(), Vec<ResourceError>> {
+        // Same file is often used by many sprites, report it as missing or unreadable only once
+        let mut missing: HashSet<&str> = HashSet::new();
+        let mut errors = vec![];
+        for record in resources {
+            if missing.contains(record.path.as_str()) {
+                continue;
+            }
+            if let Err(error) = self.validate_record(record) {
+                if let ResourceError::FileNotFound(_) | ResourceError::ImageUnreadable(_) = error {
+                    missing.insert(&record.path);
+                }
+                errors.push(error)
+            }
+        }
+        collect_errors(errors)
+    }
+}
+
+#[cfg(test)]
+fn test_mod_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "factorio-prototypes-{}-{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(dir.join("graphics")).unwrap();
+    dir
+}
+
+#[cfg(test)]
+fn write_png_header(path: &PathBuf, width: u32, height: u32) {
+    let mut header = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    std::fs::write(path, header).unwrap();
+}
+
+#[cfg(test)]
+fn image_record(
+    path: &str,
+    width: SpriteSizeType,
+    height: SpriteSizeType,
+    frame: Option<(SpriteSizeType, SpriteSizeType)>,
+) -> ResourceRecord {
+    ResourceRecord {
+        path: path.into(),
+        resource_type: ResourceType::Image(width, height, frame),
+    }
+}
+
+#[test]
+fn frame_grid_mismatch() {
+    let dir = test_mod_dir("frame-grid");
+    write_png_header(&dir.join("graphics/sheet.png"), 96, 64);
+    let mut validator = FileSystemResourceValidator::new();
+    validator.add_mod("test", &dir);
+    let path = "__test__/graphics/sheet.png";
+    assert!(validator
+        .validate(&[image_record(path, 64, 64, Some((32, 32)))])
+        .is_ok());
+    let errors = validator
+        .validate(&[image_record(path, 64, 64, Some((64, 64)))])
+        .unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [ResourceError::FrameGridMismatch(p, 64, 64, 96, 64)] if p == path
+    ));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn errors_are_collected_once_per_file() {
+    let dir = test_mod_dir("collect");
+    write_png_header(&dir.join("graphics/small.png"), 16, 16);
+    std::fs::write(dir.join("graphics/broken.png"), b"not a png").unwrap();
+    let mut validator = FileSystemResourceValidator::new();
+    validator.add_mod("test", &dir);
+    let missing = "__test__/graphics/missing.png";
+    let broken = "__test__/graphics/broken.png";
+    let small = "__test__/graphics/small.png";
+    let errors = validator
+        .validate(&[
+            image_record(missing, 1, 1, None),
+            image_record(broken, 1, 1, None),
+            image_record(missing, 2, 2, None),
+            image_record(small, 32, 32, None),
+            image_record(broken, 2, 2, None),
+        ])
+        .unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [
+            ResourceError::FileNotFound(a),
+            ResourceError::ImageUnreadable(b),
+            ResourceError::ImageSizeIncorrect(c, 32, 32, 16, 16),
+        ] if a == missing && b == broken && c == small
+    ));
+    std::fs::remove_dir_all(dir).unwrap();
+}
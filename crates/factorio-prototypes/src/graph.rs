@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+/// Cycles of a directed graph, given as successors of every node: strongly connected components
+/// with more than one node and nodes that are their own successor. Successors that aren't keys
+/// of `successors` are ignored. Nodes of every cycle are sorted and cycles are ordered by their
+/// nodes, so the result doesn't depend on the order of successors.
+pub(crate) fn find_cycles<N: Ord + Copy>(successors: &BTreeMap<N, Vec<N>>) -> Vec<Vec<N>> {
+    let nodes: Vec<N> = successors.keys().copied().collect();
+    let successors: Vec<Vec<usize>> = successors
+        .values()
+        .map(|next| {
+            next.iter()
+                .filter_map(|n| nodes.binary_search(n).ok())
+                .collect()
+        })
+        .collect();
+
+    // Tarjan's strongly connected components algorithm, with explicit call stack
+    let mut index: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut lowlink = vec![0; nodes.len()];
+    let mut on_stack = vec![false; nodes.len()];
+    let mut stack: Vec<usize> = vec![];
+    let mut next_index = 0;
+    let mut result = vec![];
+    for start in 0..nodes.len() {
+        if index[start].is_some() {
+            continue;
+        }
+        let mut calls: Vec<(usize, usize)> = vec![(start, 0)];
+        index[start] = Some(next_index);
+        lowlink[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+        while let Some((v, i)) = calls.last().copied() {
+            if let Some(&w) = successors[v].get(i) {
+                calls.last_mut().unwrap().1 += 1;
+                match index[w] {
+                    None => {
+                        index[w] = Some(next_index);
+                        lowlink[w] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        calls.push((w, 0));
+                    }
+                    Some(w_index) if on_stack[w] => lowlink[v] = lowlink[v].min(w_index),
+                    _ => {}
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(u, _)) = calls.last() {
+                lowlink[u] = lowlink[u].min(lowlink[v]);
+            }
+            if index[v] == Some(lowlink[v]) {
+                let mut component = vec![];
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(nodes[w]);
+                    if w == v {
+                        break;
+                    }
+                }
+                if component.len() > 1 || successors[v].contains(&v) {
+                    component.sort();
+                    result.push(component)
+                }
+            }
+        }
+    }
+    result.sort();
+    result
+}
+
+#[test]
+fn graph_find_cycles() {
+    let successors = BTreeMap::from([
+        ("a", vec!["b"]),
+        ("b", vec!["c", "missing"]),
+        ("c", vec!["a", "d"]),
+        ("d", vec![]),
+        ("e", vec!["e"]),
+        ("f", vec!["d"]),
+    ]);
+    assert_eq!(
+        find_cycles(&successors),
+        vec![vec!["a", "b", "c"], vec!["e"]]
+    );
+    assert!(find_cycles(&BTreeMap::from([("a", vec!["b"]), ("b", vec![])])).is_empty());
+}
@@ -0,0 +1,189 @@
+use super::additional_types::IconSpecification;
+use super::prototype_type::PrototypeType;
+use super::{
+    AmmoCategory, DamageType, DataTable, EquipmentCategory, FuelCategory, ItemGroup, ItemSubGroup,
+    ModuleCategory, PrototypeBaseSpec, PrototypesErr, RecipeCategory, ResourceCategory,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+// Localised name and description are not read, they are `None` in prototypes made from JSON.
+fn prototype_base(value: &Value) -> PrototypeBaseSpec {
+    PrototypeBaseSpec {
+        localised_description: None,
+        localised_name: None,
+        order: value_string(value, "order").unwrap_or_default(),
+    }
+}
+
+fn value_string(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(Into::into)
+}
+
+// Name of the prototype, checking that `type` matches if it's present
+fn prototype_name(value: &Value, ty: PrototypeType) -> Result<String, PrototypesErr> {
+    match value.get("type").and_then(Value::as_str) {
+        Some(t) if t != ty.as_ref() => return Err(PrototypesErr::InvalidPrototypeType(t.into())),
+        _ => {}
+    }
+    value_string(value, "name")
+        .ok_or_else(|| PrototypesErr::FieldRequired(format!("name of {}", ty.as_ref())))
+}
+
+macro_rules! try_from_json {
+    ($($prot:ident),*) => {
+        $(
+            impl TryFrom<&Value> for $prot {
+                type Error = PrototypesErr;
+
+                fn try_from(value: &Value) -> Result<Self, Self::Error> {
+                    Ok(Self {
+                        name: prototype_name(value, PrototypeType::$prot)?,
+                        prototype_base: prototype_base(value),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+try_from_json!(
+    EquipmentCategory,
+    FuelCategory,
+    ModuleCategory,
+    RecipeCategory,
+    ResourceCategory
+);
+
+impl TryFrom<&Value> for AmmoCategory {
+    type Error = PrototypesErr;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: prototype_name(value, PrototypeType::AmmoCategory)?,
+            prototype_base: prototype_base(value),
+            bonus_gui_order: value_string(value, "bonus_gui_order").unwrap_or_default(),
+        })
+    }
+}
+
+impl TryFrom<&Value> for DamageType {
+    type Error = PrototypesErr;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: prototype_name(value, PrototypeType::DamageType)?,
+            prototype_base: prototype_base(value),
+            hidden: value
+                .get("hidden")
+                .and_then(Value::as_bool)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl TryFrom<&Value> for ItemGroup {
+    type Error = PrototypesErr;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let name = prototype_name(value, PrototypeType::ItemGroup)?;
+        let prototype_base = prototype_base(value);
+        let icon = IconSpecification::deserialize(value)
+            .map_err(|_| PrototypesErr::FieldRequired(format!("icon of {name}")))?;
+        Ok(Self {
+            order_in_recipe: value_string(value, "order_in_recipe")
+                .unwrap_or_else(|| prototype_base.order.clone()),
+            name,
+            prototype_base,
+            icon,
+        })
+    }
+}
+
+impl TryFrom<&Value> for ItemSubGroup {
+    type Error = PrototypesErr;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let name = prototype_name(value, PrototypeType::ItemSubGroup)?;
+        Ok(Self {
+            group: value_string(value, "group")
+                .ok_or_else(|| PrototypesErr::FieldRequired(format!("group of {name}")))?,
+            name,
+            prototype_base: prototype_base(value),
+        })
+    }
+}
+
+impl DataTable {
+    /// Add prototypes from JSON in the shape of `data.raw`: an object of prototype types, each
+    /// an object of prototypes by name. Meant for building tables from test fixtures without Lua.
+    ///
+    /// Only the simplest prototypes are supported: item groups and subgroups, damage types and
+    /// the category prototypes.
+    pub fn extend_from_json(&mut self, raw: &Value) -> Result<(), PrototypesErr> {
+        let categories = raw
+            .as_object()
+            .ok_or_else(|| PrototypesErr::FieldRequired("data.raw object".into()))?;
+        for (ty_str, prototypes) in categories {
+            for (name, prototype) in prototypes.as_object().into_iter().flatten() {
+                let ty: PrototypeType = ty_str.parse().map_err(|_| PrototypesErr::UnknownType {
+                    category: ty_str.clone(),
+                    name: name.clone(),
+                    ty: ty_str.clone(),
+                })?;
+                match ty {
+                    PrototypeType::AmmoCategory => {
+                        self.extend(AmmoCategory::try_from(prototype)?)?
+                    }
+                    PrototypeType::DamageType => self.extend(DamageType::try_from(prototype)?)?,
+                    PrototypeType::EquipmentCategory => {
+                        self.extend(EquipmentCategory::try_from(prototype)?)?
+                    }
+                    PrototypeType::FuelCategory => {
+                        self.extend(FuelCategory::try_from(prototype)?)?
+                    }
+                    PrototypeType::ItemGroup => self.extend(ItemGroup::try_from(prototype)?)?,
+                    PrototypeType::ItemSubGroup => {
+                        self.extend(ItemSubGroup::try_from(prototype)?)?
+                    }
+                    PrototypeType::ModuleCategory => {
+                        self.extend(ModuleCategory::try_from(prototype)?)?
+                    }
+                    PrototypeType::RecipeCategory => {
+                        self.extend(RecipeCategory::try_from(prototype)?)?
+                    }
+                    PrototypeType::ResourceCategory => {
+                        self.extend(ResourceCategory::try_from(prototype)?)?
+                    }
+                    _ => return Err(PrototypesErr::InvalidPrototypeType(ty_str.clone())),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn extend_from_json() {
+    let raw: Value = serde_json::from_str(
+        r#"{
+            "item-group": {"logistics": {"type": "item-group", "name": "logistics", "order": "a",
+                "icon": "__base__/graphics/item-group/logistics.png", "icon_size": 128}},
+            "item-subgroup": {"belt": {"type": "item-subgroup", "name": "belt", "group": "logistics"}},
+            "recipe-category": {"crafting": {"type": "recipe-category", "name": "crafting"}}
+        }"#,
+    )
+    .unwrap();
+    let mut data_table = DataTable::default();
+    data_table.extend_from_json(&raw).unwrap();
+    assert_eq!(data_table.item_group["logistics"].order_in_recipe, "a");
+    assert!(data_table.recipe_category.contains_key("crafting"));
+    assert!(data_table.validate_subgroups_and_groups().is_ok());
+    let wrong_type: Value =
+        serde_json::from_str(r#"{"fuel-category": {"chemical": {"type": "recipe-category"}}}"#)
+            .unwrap();
+    assert!(matches!(
+        DataTable::default().extend_from_json(&wrong_type),
+        Err(PrototypesErr::InvalidPrototypeType(_))
+    ));
+}
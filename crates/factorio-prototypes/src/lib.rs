@@ -1,347 +1,2796 @@
 //! # Prototypes
 //!
 //! This module aims to simulate the way Factorio loads mods.
+//!
+//! ## Features
+//!
+//! - `std` (default): filesystem access, namely [FileSystemResourceValidator] and path checks in
+//! [FileType](additional_types::FileType).
+//!
+//! - `indexmap`: [PrototypeFromLua] impl for `IndexMap`, which keeps the order of Lua table
+//! entries.
+//!
+//! - `blueprint`: decoding of blueprint strings and checking them against a [DataTable], see
+//! [blueprint].
+//!
+//! - `serde`: `Serialize` for string-backed enums (render layers, mod setting types and others),
+//! [CollisionMask] and [EffectTypeLimitation]. `Deserialize` is always available.
+//!
+//! Prototype definitions and parsing logic don't touch the filesystem, so for WASM builds disable
+//! default features and provide your own [ResourceValidator]. Full `no_std` is not possible, as
+//! `mlua` and the collections used by [DataTable] require `std`.
 
 mod abstract_prototypes;
 pub mod additional_types;
+mod base_game_stubs;
+#[cfg(feature = "blueprint")]
+pub mod blueprint;
+mod crafting_graph;
+#[cfg(all(feature = "json", feature = "std"))]
+mod data_table_cache;
+#[cfg(feature = "std")]
+mod fs_resource_validator;
+mod graph;
+#[cfg(feature = "json")]
+mod json_fixtures;
+pub mod lua_compat;
+mod memory_report;
 pub mod prototype_type;
+mod shared_data_table;
 mod utility;
 
 use std::{
-    collections::HashMap,
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap},
     fmt,
     hash::Hash,
     marker::PhantomData,
-    rc::{Rc, Weak},
+    ops::ControlFlow,
+    sync::{Arc, OnceLock, Weak},
 };
 
 pub use abstract_prototypes::*;
 use additional_types::*;
+pub use base_game_stubs::BASE_GAME_STUBS;
+pub use crafting_graph::*;
+#[cfg(all(feature = "json", feature = "std"))]
+pub use data_table_cache::CacheError;
 use factorio_prototypes_macros::{
-    prot_from_lua_blanket, Base, DataTableAccessable, ModSetting, Prototype, PrototypeFromLua,
+    prot_from_lua_blanket, Base, DataTableAccessable, HeapSize, ModSetting, Prototype,
+    PrototypeFromLua, ResourceWalk,
 };
+#[cfg(feature = "std")]
+pub use fs_resource_validator::*;
+use lua_compat::*;
+pub use memory_report::*;
+pub use shared_data_table::*;
 //use mlua::prelude::*;
 use prototype_type::PrototypeType;
 use thiserror::Error;
 pub use utility::*;
 
-#[cfg(feature = "concepts")]
-use crate::concepts::LocalisedString;
+#[cfg(feature = "concepts")]
+use crate::concepts::LocalisedString;
+
+#[cfg(not(feature = "concepts"))]
+type LocalisedString = String;
+
+// TODO: Make fields not acessible through traits pub
+
+// TODO
+// Current prototype definitions are based off Factorio wiki, which describe prototypes
+// *definitions*, meaning how they are defined in lua code and how they are parsed.
+// However, https://lua-api.factorio.com/ has a documentation on Lua*Prototype, which seems to have
+// less prototype types than wiki. And as it turns out, runtime prototypes are less diverse than
+// data-stage. For example, entire Entity abstract prtototype *and* it's subclasses are boiled down
+// to LuaEntityPrototype, which has all the protperties (read-only).
+// So, my idea is to implement all Lua*Prototype as legit structs and reuire all its subclasses
+// (trait implementors) to be able to convert into it. Also, conversion may be done at `data` table
+// parsing, as DataTable won't likely be used for reading specific prototype types (or maybe it
+// will, I don't know). This simplifies handling of PrototypeReference and Prototype::find
+// (DataTableAccessable::find ATM), as it would require only one HashMap to be checked. There are
+// cases of PrototypeReference of type Entity (and iirc, there are no cases of referencing specific
+// entity type). Another solution would be for Entity struct to have find() method just like all
+// Prototypes that checks all its subclasses. Which isn't very efficient for simple lookup, but
+// efficient for organization/structure.
+
+// Factorio prototypes
+// Source info:
+// For prototypes: https://wiki.factorio.com/Prototype_definitions
+// For settings: https://wiki.factorio.com/Tutorial:Mod_settings
+
+// Prototype
+// Contains all values (accessors) for every prototype in the game
+pub trait Prototype: fmt::Debug {
+    fn name(&self) -> &String;
+    fn prototype_type(&self) -> PrototypeType;
+}
+
+impl<T: Prototype + ?Sized> Prototype for Arc<T> {
+    fn name(&self) -> &String {
+        (**self).name()
+    }
+
+    fn prototype_type(&self) -> PrototypeType {
+        (**self).prototype_type()
+    }
+}
+
+/// Shorthand for prototype category/type, used in [DataTable]. Prototypes are stored in [Arc],
+/// so that references linked by [DataTable::freeze] share them instead of holding copies.
+pub type PrototypeCategory<T> = HashMap<String, Arc<T>>;
+
+/// Calls given macro with all prototype categories of [DataTable], as `field: PrototypeStruct`
+/// pairs in `group { .. }` blocks: `entities` has all entity categories, `items` all item
+/// categories and `other` everything else.
+/// Everything that has to cover every category, or every entity category, is generated from this
+/// list. Callbacks that only need one group match the other blocks as `$other:tt`.
+macro_rules! data_table_categories {
+    ($callback:ident) => {
+        $callback! {
+            other {
+                ambient_sound: AmbientSoundPrototype,
+                animation: AnimationPrototype,
+                editor_controller: EditorController,
+                font: Font,
+                god_controller: GodController,
+                map_gen_presets: MapGenPresets,
+                map_settings: MapSettings,
+                mouse_cursor: MouseCursor,
+                sound: SoundPrototype,
+                spectator_controller: SpectatorController,
+                sprite: SpritePrototype,
+                tile_effect: TileEffect,
+                tips_and_tricks_item_category: TipsAndTricksItemCategory,
+                trigger_target_type: TriggerTargetType,
+                wind_sound: WindSound,
+                achievement: Achievement,
+                build_entity_achievement: BuildEntityAchievement,
+                combat_robot_count: CombatRobotCountAchievement,
+                construct_with_robots_achevement: ConstructWithRobotsAchievement,
+                deconstruct_with_robots_achievement: DeconstructWithRobotsAchievement,
+                deliver_by_robots_achievement: DeliverByRobotsAchievement,
+                dont_build_entity_achievement: DontBuildEntityAchievement,
+                dont_craft_manually_achievement: DontCraftManuallyAchievement,
+                dont_use_entity_in_energy_production_achievement: DontUseEntityInEnergyProductionAchievement,
+                finish_the_game_achievement: FinishTheGameAchievement,
+                group_attack_achievement: GroupAttackAchievement,
+                kill_achievement: KillAchievement,
+                player_damaged_achievement: PlayerDamagedAchievement,
+                produce_achievement: ProduceAchievement,
+                produce_per_hour_achievement: ProducePerHourAchievement,
+                research_achievement: ResearchAchievement,
+                train_path_achievement: TrainPathAchievement,
+                ammo_category: AmmoCategory,
+                autoplace_control: AutoplaceControl,
+                custom_input: CustomInput,
+                damage_type: DamageType,
+                optimized_decorative: Decorative,
+            }
+            entities {
+                arrow: Arrow,
+                artillery_flare: ArtilleryFlare,
+                artillery_projectile: ArtilleryProjectile,
+                beam: Beam,
+                character_corpse: CharacterCorpse,
+                cliff: Cliff,
+                corpse: CorpsePrototype,
+                rail_remnants: RailRemnants,
+                deconstructible_tile_proxy: DeconstructibleTileProxy,
+                entity_ghost: EntityGhost,
+                accumulator: Accumulator,
+                artillery_turret: ArtilleryTurret,
+                beacon: Beacon,
+                boiler: Boiler,
+                burner_generator: BurnerGenerator,
+                character: Character,
+                arithmetic_combinator: ArithmeticCombinator,
+                decider_combinator: DeciderCombinator,
+                constant_combinator: ConstantCombinator,
+                container: Container,
+                logistic_container: LogisticContainer,
+                infinity_container: InfinityContainer,
+                assembling_machine: AssemblingMachine,
+                rocket_silo: RocketSilo,
+                furnace: Furnace,
+                electric_energy_interface: ElectricEnergyInterface,
+                electric_pole: ElectricPole,
+                unit_spawner: EnemySpawner,
+                fish: Fish,
+                combat_robot: CombatRobot,
+                construction_robot: ConstructionRobot,
+                logistic_robot: LogisticRobot,
+                gate: Gate,
+                generator: Generator,
+                heat_interface: HeatInterface,
+                heat_pipe: HeatPipe,
+                inserter: Inserter,
+                lab: Lab,
+                lamp: Lamp,
+                land_mine: LandMine,
+                linked_container: LinkedContainer,
+                market: Market,
+                mining_drill: MiningDrill,
+                offshore_pump: OffshorePump,
+                pipe: Pipe,
+                infinity_pipe: InfinityPipe,
+                pipe_to_ground: PipeToGround,
+                player_port: PlayerPort,
+                power_switch: PowerSwitch,
+                programmable_speaker: ProgrammableSpeaker,
+                pump: Pump,
+                radar: Radar,
+                curved_rail: CurvedRail,
+                straight_rail: StraightRail,
+                rail_chain_signal: RailChainSignal,
+                rail_signal: RailSignal,
+                reactor: Reactor,
+                roboport: Roboport,
+                simple_entity: SimpleEntity,
+                simple_entity_with_owner: SimpleEntityWithOwner,
+                simple_entity_with_force: SimpleEntityWithForce,
+                solar_panel: SolarPanel,
+                spider_leg: SpiderLeg,
+                storage_tank: StorageTank,
+                train_stop: TrainStop,
+                linked_belt: LinkedBelt,
+                loader_1x1: Loader1x1,
+                loader: Loader1x2,
+                splitter: Splitter,
+                transport_belt: TransportBelt,
+                underground_belt: UndergroundBelt,
+                tree: Tree,
+                turret: TurretPrototype,
+                ammo_turret: AmmoTurret,
+                electric_turret: ElectricTurret,
+                fluid_turret: FluidTurret,
+                unit: Unit,
+                car: Car,
+                artillery_wagon: ArtilleryWagon,
+                cargo_wagon: CargoWagon,
+                fluid_wagon: FluidWagon,
+                locomotive: Locomotive,
+                spider_vehicle: SpiderVehicle,
+                wall: Wall,
+                explosion: Explosion,
+                flame_thrower_explosion: FlameThrowerExplosion,
+                fire: FireFlame,
+                stream: FluidStream,
+                flying_text: FlyingText,
+                highlight_box: HighlightBoxEntity,
+                item_entity: ItemEntity,
+                item_request_proxy: ItemRequestProxy,
+                particle_source: ParticleSource,
+                projectile: Projectile,
+                resource: ResourceEntity,
+                rocket_silo_rocket: RocketSiloRocket,
+                rocket_silo_rocket_shadow: RocketSiloRocketShadow,
+                smoke_with_trigger: SmokeWithTrigger,
+                speech_bubble: SpeechBubble,
+                sticker: Sticker,
+                tile_ghost: TileGhost,
+            }
+            other {
+                active_defense_equipment: ActiveDefenseEquipment,
+                battery_equipment: BatteryEquipment,
+                belt_immunity_equipment: BeltImmunityEquipment,
+                energy_shield_equipment: EnergyShieldEquipment,
+                generator_equipment: GeneratorEquipment,
+                movement_bonus_equipment: MovementBonusEquipment,
+                night_vision_equipment: NightVisionEquipment,
+                roboport_equipment: RoboportEquipment,
+                solar_panel_equipment: SolarPanelEquipment,
+                equipment_category: EquipmentCategory,
+                equipment_grid: EquipmentGrid,
+                fluid: Fluid,
+                fuel_category: FuelCategory,
+                gui_style: GuiStyle,
+            }
+            items {
+                item: ItemPrototype,
+                ammo: AmmoItem,
+                capsule: Capsule,
+                gun: Gun,
+                item_with_entity_data: ItemWithEntityData,
+                item_with_label: ItemWithLabel,
+                item_with_inventory: ItemWithInventory,
+                blueprint_book: BlueprintBook,
+                item_with_tags: ItemWithTags,
+                selection_tool: SelectionToolPrototype,
+                blueprint: BlueprintItem,
+                copy_paste_tool: CopyPasteTool,
+                deconstruction_item: DeconstructionItem,
+                upgrade_item: UpgradeItem,
+                module: Module,
+                rail_planner: RailPlanner,
+                spidertron_remote: SpidertronRemote,
+                tool: Tool,
+                armor: Armor,
+                repair_tool: RepairTool,
+            }
+            other {
+                item_group: ItemGroup,
+                item_subgroup: ItemSubGroup,
+                module_category: ModuleCategory,
+                noise_expression: NamedNoiseExpression,
+                noise_layer: NoiseLayer,
+                optimized_particle: Particle,
+                recipe: Recipe,
+                recipe_category: RecipeCategory,
+                resource_category: ResourceCategory,
+                shortcut: Shortcut,
+                technology: Technology,
+                tile: Tile,
+                tips_and_tricks_item: TipsAndTricksItem,
+                trivial_smoke: TrivialSmoke,
+                tutorial: Tutorial,
+                utility_constants: UtilityConstants,
+                utility_sounds: UtilitySounds,
+                utility_sprites: UtilitySprites,
+                virtual_signal: VirtualSignal,
+                bool_setting: BoolModSetting,
+                int_setting: IntModSetting,
+                double_setting: DoubleModSetting,
+                string_setting: StringModSetting,
+            }
+        }
+    };
+}
+pub(crate) use data_table_categories;
+
+macro_rules! data_table {
+    ($($group:ident { $($category:ident: $prototype:ty,)* })*) => {
+        /// Struct representing global `data` table in lua environment
+        #[derive(Debug, Clone, Default)]
+        pub struct DataTable {
+            references: Vec<Weak<dyn PrototypeReferenceValidate>>,
+            resource_records: Vec<ResourceRecord>,
+            key_tracker: Option<KeyTracker>,
+            diagnostics: Vec<Diagnostic>,
+            // Prototypes
+            $($($category: PrototypeCategory<$prototype>,)*)*
+        }
+
+        impl DataTable {
+            /// Whether there is at least one prototype of given type
+            pub fn contains_type(&self, ty: PrototypeType) -> bool {
+                $($(
+                    if self.$category.values().next().map_or(false, |p| p.prototype_type() == ty) {
+                        return true;
+                    }
+                )*)*
+                false
+            }
+
+            /// Whether a prototype of given type and name exists
+            pub fn contains(&self, ty: PrototypeType, name: &str) -> bool {
+                $($(
+                    if self.$category.get(name).map_or(false, |p| p.prototype_type() == ty) {
+                        return true;
+                    }
+                )*)*
+                false
+            }
+
+            /// Resources nested anywhere in prototypes, including the ones not recorded during
+            /// extraction
+            pub fn nested_resources(&self) -> Vec<ResourceRecord> {
+                let mut records = vec![];
+                $($( self.$category.values().for_each(|p| p.walk_resources(&mut records)); )*)*
+                records
+            }
+        }
+    };
+}
+
+data_table_categories!(data_table);
+
+/// Planned DataTable api:
+/// FromLua just extracts prototypes without doing any checks
+/// `load()` will do FromLua and run checks for prototype references and possibly use provided
+/// resource validator to validate resources.
+///
+/// A draft of how choosing a prototype will work in FromLua
+/// ```
+/// let ptype = t.get::<_, String>("type").parse::<Prototypetype>().map_err(LuaError::external);
+/// match ptype {
+///     // ...
+///     PrototypeType::Arrow => Arrow::extend(Arrow::prototype_from_lua(value, lua,
+///     data_table)).map_err(LuaError::external)?,
+///     // ...
+/// }
+/// ```
+impl DataTable {
+    /// Shorthand for [DataTableAccessable::find]
+    pub fn find<T: DataTableAccessable>(&self, name: &str) -> Result<&T, PrototypesErr> {
+        T::find(self, name)
+    }
+
+    /// Shorthand for [DataTableAccessable::category]
+    pub fn category<T: DataTableAccessable>(&self) -> Result<&PrototypeCategory<T>, PrototypesErr> {
+        T::category(self)
+    }
+
+    /// All prototypes of given type for which `pred` returns `true`. Empty for abstract
+    /// prototypes, as they can't be borrowed.
+    pub fn find_where<T: DataTableAccessable>(&self, pred: impl Fn(&T) -> bool) -> Vec<&T> {
+        T::category(self)
+            .map(|category| {
+                category
+                    .values()
+                    .map(Arc::as_ref)
+                    .filter(|p| pred(p))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Shorthand for [DataTableAccessable::find_cloned]
+    pub fn find_cloned<T: DataTableAccessable>(&self, name: &str) -> Result<T, PrototypesErr> {
+        T::find_cloned(self, name)
+    }
+
+    /// Shorthand for [DataTableAccessable::find_cow]
+    ///
+    /// Use [find](Self::find) for concrete prototypes, [find_cloned](Self::find_cloned) when an
+    /// owned prototype is needed and this in generic code that only reads the prototype and
+    /// may be used with abstract prototypes, like [AbstractEntity].
+    pub fn find_cow<T: DataTableAccessable>(
+        &self,
+        name: &str,
+    ) -> Result<Cow<'_, T>, PrototypesErr> {
+        T::find_cow(self, name)
+    }
+
+    /// Shorthand for [DataTableAccessable::extend]
+    pub fn extend<T: DataTableAccessable>(&mut self, prototype: T) -> Result<(), PrototypesErr> {
+        prototype.extend(self)
+    }
+
+    /// Creates new reference and keeps track of it to later be validated through [Self::validate_references]
+    pub fn new_reference<T: DataTableAccessable + 'static>(
+        &mut self,
+        name: String,
+    ) -> Arc<PrototypeReference<T>> {
+        let prot_reference = Arc::new(PrototypeReference::<T>::new(name));
+        self.references.push(Arc::downgrade(
+            &(prot_reference.clone() as Arc<dyn PrototypeReferenceValidate>),
+        ));
+        prot_reference
+    }
+
+    /// Same as [Self::new_reference], but validates the reference right away. Intended for
+    /// converting name fields to typed references after the data table is loaded.
+    pub fn make_reference<T: DataTableAccessable + 'static>(
+        &mut self,
+        name: String,
+    ) -> Result<Arc<PrototypeReference<T>>, PrototypesErr> {
+        if !PrototypeReference::<T>::new(name.clone()).is_valid(self) {
+            return Err(PrototypesErr::PrototypeNotFound(name));
+        }
+        Ok(self.new_reference(name))
+    }
+
+    /// Validates all tracked references, as well as entities referenced by capsule actions.
+    pub fn validate_references(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors: Vec<PrototypesErr> = self
+            .references
+            .iter()
+            .filter_map(|r| r.upgrade()?.validate(self).err())
+            .collect();
+        errors.extend(
+            self.capsule_entity_references()
+                .into_iter()
+                .filter(|(_, entity)| self.find_entity(entity).is_none())
+                .map(|(capsule, entity)| {
+                    PrototypesErr::missing_reference(capsule, "capsule_action", entity)
+                }),
+        );
+        collect_errors(errors)
+    }
+
+    /// Entities referenced by capsule actions, as pairs of capsule and entity name
+    pub fn capsule_entity_references(&self) -> Vec<(&Capsule, &str)> {
+        self.capsule
+            .values()
+            .map(Arc::as_ref)
+            .flat_map(|capsule| {
+                capsule
+                    .capsule_action
+                    .referenced_entities()
+                    .into_iter()
+                    .map(move |entity| (capsule, entity))
+            })
+            .collect()
+    }
+
+    /// TrivialSmoke prototypes referenced by entities, as entity, referencing field and smoke
+    /// name. Covers `created_smoke`, smoke of energy sources and `smoke` of generators, fire,
+    /// streams, particle sources and projectiles. Smoke created by triggers is not included.
+    pub fn trivial_smoke_references(&self) -> Vec<(&dyn Prototype, &'static str, &str)> {
+        let mut result: Vec<(&dyn Prototype, &'static str, &str)> = self
+            .entities()
+            .into_iter()
+            .filter_map(|(p, e)| Some((p, e.created_smoke.as_ref()?)))
+            .map(|(p, smoke)| (p, "created_smoke", smoke.smoke_name.as_str()))
+            .collect();
+        let mut sources: Vec<(&dyn Prototype, &'static str, &[SmokeSource])> = self
+            .fuel_energy_sources()
+            .into_iter()
+            .map(|(p, field, energy_source)| (p, field, energy_source.smoke()))
+            .collect();
+        for p in self.generator.values() {
+            sources.push((p, "smoke", p.smoke.as_deref().unwrap_or_default()))
+        }
+        for p in self.fire.values() {
+            sources.push((p, "smoke", p.smoke.as_deref().unwrap_or_default()))
+        }
+        for p in self.stream.values() {
+            sources.push((
+                p,
+                "smoke_sources",
+                p.smoke_sources.as_deref().unwrap_or_default(),
+            ))
+        }
+        for p in self.particle_source.values() {
+            if let ParticleSourceParticleOrSmoke::Smoke(smoke) = &p.particle_or_smoke {
+                sources.push((p, "smoke", smoke))
+            }
+        }
+        for p in self.projectile.values() {
+            sources.push((p, "smoke", &p.smoke))
+        }
+        result.extend(
+            sources.into_iter().flat_map(|(p, field, smoke)| {
+                smoke.iter().map(move |s| (p, field, s.smoke_name()))
+            }),
+        );
+        result
+    }
+
+    /// Validates that every TrivialSmoke referenced by an entity exists, see
+    /// [trivial_smoke_references](Self::trivial_smoke_references).
+    pub fn validate_trivial_smoke_references(&self) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .trivial_smoke_references()
+            .into_iter()
+            .filter(|(_, _, smoke)| !self.trivial_smoke.contains_key(*smoke))
+            .map(|(p, field, smoke)| PrototypesErr::missing_reference(p, field, smoke))
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// All fluid boxes of entities, as entity, name of the fluid box field and fluid box. Fluid
+    /// boxes of energy sources are not included.
+    pub fn fluid_boxes(&self) -> Vec<(&dyn Prototype, &'static str, &FluidBox)> {
+        let mut result: Vec<(&dyn Prototype, &'static str, &FluidBox)> = vec![];
+        macro_rules! extend_fluid_boxes {
+            ($($category:ident),*) => {
+                $( result.extend(self.$category.values().map(|p| {
+                    (p as &dyn Prototype, "fluid_box", &p.fluid_box)
+                })); )*
+            };
+        }
+        for p in self.boiler.values() {
+            result.extend([
+                (p as &dyn Prototype, "fluid_box", &p.fluid_box),
+                (p, "output_fluid_box", &p.output_fluid_box),
+            ])
+        }
+        for p in self.assembling_machine.values() {
+            let fluid_boxes = p.crafting_machine.fluid_boxes.iter().flatten();
+            result.extend(fluid_boxes.map(|fb| (p as &dyn Prototype, "fluid_boxes", fb)))
+        }
+        for p in self.rocket_silo.values() {
+            let fluid_boxes = p.crafting_machine.fluid_boxes.iter().flatten();
+            result.extend(fluid_boxes.map(|fb| (p as &dyn Prototype, "fluid_boxes", fb)))
+        }
+        for p in self.furnace.values() {
+            let fluid_boxes = p.crafting_machine.fluid_boxes.iter().flatten();
+            result.extend(fluid_boxes.map(|fb| (p as &dyn Prototype, "fluid_boxes", fb)))
+        }
+        for p in self.mining_drill.values() {
+            let input = p.input_fluid_box.iter().map(|fb| ("input_fluid_box", fb));
+            let output = p.output_fluid_box.iter().map(|fb| ("output_fluid_box", fb));
+            result.extend(
+                input
+                    .chain(output)
+                    .map(|(field, fb)| (p as &dyn Prototype, field, fb)),
+            )
+        }
+        extend_fluid_boxes!(
+            generator,
+            offshore_pump,
+            pipe,
+            infinity_pipe,
+            pipe_to_ground,
+            pump,
+            storage_tank,
+            fluid_turret
+        );
+        result
+    }
+
+    /// Checks if entities `a` and `b` have fluid boxes that can be connected to each other, meaning
+    /// that both have pipe connections and their filters don't conflict
+    pub fn fluid_connections_between(&self, a: &str, b: &str) -> bool {
+        let fluid_boxes = self.fluid_boxes();
+        let connectable = |name: &str| {
+            fluid_boxes
+                .iter()
+                .filter(move |(p, _, fb)| p.name() == name && !fb.connections().is_empty())
+                .map(|(_, _, fb)| *fb)
+        };
+        connectable(a).any(|fa| {
+            connectable(b).any(|fb| match (fa.filter(), fb.filter()) {
+                (Some(fa), Some(fb)) => fa == fb,
+                _ => true,
+            })
+        })
+    }
+
+    /// Validates that fluid box filters reference existing fluids.
+    pub fn validate_fluid_box_filters(&self) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .fluid_boxes()
+            .into_iter()
+            .filter_map(|(p, field, fb)| Some((p, field, fb.filter()?)))
+            .filter(|(_, _, fluid)| !self.fluid.contains_key(*fluid))
+            .map(|(p, field, fluid)| PrototypesErr::missing_reference(p, field, fluid))
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Validates that boilers heating water inside have `target_temperature` and generators that
+    /// don't burn fluid have `maximum_temperature` above default temperature of their input fluid.
+    /// Fluids that don't exist are skipped, they are reported by
+    /// [validate_fluid_box_filters](Self::validate_fluid_box_filters).
+    pub fn validate_fluid_temperatures(&self) -> Result<(), Vec<PrototypesErr>> {
+        let filter_fluid = |fluid_box: &FluidBox| self.fluid.get(fluid_box.filter()?);
+        let message = "must be above default temperature of the input fluid";
+        let mut errors: Vec<PrototypesErr> = self
+            .boiler
+            .values()
+            .filter(|b| b.mode == BoilerMode::HeatWaterInside)
+            .filter(|b| {
+                filter_fluid(&b.fluid_box)
+                    .map_or(false, |f| b.target_temperature <= f.default_temperature)
+            })
+            .map(|b| PrototypesErr::invalid_field(b, "target_temperature", message))
+            .collect();
+        errors.extend(
+            self.generator
+                .values()
+                .filter(|g| !g.burns_fluid)
+                .filter(|g| {
+                    filter_fluid(&g.fluid_box)
+                        .map_or(false, |f| g.maximum_temperature <= f.default_temperature)
+                })
+                .map(|g| PrototypesErr::invalid_field(g, "maximum_temperature", message)),
+        );
+        collect_errors(errors)
+    }
+
+    /// Resources that can be mined by mining drill with given name
+    pub fn resources_minable_by(&self, drill_name: &str) -> Vec<&ResourceEntity> {
+        self.mining_drill
+            .get(drill_name)
+            .map(|drill| {
+                self.resource
+                    .values()
+                    .map(Arc::as_ref)
+                    .filter(|r| drill.resource_categories.contains(&r.category))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Mining drills that can mine resource with given name
+    pub fn drills_for_resource(&self, resource_name: &str) -> Vec<&MiningDrill> {
+        self.resource
+            .get(resource_name)
+            .map(|resource| {
+                self.mining_drill
+                    .values()
+                    .map(Arc::as_ref)
+                    .filter(|d| d.resource_categories.contains(&resource.category))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Resources that no mining drill can mine
+    pub fn unminable_resources(&self) -> Vec<&ResourceEntity> {
+        self.resource
+            .values()
+            .map(Arc::as_ref)
+            .filter(|r| {
+                !self
+                    .mining_drill
+                    .values()
+                    .any(|d| d.resource_categories.contains(&r.category))
+            })
+            .collect()
+    }
+
+    /// Validates that resource categories of mining drills and resources exist.
+    pub fn validate_resource_categories(&self) -> Result<(), Vec<PrototypesErr>> {
+        let drill_categories = self.mining_drill.values().flat_map(|d| {
+            d.resource_categories
+                .iter()
+                .map(move |c| (d as &dyn Prototype, "resource_categories", c.as_str()))
+        });
+        let resource_categories = self
+            .resource
+            .values()
+            .map(|r| (r as &dyn Prototype, "category", r.category.as_str()));
+        let errors: Vec<PrototypesErr> = drill_categories
+            .chain(resource_categories)
+            .filter(|(_, _, category)| !self.resource_category.contains_key(*category))
+            .map(|(p, field, category)| PrototypesErr::missing_reference(p, field, category))
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Units spawned by enemy spawner with given name, with their spawn curves as pairs of
+    /// evolution factor and spawn weight. Units that don't exist are skipped.
+    pub fn units_spawned_by(&self, spawner_name: &str) -> Vec<(&Unit, Vec<(f64, f64)>)> {
+        self.unit_spawner
+            .get(spawner_name)
+            .map(|spawner| {
+                spawner
+                    .result_units
+                    .iter()
+                    .filter_map(|usd| Some((self.unit.get(&usd.unit)?.as_ref(), usd.weights())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Validates that units in `result_units` of enemy spawners exist.
+    pub fn validate_spawner_units(&self) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .unit_spawner
+            .values()
+            .flat_map(|spawner| {
+                spawner
+                    .result_units
+                    .iter()
+                    .map(move |usd| (spawner, &usd.unit))
+            })
+            .filter(|(_, unit)| !self.unit.contains_key(*unit))
+            .map(|(spawner, unit)| PrototypesErr::missing_reference(spawner, "result_units", unit))
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// All autoplace specifications of decoratives, tiles and entities, paired with prototype
+    /// they belong to
+    pub fn autoplace_specifications(&self) -> Vec<(&dyn Prototype, &AutoplaceSpecification)> {
+        let mut result: Vec<(&dyn Prototype, &AutoplaceSpecification)> = vec![];
+        for p in self.optimized_decorative.values() {
+            result.extend(p.autoplace.iter().map(|a| (p as &dyn Prototype, a)))
+        }
+        for p in self.tile.values() {
+            result.extend(p.autoplace.iter().map(|a| (p as &dyn Prototype, a)))
+        }
+        macro_rules! extend_entity_autoplace {
+            ($($category:ident),*) => {
+                $( for p in self.$category.values() {
+                    result.extend(p.entity.autoplace.iter().map(|a| (p as &dyn Prototype, a)))
+                } )*
+            };
+        }
+        extend_entity_autoplace!(
+            resource,
+            tree,
+            simple_entity,
+            cliff,
+            fish,
+            unit_spawner,
+            turret
+        );
+        result
+    }
+
+    /// Validates that autoplace controls referenced by autoplace specifications exist.
+    pub fn validate_autoplace_controls(&self) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .autoplace_specifications()
+            .into_iter()
+            .filter(|(_, a)| !a.control.is_empty())
+            .filter(|(_, a)| !self.autoplace_control.contains_key(&a.control))
+            .map(|(p, a)| PrototypesErr::missing_reference(p, "autoplace", &a.control))
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// All entities, together with their common entity properties
+    fn entities(&self) -> Vec<(&dyn Prototype, &EntityBase)> {
+        let mut result: Vec<(&dyn Prototype, &EntityBase)> = vec![];
+        macro_rules! collect_entities {
+            (other $other:tt entities { $($category:ident: $prototype:ty,)* } $($rest:tt)*) => {
+                $( result.extend(self.$category.values().map(|p| (p as &dyn Prototype, &p.entity))); )*
+            };
+        }
+        data_table_categories!(collect_entities);
+        result
+    }
+
+    /// Entity of any entity type with given name
+    fn find_entity(&self, name: &str) -> Option<&dyn Prototype> {
+        macro_rules! find_entity {
+            (other $other:tt entities { $($category:ident: $prototype:ty,)* } $($rest:tt)*) => {
+                $( if let Some(p) = self.$category.get(name) {
+                    return Some(p);
+                } )*
+            };
+        }
+        data_table_categories!(find_entity);
+        None
+    }
+
+    /// Errors with [PrototypesErr::DuplicateName] if a prototype of another type that shares the
+    /// namespace with `prototype` has the same name. Like in Factorio, all entities share one
+    /// namespace and so do all items, names of other prototypes are only unique within their type.
+    pub(crate) fn check_namespace(&self, prototype: &dyn Prototype) -> Result<(), PrototypesErr> {
+        let ty = prototype.prototype_type();
+        let name = prototype.name().as_str();
+        let mut existing: Option<PrototypeType> = None;
+        macro_rules! find_in_namespace {
+            (
+                other $o1:tt
+                entities { $($entity:ident: $entity_prototype:ty,)* }
+                other $o2:tt
+                items { $($item:ident: $item_prototype:ty,)* }
+                $($rest:tt)*
+            ) => {
+                if [$(<$entity_prototype>::PROTOTYPE_TYPE),*].contains(&ty) {
+                    $( existing = existing.or_else(|| self.$entity.get(name).map(Prototype::prototype_type)); )*
+                } else if [$(<$item_prototype>::PROTOTYPE_TYPE),*].contains(&ty) {
+                    $( existing = existing.or_else(|| self.$item.get(name).map(Prototype::prototype_type)); )*
+                }
+            };
+        }
+        data_table_categories!(find_in_namespace);
+        match existing {
+            Some(existing) if existing != ty => Err(PrototypesErr::DuplicateName {
+                ty,
+                name: name.into(),
+                existing,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Icons of all entities that have one, by entity name
+    fn entity_icons(&self) -> BTreeMap<&str, &IconSpecification> {
+        self.entities()
+            .into_iter()
+            .filter_map(|(p, e)| Some((p.name().as_str(), e.icon.as_ref()?)))
+            .collect()
+    }
+
+    /// Items of all item types, together with their common item properties
+    fn items(&self) -> Vec<(&dyn Prototype, &ItemBase)> {
+        let mut result: Vec<(&dyn Prototype, &ItemBase)> = vec![];
+        macro_rules! collect_items {
+            (other $o1:tt entities $e:tt other $o2:tt items { $($category:ident: $prototype:ty,)* } $($rest:tt)*) => {
+                $( result.extend(self.$category.values().map(|p| (p as &dyn Prototype, &p.item))); )*
+            };
+        }
+        data_table_categories!(collect_items);
+        result
+    }
+
+    /// Energy sources of entities and equipment that can use fuel, as prototype, name of the
+    /// energy source field and energy source
+    fn fuel_energy_sources(&self) -> Vec<(&dyn Prototype, &'static str, &EnergySource)> {
+        let mut result: Vec<(&dyn Prototype, &'static str, &EnergySource)> = vec![];
+        macro_rules! extend_energy_sources {
+            ($key:literal: $($category:ident: $($field:ident).+),*) => {
+                $( result.extend(
+                    self.$category.values().map(|p| (p as &dyn Prototype, $key, &p.$($field).+)),
+                ); )*
+            };
+        }
+        extend_energy_sources!("burner": burner_generator: burner);
+        extend_energy_sources!(
+            "energy_source":
+            beacon: energy_source,
+            boiler: energy_source,
+            assembling_machine: crafting_machine.energy_source,
+            furnace: crafting_machine.energy_source,
+            rocket_silo: crafting_machine.energy_source,
+            inserter: energy_source,
+            lab: energy_source,
+            mining_drill: energy_source,
+            pump: energy_source,
+            radar: energy_source,
+            reactor: energy_source,
+            car: energy_source,
+            locomotive: energy_source,
+            spider_vehicle: energy_source
+        );
+        result.extend(
+            self.generator_equipment
+                .values()
+                .filter_map(|p| Some((p as &dyn Prototype, "burner", p.burner.as_ref()?))),
+        );
+        result.extend(
+            self.roboport_equipment
+                .values()
+                .filter_map(|p| Some((p as &dyn Prototype, "burner", p.burner.as_ref()?))),
+        );
+        result
+    }
+
+    /// Subgroups of all items, as pairs of item and subgroup name
+    fn item_subgroups(&self) -> Vec<(&dyn Prototype, &str)> {
+        self.items()
+            .into_iter()
+            .map(|(p, i)| (p, i.subgroup.as_str()))
+            .collect()
+    }
+
+    /// Item of any item type with given name
+    fn find_item(&self, name: &str) -> Option<&dyn Prototype> {
+        macro_rules! find_item {
+            (other $o1:tt entities $e:tt other $o2:tt items { $($category:ident: $prototype:ty,)* } $($rest:tt)*) => {
+                $( if let Some(p) = self.$category.get(name) {
+                    return Some(p);
+                } )*
+            };
+        }
+        data_table_categories!(find_item);
+        None
+    }
+
+    /// Item or fluid prototype of a product
+    fn find_product(&self, product: &ProductType) -> Option<&dyn Prototype> {
+        match product {
+            ProductType::Item(name) => self.find_item(name),
+            ProductType::Fluid(name) => self.fluid.get(name).map(|f| f as &dyn Prototype),
+        }
+    }
+
+    /// Names of items in subgroup with given name
+    pub fn items_in_subgroup(&self, subgroup: &str) -> Vec<&str> {
+        self.item_subgroups()
+            .into_iter()
+            .filter(|(_, sg)| *sg == subgroup)
+            .map(|(p, _)| p.name().as_str())
+            .collect()
+    }
+
+    /// Validates that every subgroup of items, recipes and fluids exists and that every subgroup
+    /// belongs to an existing group.
+    pub fn validate_subgroups_and_groups(&self) -> Result<(), Vec<PrototypesErr>> {
+        let fluid_subgroups = self
+            .fluid
+            .values()
+            .map(|f| (f as &dyn Prototype, f.subgroup.as_str()))
+            .filter(|(_, sg)| !sg.is_empty());
+        let recipe_subgroups = self
+            .recipe
+            .values()
+            .map(|r| (r as &dyn Prototype, r.subgroup.as_str()))
+            .filter(|(_, sg)| !sg.is_empty());
+        let mut errors: Vec<PrototypesErr> = self
+            .item_subgroups()
+            .into_iter()
+            .chain(fluid_subgroups)
+            .chain(recipe_subgroups)
+            .filter(|(_, sg)| !self.item_subgroup.contains_key(*sg))
+            .map(|(p, sg)| PrototypesErr::missing_reference(p, "subgroup", sg))
+            .collect();
+        errors.extend(
+            self.item_subgroup
+                .values()
+                .filter(|sg| !self.item_group.contains_key(&sg.group))
+                .map(|sg| PrototypesErr::missing_reference(sg, "group", &sg.group)),
+        );
+        collect_errors(errors)
+    }
+
+    /// Effective in-game sort key of an item: group order, subgroup order, item order and item
+    /// name. Sorting items by this key reproduces the ordering used in GUI.
+    pub fn sort_key(
+        &self,
+        ty: PrototypeType,
+        name: &str,
+    ) -> Result<(&str, &str, &str, &str), PrototypesErr> {
+        let mut item = Err(PrototypesErr::InvalidPrototypeType(ty.as_ref().into()));
+        macro_rules! item_order_subgroup {
+            (other $o1:tt entities $e:tt other $o2:tt items { $($category:ident: $prototype:ty,)* } $($rest:tt)*) => {
+                $( if ty == <$prototype>::PROTOTYPE_TYPE {
+                    item = self
+                        .$category
+                        .get(name)
+                        .map(|p| (p.prototype_base.order.as_str(), p.item.subgroup.as_str()))
+                        .ok_or_else(|| PrototypesErr::PrototypeNotFound(name.into()));
+                } )*
+            };
+        }
+        data_table_categories!(item_order_subgroup);
+        let (order, subgroup_name) = item?;
+        let subgroup = self
+            .item_subgroup
+            .get(subgroup_name)
+            .ok_or_else(|| PrototypesErr::PrototypeNotFound(subgroup_name.into()))?;
+        let group = self
+            .item_group
+            .get(&subgroup.group)
+            .ok_or_else(|| PrototypesErr::PrototypeNotFound(subgroup.group.clone()))?;
+        Ok((
+            group.prototype_base.order.as_str(),
+            subgroup.prototype_base.order.as_str(),
+            order,
+            name,
+        ))
+    }
+
+    /// Crafting dependencies of all items and fluids, see [CraftingGraph]
+    pub fn crafting_graph(&self) -> CraftingGraph {
+        CraftingGraph::new(self)
+    }
+
+    /// Validates that there are no recipe cycles (see [CraftingGraph::find_cycles]) except the
+    /// ones that include any of `intended` recipes. Returns all unintended cycles.
+    pub fn validate_no_unintended_cycles(
+        &self,
+        intended: &[&str],
+    ) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .crafting_graph()
+            .find_cycles()
+            .into_iter()
+            .filter(|cycle| !cycle.iter().any(|r| intended.contains(&r.as_str())))
+            .map(PrototypesErr::RecipeCycle)
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Rocket used by rocket silo with given name
+    pub fn rocket_for_silo(&self, silo: &str) -> Option<&RocketSiloRocket> {
+        self.rocket_silo_rocket
+            .get(&self.rocket_silo.get(silo)?.rocket_entity)
+            .map(Arc::as_ref)
+    }
+
+    /// Validates that rocket of every rocket silo exists, as well as shadow and dying explosion of
+    /// every rocket if they are specified.
+    pub fn validate_rocket_silos(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        for silo in self.rocket_silo.values() {
+            if !self.rocket_silo_rocket.contains_key(&silo.rocket_entity) {
+                errors.push(PrototypesErr::missing_reference(
+                    silo,
+                    "rocket_entity",
+                    &silo.rocket_entity,
+                ))
+            }
+        }
+        for rocket in self.rocket_silo_rocket.values() {
+            if let Some(shadow) = &rocket.shadow_slave_entity {
+                if !self.rocket_silo_rocket_shadow.contains_key(shadow) {
+                    errors.push(PrototypesErr::missing_reference(
+                        rocket,
+                        "shadow_slave_entity",
+                        shadow,
+                    ))
+                }
+            }
+            if let Some(explosion) = &rocket.dying_explosion {
+                if self.find_entity(explosion).is_none() {
+                    errors.push(PrototypesErr::missing_reference(
+                        rocket,
+                        "dying_explosion",
+                        explosion,
+                    ))
+                }
+            }
+        }
+        collect_errors(errors)
+    }
+
+    /// Modules that can be used when crafting recipe with given name: the ones with empty
+    /// `limitation` or with the recipe in it, unless the recipe is in `limitation_blacklist`
+    pub fn modules_allowed_for(&self, recipe: &str) -> Vec<&Module> {
+        self.module
+            .values()
+            .map(Arc::as_ref)
+            .filter(|m| m.limitation.is_empty() || m.limitation.iter().any(|r| r == recipe))
+            .filter(|m| !m.limitation_blacklist.iter().any(|r| r == recipe))
+            .collect()
+    }
+
+    /// Validates that category of every module exists, as well as recipes in `limitation` and
+    /// `limitation_blacklist`.
+    pub fn validate_module_limitations(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        for module in self.module.values() {
+            if !self.module_category.contains_key(&module.category) {
+                errors.push(PrototypesErr::missing_reference(
+                    module,
+                    "category",
+                    &module.category,
+                ))
+            }
+            let limitation = module.limitation.iter().map(|r| ("limitation", r));
+            let blacklist = module
+                .limitation_blacklist
+                .iter()
+                .map(|r| ("limitation_blacklist", r));
+            errors.extend(
+                limitation
+                    .chain(blacklist)
+                    .filter(|(_, r)| !self.recipe.contains_key(*r))
+                    .map(|(field, r)| PrototypesErr::missing_reference(module, field, r)),
+            );
+        }
+        collect_errors(errors)
+    }
+
+    /// Validates that prototypes which can only have one instance, like MapSettings, use the
+    /// required name. Every instance with a different name is reported.
+    pub fn validate_singletons(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        macro_rules! check_singletons {
+            ($($category:ident: $name:literal),*) => {
+                $( errors.extend(
+                    self.$category
+                        .values()
+                        .filter(|p| p.name() != $name)
+                        .map(|p| PrototypesErr::InvalidSingletonName {
+                            ty: p.prototype_type(),
+                            name: p.name().clone(),
+                            expected: $name,
+                        }),
+                ); )*
+            };
+        }
+        check_singletons!(
+            map_settings: "map-settings",
+            editor_controller: "default",
+            god_controller: "default",
+            spectator_controller: "default",
+            tile_effect: "water"
+        );
+        collect_errors(errors)
+    }
+
+    /// Entities in fast replaceable group with given name
+    pub fn fast_replace_group(&self, group: &str) -> Vec<&dyn Prototype> {
+        self.entities()
+            .into_iter()
+            .filter(|(_, e)| e.fast_replaceable_group == group)
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    /// Validates that members of every fast replaceable group have the same `tile_width` and
+    /// `tile_height`. Such entities can't be fast-replaced with each other in-game, even though
+    /// they share a group. Every member of a mismatched group is reported.
+    ///
+    /// The game accepts such groups, so this pass is not part of
+    /// [validate_all](Self::validate_all).
+    pub fn validate_fast_replaceable_groups(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut groups: BTreeMap<&str, Vec<(&dyn Prototype, &EntityBase)>> = BTreeMap::new();
+        for (p, entity) in self.entities() {
+            if !entity.fast_replaceable_group.is_empty() {
+                groups
+                    .entry(&entity.fast_replaceable_group)
+                    .or_default()
+                    .push((p, entity))
+            }
+        }
+        let errors: Vec<PrototypesErr> = groups
+            .into_values()
+            .filter(|members| {
+                let sizes: BTreeSet<(u32, u32)> = members
+                    .iter()
+                    .map(|(_, e)| (e.tile_width, e.tile_height))
+                    .collect();
+                sizes.len() > 1
+            })
+            .flatten()
+            .map(|(p, _)| {
+                PrototypesErr::invalid_field(
+                    p,
+                    "fast_replaceable_group",
+                    "is shared with entities of different size",
+                )
+            })
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Upgrade chain of entity with given name: the entity itself, its `next_upgrade`, the
+    /// `next_upgrade` of that, and so on. Stops at missing entity or before an entity repeats.
+    pub fn upgrade_chain(&self, entity: &str) -> Vec<&str> {
+        let entities: BTreeMap<&str, &EntityBase> = self
+            .entities()
+            .into_iter()
+            .map(|(p, e)| (p.name().as_str(), e))
+            .collect();
+        let mut chain: Vec<&str> = vec![];
+        let mut current = entities.get_key_value(entity);
+        while let Some((name, e)) = current {
+            if chain.contains(name) {
+                break;
+            }
+            chain.push(name);
+            current = e
+                .next_upgrade
+                .as_deref()
+                .and_then(|next| entities.get_key_value(next));
+        }
+        chain
+    }
+
+    /// Validates `next_upgrade` of every entity against the limitations listed on the wiki: the
+    /// entity must be minable, must not be rolling stock or have `"not-upgradable"` flag, and the
+    /// target must exist, be of the same type, have the same `fast_replaceable_group` and
+    /// `collision_box` and be placeable by an item that isn't hidden. Upgrade chains that loop back
+    /// are reported once per cycle.
+    pub fn validate_next_upgrade_constraints(&self) -> Result<(), Vec<PrototypesErr>> {
+        let entities: BTreeMap<&str, (&dyn Prototype, &EntityBase)> = self
+            .entities()
+            .into_iter()
+            .map(|(p, e)| (p.name().as_str(), (p, e)))
+            .collect();
+        let placeable: BTreeSet<&str> = self
+            .items()
+            .into_iter()
+            .filter(|(_, i)| {
+                !i.flags.map_or(false, |f| {
+                    f & ItemPrototypeFlags::HIDDEN == ItemPrototypeFlags::HIDDEN
+                })
+            })
+            .map(|(_, i)| i.place_result.as_str())
+            .collect();
+        let mut errors = vec![];
+        for (name, (prot, entity)) in &entities {
+            let next_upgrade = match &entity.next_upgrade {
+                Some(next_upgrade) => next_upgrade.as_str(),
+                None => continue,
+            };
+            let mut reasons: Vec<&'static str> = vec![];
+            if entity.flags.map_or(false, |f| {
+                f & EntityPrototypeFlags::NOT_UPGRADABLE == EntityPrototypeFlags::NOT_UPGRADABLE
+            }) {
+                reasons.push("entity has \"not-upgradable\" flag")
+            }
+            if entity.minable.is_none() {
+                reasons.push("entity is not minable")
+            }
+            if matches!(
+                prot.prototype_type(),
+                PrototypeType::Locomotive
+                    | PrototypeType::CargoWagon
+                    | PrototypeType::FluidWagon
+                    | PrototypeType::ArtilleryWagon
+            ) {
+                reasons.push("entity is rolling stock")
+            }
+            match entities.get(next_upgrade) {
+                Some((target_prot, target)) => {
+                    if target_prot.prototype_type() != prot.prototype_type() {
+                        reasons.push("target is of different type")
+                    }
+                    if target.fast_replaceable_group != entity.fast_replaceable_group {
+                        reasons.push("target has different `fast_replaceable_group`")
+                    }
+                    if target.collision_box != entity.collision_box {
+                        reasons.push("target has different `collision_box`")
+                    }
+                    if !placeable.contains(next_upgrade) {
+                        reasons.push("target is not placed by any item that isn't hidden")
+                    }
+                }
+                None => errors.push(PrototypesErr::missing_reference(
+                    *prot,
+                    "next_upgrade",
+                    next_upgrade,
+                )),
+            }
+            errors.extend(
+                reasons
+                    .into_iter()
+                    .map(|reason| PrototypesErr::InvalidNextUpgrade {
+                        ty: prot.prototype_type(),
+                        name: name.to_string(),
+                        next_upgrade: next_upgrade.into(),
+                        reason,
+                    }),
+            );
+        }
+        let upgrades: BTreeMap<&str, Vec<&str>> = entities
+            .iter()
+            .map(|(name, (_, e))| (*name, e.next_upgrade.as_deref().into_iter().collect()))
+            .collect();
+        let cycles = graph::find_cycles(&upgrades)
+            .into_iter()
+            .map(|cycle| cycle.into_iter().map(String::from).collect());
+        errors.extend(cycles.map(PrototypesErr::UpgradeCycle));
+        collect_errors(errors)
+    }
+
+    /// Items and fluids obtained by mining entity with given name, with their average amounts.
+    /// Empty if the entity is not minable.
+    pub fn mining_result(&self, entity: &str) -> Vec<(&dyn Prototype, f64)> {
+        self.entities()
+            .into_iter()
+            .find(|(p, _)| p.name() == entity)
+            .and_then(|(_, e)| e.minable.as_ref())
+            .map(|minable| {
+                minable
+                    .products()
+                    .iter()
+                    .filter_map(|p| Some((self.find_product(&p.product())?, p.expected_amount())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// All signals available in the signal picker: virtual signals, items and fluids that are
+    /// not hidden
+    pub fn all_signals(&self) -> Vec<SignalId> {
+        let mut result: Vec<SignalId> = self
+            .items()
+            .into_iter()
+            .filter(|(_, i)| {
+                !i.flags.map_or(false, |f| {
+                    f & ItemPrototypeFlags::HIDDEN == ItemPrototypeFlags::HIDDEN
+                })
+            })
+            .map(|(p, _)| SignalId::Item(p.name().clone()))
+            .collect();
+        result.extend(
+            self.fluid
+                .values()
+                .filter(|f| !f.hidden)
+                .map(|f| SignalId::Fluid(f.name.clone())),
+        );
+        result.extend(
+            self.virtual_signal
+                .keys()
+                .map(|name| SignalId::Virtual(name.clone())),
+        );
+        result
+    }
+
+    /// Whether entity with given name can be placed on tile with given name, meaning that their
+    /// collision masks don't intersect. `false` if either of them doesn't exist.
+    pub fn can_place_on(&self, entity: &str, tile: &str) -> bool {
+        let entity = self
+            .entities()
+            .into_iter()
+            .find(|(p, _)| p.name() == entity);
+        match (entity, self.tile.get(tile)) {
+            (Some((_, entity)), Some(tile)) => {
+                !entity.collision_mask.intersects(tile.collision_mask)
+            }
+            _ => false,
+        }
+    }
+
+    /// Validates that every fluid turret has `turret_has_direction` set, which the game requires.
+    pub fn validate_fluid_turrets(&self) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .fluid_turret
+            .values()
+            .filter(|turret| !turret.turret.turret_has_direction)
+            .map(|turret| {
+                PrototypesErr::invalid_field(turret, "turret_has_direction", "must be true")
+            })
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Validates that every entity of a type that requires `map_color` has one.
+    pub fn validate_map_color_requirements(&self) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .entities()
+            .into_iter()
+            .filter(|(p, e)| {
+                EntityBase::map_color_required(p.prototype_type()) && e.map_color.is_none()
+            })
+            .map(|(p, _)| PrototypesErr::invalid_field(p, "map_color", "is required"))
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Validates that every item has an icon, either its own or inherited from the entity from
+    /// `place_result`. Items without one are drawn blank in-game.
+    pub fn validate_items_have_icons_or_entities(&self) -> Result<(), Vec<PrototypesErr>> {
+        let entity_icons = self.entity_icons();
+        let errors: Vec<PrototypesErr> = self
+            .items()
+            .into_iter()
+            .filter(|(_, i)| i.effective_icon_from(&entity_icons).is_none())
+            .map(|(p, _)| PrototypesErr::invalid_field(p, "icon", "is required"))
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Validates that icon masks line up with the icons they are drawn over: `icon_tintable_mask`
+    /// with `icon_tintable` of [ItemWithEntityData] and `icon_color_indicator_mask` with icon of
+    /// [SpidertronRemote].
+    pub fn validate_icon_layers(&self) -> Result<(), Vec<PrototypesErr>> {
+        let message = "layers don't match the icon it is drawn over";
+        let mut errors = vec![];
+        for item in self.item_with_entity_data.values() {
+            if let (Some(tintable), Some(mask)) = (&item.icon_tintable, &item.icon_tintable_mask) {
+                if !mask.matches_layout(tintable) {
+                    errors.push(PrototypesErr::invalid_field(
+                        item,
+                        "icon_tintable_mask",
+                        message,
+                    ))
+                }
+            }
+        }
+        let entity_icons = self.entity_icons();
+        for remote in self.spidertron_remote.values() {
+            if let Some(icon) = remote.item.effective_icon_from(&entity_icons) {
+                if !remote.icon_color_indicator_mask.matches_layout(icon) {
+                    errors.push(PrototypesErr::invalid_field(
+                        remote,
+                        "icon_color_indicator_mask",
+                        message,
+                    ))
+                }
+            }
+        }
+        collect_errors(errors)
+    }
+
+    /// Validates custom inputs: `item_to_spawn` must be an existing item and is required for
+    /// `spawn-item` action, `linked_game_control` must be one of [GAME_CONTROLS], and
+    /// `key_sequence` can only be empty if `linked_game_control` is set.
+    pub fn validate_custom_input_controls(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        for input in self.custom_input.values() {
+            match &input.item_to_spawn {
+                Some(item) if self.find_item(item).is_none() => errors.push(
+                    PrototypesErr::missing_reference(input, "item_to_spawn", item),
+                ),
+                None if input.action == CustomInputAction::SpawnItem => errors.push(
+                    PrototypesErr::invalid_field(input, "item_to_spawn", "is required"),
+                ),
+                _ => {}
+            }
+            if input.linked_game_control.is_empty() {
+                if input.key_sequence.is_empty() {
+                    errors.push(PrototypesErr::invalid_field(
+                        input,
+                        "key_sequence",
+                        "can only be empty if `linked_game_control` is set",
+                    ))
+                }
+            } else if !GAME_CONTROLS.contains(&input.linked_game_control.as_str()) {
+                errors.push(PrototypesErr::invalid_field(
+                    input,
+                    "linked_game_control",
+                    "is not a known game control",
+                ))
+            }
+        }
+        collect_errors(errors)
+    }
+
+    /// Validates that `category` of every TipsAndTricksItem is an existing
+    /// TipsAndTricksItemCategory, that a set `tutorial` exists and that all `dependencies` are
+    /// existing tips that don't form a cycle.
+    pub fn validate_tips_and_tricks(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        for tip in self.tips_and_tricks_item.values() {
+            if !self
+                .tips_and_tricks_item_category
+                .contains_key(&tip.category)
+            {
+                errors.push(PrototypesErr::missing_reference(
+                    tip,
+                    "category",
+                    &tip.category,
+                ))
+            }
+            if !tip.tutorial.is_empty() && !self.tutorial.contains_key(&tip.tutorial) {
+                errors.push(PrototypesErr::missing_reference(
+                    tip,
+                    "tutorial",
+                    &tip.tutorial,
+                ))
+            }
+            errors.extend(
+                tip.dependencies
+                    .iter()
+                    .filter(|dep| !self.tips_and_tricks_item.contains_key(*dep))
+                    .map(|dep| PrototypesErr::missing_reference(tip, "dependencies", dep)),
+            );
+        }
+        let cycles = graph::find_cycles(&self.tip_dependencies())
+            .into_iter()
+            .map(|cycle| cycle.into_iter().map(String::from).collect());
+        errors.extend(cycles.map(PrototypesErr::TipDependencyCycle));
+        collect_errors(errors)
+    }
+
+    /// Tips and tricks items ordered so that each one comes after all of its `dependencies`,
+    /// with tips on the same depth ordered by name. Dependencies that don't exist are ignored.
+    /// Tips that are in a dependency cycle or depend on one are left out, they are reported by
+    /// [validate_tips_and_tricks](Self::validate_tips_and_tricks).
+    pub fn tips_dependency_order(&self) -> Vec<&TipsAndTricksItem> {
+        // Kahn's algorithm
+        let mut remaining: BTreeMap<&str, BTreeSet<&str>> = self
+            .tip_dependencies()
+            .into_iter()
+            .map(|(name, deps)| (name, deps.into_iter().collect()))
+            .collect();
+        let mut order = vec![];
+        loop {
+            let ready: Vec<&str> = remaining
+                .iter()
+                .filter(|(_, deps)| deps.is_empty())
+                .map(|(name, _)| *name)
+                .collect();
+            if ready.is_empty() {
+                break;
+            }
+            for name in ready {
+                remaining.remove(name);
+                remaining.values_mut().for_each(|deps| {
+                    deps.remove(name);
+                });
+                order.push(self.tips_and_tricks_item[name].as_ref());
+            }
+        }
+        order
+    }
+
+    /// Existing dependencies of every tips and tricks item, by tip name
+    fn tip_dependencies(&self) -> BTreeMap<&str, Vec<&str>> {
+        self.tips_and_tricks_item
+            .values()
+            .map(|tip| {
+                let deps = tip
+                    .dependencies
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|dep| self.tips_and_tricks_item.contains_key(*dep))
+                    .collect();
+                (tip.name.as_str(), deps)
+            })
+            .collect()
+    }
+
+    /// Validates that legs of every SpiderVehicle engine are existing SpiderLeg entities, that its
+    /// `guns` are existing Gun items and that every SpiderLeg has `part_length` > 0.
+    pub fn validate_spidertron(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        for spider in self.spider_vehicle.values() {
+            errors.extend(
+                spider
+                    .spider_engine
+                    .legs()
+                    .iter()
+                    .map(SpiderLegSpecification::leg)
+                    .filter(|leg| !self.spider_leg.contains_key(*leg))
+                    .map(|leg| PrototypesErr::missing_reference(spider, "spider_engine", leg)),
+            );
+            errors.extend(
+                spider
+                    .guns
+                    .iter()
+                    .filter(|gun| !self.gun.contains_key(*gun))
+                    .map(|gun| PrototypesErr::missing_reference(spider, "guns", gun)),
+            );
+        }
+        for leg in self.spider_leg.values() {
+            if leg.part_length <= 0.0 {
+                errors.push(PrototypesErr::invalid_field(
+                    leg,
+                    "part_length",
+                    "must be > 0",
+                ))
+            }
+        }
+        collect_errors(errors)
+    }
+
+    /// Radius visualization sprites of entities with the radius they are drawn with. Covers
+    /// `radius_visualisation_specification` of every entity and pictures of beacons, electric
+    /// poles and mining drills, which use supply area distance or resource searching radius.
+    pub fn radius_visualizations(&self) -> Vec<(&dyn Prototype, &Sprite, f64)> {
+        self.radius_visualization_sources()
+            .into_iter()
+            .filter_map(|(p, _, sprite, radius)| Some((p, sprite?, radius)))
+            .collect()
+    }
+
+    /// Validates that every radius visualization sprite (see
+    /// [radius_visualizations](Self::radius_visualizations)) is drawn with a positive radius.
+    pub fn validate_radius_visualizations(&self) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .radius_visualization_sources()
+            .into_iter()
+            .filter(|(_, _, sprite, radius)| sprite.is_some() && *radius <= 0.0)
+            .map(|(p, field, _, _)| {
+                PrototypesErr::invalid_field(p, field, "is drawn with radius that isn't positive")
+            })
+            .collect();
+        collect_errors(errors)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn radius_visualization_sources(
+        &self,
+    ) -> Vec<(&dyn Prototype, &'static str, Option<&Sprite>, f64)> {
+        let mut sources: Vec<(&dyn Prototype, &'static str, Option<&Sprite>, f64)> = self
+            .entities()
+            .into_iter()
+            .filter_map(|(p, entity)| {
+                let spec = entity.radius_visualization_specification.as_ref()?;
+                Some((
+                    p,
+                    "radius_visualisation_specification",
+                    spec.sprite.as_ref(),
+                    spec.distance,
+                ))
+            })
+            .collect();
+        sources.extend(self.beacon.values().map(|b| {
+            (
+                b as &dyn Prototype,
+                "radius_visualisation_picture",
+                b.radius_visualization_picture.as_ref(),
+                b.supply_area_distance,
+            )
+        }));
+        sources.extend(self.electric_pole.values().map(|e| {
+            (
+                e as &dyn Prototype,
+                "radius_visualisation_picture",
+                e.radius_visualisation_picture.as_ref(),
+                e.supply_area_distance,
+            )
+        }));
+        sources.extend(self.mining_drill.values().map(|m| {
+            (
+                m as &dyn Prototype,
+                "radius_visualisation_picture",
+                m.radius_visualisation_picture.as_ref(),
+                m.resource_searching_radius,
+            )
+        }));
+        sources
+    }
+
+    /// Validates that entities with `circuit_wire_max_distance` > 0 have
+    /// `circuit_connector_sprites` for each orientation they can be in: one set for most entities,
+    /// 4 for rotatable ones (inserters, mining drills, pumps, storage tanks, train stops), 7 for
+    /// transport belts and 8 for rail signals.
+    pub fn validate_circuit_connector_sprites(&self) -> Result<(), Vec<PrototypesErr>> {
+        // (prototype, circuit_wire_max_distance, sprite sets, required sprite sets)
+        let mut entities: Vec<(&dyn Prototype, f64, usize, usize)> = vec![];
+        macro_rules! connector_sprites {
+            (flatten $required:expr; $($category:ident),*) => {
+                $( entities.extend(self.$category.values().map(|p| (
+                    p as &dyn Prototype,
+                    p.circuit_wire_max_distance,
+                    p.circuit_connector_sprites.iter().flatten().count(),
+                    $required,
+                ))); )*
+            };
+            ($required:expr; $($category:ident),*) => {
+                $( entities.extend(self.$category.values().map(|p| (
+                    p as &dyn Prototype,
+                    p.circuit_wire_max_distance,
+                    p.circuit_connector_sprites.iter().count(),
+                    $required,
+                ))); )*
+            };
+        }
+        connector_sprites!(1; accumulator, container, logistic_container, infinity_container);
+        connector_sprites!(1; lamp, programmable_speaker, roboport, wall);
+        connector_sprites!(4; mining_drill, offshore_pump, pump, train_stop);
+        connector_sprites!(flatten 4; inserter, storage_tank);
+        connector_sprites!(flatten 7; transport_belt);
+        connector_sprites!(8; rail_signal, rail_chain_signal);
+        let errors: Vec<PrototypesErr> = entities
+            .into_iter()
+            .filter(|(_, max_distance, count, required)| *max_distance > 0.0 && count < required)
+            .map(|(p, _, _, _)| {
+                PrototypesErr::invalid_field(
+                    p,
+                    "circuit_connector_sprites",
+                    "needs a sprite set for each orientation when `circuit_wire_max_distance` > 0",
+                )
+            })
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Validates that armors listed in animations of every Character are existing Armor items and
+    /// that `character_corpse` is an existing CharacterCorpse.
+    pub fn validate_character_references(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        for character in self.character.values() {
+            errors.extend(
+                character
+                    .animations
+                    .iter()
+                    .flat_map(|a| a.armors.iter().flatten())
+                    .filter(|armor| !self.armor.contains_key(*armor))
+                    .map(|armor| PrototypesErr::missing_reference(character, "animations", armor)),
+            );
+            if let Some(corpse) = &character.character_corpse {
+                if !self.character_corpse.contains_key(corpse) {
+                    errors.push(PrototypesErr::missing_reference(
+                        character,
+                        "character_corpse",
+                        corpse,
+                    ))
+                }
+            }
+        }
+        collect_errors(errors)
+    }
+
+    /// Items that are fuel of given FuelCategory
+    pub fn fuels_in_category(&self, category: &str) -> Vec<&dyn Prototype> {
+        self.items()
+            .into_iter()
+            .filter(|(_, i)| i.fuel_value.0 > 0.0 && i.fuel_category == category)
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    /// Validates that every item with `fuel_value` has a `fuel_category` that exists, and that all
+    /// fuel categories accepted by burner energy sources exist.
+    pub fn validate_fuel_categories(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        for (item, item_base) in self.items() {
+            if item_base.fuel_value.0 > 0.0 {
+                if item_base.fuel_category.is_empty() {
+                    errors.push(PrototypesErr::invalid_field(
+                        item,
+                        "fuel_category",
+                        "is required when `fuel_value` is set",
+                    ))
+                } else if !self.fuel_category.contains_key(&item_base.fuel_category) {
+                    errors.push(PrototypesErr::missing_reference(
+                        item,
+                        "fuel_category",
+                        &item_base.fuel_category,
+                    ))
+                }
+            }
+        }
+        for (p, field, energy_source) in self.fuel_energy_sources() {
+            if let Some(fuel_categories) = energy_source.fuel_categories() {
+                errors.extend(
+                    fuel_categories
+                        .iter()
+                        .filter(|c| !self.fuel_category.contains_key(*c))
+                        .map(|c| PrototypesErr::missing_reference(p, field, c)),
+                )
+            }
+        }
+        collect_errors(errors)
+    }
+
+    /// Default circuit network signals of all entities, as entity, signal field and signal
+    fn default_signals(&self) -> Vec<(&dyn Prototype, &'static str, &SignalIDConnector)> {
+        let mut result: Vec<(&dyn Prototype, &'static str, &SignalIDConnector)> = vec![];
+        macro_rules! extend_signals {
+            ($($category:ident: $($field:ident),+;)*) => {
+                $( for p in self.$category.values() {
+                    $( if let Some(signal) = &p.$field {
+                        result.push((p, stringify!($field), signal))
+                    } )+
+                } )*
+            };
+        }
+        extend_signals!(
+            accumulator: default_output_signal;
+            inserter: default_stack_control_input_signal;
+            rail_chain_signal:
+                default_red_output_signal,
+                default_orange_output_signal,
+                default_green_output_signal,
+                default_blue_output_signal;
+            rail_signal:
+                default_red_output_signal,
+                default_orange_output_signal,
+                default_green_output_signal;
+            roboport:
+                default_available_logistic_output_signal,
+                default_total_logistic_output_signal,
+                default_available_construction_output_signal,
+                default_total_construction_output_signal;
+            train_stop:
+                default_train_stopped_signal,
+                default_trains_count_signal,
+                default_trains_limit_signal;
+            wall: default_output_signal;
+        );
+        result
+    }
+
+    /// Validates that default circuit network signals of entities refer to existing items, fluids
+    /// or virtual signals.
+    pub fn validate_default_signals(&self) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .default_signals()
+            .into_iter()
+            .filter(|(_, _, signal)| signal.resolve(self).is_none())
+            .map(|(p, field, signal)| PrototypesErr::missing_reference(p, field, &signal.name))
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Validates that tiles referenced by other tiles through `transition_merges_with_tile`,
+    /// `allowed_neighbors`, `next_direction` and `to_tiles` of `transitions` exist.
+    pub fn validate_tile_references(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        for tile in self.tile.values() {
+            let merges_with = tile
+                .transition_merges_with_tile
+                .iter()
+                .map(|name| ("transition_merges_with_tile", name));
+            let neighbors = tile
+                .allowed_neighbors
+                .iter()
+                .flatten()
+                .map(|name| ("allowed_neighbors", name));
+            let next_direction = tile
+                .next_direction
+                .iter()
+                .map(|name| ("next_direction", name));
+            let transitions = tile
+                .transitions
+                .iter()
+                .flatten()
+                .flat_map(|t| t.to_tiles().iter())
+                .map(|name| ("transitions", name));
+            errors.extend(
+                merges_with
+                    .chain(neighbors)
+                    .chain(next_direction)
+                    .chain(transitions)
+                    .filter(|(_, name)| !self.tile.contains_key(*name))
+                    .map(|(field, name)| PrototypesErr::missing_reference(tile, field, name)),
+            )
+        }
+        collect_errors(errors)
+    }
+
+    /// Validates that `unlock-recipe` effects of technologies refer to existing recipes and
+    /// `give-item` effects to existing items, in both difficulties. Each missing prototype is
+    /// reported once per technology.
+    pub fn validate_technology_effects(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        for technology in self.technology.values() {
+            let mut missing: BTreeSet<&str> = BTreeSet::new();
+            for data in [
+                technology.difficulty.normal(),
+                technology.difficulty.expensive(),
+            ] {
+                missing.extend(
+                    data.unlocked_recipes()
+                        .into_iter()
+                        .filter(|r| !self.recipe.contains_key(*r)),
+                );
+                missing.extend(
+                    data.given_items()
+                        .into_iter()
+                        .filter(|i| self.find_item(i).is_none()),
+                );
+            }
+            errors.extend(
+                missing
+                    .into_iter()
+                    .map(|name| PrototypesErr::missing_reference(technology, "effects", name)),
+            )
+        }
+        collect_errors(errors)
+    }
+
+    /// Validates prototypes referenced by achievements: entities to build, not build, kill or use
+    /// in energy production, damage types, produced items and fluids, researched technologies and
+    /// entity types in `type_to_kill` and `type_of_dealer`. Each dangling reference is reported
+    /// once per achievement.
+    pub fn validate_achievement_references(&self) -> Result<(), Vec<PrototypesErr>> {
+        fn missing_entities<'a>(
+            entities: &BTreeSet<&str>,
+            owner: &dyn Prototype,
+            field: &'static str,
+            names: impl IntoIterator<Item = &'a String>,
+        ) -> Vec<PrototypesErr> {
+            let missing: BTreeSet<&str> = names
+                .into_iter()
+                .map(String::as_str)
+                .filter(|name| !name.is_empty() && !entities.contains(name))
+                .collect();
+            missing
+                .into_iter()
+                .map(|name| PrototypesErr::missing_reference(owner, field, name))
+                .collect()
+        }
+        fn invalid_type(
+            owner: &dyn Prototype,
+            field: &'static str,
+            ty: Option<&String>,
+        ) -> Option<PrototypesErr> {
+            ty.filter(|ty| ty.parse::<PrototypeType>().is_err())
+                .map(|_| PrototypesErr::invalid_field(owner, field, "is not a prototype type"))
+        }
+        let entities: BTreeSet<&str> = self
+            .entities()
+            .into_iter()
+            .map(|(p, _)| p.name().as_str())
+            .collect();
+        let mut errors = vec![];
+        for a in self.build_entity_achievement.values() {
+            errors.extend(missing_entities(&entities, a, "to_build", [&a.to_build]))
+        }
+        for a in self.dont_build_entity_achievement.values() {
+            errors.extend(missing_entities(&entities, a, "dont_build", &a.dont_buid))
+        }
+        for a in self
+            .dont_use_entity_in_energy_production_achievement
+            .values()
+        {
+            errors.extend(missing_entities(&entities, a, "excluded", &a.excluded));
+            errors.extend(missing_entities(&entities, a, "included", &a.included));
+        }
+        for a in self.kill_achievement.values() {
+            errors.extend(missing_entities(&entities, a, "to_kill", [&a.to_kill]));
+            if !a.damage_type.is_empty() && !self.damage_type.contains_key(&a.damage_type) {
+                errors.push(PrototypesErr::missing_reference(
+                    a,
+                    "damage_type",
+                    &a.damage_type,
+                ))
+            }
+            errors.extend(invalid_type(a, "type_to_kill", a.type_to_kill.as_ref()))
+        }
+        for a in self.player_damaged_achievement.values() {
+            errors.extend(invalid_type(a, "type_of_dealer", a.type_of_dealer.as_ref()))
+        }
+        let products = self
+            .produce_achievement
+            .values()
+            .map(|a| (a as &dyn Prototype, &a.product))
+            .chain(
+                self.produce_per_hour_achievement
+                    .values()
+                    .map(|a| (a as &dyn Prototype, &a.product)),
+            );
+        for (a, product) in products {
+            if self.find_product(product).is_none() {
+                let field = match product {
+                    ProductType::Item(_) => "item_product",
+                    ProductType::Fluid(_) => "fluid_product",
+                };
+                errors.push(PrototypesErr::missing_reference(a, field, product.name()))
+            }
+        }
+        for a in self.research_achievement.values() {
+            if let ResearchTarget::Technology(t) = &a.target {
+                if !self.technology.contains_key(t) {
+                    errors.push(PrototypesErr::missing_reference(a, "technology", t))
+                }
+            }
+        }
+        collect_errors(errors)
+    }
+
+    /// Validates that science packs in research units of technologies exist and are tools, in
+    /// both difficulties. Each missing science pack is reported once per technology.
+    pub fn validate_science_packs(&self) -> Result<(), Vec<PrototypesErr>> {
+        let mut errors = vec![];
+        for technology in self.technology.values() {
+            let missing: BTreeSet<&str> = [
+                technology.difficulty.normal(),
+                technology.difficulty.expensive(),
+            ]
+            .into_iter()
+            .flat_map(|data| data.science_packs())
+            .map(|(name, _)| name)
+            .filter(|name| !self.tool.contains_key(*name))
+            .collect();
+            errors.extend(
+                missing
+                    .into_iter()
+                    .map(|name| PrototypesErr::missing_reference(technology, "unit", name)),
+            )
+        }
+        collect_errors(errors)
+    }
+
+    /// Validates that `cliff_explosive` of cliffs names a capsule with `destroy-cliffs` action.
+    pub fn validate_cliff_explosives(&self) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .cliff
+            .values()
+            .filter(|c| !c.cliff_explosive.is_empty())
+            .filter_map(|c| match self.capsule.get(&c.cliff_explosive) {
+                None => Some(PrototypesErr::missing_reference(
+                    c,
+                    "cliff_explosive",
+                    &c.cliff_explosive,
+                )),
+                Some(capsule) => match capsule.capsule_action {
+                    CapsuleAction::DestroyCliffs(_) => None,
+                    _ => Some(PrototypesErr::invalid_field(
+                        c,
+                        "cliff_explosive",
+                        "must be a capsule with destroy-cliffs action",
+                    )),
+                },
+            })
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Validates that decoratives in the "decals" render layer have `tile_layer` set.
+    pub fn validate_render_layers(&self) -> Result<(), Vec<PrototypesErr>> {
+        let errors: Vec<PrototypesErr> = self
+            .optimized_decorative
+            .values()
+            .filter(|d| d.render_layer.is_decal() && d.tile_layer.is_none())
+            .map(|d| {
+                PrototypesErr::invalid_field(d, "tile_layer", "is required in \"decals\" layer")
+            })
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Validates that mining results of entities and tiles are existing items or fluids.
+    pub fn validate_mining_results(&self) -> Result<(), Vec<PrototypesErr>> {
+        let entities = self
+            .entities()
+            .into_iter()
+            .filter_map(|(p, e)| Some((p, e.minable.as_ref()?)));
+        let tiles = self
+            .tile
+            .values()
+            .filter_map(|t| Some((t as &dyn Prototype, t.minable.as_ref()?)));
+        let errors: Vec<PrototypesErr> = entities
+            .chain(tiles)
+            .flat_map(|(owner, minable)| minable.products().iter().map(move |p| (owner, p)))
+            .filter(|(_, p)| self.find_product(&p.product()).is_none())
+            .map(|(owner, p)| PrototypesErr::missing_reference(owner, "minable", p.name()))
+            .collect();
+        collect_errors(errors)
+    }
+
+    /// Estimate of memory used by each prototype category, see [MemoryReport]
+    pub fn memory_report(&self) -> MemoryReport {
+        MemoryReport::new(self)
+    }
 
-#[cfg(not(feature = "concepts"))]
-type LocalisedString = String;
+    /// Freeze the table into [SharedDataTable], which can be shared between threads
+    pub fn into_shared(mut self) -> SharedDataTable {
+        self.references_cleanup();
+        SharedDataTable::new(self)
+    }
 
-// TODO: Make fields not acessible through traits pub
+    /// Validate all tracked references, link them to their prototypes and freeze the table into
+    /// [FrozenDataTable]. Linked references resolve without a lookup, see
+    /// [FrozenDataTable::get].
+    ///
+    /// Links share the [Arc] the prototype is stored in, nothing is cloned. Abstract prototypes
+    /// aren't stored in the table, references to them hold their own.
+    pub fn freeze(mut self) -> Result<FrozenDataTable, Vec<PrototypesErr>> {
+        self.references_cleanup();
+        let errors: Vec<PrototypesErr> = self
+            .references
+            .iter()
+            .filter_map(|r| r.upgrade()?.link(&self).err())
+            .collect();
+        collect_errors(errors)?;
+        Ok(FrozenDataTable::new(self))
+    }
 
-// TODO
-// Current prototype definitions are based off Factorio wiki, which describe prototypes
-// *definitions*, meaning how they are defined in lua code and how they are parsed.
-// However, https://lua-api.factorio.com/ has a documentation on Lua*Prototype, which seems to have
-// less prototype types than wiki. And as it turns out, runtime prototypes are less diverse than
-// data-stage. For example, entire Entity abstract prtototype *and* it's subclasses are boiled down
-// to LuaEntityPrototype, which has all the protperties (read-only).
-// So, my idea is to implement all Lua*Prototype as legit structs and reuire all its subclasses
-// (trait implementors) to be able to convert into it. Also, conversion may be done at `data` table
-// parsing, as DataTable won't likely be used for reading specific prototype types (or maybe it
-// will, I don't know). This simplifies handling of PrototypeReference and Prototype::find
-// (DataTableAccessable::find ATM), as it would require only one HashMap to be checked. There are
-// cases of PrototypeReference of type Entity (and iirc, there are no cases of referencing specific
-// entity type). Another solution would be for Entity struct to have find() method just like all
-// Prototypes that checks all its subclasses. Which isn't very efficient for simple lookup, but
-// efficient for organization/structure.
+    /// Cleanup up Weak references
+    pub fn references_cleanup(&mut self) {
+        self.references.retain(|r| r.upgrade().is_some())
+    }
 
-// Factorio prototypes
-// Source info:
-// For prototypes: https://wiki.factorio.com/Prototype_definitions
-// For settings: https://wiki.factorio.com/Tutorial:Mod_settings
+    /// Create new resource record
+    pub fn register_resource(&mut self, resource_record: ResourceRecord) {
+        self.resource_records.push(resource_record);
+    }
 
-// Prototype
-// Contains all values (accessors) for every prototype in the game
-pub trait Prototype: fmt::Debug {
-    fn name(&self) -> &String;
-    fn prototype_type(&self) -> PrototypeType;
-}
+    /// All recorded image resources
+    pub fn image_resources(&self) -> Vec<&ResourceRecord> {
+        self.resource_records
+            .iter()
+            .filter(|r| matches!(r.resource_type, ResourceType::Image(..)))
+            .collect()
+    }
 
-/// Shorthand for prototype category/type, used in [DataTable]
-pub type PrototypeCategory<T> = HashMap<String, T>;
+    /// All recorded sound resources
+    pub fn sound_resources(&self) -> Vec<&ResourceRecord> {
+        self.resource_records
+            .iter()
+            .filter(|r| matches!(r.resource_type, ResourceType::Sound))
+            .collect()
+    }
 
-/// Struct representing global `data` table in lua environment
-#[derive(Debug, Clone)]
-pub struct DataTable {
-    references: Vec<Weak<dyn PrototypeReferenceValidate>>,
-    resource_records: Vec<ResourceRecord>,
-    // Prototypes
-    ambient_sound: PrototypeCategory<AmbientSoundPrototype>,
-    animation: PrototypeCategory<AnimationPrototype>,
-    editor_controller: PrototypeCategory<EditorController>,
-    font: PrototypeCategory<Font>,
-    god_controller: PrototypeCategory<GodController>,
-    map_gen_presets: PrototypeCategory<MapGenPresets>,
-    map_settings: PrototypeCategory<MapSettings>,
-    mouse_cursor: PrototypeCategory<MouseCursor>,
-    sound: PrototypeCategory<SoundPrototype>,
-    spectator_controller: PrototypeCategory<SpectatorController>,
-    sprite: PrototypeCategory<SpritePrototype>,
-    tile_effect: PrototypeCategory<TileEffect>,
-    tips_and_tricks_item_category: PrototypeCategory<TipsAndTricksItemCategory>,
-    trigger_target_type: PrototypeCategory<TriggerTargetType>,
-    wind_sound: PrototypeCategory<WindSound>,
-    achievement: PrototypeCategory<Achievement>,
-    build_entity_achievement: PrototypeCategory<BuildEntityAchievement>,
-    combat_robot_count: PrototypeCategory<CombatRobotCountAchievement>,
-    construct_with_robots_achevement: PrototypeCategory<ConstructWithRobotsAchievement>,
-    deconstruct_with_robots_achievement: PrototypeCategory<DeconstructWithRobotsAchievement>,
-    deliver_by_robots_achievement: PrototypeCategory<DeliverByRobotsAchievement>,
-    dont_build_entity_achievement: PrototypeCategory<DontBuildEntityAchievement>,
-    dont_craft_manually_achievement: PrototypeCategory<DontCraftManuallyAchievement>,
-    dont_use_entity_in_energy_production_achievement:
-        PrototypeCategory<DontUseEntityInEnergyProductionAchievement>,
-    finish_the_game_achievement: PrototypeCategory<FinishTheGameAchievement>,
-    group_attack_achievement: PrototypeCategory<GroupAttackAchievement>,
-    kill_achievement: PrototypeCategory<KillAchievement>,
-    player_damaged_achievement: PrototypeCategory<PlayerDamagedAchievement>,
-    produce_achievement: PrototypeCategory<ProduceAchievement>,
-    produce_per_hour_achievement: PrototypeCategory<ProducePerHourAchievement>,
-    research_achievement: PrototypeCategory<ResearchAchievement>,
-    train_path_achievement: PrototypeCategory<TrainPathAchievement>,
-    ammo_category: PrototypeCategory<AmmoCategory>,
-    autoplace_control: PrototypeCategory<AutoplaceControl>,
-    custom_input: PrototypeCategory<CustomInput>,
-    damage_type: PrototypeCategory<DamageType>,
-    optimized_decorative: PrototypeCategory<Decorative>,
-    arrow: PrototypeCategory<Arrow>,
-    artillery_flare: PrototypeCategory<ArtilleryFlare>,
-    artillery_projectile: PrototypeCategory<ArtilleryProjectile>,
-    beam: PrototypeCategory<Beam>,
-    character_corpse: PrototypeCategory<CharacterCorpse>,
-    cliff: PrototypeCategory<Cliff>,
-    corpse: PrototypeCategory<CorpsePrototype>,
-    rail_remnants: PrototypeCategory<RailRemnants>,
-    deconstructible_tile_proxy: PrototypeCategory<DeconstructibleTileProxy>,
-    entity_ghost: PrototypeCategory<EntityGhost>,
-    accumulator: PrototypeCategory<Accumulator>,
-    artillery_turret: PrototypeCategory<ArtilleryTurret>,
-    beacon: PrototypeCategory<Beacon>,
-    boiler: PrototypeCategory<Boiler>,
-    burner_generator: PrototypeCategory<BurnerGenerator>,
-    character: PrototypeCategory<Character>,
-    arithmetic_combinator: PrototypeCategory<ArithmeticCombinator>,
-    decider_combinator: PrototypeCategory<DeciderCombinator>,
-    constant_combinator: PrototypeCategory<ConstantCombinator>,
-    container: PrototypeCategory<Container>,
-    logistic_container: PrototypeCategory<LogisticContainer>,
-    infinity_container: PrototypeCategory<InfinityContainer>,
-    assembling_machine: PrototypeCategory<AssemblingMachine>,
-    rocket_silo: PrototypeCategory<RocketSilo>,
-    furnace: PrototypeCategory<Furnace>,
-    electric_energy_interface: PrototypeCategory<ElectricEnergyInterface>,
-    electric_pole: PrototypeCategory<ElectricPole>,
-    unit_spawner: PrototypeCategory<EnemySpawner>,
-    fish: PrototypeCategory<Fish>,
-    combat_robot: PrototypeCategory<CombatRobot>,
-    construction_robot: PrototypeCategory<ConstructionRobot>,
-    logistic_robot: PrototypeCategory<LogisticRobot>,
-    gate: PrototypeCategory<Gate>,
-    generator: PrototypeCategory<Generator>,
-    heat_interface: PrototypeCategory<HeatInterface>,
-    heat_pipe: PrototypeCategory<HeatPipe>,
-    inserter: PrototypeCategory<Inserter>,
-    lab: PrototypeCategory<Lab>,
-    lamp: PrototypeCategory<Lamp>,
-    land_mine: PrototypeCategory<LandMine>,
-    linked_container: PrototypeCategory<LinkedContainer>,
-    market: PrototypeCategory<Market>,
-    mining_drill: PrototypeCategory<MiningDrill>,
-    offshore_pump: PrototypeCategory<OffshorePump>,
-    pipe: PrototypeCategory<Pipe>,
-    infinity_pipe: PrototypeCategory<InfinityPipe>,
-    pipe_to_ground: PrototypeCategory<PipeToGround>,
-    player_port: PrototypeCategory<PlayerPort>,
-    power_switch: PrototypeCategory<PowerSwitch>,
-    programmable_speaker: PrototypeCategory<ProgrammableSpeaker>,
-    pump: PrototypeCategory<Pump>,
-    radar: PrototypeCategory<Radar>,
-    curved_rail: PrototypeCategory<CurvedRail>,
-    straight_rail: PrototypeCategory<StraightRail>,
-    rail_chain_signal: PrototypeCategory<RailChainSignal>,
-    rail_signal: PrototypeCategory<RailSignal>,
-    reactor: PrototypeCategory<Reactor>,
-    roboport: PrototypeCategory<Roboport>,
-    simple_entity: PrototypeCategory<SimpleEntity>,
-    simple_entity_with_owner: PrototypeCategory<SimpleEntityWithOwner>,
-    simple_entity_with_force: PrototypeCategory<SimpleEntityWithForce>,
-    solar_panel: PrototypeCategory<SolarPanel>,
-    spider_leg: PrototypeCategory<SpiderLeg>,
-    storage_tank: PrototypeCategory<StorageTank>,
-    train_stop: PrototypeCategory<TrainStop>,
-    linked_belt: PrototypeCategory<LinkedBelt>,
-    loader_1x1: PrototypeCategory<Loader1x1>,
-    loader: PrototypeCategory<Loader1x2>,
-    splitter: PrototypeCategory<Splitter>,
-    transport_belt: PrototypeCategory<TransportBelt>,
-    underground_belt: PrototypeCategory<UndergroundBelt>,
-    tree: PrototypeCategory<Tree>,
-    turret: PrototypeCategory<TurretPrototype>,
-    ammo_turret: PrototypeCategory<AmmoTurret>,
-    electric_turret: PrototypeCategory<ElectricTurret>,
-    fluid_turret: PrototypeCategory<FluidTurret>,
-    unit: PrototypeCategory<Unit>,
-    car: PrototypeCategory<Car>,
-    artillery_wagon: PrototypeCategory<ArtilleryWagon>,
-    cargo_wagon: PrototypeCategory<CargoWagon>,
-    fluid_wagon: PrototypeCategory<FluidWagon>,
-    locomotive: PrototypeCategory<Locomotive>,
-    spider_vehicle: PrototypeCategory<SpiderVehicle>,
-    wall: PrototypeCategory<Wall>,
-    explosion: PrototypeCategory<Explosion>,
-    flame_thrower_explosion: PrototypeCategory<FlameThrowerExplosion>,
-    fire: PrototypeCategory<FireFlame>,
-    stream: PrototypeCategory<FluidStream>,
-    flying_text: PrototypeCategory<FlyingText>,
-    highlight_box: PrototypeCategory<HighlightBoxEntity>,
-    item_entity: PrototypeCategory<ItemEntity>,
-    item_request_proxy: PrototypeCategory<ItemRequestProxy>,
-    particle_source: PrototypeCategory<ParticleSource>,
-    projectile: PrototypeCategory<Projectile>,
-    resource: PrototypeCategory<ResourceEntity>,
-    rocket_silo_rocket: PrototypeCategory<RocketSiloRocket>,
-    rocket_silo_rocket_shadow: PrototypeCategory<RocketSiloRocketShadow>,
-    smoke_with_trigger: PrototypeCategory<SmokeWithTrigger>,
-    speech_bubble: PrototypeCategory<SpeechBubble>,
-    sticker: PrototypeCategory<Sticker>,
-    tile_ghost: PrototypeCategory<TileGhost>,
-    active_defense_equipment: PrototypeCategory<ActiveDefenseEquipment>,
-    battery_equipment: PrototypeCategory<BatteryEquipment>,
-    belt_immunity_equipment: PrototypeCategory<BeltImmunityEquipment>,
-    energy_shield_equipment: PrototypeCategory<EnergyShieldEquipment>,
-    generator_equipment: PrototypeCategory<GeneratorEquipment>,
-    movement_bonus_equipment: PrototypeCategory<MovementBonusEquipment>,
-    night_vision_equipment: PrototypeCategory<NightVisionEquipment>,
-    roboport_equipment: PrototypeCategory<RoboportEquipment>,
-    solar_panel_equipment: PrototypeCategory<SolarPanelEquipment>,
-    equipment_category: PrototypeCategory<EquipmentCategory>,
-    equipment_grid: PrototypeCategory<EquipmentGrid>,
-    fluid: PrototypeCategory<Fluid>,
-    fuel_category: PrototypeCategory<FuelCategory>,
-    gui_style: PrototypeCategory<GuiStyle>,
-    item: PrototypeCategory<ItemPrototype>,
-    ammo: PrototypeCategory<AmmoItem>,
-    capsule: PrototypeCategory<Capsule>,
-    gun: PrototypeCategory<Gun>,
-    item_with_entity_data: PrototypeCategory<ItemWithEntityData>,
-    item_with_label: PrototypeCategory<ItemWithLabel>,
-    item_with_inventory: PrototypeCategory<ItemWithInventory>,
-    blueprint_book: PrototypeCategory<BlueprintBook>,
-    item_with_tags: PrototypeCategory<ItemWithTags>,
-    selection_tool: PrototypeCategory<SelectionToolPrototype>,
-    blueprint: PrototypeCategory<BlueprintItem>,
-    copy_paste_tool: PrototypeCategory<CopyPasteTool>,
-    deconstruction_item: PrototypeCategory<DeconstructionItem>,
-    upgrade_item: PrototypeCategory<UpgradeItem>,
-    module: PrototypeCategory<Module>,
-    rail_planner: PrototypeCategory<RailPlanner>,
-    spidertron_remote: PrototypeCategory<SpidertronRemote>,
-    tool: PrototypeCategory<Tool>,
-    armor: PrototypeCategory<Armor>,
-    repair_tool: PrototypeCategory<RepairTool>,
-    item_group: PrototypeCategory<ItemGroup>,
-    item_subgroup: PrototypeCategory<ItemSubGroup>,
-    module_category: PrototypeCategory<ModuleCategory>,
-    noise_expression: PrototypeCategory<NamedNoiseExpression>,
-    noise_layer: PrototypeCategory<NoiseLayer>,
-    optimized_particle: PrototypeCategory<Particle>,
-    recipe: PrototypeCategory<Recipe>,
-    recipe_category: PrototypeCategory<RecipeCategory>,
-    resource_category: PrototypeCategory<ResourceCategory>,
-    shortcut: PrototypeCategory<Shortcut>,
-    technology: PrototypeCategory<Technology>,
-    tile: PrototypeCategory<Tile>,
-    tips_and_tricks_item: PrototypeCategory<TipsAndTricksItem>,
-    trivial_smoke: PrototypeCategory<TrivialSmoke>,
-    tutorial: PrototypeCategory<Tutorial>,
-    utility_constants: PrototypeCategory<UtilityConstants>,
-    utility_sounds: PrototypeCategory<UtilitySounds>,
-    utility_sprites: PrototypeCategory<UtilitySprites>,
-    virtual_signal: PrototypeCategory<VirtualSignal>,
-    bool_setting: PrototypeCategory<BoolModSetting>,
-    int_setting: PrototypeCategory<IntModSetting>,
-    double_setting: PrototypeCategory<DoubleModSetting>,
-    string_setting: PrototypeCategory<StringModSetting>,
-}
+    /// Paths of all recorded resources without duplicates, as sprite sheets are often shared
+    /// between many prototypes
+    pub fn unique_resource_paths(&self) -> BTreeSet<&str> {
+        self.resource_records
+            .iter()
+            .map(|r| r.path.as_str())
+            .collect()
+    }
 
-/// Planned DataTable api:
-/// FromLua just extracts prototypes without doing any checks
-/// `load()` will do FromLua and run checks for prototype references and possibly use provided
-/// resource validator to validate resources.
-///
-/// A draft of how choosing a prototype will work in FromLua
-/// ```
-/// let ptype = t.get::<_, String>("type").parse::<Prototypetype>().map_err(LuaError::external);
-/// match ptype {
-///     // ...
-///     PrototypeType::Arrow => Arrow::extend(Arrow::prototype_from_lua(value, lua,
-///     data_table)).map_err(LuaError::external)?,
-///     // ...
-/// }
-/// ```
-impl DataTable {
-    /// Shorthand for [DataTableAccessable::find]
-    pub fn find<T: DataTableAccessable>(&self, name: &str) -> Result<&T, PrototypesErr> {
-        T::find(self, name)
+    /// Report keys of Lua tables that were not used while extracting prototypes as
+    /// [Diagnostic::UnknownKey], which catches typos like `colour` instead of `color`.
+    ///
+    /// Only tables extracted by derived `PrototypeFromLua` are checked. Keys read with `get_prot`
+    /// count as used, as well as keys that are left unread on purpose, like ones of `loaded_if`
+    /// fields whose condition is `false`. The `type` key is never reported.
+    pub fn report_unknown_keys(&mut self, enabled: bool) {
+        self.key_tracker = enabled.then(KeyTracker::default)
     }
 
-    pub fn find_cloned<T: DataTableAccessable>(&self, name: &str) -> Result<T, PrototypesErr> {
-        T::find_cloned(self, name)
+    /// Diagnostics collected while extracting prototypes
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
     }
 
-    /// Shorthand for [DataTableAccessable::extend]
-    pub fn extend<T: DataTableAccessable>(&mut self, prototype: T) -> Result<(), PrototypesErr> {
-        prototype.extend(self)
+    /// Take collected diagnostics, leaving none
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
     }
 
-    /// Creates new reference and keeps track of it to later be validated through [Self::validate_references]
-    pub fn new_reference<T: DataTableAccessable + 'static>(
-        &mut self,
-        name: String,
-    ) -> Rc<PrototypeReference<T>> {
-        let prot_reference = Rc::new(PrototypeReference::<T>::new(name));
-        self.references.push(Rc::downgrade(
-            &(prot_reference.clone() as Rc<dyn PrototypeReferenceValidate>),
-        ));
-        prot_reference
+    /// Start extracting a prototype from `table`. Tables can be entered multiple times, for
+    /// types that use the same table, unused keys are checked after the outermost one.
+    pub(crate) fn enter_table(&mut self, table: &LuaTable) {
+        if let Some(tracker) = &mut self.key_tracker {
+            tracker
+                .tables
+                .entry(table.to_pointer() as usize)
+                .or_default()
+                .0 += 1
+        }
     }
 
-    /// Validates all tracked references.
-    pub fn validate_references(&self) -> Result<(), PrototypesErr> {
-        for prot_reference in &self.references {
-            if let Some(pref) = prot_reference.upgrade() {
-                pref.validate(self)?
+    /// Finish extracting `type_name` from `table`, see [enter_table](Self::enter_table)
+    pub(crate) fn exit_table(&mut self, table: &LuaTable, type_name: &'static str) {
+        let tracker = match &mut self.key_tracker {
+            Some(tracker) => tracker,
+            None => return,
+        };
+        let pointer = table.to_pointer() as usize;
+        let (depth, used) = match tracker.tables.get_mut(&pointer) {
+            Some(entry) => entry,
+            None => return,
+        };
+        *depth -= 1;
+        if *depth > 0 {
+            return;
+        }
+        let used = std::mem::take(used);
+        tracker.tables.remove(&pointer);
+        for (key, _) in table.clone().pairs::<LuaValue, LuaValue>().flatten() {
+            if let LuaValue::String(key) = key {
+                if let Ok(key) = key.to_str() {
+                    if key != "type" && !used.contains(key) {
+                        self.diagnostics.push(Diagnostic::UnknownKey {
+                            type_name,
+                            key: key.into(),
+                        })
+                    }
+                }
             }
         }
-        Ok(())
     }
 
-    /// Cleanup up Weak references
-    pub fn references_cleanup(&mut self) {
-        self.references.retain(|r| r.upgrade().is_some())
+    /// Mark `key` of `table` as used, see [report_unknown_keys](Self::report_unknown_keys). Done
+    /// by `get_prot`, handwritten impls call it directly for keys they skip.
+    pub(crate) fn use_key(&mut self, table: &LuaTable, key: &str) {
+        if let Some(tracker) = &mut self.key_tracker {
+            if let Some((_, used)) = tracker.tables.get_mut(&(table.to_pointer() as usize)) {
+                used.insert(key.into());
+            }
+        }
     }
 
-    /// Create new resource record
-    pub fn register_resource(&mut self, resource_record: ResourceRecord) {
-        self.resource_records.push(resource_record);
+    /// Runs all validation passes that don't need outside input and returns all failures.
+    /// Resources ([validate_resources](Self::validate_resources)) and recipe cycles are not
+    /// included.
+    ///
+    /// Like this, every `validate_*` pass returns all failures it finds instead of stopping on the
+    /// first one.
+    ///
+    /// Errors are sorted by type and name of the prototype they belong to (see
+    /// [PrototypesErr::owner]), errors of one prototype are kept in the order they are reported
+    /// in. Errors that don't belong to a single prototype, like cycles, come last in pass order.
+    /// With `rayon` feature the passes run in parallel. Errors are merged in pass order before
+    /// sorting, so the result doesn't depend on thread scheduling.
+    pub fn validate_all(&self) -> Result<(), Vec<PrototypesErr>> {
+        let passes = validation_passes();
+        #[cfg(feature = "rayon")]
+        let results: Vec<Vec<PrototypesErr>> = {
+            use rayon::prelude::*;
+            passes.par_iter().map(|pass| pass(self)).collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let results: Vec<Vec<PrototypesErr>> = passes.iter().map(|pass| pass(self)).collect();
+        merge_validation_errors(results)
     }
 
-    /// Validate resources
-    /// callback is a function that should find the file and perform necessary checks, returning
-    /// the Result of the check.
+    /// Validate recorded resources with given validator, which finds the files and performs
+    /// necessary checks. All failures are returned instead of stopping on first one.
     pub fn validate_resources(
         &self,
         validator: &impl ResourceValidator,
-    ) -> Result<(), ResourceError> {
+    ) -> Result<(), Vec<ResourceError>> {
         validator.validate(&self.resource_records)
     }
 }
 
+/// Keys of Lua tables that are being extracted, by table address, with the number of times each
+/// table was entered
+#[derive(Debug, Clone, Default)]
+struct KeyTracker {
+    tables: HashMap<usize, (u32, BTreeSet<String>)>,
+}
+
+/// Non-fatal issue found while extracting prototypes, see [DataTable::diagnostics]
+#[derive(Debug, Clone, Error)]
+pub enum Diagnostic {
+    #[error("Unknown key `{key}` in {type_name}")]
+    UnknownKey {
+        type_name: &'static str,
+        key: String,
+    },
+    #[error("Prototype data.raw[\"{}\"][\"{name}\"] was skipped, its type can't be loaded from Lua yet", .ty.as_ref())]
+    UnsupportedType { ty: PrototypeType, name: String },
+    #[error("Prototype data.raw[\"{}\"][\"{name}\"] was defined again and overwritten", .ty.as_ref())]
+    Overwritten { ty: PrototypeType, name: String },
+}
+
+#[test]
+fn unknown_keys_report() {
+    let lua = Lua::new();
+    let mut data_table = DataTable::default();
+    data_table.report_unknown_keys(true);
+    let setting: LuaValue = lua
+        .load(
+            r#"{
+                type = "bool-setting", name = "setting", setting_type = "startup",
+                default_value = true, hiden = true,
+            }"#,
+        )
+        .eval()
+        .unwrap();
+    BoolModSetting::prototype_from_lua(setting, &lua, &mut data_table).unwrap();
+    // Handwritten impls read keys of the prototype table too, waypoints are skipped on purpose
+    let table: LuaTable = lua
+        .load(
+            r#"{
+                shift_animation_waypoint_stop_duration = 0,
+                shift_animation_waypoints = {north = {{0, 0}}},
+            }"#,
+        )
+        .eval()
+        .unwrap();
+    data_table.enter_table(&table);
+    ShiftAnimation::prototype_from_lua(LuaValue::Table(table.clone()), &lua, &mut data_table)
+        .unwrap();
+    data_table.exit_table(&table, "ShiftAnimation");
+    let keys: Vec<&str> = data_table
+        .diagnostics()
+        .iter()
+        .filter_map(|d| match d {
+            Diagnostic::UnknownKey { key, .. } => Some(key.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(keys, ["hiden"]);
+}
+
+#[test]
+fn extend_overwrites() {
+    let mut data_table = DataTable::default();
+    data_table.add_stubs("fuel-category chemical").unwrap();
+    let mut category = FuelCategory::clone(&data_table.fuel_category["chemical"]);
+    category.prototype_base.order = "b".into();
+    data_table.extend(category).unwrap();
+    assert_eq!(
+        data_table.fuel_category["chemical"].prototype_base.order,
+        "b"
+    );
+    assert!(matches!(
+        data_table.diagnostics(),
+        [Diagnostic::Overwritten { ty: PrototypeType::FuelCategory, name }] if name == "chemical"
+    ));
+}
+
+#[test]
+fn validate_all_sorts_errors() {
+    let mut data_table = DataTable::default();
+    data_table
+        .add_stubs(
+            "item-subgroup b-subgroup missing-group
+            item-subgroup a-subgroup missing-group
+            item b-item
+            item a-item
+            fluid water",
+        )
+        .unwrap();
+    let _reference = data_table.new_reference::<Fluid>("missing-fluid".into());
+    let errors = data_table.validate_all().unwrap_err();
+    let owners: Vec<_> = errors.iter().map(PrototypesErr::owner).collect();
+    assert_eq!(
+        owners,
+        [
+            Some((PrototypeType::Fluid, "water")),
+            Some((PrototypeType::ItemPrototype, "a-item")),
+            Some((PrototypeType::ItemPrototype, "b-item")),
+            Some((PrototypeType::ItemSubGroup, "a-subgroup")),
+            Some((PrototypeType::ItemSubGroup, "b-subgroup")),
+            None,
+        ]
+    );
+    // Passes run in parallel with `rayon` feature, but the result doesn't change between runs
+    let shared_errors = data_table.into_shared().validate_all().unwrap_err();
+    let to_strings = |errors: &[PrototypesErr]| -> Vec<String> {
+        errors.iter().map(ToString::to_string).collect()
+    };
+    assert_eq!(to_strings(&errors), to_strings(&shared_errors));
+}
+
+#[test]
+fn extend_rejects_duplicate_names_in_namespace() {
+    let mut data_table = DataTable::default();
+    // Items, fluids and entities have separate namespaces
+    data_table
+        .add_stubs("item iron-chest\nfluid iron-chest\ncontainer iron-chest")
+        .unwrap();
+    assert!(matches!(
+        data_table.add_stubs("tool iron-chest"),
+        Err(PrototypesErr::DuplicateName {
+            ty: PrototypeType::Tool,
+            existing: PrototypeType::ItemPrototype,
+            ..
+        })
+    ));
+    assert!(matches!(
+        data_table.add_stubs("furnace iron-chest"),
+        Err(PrototypesErr::DuplicateName {
+            ty: PrototypeType::Furnace,
+            existing: PrototypeType::Container,
+            ..
+        })
+    ));
+    assert!(!data_table.tool.contains_key("iron-chest"));
+    assert!(!data_table.furnace.contains_key("iron-chest"));
+}
+
+#[test]
+fn validate_next_upgrade_constraints_rules() {
+    fn container<'a>(data_table: &'a mut DataTable, name: &str) -> &'a mut EntityBase {
+        &mut Arc::make_mut(data_table.container.get_mut(name).unwrap()).entity
+    }
+    fn item<'a>(data_table: &'a mut DataTable, name: &str) -> &'a mut ItemBase {
+        &mut Arc::make_mut(data_table.item.get_mut(name).unwrap()).item
+    }
+    let minable = || MinableProperties {
+        mining_time: 0.1,
+        results: ProductResults::Results { results: vec![] },
+        fluid_amount: 0.0,
+        mining_particle: None,
+        required_fluid: None,
+        mining_trigger: None,
+    };
+    // Minable `chest` upgrades to `chest-2`, which is placed by an item of the same name
+    let valid = || {
+        let mut data_table = DataTable::default();
+        data_table
+            .add_stubs("container chest\ncontainer chest-2\nitem chest-2")
+            .unwrap();
+        item(&mut data_table, "chest-2").place_result = "chest-2".into();
+        let chest = container(&mut data_table, "chest");
+        chest.next_upgrade = Some("chest-2".into());
+        chest.minable = Some(minable());
+        data_table
+    };
+    let reasons = |data_table: &DataTable| -> Vec<&'static str> {
+        let errors = data_table.validate_next_upgrade_constraints().unwrap_err();
+        errors
+            .into_iter()
+            .map(|e| match e {
+                PrototypesErr::InvalidNextUpgrade { reason, .. } => reason,
+                e => panic!("unexpected error: {e}"),
+            })
+            .collect()
+    };
+    assert!(valid().validate_next_upgrade_constraints().is_ok());
+
+    let mut data_table = valid();
+    container(&mut data_table, "chest").flags = Some(EntityPrototypeFlags::NOT_UPGRADABLE);
+    assert_eq!(reasons(&data_table), ["entity has \"not-upgradable\" flag"]);
+
+    let mut data_table = valid();
+    container(&mut data_table, "chest").minable = None;
+    assert_eq!(reasons(&data_table), ["entity is not minable"]);
+
+    let mut data_table = valid();
+    let chest = Container::clone(&data_table.container["chest"]);
+    let wagon = CargoWagon {
+        name: "wagon".into(),
+        prototype_base: chest.prototype_base,
+        entity: chest.entity,
+        entity_with_health: chest.entity_with_health,
+        entity_with_owner: chest.entity_with_owner,
+        vehicle: VehicleBase::stub(),
+        rolling_stock: RollingStockBase {
+            max_speed: 1.5,
+            air_resistance: 0.01,
+            joint_distance: 4.0,
+            connection_distance: 3.0,
+            pictures: RotatedSprite { layers: vec![] },
+            vertical_selection_shift: -0.8,
+            drive_over_tie_trigger: None,
+            tie_distance: 10.0,
+            back_light: None,
+            stand_by_light: None,
+            wheels: None,
+            horizontal_doors: None,
+            vertical_doors: None,
+            color: None,
+            allow_manual_color: true,
+            allow_robot_dispatch_in_automatic_mode: false,
+        },
+        inventory_size: 40,
+    };
+    data_table.extend(wagon).unwrap();
+    assert_eq!(
+        reasons(&data_table),
+        ["entity is rolling stock", "target is of different type"]
+    );
+
+    let mut data_table = valid();
+    data_table
+        .add_stubs("furnace furnace\nitem furnace")
+        .unwrap();
+    item(&mut data_table, "furnace").place_result = "furnace".into();
+    container(&mut data_table, "chest").next_upgrade = Some("furnace".into());
+    assert_eq!(reasons(&data_table), ["target is of different type"]);
+
+    let mut data_table = valid();
+    let target = &mut container(&mut data_table, "chest-2");
+    target.fast_replaceable_group = "container".into();
+    target.collision_box = BoundingBox((-0.35, -0.35).into(), (0.35, 0.35).into());
+    assert_eq!(
+        reasons(&data_table),
+        [
+            "target has different `fast_replaceable_group`",
+            "target has different `collision_box`"
+        ]
+    );
+
+    let mut data_table = valid();
+    item(&mut data_table, "chest-2").flags = Some(ItemPrototypeFlags::HIDDEN);
+    assert_eq!(
+        reasons(&data_table),
+        ["target is not placed by any item that isn't hidden"]
+    );
+
+    // `chest-2` upgrades back to `chest`, which is placed by an item too
+    let mut data_table = valid();
+    data_table.add_stubs("item chest").unwrap();
+    item(&mut data_table, "chest").place_result = "chest".into();
+    let target = &mut container(&mut data_table, "chest-2");
+    target.next_upgrade = Some("chest".into());
+    target.minable = Some(minable());
+    assert!(matches!(
+        data_table.validate_next_upgrade_constraints().unwrap_err().as_slice(),
+        [PrototypesErr::UpgradeCycle(cycle)] if cycle.len() == 2
+    ));
+}
+
+#[test]
+fn validate_singletons_names() {
+    // Loading from Lua rejects other names already, these are added directly
+    let spectator = |name: &str| SpectatorController {
+        name: name.into(),
+        movement_speed: 0.75,
+    };
+    let mut data_table = DataTable::default();
+    data_table.extend(spectator("default")).unwrap();
+    assert!(data_table.validate_singletons().is_ok());
+    data_table.extend(spectator("spectator")).unwrap();
+    data_table
+        .extend(GodController {
+            name: "god".into(),
+            inventory_size: 80,
+            movement_speed: 0.75,
+            item_pickup_distance: 1.0,
+            loot_pickup_distance: 2.0,
+            mining_speed: 0.5,
+            crafting_categories: None,
+            mining_categories: None,
+        })
+        .unwrap();
+    let errors = data_table.validate_singletons().unwrap_err();
+    let reported: Vec<_> = errors.iter().map(PrototypesErr::owner).collect();
+    assert_eq!(
+        reported,
+        [
+            Some((PrototypeType::GodController, "god")),
+            Some((PrototypeType::SpectatorController, "spectator")),
+        ]
+    );
+    assert!(errors.iter().all(|e| matches!(
+        e,
+        PrototypesErr::InvalidSingletonName {
+            expected: "default",
+            ..
+        }
+    )));
+}
+
+#[test]
+fn validate_fluid_temperatures_checks() {
+    let lua = Lua::new();
+    let entity: LuaFunction = lua
+        .load(
+            r#"
+                local animation = {filename = "__core__/graphics/empty.png", size = 1}
+                local animation_4_way = {
+                    north = animation, east = animation, south = animation, west = animation,
+                }
+                local function fluid_box(filter)
+                    return {
+                        pipe_connections = {}, secondary_draw_orders = {north = -1}, filter = filter,
+                    }
+                end
+                return function(ty, name, fluid, temperature, mode_or_burns_fluid)
+                    local entity = {
+                        type = ty, name = name,
+                        map_generator_bounding_box = {{-1, -1}, {1, 1}},
+                        repair_sound = {filename = "__core__/sound/silence-1sec.ogg"},
+                        integration_patch = {},
+                        energy_source = {type = "void"},
+                        fluid_box = fluid_box(fluid),
+                    }
+                    if ty == "boiler" then
+                        entity.output_fluid_box = fluid_box("steam")
+                        entity.energy_consumption = "1.8MW"
+                        entity.burning_cooldown = 20
+                        entity.target_temperature = temperature
+                        entity.mode = mode_or_burns_fluid
+                        entity.structure = animation_4_way
+                        entity.fire = animation_4_way
+                        entity.fire_glow = animation_4_way
+                    else
+                        entity.horizontal_animation = animation
+                        entity.vertical_animation = animation
+                        entity.effectivity = 1
+                        entity.fluid_usage_per_tick = 0.5
+                        entity.maximum_temperature = temperature
+                        entity.burns_fluid = mode_or_burns_fluid
+                    end
+                    return entity
+                end
+            "#,
+        )
+        .call(())
+        .unwrap();
+    let mut data_table = DataTable::default();
+    data_table.add_stubs("fluid water\nfluid steam").unwrap();
+    let boiler = |name: &str, fluid: &str, target_temperature: f64, mode: &str| {
+        let value = entity
+            .call(("boiler", name, fluid, target_temperature, mode))
+            .unwrap();
+        Boiler::prototype_from_lua(value, &lua, &mut DataTable::default()).unwrap()
+    };
+    let generator = |name: &str, maximum_temperature: f64, burns_fluid: bool| {
+        let value = entity
+            .call(("generator", name, "steam", maximum_temperature, burns_fluid))
+            .unwrap();
+        Generator::prototype_from_lua(value, &lua, &mut DataTable::default()).unwrap()
+    };
+    data_table
+        .extend(boiler("boiler", "water", 165.0, "heat-water-inside"))
+        .unwrap();
+    data_table
+        .extend(generator("steam-engine", 165.0, false))
+        .unwrap();
+    assert!(data_table.validate_fluid_temperatures().is_ok());
+    // Checked only when the boiler heats its input and the generator doesn't burn it, and the
+    // input fluid exists
+    for cold_boiler in [
+        boiler("cold-boiler", "water", 15.0, "heat-water-inside"),
+        boiler("heat-exchanger", "water", 15.0, "output-to-separate-pipe"),
+        boiler("oil-boiler", "crude-oil", 15.0, "heat-water-inside"),
+    ] {
+        data_table.extend(cold_boiler).unwrap();
+    }
+    data_table
+        .extend(generator("cold-engine", 15.0, false))
+        .unwrap();
+    data_table
+        .extend(generator("steam-burner", 15.0, true))
+        .unwrap();
+    let errors = data_table.validate_fluid_temperatures().unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [
+            PrototypesErr::InvalidFieldValue {
+                ty: PrototypeType::Boiler,
+                name: boiler,
+                field: "target_temperature",
+                ..
+            },
+            PrototypesErr::InvalidFieldValue {
+                ty: PrototypeType::Generator,
+                name: generator,
+                field: "maximum_temperature",
+                ..
+            },
+        ] if boiler == "cold-boiler" && generator == "cold-engine"
+    ));
+}
+
+#[test]
+fn validate_spidertron_checks() {
+    let mut data_table = DataTable::default();
+    data_table.add_stubs("container spidertron").unwrap();
+    let stub = data_table.container.remove("spidertron").unwrap();
+    let leg = |name: &str, part_length: f64| SpiderLeg {
+        name: name.into(),
+        prototype_base: stub.prototype_base.clone(),
+        entity: stub.entity.clone(),
+        entity_with_health: stub.entity_with_health.clone(),
+        part_length,
+        initial_movement_speed: 0.06,
+        movement_acceleration: 0.03,
+        target_position_randomisation_distance: 0.25,
+        minimal_step_size: 1.0,
+        movement_based_position_selection_distance: 4.0,
+        graphics_set: SpiderLegGraphicsSet::default(),
+        walking_sound_volume_modifier: 1.0,
+    };
+    let spidertron = |name: &str, legs: &[&str], guns: &[&str]| SpiderVehicle {
+        name: name.into(),
+        prototype_base: stub.prototype_base.clone(),
+        entity: stub.entity.clone(),
+        entity_with_health: stub.entity_with_health.clone(),
+        entity_with_owner: stub.entity_with_owner.clone(),
+        vehicle: VehicleBase::stub(),
+        energy_source: EnergySource::Void,
+        inventory_size: 80,
+        graphics_set: SpiderVehicleGraphicsSet::default(),
+        spider_engine: SpiderEnginePrototype::new(
+            true,
+            legs.iter()
+                .map(|leg| {
+                    let position = Factorio2DVector(0.0, 0.0);
+                    SpiderLegSpecification::new(leg.to_string(), position, position)
+                })
+                .collect(),
+        ),
+        height: 1.5,
+        chunk_exploration_radius: 3,
+        movement_energy_consumption: Energy(250_000.0),
+        automatic_weapon_cycling: true,
+        chain_shooting_cooldown_modifier: 0.5,
+        torso_rotation_speed: 1.0,
+        trash_inventory_size: 0,
+        guns: guns.iter().map(ToString::to_string).collect(),
+    };
+    data_table.extend(leg("spidertron-leg", 3.5)).unwrap();
+    data_table
+        .extend(spidertron(
+            "spidertron",
+            &["spidertron-leg", "spidertron-leg"],
+            &[],
+        ))
+        .unwrap();
+    assert!(data_table.validate_spidertron().is_ok());
+    data_table.extend(leg("broken-leg", 0.0)).unwrap();
+    data_table
+        .extend(spidertron(
+            "broken-spidertron",
+            &["spidertron-leg", "missing-leg"],
+            &["spidertron-rocket-launcher-1"],
+        ))
+        .unwrap();
+    let errors = data_table.validate_spidertron().unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [
+            PrototypesErr::MissingReference {
+                field: "spider_engine",
+                target: leg,
+                ..
+            },
+            PrototypesErr::MissingReference {
+                field: "guns",
+                target: gun,
+                ..
+            },
+            PrototypesErr::InvalidFieldValue {
+                ty: PrototypeType::SpiderLeg,
+                name: broken_leg,
+                field: "part_length",
+                ..
+            },
+        ] if leg == "missing-leg"
+            && gun == "spidertron-rocket-launcher-1"
+            && broken_leg == "broken-leg"
+    ));
+}
+
+#[test]
+fn validate_no_unintended_cycles_checks() {
+    let item = |name: &str| ProductType::Item(name.into());
+    let mut data_table = DataTable::default();
+    data_table
+        .extend(Recipe::stub(
+            "iron-gear-wheel",
+            &[(item("iron-plate"), 2.0)],
+            &[(item("iron-gear-wheel"), 1.0)],
+        ))
+        .unwrap();
+    assert!(data_table.validate_no_unintended_cycles(&[]).is_ok());
+    data_table
+        .extend(Recipe::stub(
+            "fill-water-barrel",
+            &[
+                (ProductType::Fluid("water".into()), 50.0),
+                (item("empty-barrel"), 1.0),
+            ],
+            &[(item("water-barrel"), 1.0)],
+        ))
+        .unwrap();
+    data_table
+        .extend(Recipe::stub(
+            "empty-water-barrel",
+            &[(item("water-barrel"), 1.0)],
+            &[
+                (ProductType::Fluid("water".into()), 50.0),
+                (item("empty-barrel"), 1.0),
+            ],
+        ))
+        .unwrap();
+    let errors = data_table.validate_no_unintended_cycles(&[]).unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [PrototypesErr::RecipeCycle(cycle)] if cycle == &["empty-water-barrel", "fill-water-barrel"]
+    ));
+    assert!(data_table
+        .validate_no_unintended_cycles(&["empty-water-barrel"])
+        .is_ok());
+    data_table
+        .extend(Recipe::stub(
+            "kovarex-enrichment-process",
+            &[(item("uranium-235"), 40.0), (item("uranium-238"), 5.0)],
+            &[(item("uranium-235"), 41.0), (item("uranium-238"), 2.0)],
+        ))
+        .unwrap();
+    let errors = data_table
+        .validate_no_unintended_cycles(&["fill-water-barrel"])
+        .unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [PrototypesErr::RecipeCycle(cycle)] if cycle == &["kovarex-enrichment-process"]
+    ));
+}
+
 /// [mlua::FromLua] alternative with [DataTable] reference being passed
 pub trait PrototypeFromLua<'lua>: Sized {
     fn prototype_from_lua(
@@ -365,11 +2814,11 @@ impl<'lua, T: PrototypeFromLua<'lua>> PrototypeFromLua<'lua> for Vec<T> {
                 .map(|v| T::prototype_from_lua(v, lua, data_table))
                 .collect()
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "Vec",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "Vec",
+                Some("expected table".into()),
+            ))
         }
     }
 }
@@ -388,6 +2837,17 @@ impl<'lua, T: PrototypeFromLua<'lua>> PrototypeFromLua<'lua> for Option<T> {
     }
 }
 
+impl<'lua, T: PrototypeFromLua<'lua>> PrototypeFromLua<'lua> for Box<T> {
+    #[inline]
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<Self> {
+        T::prototype_from_lua(value, lua, data_table).map(Box::new)
+    }
+}
+
 impl<'lua> PrototypeFromLua<'lua> for LuaValue<'lua> {
     #[inline]
     fn prototype_from_lua(
@@ -399,6 +2859,40 @@ impl<'lua> PrototypeFromLua<'lua> for LuaValue<'lua> {
     }
 }
 
+// Common part of map impls, `to` is the type name used in conversion error
+fn prot_pairs_from_lua<'lua, K, V>(
+    value: LuaValue<'lua>,
+    lua: &'lua Lua,
+    data_table: &mut DataTable,
+    to: &'static str,
+) -> LuaResult<Vec<(K, V)>>
+where
+    K: PrototypeFromLua<'lua>,
+    V: PrototypeFromLua<'lua>,
+{
+    let type_name = value.type_name();
+    if let LuaValue::Table(t) = value {
+        let pairs = t
+            .pairs()
+            .collect::<LuaResult<Vec<(LuaValue, LuaValue)>>>()?;
+        pairs
+            .into_iter()
+            .map(|(k, v)| {
+                Ok((
+                    K::prototype_from_lua(k, lua, data_table)?,
+                    V::prototype_from_lua(v, lua, data_table)?,
+                ))
+            })
+            .collect::<LuaResult<Vec<(K, V)>>>()
+    } else {
+        Err(conversion_error(
+            type_name,
+            to,
+            Some("expected table".into()),
+        ))
+    }
+}
+
 impl<'lua, K, V> PrototypeFromLua<'lua> for HashMap<K, V>
 where
     K: Eq + Hash + PrototypeFromLua<'lua>,
@@ -409,28 +2903,44 @@ where
         lua: &'lua Lua,
         data_table: &mut DataTable,
     ) -> LuaResult<Self> {
-        let type_name = value.type_name();
-        if let LuaValue::Table(t) = value {
-            let pairs = t
-                .pairs()
-                .collect::<LuaResult<Vec<(LuaValue, LuaValue)>>>()?;
-            let prot_pairs = pairs
-                .into_iter()
-                .map(|(k, v)| {
-                    Ok((
-                        K::prototype_from_lua(k, lua, data_table)?,
-                        V::prototype_from_lua(v, lua, data_table)?,
-                    ))
-                })
-                .collect::<LuaResult<Vec<(K, V)>>>()?;
-            Ok(HashMap::from_iter(prot_pairs))
-        } else {
-            Err(LuaError::FromLuaConversionError {
-                from: type_name,
-                to: "HashMap",
-                message: Some("expected table".into()),
-            })
-        }
+        Ok(HashMap::from_iter(prot_pairs_from_lua(
+            value, lua, data_table, "HashMap",
+        )?))
+    }
+}
+
+/// Sorted by key, for data where order matters but Lua order can't be relied on
+impl<'lua, K, V> PrototypeFromLua<'lua> for BTreeMap<K, V>
+where
+    K: Ord + PrototypeFromLua<'lua>,
+    V: PrototypeFromLua<'lua>,
+{
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<Self> {
+        Ok(BTreeMap::from_iter(prot_pairs_from_lua(
+            value, lua, data_table, "BTreeMap",
+        )?))
+    }
+}
+
+/// Keeps the order in which `pairs()` returned the entries
+#[cfg(feature = "indexmap")]
+impl<'lua, K, V> PrototypeFromLua<'lua> for indexmap::IndexMap<K, V>
+where
+    K: Eq + Hash + PrototypeFromLua<'lua>,
+    V: PrototypeFromLua<'lua>,
+{
+    fn prototype_from_lua(
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<Self> {
+        Ok(indexmap::IndexMap::from_iter(prot_pairs_from_lua(
+            value, lua, data_table, "IndexMap",
+        )?))
     }
 }
 
@@ -448,14 +2958,13 @@ where
             .map(|v| T::prototype_from_lua(v, lua, data_table))
             .into_iter()
             .collect::<Result<Vec<T>, LuaError>>()?;
-        let result: [T; N] =
-            values_arr
-                .try_into()
-                .map_err(|v: Vec<T>| LuaError::FromLuaConversionError {
-                    from: "table",
-                    to: "Array",
-                    message: Some(format!("Expected table of length {}, got {}", N, v.len())),
-                })?;
+        let result: [T; N] = values_arr.try_into().map_err(|v: Vec<T>| {
+            conversion_error(
+                "table",
+                "Array",
+                Some(format!("Expected table of length {}, got {}", N, v.len())),
+            )
+        })?;
         Ok(result)
     }
 }
@@ -483,54 +2992,339 @@ prot_from_lua_blanket!(Factorio3DVector);
 prot_from_lua_blanket!(Position);
 prot_from_lua_blanket!(RealOrientation);
 prot_from_lua_blanket!(Direction);
+prot_from_lua_blanket!(Ticks);
+prot_from_lua_blanket!(KeySequence);
 prot_from_lua_blanket!(EffectTypeLimitation);
 #[cfg(feature = "concepts")]
 prot_from_lua_blanket!(LocalisedString);
 
 fn prot_from_lua_err(cond: bool, type_name: &'static str, message: impl ToString) -> LuaResult<()> {
     if cond {
-        Err(mlua::Error::FromLuaConversionError {
-            from: "table",
-            to: type_name,
-            message: Some(message.to_string()),
-        })
+        Err(conversion_error(
+            "table",
+            type_name,
+            Some(message.to_string()),
+        ))
     } else {
         Ok(())
     }
 }
 
-/// Trait for getting a prototype from table
-trait GetPrototype<'lua> {
-    fn get_prot<K: ToLua<'lua>, V: PrototypeFromLua<'lua>>(
-        &self,
-        key: K,
-        lua: &'lua Lua,
-        data_table: &mut DataTable,
-    ) -> LuaResult<V>;
-}
-
-impl<'lua> GetPrototype<'lua> for mlua::Table<'lua> {
-    fn get_prot<K: ToLua<'lua>, V: PrototypeFromLua<'lua>>(
-        &self,
-        key: K,
-        lua: &'lua Lua,
-        data_table: &mut DataTable,
-    ) -> LuaResult<V> {
-        let value = self.get::<K, LuaValue>(key)?;
-        V::prototype_from_lua(value, lua, data_table)
-    }
+/// Adds name of the field to conversion error message, so that errors in nested prototypes read
+/// like `field "a": field "b": expected f64, got string`
+fn prot_field_err(key: &str, err: mlua::Error) -> mlua::Error {
+    match err {
+        mlua::Error::FromLuaConversionError { from, to, message } => {
+            let message = match message {
+                Some(message) if message.starts_with("field \"") => message,
+                Some(message) => format!("expected {to}, got {from}: {message}"),
+                None => format!("expected {to}, got {from}"),
+            };
+            mlua::Error::FromLuaConversionError {
+                from,
+                to,
+                message: Some(format!("field \"{key}\": {message}")),
+            }
+        }
+        err => err,
+    }
+}
+
+#[test]
+fn prot_field_err_message() {
+    let err = conversion_error("string", "f64", None);
+    let err = prot_field_err("north", err);
+    let err = prot_field_err("shift_animation_waypoints", err);
+    match err {
+        mlua::Error::FromLuaConversionError { message, .. } => assert_eq!(
+            message.unwrap(),
+            "field \"shift_animation_waypoints\": field \"north\": expected f64, got string"
+        ),
+        _ => panic!("unexpected error kind"),
+    }
+}
+
+/// Trait for getting a prototype from table
+trait GetPrototype<'lua> {
+    /// Errors include the key and the type of Lua value found, see [prot_field_err]
+    fn get_prot<K: IntoLuaCompat<'lua> + fmt::Display, V: PrototypeFromLua<'lua>>(
+        &self,
+        key: K,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<V>;
+}
+
+impl<'lua> GetPrototype<'lua> for LuaTable<'lua> {
+    fn get_prot<K: IntoLuaCompat<'lua> + fmt::Display, V: PrototypeFromLua<'lua>>(
+        &self,
+        key: K,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<V> {
+        let key_name = key.to_string();
+        data_table.use_key(self, &key_name);
+        let value = self.get_as::<LuaValue>(key)?;
+        V::prototype_from_lua(value, lua, data_table).map_err(|e| prot_field_err(&key_name, e))
+    }
+}
+
+/// Type of prototype at `data.raw[category][name]`, parsed from its `type` field. Used to pick the
+/// parser for each prototype when loading `data.raw`.
+pub fn raw_prototype_type(
+    category: &str,
+    name: &str,
+    ty: Option<&str>,
+) -> Result<PrototypeType, PrototypesErr> {
+    let ty = ty.ok_or_else(|| PrototypesErr::MissingType {
+        category: category.into(),
+        name: name.into(),
+    })?;
+    ty.parse().map_err(|_| PrototypesErr::UnknownType {
+        category: category.into(),
+        name: name.into(),
+        ty: ty.into(),
+    })
+}
+
+#[test]
+fn raw_prototype_type_errors() {
+    assert!(matches!(
+        raw_prototype_type("item", "iron-plate", None),
+        Err(PrototypesErr::MissingType { .. })
+    ));
+    match raw_prototype_type("itme", "iron-plate", Some("itme")) {
+        Err(PrototypesErr::UnknownType { category, name, ty }) => {
+            assert_eq!(
+                (category.as_str(), name.as_str(), ty.as_str()),
+                ("itme", "iron-plate", "itme")
+            )
+        }
+        other => panic!("unexpected result {other:?}"),
+    }
+}
+
+// Parser of prototypes that implement PrototypeFromLua, by type. `None` for other types.
+fn parse_raw_prototype<'lua>(
+    ty: PrototypeType,
+    value: LuaValue<'lua>,
+    lua: &'lua Lua,
+    data_table: &mut DataTable,
+) -> LuaResult<Option<Box<dyn Prototype>>> {
+    macro_rules! parse_prototype {
+        ($($group:ident { $($category:ident: $prototype:ty,)* })*) => {
+            $($( if ty == <$prototype>::PROTOTYPE_TYPE {
+                return (&RawParser::<$prototype>(PhantomData)).parse(value, lua, data_table);
+            } )*)*
+        };
+    }
+    data_table_categories!(parse_prototype);
+    Ok(None)
+}
+
+// Parse prototype like [parse_raw_prototype] and add it to the table. `false` for types that
+// can't be loaded from Lua.
+fn extend_raw_prototype<'lua>(
+    ty: PrototypeType,
+    value: LuaValue<'lua>,
+    lua: &'lua Lua,
+    data_table: &mut DataTable,
+) -> LuaResult<bool> {
+    macro_rules! extend_prototype {
+        ($($group:ident { $($category:ident: $prototype:ty,)* })*) => {
+            $($( if ty == <$prototype>::PROTOTYPE_TYPE {
+                return (&RawParser::<$prototype>(PhantomData)).extend(value, lua, data_table);
+            } )*)*
+        };
+    }
+    data_table_categories!(extend_prototype);
+    Ok(false)
+}
+
+// Picks the parser for [parse_raw_prototype] by autoref specialization: `LoadableParser` is
+// implemented for `RawParser<T>` of every `T` that implements PrototypeFromLua and is found by
+// method resolution before `UnsupportedParser` of `&RawParser<T>`, which returns `None`.
+struct RawParser<T>(PhantomData<T>);
+
+trait LoadableParser<'lua> {
+    fn parse(
+        &self,
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<Option<Box<dyn Prototype>>>;
+
+    fn extend(
+        &self,
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<bool>;
+}
+
+impl<'lua, T: PrototypeFromLua<'lua> + DataTableAccessable + 'static> LoadableParser<'lua>
+    for RawParser<T>
+{
+    fn parse(
+        &self,
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<Option<Box<dyn Prototype>>> {
+        let prototype = T::prototype_from_lua(value, lua, data_table)?;
+        Ok(Some(Box::new(prototype)))
+    }
+
+    fn extend(
+        &self,
+        value: LuaValue<'lua>,
+        lua: &'lua Lua,
+        data_table: &mut DataTable,
+    ) -> LuaResult<bool> {
+        let prototype = T::prototype_from_lua(value, lua, data_table)?;
+        data_table.extend(prototype).map_err(LuaError::external)?;
+        Ok(true)
+    }
+}
+
+trait UnsupportedParser<'lua> {
+    fn parse(
+        &self,
+        _value: LuaValue<'lua>,
+        _lua: &'lua Lua,
+        _data_table: &mut DataTable,
+    ) -> LuaResult<Option<Box<dyn Prototype>>> {
+        Ok(None)
+    }
+
+    fn extend(
+        &self,
+        _value: LuaValue<'lua>,
+        _lua: &'lua Lua,
+        _data_table: &mut DataTable,
+    ) -> LuaResult<bool> {
+        Ok(false)
+    }
+}
+
+impl<'lua, T> UnsupportedParser<'lua> for &RawParser<T> {}
+
+impl DataTable {
+    /// Parse every prototype of `data_raw` and pass it to `f` instead of adding it to the table,
+    /// so that prototypes can be processed one at a time. Stops early when `f` returns
+    /// [ControlFlow::Break].
+    ///
+    /// References, resources and diagnostics are still recorded in this table. Prototypes of
+    /// types that can't be loaded from Lua yet are skipped and reported as
+    /// [Diagnostic::UnsupportedType].
+    pub fn parse_each<'lua>(
+        &mut self,
+        lua: &'lua Lua,
+        data_raw: LuaTable<'lua>,
+        mut f: impl FnMut(Box<dyn Prototype>) -> ControlFlow<()>,
+    ) -> LuaResult<()> {
+        for category in data_raw.pairs::<String, LuaTable>() {
+            let (category, prototypes) = category?;
+            for prototype in prototypes.pairs::<String, LuaValue>() {
+                let (name, value) = prototype?;
+                let ty = match &value {
+                    LuaValue::Table(t) => t.get_as::<Option<String>>("type")?,
+                    _ => None,
+                };
+                let ty = raw_prototype_type(&category, &name, ty.as_deref())
+                    .map_err(LuaError::external)?;
+                match parse_raw_prototype(ty, value, lua, self)? {
+                    Some(prototype) => {
+                        if f(prototype).is_break() {
+                            return Ok(());
+                        }
+                    }
+                    None => self
+                        .diagnostics
+                        .push(Diagnostic::UnsupportedType { ty, name }),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse every prototype of `data_raw` and add it to the table, like
+    /// [parse_each](Self::parse_each) does without adding them. Prototypes of types that can't be
+    /// loaded from Lua yet are skipped and reported as [Diagnostic::UnsupportedType].
+    pub fn extend_from_lua<'lua>(
+        &mut self,
+        lua: &'lua Lua,
+        data_raw: LuaTable<'lua>,
+    ) -> LuaResult<()> {
+        for category in data_raw.pairs::<String, LuaTable>() {
+            let (category, prototypes) = category?;
+            for prototype in prototypes.pairs::<String, LuaValue>() {
+                let (name, value) = prototype?;
+                let ty = match &value {
+                    LuaValue::Table(t) => t.get_as::<Option<String>>("type")?,
+                    _ => None,
+                };
+                let ty = raw_prototype_type(&category, &name, ty.as_deref())
+                    .map_err(LuaError::external)?;
+                if !extend_raw_prototype(ty, value, lua, self)? {
+                    self.diagnostics
+                        .push(Diagnostic::UnsupportedType { ty, name })
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn extend_from_lua() {
+    let lua = Lua::new();
+    let data_raw = lua
+        .load(
+            r#"{
+                ["item-group"] = {logistics = {
+                    type = "item-group", name = "logistics", order = "a",
+                    icon = "__base__/graphics/item-group/logistics.png", icon_size = 128,
+                }},
+                ["recipe-category"] = {crafting = {type = "recipe-category", name = "crafting"}},
+                recipe = {["iron-plate"] = {type = "recipe", name = "iron-plate"}},
+            }"#,
+        )
+        .eval()
+        .unwrap();
+    let mut data_table = DataTable::default();
+    data_table.extend_from_lua(&lua, data_raw).unwrap();
+    assert_eq!(data_table.item_group["logistics"].order_in_recipe, "a");
+    assert!(data_table.recipe_category.contains_key("crafting"));
+    assert!(matches!(
+        data_table.diagnostics(),
+        [Diagnostic::UnsupportedType { ty: PrototypeType::Recipe, name }] if name == "iron-plate"
+    ));
 }
 
 /// Validate PrototypeReference. Any type.
-trait PrototypeReferenceValidate: fmt::Debug {
+///
+/// `Send` and `Sync`, so that [DataTable] tracking the references is too.
+trait PrototypeReferenceValidate: fmt::Debug + Send + Sync {
     fn validate(&self, data_table: &DataTable) -> Result<(), PrototypesErr>;
+    /// Link the reference to its prototype, see [DataTable::freeze]
+    fn link(&self, data_table: &DataTable) -> Result<(), PrototypesErr>;
 }
 
 /// Reference to a prototype by name.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct PrototypeReference<T: DataTableAccessable> {
     pub name: String,
-    prot: PhantomData<T>,
+    link: OnceLock<Arc<T>>,
+}
+
+impl<T: DataTableAccessable> fmt::Debug for PrototypeReference<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrototypeReference")
+            .field("name", &self.name)
+            .field("linked", &self.link.get().is_some())
+            .finish()
+    }
 }
 
 impl<T: DataTableAccessable> PrototypeReference<T> {
@@ -538,10 +3332,17 @@ impl<T: DataTableAccessable> PrototypeReference<T> {
     pub fn new(name: String) -> Self {
         Self {
             name,
-            prot: PhantomData,
+            link: OnceLock::new(),
         }
     }
 
+    /// Prototype the reference was linked to, by [DataTable::freeze] if the reference was
+    /// tracked by the table or by [FrozenDataTable::get]. Doesn't look anything up, abstract
+    /// prototypes included.
+    pub fn linked(&self) -> Option<&T> {
+        self.link.get().map(Arc::as_ref)
+    }
+
     pub fn find<'a>(&self, data_table: &'a DataTable) -> Result<&'a T, PrototypesErr> {
         data_table.find::<T>(&self.name)
     }
@@ -554,16 +3355,38 @@ impl<T: DataTableAccessable> PrototypeReference<T> {
     pub fn is_valid(&self, data_table: &DataTable) -> bool {
         self.find(data_table).is_ok() || self.find_cloned(data_table).is_ok()
     }
+
+    /// Reinterprets the name under another prototype type. Errors if the name doesn't resolve
+    /// under the new type.
+    pub fn retype<U: DataTableAccessable>(
+        &self,
+        data_table: &DataTable,
+    ) -> Result<PrototypeReference<U>, PrototypesErr> {
+        let result = PrototypeReference::<U>::new(self.name.clone());
+        if result.is_valid(data_table) {
+            Ok(result)
+        } else {
+            Err(PrototypesErr::PrototypeNotFound(self.name.clone()))
+        }
+    }
 }
 
-impl<T: DataTableAccessable> PrototypeReferenceValidate for PrototypeReference<T> {
+impl<T: DataTableAccessable + 'static> PrototypeReferenceValidate for PrototypeReference<T> {
     /// Validates the reference
     fn validate(&self, data_table: &DataTable) -> Result<(), PrototypesErr> {
-        data_table.find::<T>(&self.name).map(|_| ())
+        data_table.find_cow::<T>(&self.name).map(|_| ())
+    }
+
+    fn link(&self, data_table: &DataTable) -> Result<(), PrototypesErr> {
+        if self.link.get().is_none() {
+            // A reference tracked by several tables keeps the link of the first one frozen
+            let _ = self.link.set(T::find_shared(data_table, &self.name)?);
+        }
+        Ok(())
     }
 }
 
-impl<'lua, T: DataTableAccessable + 'static> PrototypeFromLua<'lua> for Rc<PrototypeReference<T>> {
+impl<'lua, T: DataTableAccessable + 'static> PrototypeFromLua<'lua> for Arc<PrototypeReference<T>> {
     fn prototype_from_lua(
         value: LuaValue<'lua>,
         _lua: &'lua Lua,
@@ -574,34 +3397,113 @@ impl<'lua, T: DataTableAccessable + 'static> PrototypeFromLua<'lua> for Rc<Proto
             let result = data_table.new_reference(name);
             Ok(result)
         } else {
-            Err(LuaError::FromLuaConversionError {
-                from: value.type_name(),
-                to: "PrototypeReference",
-                message: Some("expected string".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "PrototypeReference",
+                Some("expected string".into()),
+            ))
         }
     }
 }
 
 /// Trait for manipulating prototypes in [Data table](DataTable).
 /// Primarily used for [`PrototypeReference`]
+///
+/// Prototypes are `Send` and `Sync`, so that tables can be shared between threads, see
+/// [SharedDataTable].
 pub trait DataTableAccessable: Prototype
 where
-    Self: Sized + Clone,
+    Self: Sized + Clone + Send + Sync,
 {
     /// Find prototype in [Data table](DataTable) by it's name. Always returns an error for
     /// abstract prototypes
     fn find<'a>(data_table: &'a DataTable, name: &str) -> Result<&'a Self, PrototypesErr>;
+    /// All prototypes of this type, by name. Always returns an error for abstract prototypes
+    fn category(data_table: &DataTable) -> Result<&PrototypeCategory<Self>, PrototypesErr>;
     /// Find and clone the prototype. Doesn't clone when searching for abstract prototype.
     fn find_cloned(data_table: &DataTable, name: &str) -> Result<Self, PrototypesErr> {
         Ok(Self::find(data_table, name)?.clone())
     }
-    /// Extend [Data table](DataTable) with this prototype
+    /// Find the prototype and share it with the table, cloning only the [Arc] it is stored in.
+    /// Abstract prototypes are not stored, so they are made with
+    /// [find_cloned](Self::find_cloned).
+    fn find_shared(data_table: &DataTable, name: &str) -> Result<Arc<Self>, PrototypesErr> {
+        Self::category(data_table)?
+            .get(name)
+            .cloned()
+            .ok_or_else(|| PrototypesErr::PrototypeNotFound(name.into()))
+    }
+    /// Find the prototype, borrowing it if possible. Abstract prototypes can't be borrowed, so
+    /// they are returned owned, using [find_cloned](Self::find_cloned).
+    fn find_cow<'a>(data_table: &'a DataTable, name: &str) -> Result<Cow<'a, Self>, PrototypesErr> {
+        Ok(Cow::Borrowed(Self::find(data_table, name)?))
+    }
+    /// Extend [Data table](DataTable) with this prototype. Like `data:extend` in Factorio, a
+    /// prototype of this type with the same name is overwritten, which is reported as
+    /// [Diagnostic::Overwritten]. A prototype of another type in the same namespace with the
+    /// same name is an error, see [PrototypesErr::DuplicateName].
     fn extend(self, data_table: &mut DataTable) -> Result<(), PrototypesErr>;
 }
 
 pub trait ResourceValidator {
-    fn validate(&self, resources: &[ResourceRecord]) -> Result<(), ResourceError>;
+    /// Check all resources, returning every failure. Each error carries path of the resource.
+    fn validate(&self, resources: &[ResourceRecord]) -> Result<(), Vec<ResourceError>>;
+}
+
+/// Types that contain images or sounds, possibly deeply nested. Unlike resources recorded during
+/// extraction, walking covers fields that are not extracted from Lua yet.
+///
+/// Leaf types (sprites, animations, sounds) implement it by hand, everything that contains them
+/// derives it and walks each field that implements it.
+pub trait ResourceWalk {
+    /// Push records of all contained resources
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>);
+}
+
+impl<T: ResourceWalk> ResourceWalk for Option<T> {
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>) {
+        if let Some(value) = self {
+            value.walk_resources(records)
+        }
+    }
+}
+
+impl<T: ResourceWalk> ResourceWalk for Vec<T> {
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>) {
+        for value in self {
+            value.walk_resources(records)
+        }
+    }
+}
+
+impl<T: ResourceWalk + ?Sized> ResourceWalk for Box<T> {
+    fn walk_resources(&self, records: &mut Vec<ResourceRecord>) {
+        self.as_ref().walk_resources(records)
+    }
+}
+
+/// Used by `ResourceWalk` derive to walk fields that implement [ResourceWalk] and skip the rest
+pub(crate) mod resource_walk {
+    use super::{ResourceRecord, ResourceWalk};
+
+    pub struct Field<'a, T>(pub &'a T);
+
+    pub trait Walk {
+        fn walk_field(&self, records: &mut Vec<ResourceRecord>);
+    }
+
+    impl<T: ResourceWalk> Walk for Field<'_, T> {
+        fn walk_field(&self, records: &mut Vec<ResourceRecord>) {
+            self.0.walk_resources(records)
+        }
+    }
+
+    // Only picked when `Walk` is not implemented, since it needs one more autoref
+    pub trait Skip {
+        fn walk_field(&self, _records: &mut Vec<ResourceRecord>) {}
+    }
+
+    impl<T> Skip for &Field<'_, T> {}
 }
 
 /// Struct for recording resources (images, sound files)
@@ -614,18 +3516,36 @@ pub struct ResourceRecord {
 /// Resource type with additional info if needed
 #[derive(Debug, Clone)]
 pub enum ResourceType {
-    /// x and y dimensions of an image
-    Image(SpriteSizeType, SpriteSizeType),
+    /// Minimal x and y dimensions of an image, and size of a frame if the image is a sheet of
+    /// frames. Dimensions of a sheet must be multiples of the frame size.
+    Image(
+        SpriteSizeType,
+        SpriteSizeType,
+        Option<(SpriteSizeType, SpriteSizeType)>,
+    ),
     /// Only .ogg, .wav and .voc are accepted
     Sound,
+    /// Lua script, like `init_file` of a simulation
+    Script,
 }
 
 #[derive(Debug, Clone, Error)]
 pub enum ResourceError {
     #[error("File not found: \"{0}\"")]
     FileNotFound(String),
-    #[error("Image size incorrect: Expected at least {0}x{1}, got {2}x{3}")]
+    #[error("Image \"{0}\" exists but is not a readable PNG")]
+    ImageUnreadable(String),
+    #[error("Image \"{0}\" size incorrect: Expected at least {1}x{2}, got {3}x{4}")]
     ImageSizeIncorrect(
+        String,
+        SpriteSizeType,
+        SpriteSizeType,
+        SpriteSizeType,
+        SpriteSizeType,
+    ),
+    #[error("Image \"{0}\" is {3}x{4}, which is not a grid of {1}x{2} frames")]
+    FrameGridMismatch(
+        String,
         SpriteSizeType,
         SpriteSizeType,
         SpriteSizeType,
@@ -643,7 +3563,16 @@ pub trait ModSetting: Prototype {
     fn setting_type(&self) -> ModSettingType;
 }
 
-#[derive(Debug, Clone, Prototype, ModSetting, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug,
+    Clone,
+    Prototype,
+    ModSetting,
+    DataTableAccessable,
+    PrototypeFromLua,
+    ResourceWalk,
+    HeapSize,
+)]
 #[data_table(bool_setting)]
 #[ptype(BoolSetting)]
 pub struct BoolModSetting {
@@ -659,7 +3588,16 @@ pub struct BoolModSetting {
     pub forced_value: Option<bool>,
 }
 
-#[derive(Debug, Clone, Prototype, ModSetting, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug,
+    Clone,
+    Prototype,
+    ModSetting,
+    DataTableAccessable,
+    PrototypeFromLua,
+    ResourceWalk,
+    HeapSize,
+)]
 #[data_table(int_setting)]
 #[ptype(IntSetting)]
 pub struct IntModSetting {
@@ -676,7 +3614,16 @@ pub struct IntModSetting {
     pub allowed_values: Option<Vec<i64>>,
 }
 
-#[derive(Debug, Clone, Prototype, ModSetting, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug,
+    Clone,
+    Prototype,
+    ModSetting,
+    DataTableAccessable,
+    PrototypeFromLua,
+    ResourceWalk,
+    HeapSize,
+)]
 #[data_table(double_setting)]
 #[ptype(DoubleSetting)]
 pub struct DoubleModSetting {
@@ -693,7 +3640,16 @@ pub struct DoubleModSetting {
     pub allowed_values: Option<Vec<f64>>,
 }
 
-#[derive(Debug, Clone, Prototype, ModSetting, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug,
+    Clone,
+    Prototype,
+    ModSetting,
+    DataTableAccessable,
+    PrototypeFromLua,
+    ResourceWalk,
+    HeapSize,
+)]
 #[data_table(string_setting)]
 #[ptype(StringSetting)]
 pub struct StringModSetting {
@@ -713,18 +3669,35 @@ pub struct StringModSetting {
 }
 
 /// <https://wiki.factorio.com/Prototype/AmbientSound>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(ambient_sound)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct AmbientSoundPrototype {
     pub name: String,
     pub sound: Sound,
     pub track_type: TrackType,
     #[default(1.0)]
-    pub weight: f64,
+    pub weight: f64, // Default: 1 // Must be > 0
+}
+
+impl AmbientSoundPrototype {
+    fn post_extr_fn(&self, _lua: &Lua, data_table: &mut DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            !(self.weight > 0.0),
+            "AmbientSoundPrototype",
+            "`weight` must be > 0",
+        )?;
+        self.sound.register_resources(data_table);
+        Ok(())
+    }
 }
 
 /// <https://wiki.factorio.com/Prototype/Animation>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(animation)]
 pub struct AnimationPrototype {
     pub name: String,
@@ -733,7 +3706,9 @@ pub struct AnimationPrototype {
 }
 
 /// <https://wiki.factorio.com/Prototype/EditorController>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(editor_controller)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct EditorController {
@@ -766,25 +3741,27 @@ pub struct EditorController {
 impl EditorController {
     fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.name != "default" {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "EditorController",
-                message: Some("EditorController name should only be \"default\"".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "EditorController",
+                Some("EditorController name should only be \"default\"".into()),
+            ));
         }
         if self.movement_speed < 0.34375 {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "EditorController",
-                message: Some("movement speed must be >= 0.34375".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "EditorController",
+                Some("movement speed must be >= 0.34375".into()),
+            ));
         }
         Ok(())
     }
 }
 
 /// <https://wiki.factorio.com/Prototype/Font>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(font)]
 pub struct Font {
     pub name: String,
@@ -800,7 +3777,9 @@ pub struct Font {
 }
 
 /// <https://wiki.factorio.com/Prototype/GodController>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(god_controller)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct GodController {
@@ -817,25 +3796,25 @@ pub struct GodController {
 impl GodController {
     fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.name != "default" {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "GodController",
-                message: Some("GodController name should only be \"default\"".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "GodController",
+                Some("GodController name should only be \"default\"".into()),
+            ));
         }
         if self.movement_speed < 0.34375 {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "GodController",
-                message: Some("movement speed must be >= 0.34375".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "GodController",
+                Some("movement speed must be >= 0.34375".into()),
+            ));
         }
         Ok(())
     }
 }
 
 /// <https://wiki.factorio.com/Prototype/MapGenPresets>
-#[derive(Debug, Clone, Prototype, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(map_gen_presets)]
 pub struct MapGenPresets {
     pub name: String,
@@ -849,7 +3828,7 @@ impl<'lua> PrototypeFromLua<'lua> for MapGenPresets {
         data_table: &mut DataTable,
     ) -> LuaResult<Self> {
         if let LuaValue::Table(table) = value {
-            let name = table.get::<_, String>("name")?;
+            let name = table.get_as::<String>("name")?;
             let mut result = HashMap::new();
             for (k, v) in table
                 .pairs::<String, LuaValue>()
@@ -864,17 +3843,19 @@ impl<'lua> PrototypeFromLua<'lua> for MapGenPresets {
                 presets: result,
             })
         } else {
-            Err(mlua::Error::FromLuaConversionError {
-                from: value.type_name(),
-                to: "MapGenPresets",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "MapGenPresets",
+                Some("expected table".into()),
+            ))
         }
     }
 }
 
 /// <https://wiki.factorio.com/Prototype/MapSettings>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(map_settings)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct MapSettings {
@@ -892,18 +3873,20 @@ pub struct MapSettings {
 impl MapSettings {
     fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.name != "map-settings" {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "MapSettings",
-                message: Some("`name` of MapSettings prototype must be \"map-settings\"".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "MapSettings",
+                Some("`name` of MapSettings prototype must be \"map-settings\"".into()),
+            ));
         }
         Ok(())
     }
 }
 
 /// <https://wiki.factorio.com/Prototype/MouseCursor>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(mouse_cursor)]
 pub struct MouseCursor {
     pub name: String,
@@ -912,16 +3895,28 @@ pub struct MouseCursor {
 }
 
 /// <https://wiki.factorio.com/Prototype/Sound>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(sound)]
+#[post_extr_fn(Self::register_resources)]
 pub struct SoundPrototype {
     pub name: String,
     #[use_self_forced]
     pub sound: Sound,
 }
 
+impl SoundPrototype {
+    fn register_resources(&self, _lua: &Lua, data_table: &mut DataTable) -> LuaResult<()> {
+        self.sound.register_resources(data_table);
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/SpectatorController>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[post_extr_fn(Self::post_extr_fn)]
 #[data_table(spectator_controller)]
 pub struct SpectatorController {
@@ -932,27 +3927,27 @@ pub struct SpectatorController {
 impl SpectatorController {
     fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.name != "default" {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "SpectatorController",
-                message: Some(
-                    "`name` must be \"default\" since only one instance can be defined".into(),
-                ),
-            });
+            return Err(conversion_error(
+                "table",
+                "SpectatorController",
+                Some("`name` must be \"default\" since only one instance can be defined".into()),
+            ));
         }
         if self.movement_speed < 0.34375 {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "SpectatorController",
-                message: Some("`movement_speed` must be >= 0.34375".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "SpectatorController",
+                Some("`movement_speed` must be >= 0.34375".into()),
+            ));
         }
         Ok(())
     }
 }
 
 /// <https://wiki.factorio.com/Prototype/Sprite>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(sprite)]
 pub struct SpritePrototype {
     pub name: String,
@@ -961,7 +3956,9 @@ pub struct SpritePrototype {
 }
 
 /// <https://wiki.factorio.com/Prototype/TileEffect>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(tile_effect)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct TileEffect {
@@ -1015,7 +4012,9 @@ impl TileEffect {
 }
 
 /// <https://wiki.factorio.com/Prototype/TipsAndTricksItemCategory>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(tips_and_tricks_item_category)]
 pub struct TipsAndTricksItemCategory {
     pub name: String,
@@ -1024,25 +4023,46 @@ pub struct TipsAndTricksItemCategory {
 
 // 56 instances max // weird number
 /// <https://wiki.factorio.com/Prototype/TriggerTargetType>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(trigger_target_type)]
 pub struct TriggerTargetType {
     pub name: String,
 }
 
 /// <https://wiki.factorio.com/Prototype/WindSound>
-#[derive(Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua)]
+#[derive(
+    Debug, Clone, Prototype, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize,
+)]
 #[data_table(wind_sound)]
+#[post_extr_fn(Self::register_resources)]
 pub struct WindSound {
     pub name: String,
     pub sound: Sound,
 }
 
+impl WindSound {
+    fn register_resources(&self, _lua: &Lua, data_table: &mut DataTable) -> LuaResult<()> {
+        self.sound.register_resources(data_table);
+        Ok(())
+    }
+}
+
+/// Checks that `order` string consists only of printable characters, as required by the game.
+/// Strings with other characters sort unpredictably.
+pub fn validate_order_string(order: &str) -> Result<(), PrototypesErr> {
+    if order.chars().any(|c| c.is_control()) {
+        return Err(PrototypesErr::InvalidOrderString(order.into()));
+    }
+    Ok(())
+}
+
 pub(crate) trait Base {}
 
 // PrototypeBase starts here
 /// <https://wiki.factorio.com/PrototypeBase>
-#[derive(Debug, Clone, PrototypeFromLua, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, HeapSize)]
 pub struct PrototypeBaseSpec {
     pub localised_description: Option<LocalisedString>,
     pub localised_name: Option<LocalisedString>,
@@ -1050,7 +4070,7 @@ pub struct PrototypeBaseSpec {
 }
 
 /// Base for Achievement and all inherited types <https://wiki.factorio.com/Prototype/Achievement>
-#[derive(Debug, Clone, PrototypeFromLua)]
+#[derive(Debug, Clone, PrototypeFromLua, ResourceWalk, HeapSize)]
 pub struct AchievementBase {
     #[use_self_forced]
     pub icon: IconSpecification,
@@ -1063,7 +4083,7 @@ pub struct AchievementBase {
 }
 
 /// <https://wiki.factorio.com/Prototype/Achievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(achievement)]
 pub struct Achievement {
     pub name: String,
@@ -1074,7 +4094,7 @@ pub struct Achievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/BuildEntityAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(build_entity_achievement)]
 pub struct BuildEntityAchievement {
     pub name: String,
@@ -1092,7 +4112,7 @@ pub struct BuildEntityAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/CombatRobotCountAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(combat_robot_count)]
 pub struct CombatRobotCountAchievement {
     pub name: String,
@@ -1105,7 +4125,7 @@ pub struct CombatRobotCountAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/ConstructWithRobotsAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(construct_with_robots_achevement)]
 pub struct ConstructWithRobotsAchievement {
     pub name: String,
@@ -1121,7 +4141,7 @@ pub struct ConstructWithRobotsAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/DeconstructWithRobotsAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(deconstruct_with_robots_achievement)]
 pub struct DeconstructWithRobotsAchievement {
     pub name: String,
@@ -1133,7 +4153,7 @@ pub struct DeconstructWithRobotsAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/DeliverByRobotsAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(deliver_by_robots_achievement)]
 pub struct DeliverByRobotsAchievement {
     pub name: String,
@@ -1145,7 +4165,7 @@ pub struct DeliverByRobotsAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/DontBuildEntityAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(dont_build_entity_achievement)]
 pub struct DontBuildEntityAchievement {
     pub name: String,
@@ -1159,7 +4179,7 @@ pub struct DontBuildEntityAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/DontCraftManuallyAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(dont_craft_manually_achievement)]
 pub struct DontCraftManuallyAchievement {
     pub name: String,
@@ -1171,7 +4191,7 @@ pub struct DontCraftManuallyAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/DontUseEntityInEnergyProductionAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(dont_use_entity_in_energy_production_achievement)]
 pub struct DontUseEntityInEnergyProductionAchievement {
     pub name: String,
@@ -1188,7 +4208,7 @@ pub struct DontUseEntityInEnergyProductionAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/FinishTheGameAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(finish_the_game_achievement)]
 pub struct FinishTheGameAchievement {
     pub name: String,
@@ -1201,7 +4221,7 @@ pub struct FinishTheGameAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/GroupAttackAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(group_attack_achievement)]
 pub struct GroupAttackAchievement {
     pub name: String,
@@ -1214,7 +4234,7 @@ pub struct GroupAttackAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/KillAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(kill_achievement)]
 pub struct KillAchievement {
     pub name: String,
@@ -1236,7 +4256,7 @@ pub struct KillAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/PlayerDamagedAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(player_damaged_achievement)]
 pub struct PlayerDamagedAchievement {
     pub name: String,
@@ -1250,7 +4270,7 @@ pub struct PlayerDamagedAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/ProduceAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(produce_achievement)]
 pub struct ProduceAchievement {
     pub name: String,
@@ -1265,7 +4285,7 @@ pub struct ProduceAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/ProducePerHourAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(produce_per_hour_achievement)]
 pub struct ProducePerHourAchievement {
     pub name: String,
@@ -1279,7 +4299,7 @@ pub struct ProducePerHourAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/ResearchAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(research_achievement)]
 pub struct ResearchAchievement {
     pub name: String,
@@ -1292,7 +4312,7 @@ pub struct ResearchAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/TrainPathAchievement>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(train_path_achievement)]
 pub struct TrainPathAchievement {
     pub name: String,
@@ -1304,7 +4324,7 @@ pub struct TrainPathAchievement {
 }
 
 /// <https://wiki.factorio.com/Prototype/AmmoCategory>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(ammo_category)]
 pub struct AmmoCategory {
     pub name: String,
@@ -1316,7 +4336,7 @@ pub struct AmmoCategory {
 
 // 255 instances max
 /// <https://wiki.factorio.com/Prototype/AutoplaceControl>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(autoplace_control)]
 pub struct AutoplaceControl {
     pub name: String,
@@ -1330,7 +4350,7 @@ pub struct AutoplaceControl {
 }
 
 /// <https://wiki.factorio.com/Prototype/CustomInput>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(custom_input)]
 pub struct CustomInput {
     pub name: String,
@@ -1356,7 +4376,7 @@ pub struct CustomInput {
 }
 
 /// <https://wiki.factorio.com/Prototype/DamageType>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(damage_type)]
 pub struct DamageType {
     pub name: String,
@@ -1367,8 +4387,9 @@ pub struct DamageType {
 }
 
 /// <https://wiki.factorio.com/Prototype/Decorative>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(optimized_decorative)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct Decorative {
     pub name: String,
     #[use_self_forced]
@@ -1379,10 +4400,10 @@ pub struct Decorative {
     pub render_layer: RenderLayer, // Default: "decorative"
     #[default(false)]
     pub grows_through_rail_path: bool, // Default: false
-    #[default(0_i16)]
-    pub tile_layer: i16, // Default: 0 // fixme: Mandatory if render_layer is "decals" // I don't understand how this works
+    #[mandatory_if(render_layer.is_decal())]
+    pub tile_layer: Option<i16>, // Mandatory if render_layer is "decals"
     #[default(0_u16)]
-    pub decal_overdraw_priority: u16, // Default: 0 // fixme: Only loaded if render_layer is "decals"
+    pub decal_overdraw_priority: u16, // Default: 0 // Only loaded if render_layer is "decals"
     pub walking_sound: Option<Sound>,
     pub trigger_effect: Option<TriggerEffect>,
     pub autoplace: Option<AutoplaceSpecification>,
@@ -1390,15 +4411,26 @@ pub struct Decorative {
     pub collision_mask: CollisionMask, // Default: "doodad-layer"
 }
 
+impl Decorative {
+    fn post_extr_fn(&mut self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        if !self.render_layer.is_decal() {
+            self.decal_overdraw_priority = 0
+        }
+        self.pictures
+            .require_variation_count(1, usize::MAX, "Decorative", "pictures")
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Entity>
-#[derive(Debug, Clone, PrototypeFromLua, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, ResourceWalk, HeapSize)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct EntityBase {
-    #[use_self_forced] // FIXME
+    #[use_self_forced]
+    #[loaded_if(prot_table.contains_key("icon")? || prot_table.contains_key("icons")?)]
     icon: Option<IconSpecification>, // Mandatory if one of flags active: "placeable-neutral", "placeable-player", "placeable-enemy"
     #[default((0_f32, 0_f32), (0_f32, 0_f32))]
     collision_box: BoundingBox, // Default: ((0, 0), (0, 0))
-    #[default(Self::default_collision_mask(prot_table.get::<_, String>("type").ok()?))]
+    #[default(Self::default_collision_mask(prot_table.get_as::<String>("type").ok()?))]
     collision_mask: CollisionMask, // Default: ("item-layer", "object-layer", "player-layer", "water-tile") and depends on type
     map_generator_bounding_box: BoundingBox,
     #[default((0_f32, 0_f32), (0_f32, 0_f32))]
@@ -1417,7 +4449,7 @@ pub struct EntityBase {
     allow_copy_paste: bool, // Default: true
     #[default(true)]
     selectable_in_game: bool, // Default: true
-    #[default(Self::default_selection_priority(prot_table.get::<_, String>("type").ok()?))]
+    #[default(Self::default_selection_priority(prot_table.get_as::<String>("type").ok()?))]
     selection_priority: u8, // Default: 50
     #[default(RemoveDecoratives::Automatic)]
     remove_decoratives: RemoveDecoratives, // Default: "automatic"
@@ -1557,8 +4589,13 @@ impl EntityBase {
     }
 
     fn map_color_mandatory(t: String) -> bool {
+        t.parse().map_or(false, Self::map_color_required)
+    }
+
+    /// Whether entities of given type must have `map_color` set
+    fn map_color_required(ty: PrototypeType) -> bool {
         matches!(
-            t.parse::<PrototypeType>().unwrap(),
+            ty,
             PrototypeType::ArtilleryFlare | PrototypeType::ArtilleryProjectile
         )
     }
@@ -1575,18 +4612,18 @@ impl EntityBase {
             false
         };
         if self.icon.is_none() && entity_flags_cond {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "Entity",
-                message: Some("`icon` is required".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "Entity",
+                Some("`icon` is required".into()),
+            ));
         }
         Ok(())
     }
 }
 
 /// <https://wiki.factorio.com/Prototype/Arrow>
-#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(arrow)]
 pub struct Arrow {
     pub name: String,
@@ -1601,7 +4638,7 @@ pub struct Arrow {
 }
 
 /// <https://wiki.factorio.com/Prototype/ArtilleryFlare>
-#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(artillery_flare)]
 pub struct ArtilleryFlare {
     // map_color is mandatory
@@ -1634,13 +4671,13 @@ pub struct ArtilleryFlare {
     pub initial_frame_speed: f32, // Default: 1
     #[default(1_u32)]
     pub shots_per_flare: u32, // Default: 1
-    #[default(180_u32)]
-    pub early_death_ticks: u32, // Default: 3 * 60 (180)
+    #[default(Ticks(180))]
+    pub early_death_ticks: Ticks, // Default: 3 * 60 (180)
     pub shot_category: String, // Name of Prototype/AmmoCategory
 }
 
 /// <https://wiki.factorio.com/Prototype/ArtilleryProjectile>
-#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(artillery_projectile)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct ArtilleryProjectile {
@@ -1666,25 +4703,25 @@ pub struct ArtilleryProjectile {
 impl ArtilleryProjectile {
     fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.entity.collision_box != BoundingBox::from(((0.0, 0.0), (0.0, 0.0))) {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "ArtilleryProjectile",
-                message: Some("Bounding box must be zero".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "ArtilleryProjectile",
+                Some("Bounding box must be zero".into()),
+            ));
         }
         if self.entity.map_color.is_none() {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "ArtilleryFlare",
-                message: Some("`map_color` is mandatory".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "ArtilleryFlare",
+                Some("`map_color` is mandatory".into()),
+            ));
         }
         Ok(())
     }
 }
 
 /// <https://wiki.factorio.com/Prototype/Beam>
-#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(beam)]
 #[post_extr_fn(Self::post_extr_fn)]
 pub struct Beam {
@@ -1723,25 +4760,20 @@ pub struct Beam {
 impl Beam {
     fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
         if self.damage_interval == 0 {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "Beam",
-                message: Some("`damage_interval` cannot be 0".into()),
-            });
-        }
-        if self.body.is_empty() {
-            return Err(mlua::Error::FromLuaConversionError {
-                from: "table",
-                to: "Beam",
-                message: Some("`body` must have at least 1 variation".into()),
-            });
+            return Err(conversion_error(
+                "table",
+                "Beam",
+                Some("`damage_interval` cannot be 0".into()),
+            ));
         }
+        self.body
+            .require_variation_count(1, usize::MAX, "Beam", "body")?;
         Ok(())
     }
 }
 
 /// <https://wiki.factorio.com/Prototype/CharacterCorpse>
-#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(character_corpse)]
 pub struct CharacterCorpse {
     pub name: String,
@@ -1758,7 +4790,7 @@ pub struct CharacterCorpse {
 }
 
 /// <https://wiki.factorio.com/Prototype/Cliff>
-#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(cliff)]
 pub struct Cliff {
     pub name: String,
@@ -1771,20 +4803,21 @@ pub struct Cliff {
     pub grid_offset: Factorio2DVector,
     #[default(4_f32)]
     pub cliff_height: f32, // Default: 4
-    pub cliff_explosive: String, // Name of capsule that has a robot_action to explode cliffs
+    #[default("")]
+    pub cliff_explosive: String, // Default: "" // Name of capsule that has a robot_action to explode cliffs
 }
 
 /// <https://wiki.factorio.com/Prototype/Corpse>
-#[derive(Debug, Clone, PrototypeFromLua, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, ResourceWalk, HeapSize)]
 pub struct CorpseBase {
     #[default(1_f32)]
     pub dying_speed: f32, // Default: 1
     #[default(1_f32)]
     pub splash_speed: f32, // Default: 1
-    #[default(900_i32)]
-    pub time_before_shading_off: i32, // Default: 60 * 15
-    #[default(7200_i32)]
-    pub time_before_removed: i32, // Default: 60 * 120
+    #[default(Ticks(900))]
+    pub time_before_shading_off: Ticks, // Default: 60 * 15
+    #[default(Ticks(7200))]
+    pub time_before_removed: Ticks, // Default: 60 * 120
     #[default(true)]
     pub remove_on_entity_placemen: bool, // Default: true
     #[default(true)]
@@ -1822,7 +4855,7 @@ pub struct CorpseBase {
 }
 
 /// <https://wiki.factorio.com/Prototype/Corpse>
-#[derive(Debug, Clone, Prototype, Corpse!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, Corpse!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(corpse)]
 pub struct CorpsePrototype {
     pub name: String,
@@ -1835,7 +4868,7 @@ pub struct CorpsePrototype {
 }
 
 /// <https://wiki.factorio.com/Prototype/RailRemnants>
-#[derive(Debug, Clone, Prototype, Corpse!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, Corpse!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(rail_remnants)]
 pub struct RailRemnants {
     pub name: String,
@@ -1850,7 +4883,7 @@ pub struct RailRemnants {
 }
 
 /// <https://wiki.factorio.com/Prototype/DeconstructibleTileProxy>
-#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(deconstructible_tile_proxy)]
 pub struct DeconstructibleTileProxy {
     pub name: String,
@@ -1861,7 +4894,7 @@ pub struct DeconstructibleTileProxy {
 }
 
 /// <https://wiki.factorio.com/Prototype/EntityGhost>
-#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(entity_ghost)]
 pub struct EntityGhost {
     pub name: String,
@@ -1874,11 +4907,11 @@ pub struct EntityGhost {
 }
 
 /// <https://wiki.factorio.com/Prototype/EntityWithHealth>
-#[derive(Debug, Clone, PrototypeFromLua, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, ResourceWalk, HeapSize)]
 pub struct EntityWithHealthBase {
     #[default(10_f32)]
     pub max_health: f32, // Default: 10
-    #[default(Self::healing_per_tick_default(prot_table.get::<_, String>("type").unwrap()))]
+    #[default(Self::healing_per_tick_default(prot_table.get_as::<String>("type").unwrap()))]
     pub healing_per_tick: f32, // Default: 0.001666 for Prototype/Tree, 0 for the rest
     #[default(1_f32)]
     pub repair_speed_multiplier: f32, // Default: 1
@@ -1915,7 +4948,7 @@ impl EntityWithHealthBase {
 }
 
 /// <https://wiki.factorio.com/Prototype/EntityWithOwner>
-#[derive(Debug, Clone, PrototypeFromLua, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, HeapSize)]
 pub struct EntityWithOwnerBase {
     #[default(true)]
     pub is_military_target: bool, // Default: false
@@ -1924,7 +4957,7 @@ pub struct EntityWithOwnerBase {
 }
 
 /// <https://wiki.factorio.com/Prototype/Accumulator>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(accumulator)]
 pub struct Accumulator {
     pub name: String,
@@ -1956,7 +4989,7 @@ pub struct Accumulator {
 }
 
 /// <https://wiki.factorio.com/Prototype/ArtilleryTurret>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(artillery_turret)]
 pub struct ArtilleryTurret {
     pub name: String,
@@ -1983,11 +5016,11 @@ pub struct ArtilleryTurret {
     #[default(RenderLayer::LowerObject)]
     pub base_picture_render_layer: RenderLayer, // Default: "lower-object"
     pub base_shift: Option<Factorio2DVector>,
-    pub base_picture: Option<Animation4Way>,
-    pub cannon_base_pictures: Option<RotatedSprite>,
-    pub cannon_barrel_pictures: Option<RotatedSprite>,
-    pub rotating_sound: Option<InterruptibleSound>,
-    pub rotating_stopped_sound: Option<Sound>,
+    pub base_picture: Option<Box<Animation4Way>>,
+    pub cannon_base_pictures: Option<Box<RotatedSprite>>,
+    pub cannon_barrel_pictures: Option<Box<RotatedSprite>>,
+    pub rotating_sound: Option<Box<InterruptibleSound>>,
+    pub rotating_stopped_sound: Option<Box<Sound>>,
     #[default(0_u16)]
     pub turn_after_shooting_cooldown: u16, // Default: 0
     #[default(0_u16)]
@@ -2000,8 +5033,9 @@ pub struct ArtilleryTurret {
 }
 
 /// <https://wiki.factorio.com/Prototype/Beacon>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(beacon)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct Beacon {
     pub name: String,
     #[use_self_forced]
@@ -2017,17 +5051,67 @@ pub struct Beacon {
     pub supply_area_distance: f64,
     pub distribution_effectivity: f64,
     pub module_specification: ModuleSpecification,
-    pub graphics_set: Option<BeaconGraphicsSet>,
+    pub graphics_set: Option<Box<BeaconGraphicsSet>>,
+    #[loaded_if(graphics_set.is_none())]
     pub animation: Option<Animation>, // Loaded only if `graphics_set` is not present
+    #[loaded_if(graphics_set.is_none())]
     pub base_picture: Option<Sprite>, // Loaded only if `graphics_set` is not present
     pub radius_visualization_picture: Option<Sprite>,
     #[default(EffectTypeLimitation::NONE)]
     pub allowed_effects: EffectTypeLimitation, // Default: No effects are allowed
 }
 
+impl Beacon {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            self.graphics_set.is_none() && self.animation.is_none() && self.base_picture.is_none(),
+            "Beacon",
+            "either `graphics_set` or `animation` and `base_picture` must be present",
+        )
+    }
+}
+
+#[test]
+fn beacon_graphics_set_load() {
+    let lua = Lua::new();
+    let mut data_table = DataTable::default();
+    let beacon = lua
+        .load(
+            r#"{
+                type = "beacon", name = "beacon",
+                map_generator_bounding_box = {{-1.5, -1.5}, {1.5, 1.5}},
+                repair_sound = {filename = "__core__/sound/silence-1sec.ogg"},
+                integration_patch = {},
+                energy_usage = "480kW",
+                energy_source = {type = "void"},
+                supply_area_distance = 3,
+                distribution_effectivity = 0.5,
+                module_specification = {
+                    module_slots = 2,
+                    module_info_max_icons_per_row = 2,
+                    module_info_max_icon_rows = 1,
+                    module_info_icon_shift = {0, 0.7},
+                    module_info_icon_scale = 0.5,
+                    module_info_separation_multiplier = 1.1,
+                    module_info_multi_row_initial_height_modifier = -0.1,
+                },
+                graphics_set = {module_icons_suppressed = true, top_layer = "higher-object-above"},
+            }"#,
+        )
+        .eval()
+        .unwrap();
+    let beacon = Beacon::prototype_from_lua(beacon, &lua, &mut data_table).unwrap();
+    let graphics_set = beacon.graphics_set.as_deref().unwrap();
+    assert!(graphics_set.module_icons_suppressed);
+    assert_eq!(graphics_set.top_layer, RenderLayer::HigherObjectAbove);
+    assert_eq!(graphics_set.base_layer, RenderLayer::Object);
+    assert!(beacon.animation.is_none() && beacon.base_picture.is_none());
+}
+
 /// <https://wiki.factorio.com/Prototype/Boiler>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(boiler)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct Boiler {
     pub name: String,
     #[use_self_forced]
@@ -2056,9 +5140,32 @@ pub struct Boiler {
     pub patch: Option<Sprite4Way>,
 }
 
+impl Boiler {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        if self.mode == BoilerMode::HeatWaterInside {
+            if let Some(min_temp) = self.output_fluid_box.minimum_temperature {
+                prot_from_lua_err(
+                    self.target_temperature < min_temp,
+                    "Boiler",
+                    "`target_temperature` must be >= `minimum_temperature` of `output_fluid_box`",
+                )?;
+            }
+            if let Some(max_temp) = self.output_fluid_box.maximum_temperature {
+                prot_from_lua_err(
+                    self.target_temperature > max_temp,
+                    "Boiler",
+                    "`target_temperature` must be <= `maximum_temperature` of `output_fluid_box`",
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/BurnerGenerator>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(burner_generator)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct BurnerGenerator {
     pub name: String,
     #[use_self_forced]
@@ -2069,8 +5176,8 @@ pub struct BurnerGenerator {
     pub entity_with_health: EntityWithHealthBase,
     #[use_self_forced]
     pub entity_with_owner: EntityWithOwnerBase,
-    pub energy_source: EnergySource, // Emissions are ignored // Must be electric // TODO
-    pub burner: EnergySource,        // Must be a burner energy source // TODO
+    pub energy_source: EnergySource, // Emissions are ignored // Must be electric
+    pub burner: EnergySource,        // Must be a burner energy source
     pub animation: Animation4Way,
     pub max_power_output: Energy,
     pub idle_animation: Option<Animation4Way>,
@@ -2082,8 +5189,23 @@ pub struct BurnerGenerator {
     pub performance_to_sound_speedup: f64, // Default: 0.5
 }
 
+impl BurnerGenerator {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        self.energy_source.require_kind(
+            &[EnergySourceDiscriminants::Electric],
+            "BurnerGenerator",
+            "energy_source",
+        )?;
+        self.burner.require_kind(
+            &[EnergySourceDiscriminants::Burner],
+            "BurnerGenerator",
+            "burner",
+        )
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Character>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(character)]
 pub struct Character {
     pub name: String,
@@ -2137,8 +5259,28 @@ pub struct Character {
     pub tool_attack_result: Option<Trigger>,
 }
 
+impl Character {
+    /// Animation used when wearing given armor: the first one that lists the armor in `armors`.
+    /// Without armor, or if no animation lists it, the first animation without `armors` is used.
+    pub fn animation_for_armor(&self, armor: Option<&str>) -> Option<&CharacterArmorAnimation> {
+        armor
+            .and_then(|armor| {
+                self.animations.iter().find(|a| {
+                    a.armors
+                        .as_ref()
+                        .map_or(false, |armors| armors.iter().any(|n| n == armor))
+                })
+            })
+            .or_else(|| {
+                self.animations
+                    .iter()
+                    .find(|a| a.armors.as_ref().map_or(true, Vec::is_empty))
+            })
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Combinator>
-#[derive(Debug, Clone, PrototypeFromLua, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, ResourceWalk, HeapSize)]
 pub struct CombinatorBase {
     pub energy_source: EnergySource, // Must be an electric or void energy source
     pub active_energy_usage: Energy,
@@ -2163,7 +5305,7 @@ pub struct CombinatorBase {
 }
 
 /// <https://wiki.factorio.com/Prototype/ArithmeticCombinator>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Combinator!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Combinator!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(arithmetic_combinator)]
 pub struct ArithmeticCombinator {
     pub name: String,
@@ -2191,7 +5333,7 @@ pub struct ArithmeticCombinator {
 }
 
 /// <https://wiki.factorio.com/Prototype/DeciderCombinator>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Combinator!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Combinator!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(decider_combinator)]
 pub struct DeciderCombinator {
     pub name: String,
@@ -2214,7 +5356,7 @@ pub struct DeciderCombinator {
 }
 
 /// <https://wiki.factorio.com/Prototype/ConstantCombinator>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(constant_combinator)]
 pub struct ConstantCombinator {
     pub name: String,
@@ -2241,7 +5383,7 @@ pub struct ConstantCombinator {
 }
 
 /// <https://wiki.factorio.com/Prototype/Container>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(container)]
 pub struct Container {
     pub name: String,
@@ -2270,8 +5412,9 @@ pub struct Container {
 }
 
 /// <https://wiki.factorio.com/Prototype/LogisticContainer>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(logistic_container)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct LogisticContainer {
     pub name: String,
     #[use_self_forced]
@@ -2297,7 +5440,7 @@ pub struct LogisticContainer {
     #[default(true)]
     pub draw_circuit_wires: bool, // Default: true
     pub circuit_connector_sprites: Option<CircuitConnectorSprites>,
-    pub max_logistic_slots: Option<u16>, // requester-type must have > 0 and <= 1000 // Storage type must have <= 1 // TODO
+    pub max_logistic_slots: Option<u16>, // requester-type must have > 0 and <= 1000 // Storage type must have <= 1
     #[default(true)]
     pub render_not_in_network_icon: bool, // Default: true
     #[default(0_u8)]
@@ -2307,9 +5450,41 @@ pub struct LogisticContainer {
     pub animation_sound: Option<Sound>,
 }
 
+impl LogisticContainer {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        validate_max_logistic_slots(
+            "LogisticContainer",
+            self.logistic_mode,
+            self.max_logistic_slots,
+        )
+    }
+}
+
+/// Common `max_logistic_slots` check of [LogisticContainer] and [InfinityContainer]
+fn validate_max_logistic_slots(
+    type_name: &'static str,
+    logistic_mode: LogisticMode,
+    max_logistic_slots: Option<u16>,
+) -> LuaResult<()> {
+    if let Some(slots) = max_logistic_slots {
+        prot_from_lua_err(
+            logistic_mode.requires_positive_slots() && !(1..=1000).contains(&slots),
+            type_name,
+            format!("`max_logistic_slots` must be > 0 and <= 1000 for {logistic_mode} containers, got {slots}"),
+        )?;
+        prot_from_lua_err(
+            logistic_mode == LogisticMode::Storage && slots > 1,
+            type_name,
+            format!("`max_logistic_slots` must be <= 1 for storage containers, got {slots}"),
+        )?;
+    }
+    Ok(())
+}
+
 /// <https://wiki.factorio.com/Prototype/InfinityContainer>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(infinity_container)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct InfinityContainer {
     pub name: String,
     #[use_self_forced]
@@ -2320,7 +5495,7 @@ pub struct InfinityContainer {
     pub entity_with_health: EntityWithHealthBase,
     #[use_self_forced]
     pub entity_with_owner: EntityWithOwnerBase,
-    pub inventory_size: u16, // Can't be 0 // TODO
+    pub inventory_size: u16, // Can't be 0
     pub picture: Option<Sprite>,
     pub logistic_mode: Option<LogisticMode>,
     pub erase_contents_when_mined: bool,
@@ -2336,7 +5511,7 @@ pub struct InfinityContainer {
     #[default(true)]
     pub draw_circuit_wires: bool, // Default: true
     pub circuit_connector_sprites: Option<CircuitConnectorSprites>,
-    pub max_logistic_slots: Option<u16>, // requester-type must have > 0 and <= 1000 // Storage type must have <= 1 // TODO
+    pub max_logistic_slots: Option<u16>, // requester-type must have > 0 and <= 1000 // Storage type must have <= 1
     #[default(false)]
     pub render_not_in_network_icon: bool, // Default: false
     #[default(0_u8)]
@@ -2348,8 +5523,26 @@ pub struct InfinityContainer {
     pub gui_mode: GuiMode, // Default: "none"
 }
 
+impl InfinityContainer {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            self.inventory_size == 0,
+            "InfinityContainer",
+            "`inventory_size` can't be 0",
+        )?;
+        if let Some(logistic_mode) = self.logistic_mode {
+            validate_max_logistic_slots(
+                "InfinityContainer",
+                logistic_mode,
+                self.max_logistic_slots,
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/CraftingMachine>
-#[derive(Debug, Clone, PrototypeFromLua, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, ResourceWalk, HeapSize)]
 pub struct CraftingMachineBase {
     // If module inventory size > 0 and no effects allowed, its' and error // TODO
     // https://discord.com/channels/139677590393716737/306402592265732098/898733801679757332
@@ -2371,11 +5564,8 @@ pub struct CraftingMachineBase {
     #[default(false)]
     pub always_draw_idle_animation: bool, // Default: false
     pub default_recipe_tint: Option<RecipeTint>,
-    pub shift_animation_waypoints: Option<ShiftAnimationWaypoints>, // Only loaded if `shift_animation_waypoint_stop_duration` or `shift_animation_transition_duration` is not 0
-    #[default(0_u16)]
-    pub shift_animation_waypoint_stop_duration: u16, // Default: 0 // Only loaded if `shift_animation_waypoints` is present
-    #[default(0_u16)]
-    pub shift_animation_transition_duration: u16, // Default: 0 // Only loaded if `shift_animation_waypoints` is present
+    #[use_self_forced]
+    pub shift_animation: ShiftAnimation,
     pub status_colors: Option<StatusColors>,
     #[default(Factorio2DVector(0.0, -0.3))]
     pub entity_info_icon_shift: Factorio2DVector, // Default: {0, -0.3} for
@@ -2392,7 +5582,7 @@ pub struct CraftingMachineBase {
 }
 
 /// <https://wiki.factorio.com/Prototype/AssemblingMachine>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!,EntityWithOwner!, CraftingMachine!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!,EntityWithOwner!, CraftingMachine!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(assembling_machine)]
 pub struct AssemblingMachine {
     pub name: String,
@@ -2415,7 +5605,7 @@ pub struct AssemblingMachine {
 }
 
 /// <https://wiki.factorio.com/Prototype/RocketSilo>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!,EntityWithOwner!, CraftingMachine!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!,EntityWithOwner!, CraftingMachine!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(rocket_silo)]
 pub struct RocketSilo {
     pub name: String,
@@ -2465,19 +5655,19 @@ pub struct RocketSilo {
     pub door_opening_speed: f64,
     pub rocket_parts_required: u32,
     pub base_night_sprite: Option<Sprite>,
-    pub base_light: Option<LightDefinition>,
-    pub base_engine_light: Option<LightDefinition>,
-    pub alarm_trigger: Option<TriggerEffect>,
-    pub clamps_on_trigger: Option<TriggerEffect>,
-    pub clamps_off_trigger: Option<TriggerEffect>,
-    pub doors_trigger: Option<TriggerEffect>,
-    pub raise_rocket_trigger: Option<TriggerEffect>,
-    pub alarm_sound: Option<Sound>,
-    pub clamps_on_sound: Option<Sound>,
-    pub clamps_off_sound: Option<Sound>,
-    pub doors_sound: Option<Sound>,
-    pub raise_rocket_sound: Option<Sound>,
-    pub flying_sound: Option<Sound>,
+    pub base_light: Option<Box<LightDefinition>>,
+    pub base_engine_light: Option<Box<LightDefinition>>,
+    pub alarm_trigger: Option<Box<TriggerEffect>>,
+    pub clamps_on_trigger: Option<Box<TriggerEffect>>,
+    pub clamps_off_trigger: Option<Box<TriggerEffect>>,
+    pub doors_trigger: Option<Box<TriggerEffect>>,
+    pub raise_rocket_trigger: Option<Box<TriggerEffect>>,
+    pub alarm_sound: Option<Box<Sound>>,
+    pub clamps_on_sound: Option<Box<Sound>>,
+    pub clamps_off_sound: Option<Box<Sound>>,
+    pub doors_sound: Option<Box<Sound>>,
+    pub raise_rocket_sound: Option<Box<Sound>>,
+    pub flying_sound: Option<Box<Sound>>,
     #[default(0_u16)]
     pub rocket_result_inventory_size: u16, // Default: 0
     #[default(30_u8)]
@@ -2487,7 +5677,7 @@ pub struct RocketSilo {
 }
 
 /// <https://wiki.factorio.com/Prototype/Furnace>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!,EntityWithOwner!, CraftingMachine!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!,EntityWithOwner!, CraftingMachine!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(furnace)]
 pub struct Furnace {
     pub name: String,
@@ -2506,7 +5696,7 @@ pub struct Furnace {
 }
 
 /// <https://wiki.factorio.com/Prototype/ElectricEnergyInterface>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(electric_energy_interface)]
 pub struct ElectricEnergyInterface {
     pub name: String,
@@ -2535,7 +5725,7 @@ pub struct ElectricEnergyInterface {
 }
 
 /// <https://wiki.factorio.com/Prototype/ElectricEnergyInterface#picture>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub enum ElectricEnergyInterfaceVisuals {
     Picture(Sprite),
     Pictures(Sprite4Way),
@@ -2565,21 +5755,20 @@ impl<'lua> PrototypeFromLua<'lua> for ElectricEnergyInterfaceVisuals {
             {
                 Ok(Self::Animations(animation4way))
             } else {
-                Err(LuaError::FromLuaConversionError { from: value.type_name(), to: "ElectricEnergyInterfaceVisuals",
-                message: Some("One of these properties has to exist: `picture`, `pictures`, `animation`, `animations`".into()) })
+                Err(conversion_error(value.type_name(), "ElectricEnergyInterfaceVisuals", Some("One of these properties has to exist: `picture`, `pictures`, `animation`, `animations`".into())))
             }
         } else {
-            Err(LuaError::FromLuaConversionError {
-                from: value.type_name(),
-                to: "ElectricEnergyInterfaceVisuals",
-                message: Some("expected table".into()),
-            })
+            Err(conversion_error(
+                value.type_name(),
+                "ElectricEnergyInterfaceVisuals",
+                Some("expected table".into()),
+            ))
         }
     }
 }
 
 /// <https://wiki.factorio.com/Prototype/ElectricPole>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(electric_pole)]
 pub struct ElectricPole {
     pub name: String,
@@ -2608,7 +5797,7 @@ pub struct ElectricPole {
 }
 
 /// <https://wiki.factorio.com/Prototype/EnemySpawner>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(unit_spawner)]
 pub struct EnemySpawner {
     pub name: String,
@@ -2647,7 +5836,7 @@ pub struct EnemySpawner {
 }
 
 /// <https://wiki.factorio.com/Prototype/FlyingRobot>
-#[derive(Debug, Clone, PrototypeFromLua, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, HeapSize)]
 pub struct FlyingRobotBase {
     pub speed: f64,
     #[default(f64::MAX)]
@@ -2667,7 +5856,7 @@ pub struct FlyingRobotBase {
 }
 
 /// <https://wiki.factorio.com/Prototype/CombatRobot>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, FlyingRobot!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, FlyingRobot!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(combat_robot)]
 pub struct CombatRobot {
     pub name: String,
@@ -2698,7 +5887,7 @@ pub struct CombatRobot {
 }
 
 /// <https://wiki.factorio.com/Prototype/ConstructionRobot>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, FlyingRobot!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, FlyingRobot!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(construction_robot)]
 pub struct ConstructionRobot {
     // Must have collision box of zero // TODO
@@ -2734,7 +5923,7 @@ pub struct ConstructionRobot {
 }
 
 /// <https://wiki.factorio.com/Prototype/LogisticRobot>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, FlyingRobot!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, FlyingRobot!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(logistic_robot)]
 pub struct LogisticRobot {
     // Must have collision box of zero // TODO
@@ -2767,8 +5956,9 @@ pub struct LogisticRobot {
 }
 
 /// <https://wiki.factorio.com/Prototype/Gate>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(gate)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct Gate {
     pub name: String,
     #[use_self_forced]
@@ -2799,9 +5989,25 @@ pub struct Gate {
     pub opened_collision_mask: CollisionMask, // Default: ["object-layer", "item-layer", "floor-layer", "water-tile"]
 }
 
+impl Gate {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            !(self.opening_speed > 0.0),
+            "Gate",
+            "`opening_speed` must be positive",
+        )?;
+        prot_from_lua_err(
+            self.timeout_to_close == 0,
+            "Gate",
+            "`timeout_to_close` must be positive",
+        )
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Generator>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(generator)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct Generator {
     pub name: String,
     #[use_self_forced]
@@ -2833,8 +6039,40 @@ pub struct Generator {
     pub destroy_non_fuel_fluid: bool, // Default: true
 }
 
+impl Generator {
+    /// Power output per tick when consuming `fluid` at `temperature`, limited by
+    /// `max_power_output` if it's specified
+    pub fn power_at(&self, temperature: f64, fluid: &Fluid) -> Energy {
+        let energy_per_unit = if self.burns_fluid {
+            fluid.fuel_value.0
+        } else {
+            let temperature = temperature.min(self.maximum_temperature);
+            (temperature - fluid.default_temperature).max(0.0) * fluid.heat_capacity.0
+        };
+        let power = self.fluid_usage_per_tick * energy_per_unit * self.effectivity;
+        match self.max_power_output {
+            Some(max_power) => Energy(power.min(max_power.0)),
+            None => Energy(power),
+        }
+    }
+
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            self.effectivity <= 0.0,
+            "Generator",
+            "`effectivity` must be > 0",
+        )?;
+        prot_from_lua_err(
+            self.fluid_usage_per_tick <= 0.0,
+            "Generator",
+            "`fluid_usage_per_tick` must be > 0",
+        )?;
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/HeatInterface>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(heat_interface)]
 pub struct HeatInterface {
     pub name: String,
@@ -2853,7 +6091,7 @@ pub struct HeatInterface {
 }
 
 /// <https://wiki.factorio.com/Prototype/HeatPipe>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(heat_pipe)]
 pub struct HeatPipe {
     pub name: String,
@@ -2871,8 +6109,9 @@ pub struct HeatPipe {
 }
 
 /// <https://wiki.factorio.com/Prototype/Inserter>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(inserter)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct Inserter {
     pub name: String,
     #[use_self_forced]
@@ -2930,8 +6169,34 @@ pub struct Inserter {
     pub circuit_connector_sprites: Option<Vec<CircuitConnectorSprites>>,
 }
 
+impl Inserter {
+    /// Inserters with `filter_count` > 0 are filter inserters
+    pub fn is_filter(&self) -> bool {
+        self.filter_count > 0
+    }
+
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            self.insert_position == Factorio2DVector(0.0, 0.0),
+            "Inserter",
+            "`insert_position` must have non-zero length",
+        )?;
+        prot_from_lua_err(
+            self.pickup_position == Factorio2DVector(0.0, 0.0),
+            "Inserter",
+            "`pickup_position` must have non-zero length",
+        )?;
+        prot_from_lua_err(
+            self.filter_count > 5,
+            "Inserter",
+            format!("`filter_count` must be <= 5, got {}", self.filter_count),
+        )?;
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Lab>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(lab)]
 pub struct Lab {
     pub name: String,
@@ -2961,7 +6226,7 @@ pub struct Lab {
 }
 
 /// <https://wiki.factorio.com/Prototype/Lamp>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(lamp)]
 pub struct Lamp {
     pub name: String,
@@ -3003,7 +6268,7 @@ pub struct Lamp {
 }
 
 /// <https://wiki.factorio.com/Prototype/LandMine>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(land_mine)]
 pub struct LandMine {
     pub name: String,
@@ -3032,7 +6297,7 @@ pub struct LandMine {
 }
 
 /// <https://wiki.factorio.com/Prototype/LinkedContainer>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(linked_container)]
 pub struct LinkedContainer {
     pub name: String,
@@ -3053,7 +6318,7 @@ pub struct LinkedContainer {
 }
 
 /// <https://wiki.factorio.com/Prototype/Market>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(market)]
 pub struct Market {
     pub name: String,
@@ -3070,8 +6335,30 @@ pub struct Market {
     pub allow_access_to_all_forces: bool, // Default: true
 }
 
+#[test]
+fn market_load() {
+    let lua = Lua::new();
+    let mut data_table = DataTable::default();
+    let market = lua
+        .load(
+            r#"{
+                type = "market", name = "market",
+                map_generator_bounding_box = {{-1, -1}, {1, 1}},
+                repair_sound = {filename = "__core__/sound/silence-1sec.ogg"},
+                integration_patch = {},
+                picture = {filename = "__base__/graphics/entity/market/market.png", size = 32},
+            }"#,
+        )
+        .eval()
+        .unwrap();
+    let market = Market::prototype_from_lua(market, &lua, &mut data_table).unwrap();
+    assert!(market.allow_access_to_all_forces);
+    data_table.extend(market).unwrap();
+    assert!(data_table.market.contains_key("market"));
+}
+
 /// <https://wiki.factorio.com/Prototype/MiningDrill>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(mining_drill)]
 pub struct MiningDrill {
     pub name: String,
@@ -3114,7 +6401,7 @@ pub struct MiningDrill {
 }
 
 /// <https://wiki.factorio.com/Prototype/OffshorePump>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(offshore_pump)]
 pub struct OffshorePump {
     pub name: String,
@@ -3160,13 +6447,17 @@ pub struct OffshorePump {
 }
 
 /// <https://wiki.factorio.com/Prototype/Pipe>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(pipe)]
 pub struct Pipe {
     pub name: String,
+    #[use_self_forced]
     pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     pub entity: EntityBase,
+    #[use_self_forced]
     pub entity_with_health: EntityWithHealthBase,
+    #[use_self_forced]
     pub entity_with_owner: EntityWithOwnerBase,
     pub fluid_box: FluidBox,
     pub horizontal_window_bounding_box: BoundingBox,
@@ -3175,7 +6466,7 @@ pub struct Pipe {
 }
 
 /// <https://wiki.factorio.com/Prototype/InfinityPipe>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(infinity_pipe)]
 pub struct InfinityPipe {
     pub name: String,
@@ -3191,21 +6482,26 @@ pub struct InfinityPipe {
 }
 
 /// <https://wiki.factorio.com/Prototype/PipeToGround>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(pipe_to_ground)]
 pub struct PipeToGround {
     pub name: String,
+    #[use_self_forced]
     pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     pub entity: EntityBase,
+    #[use_self_forced]
     pub entity_with_health: EntityWithHealthBase,
+    #[use_self_forced]
     pub entity_with_owner: EntityWithOwnerBase,
     pub fluid_box: FluidBox,
     pub pictures: PipeToGroundPictures,
+    #[default(false)]
     pub draw_fluid_icon_override: bool, // Default: false
 }
 
 /// <https://wiki.factorio.com/Prototype/PlayerPort>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(player_port)]
 pub struct PlayerPort {
     pub name: String,
@@ -3217,7 +6513,7 @@ pub struct PlayerPort {
 }
 
 /// <https://wiki.factorio.com/Prototype/PowerSwitch>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(power_switch)]
 pub struct PowerSwitch {
     pub name: String,
@@ -3240,29 +6536,53 @@ pub struct PowerSwitch {
 }
 
 /// <https://wiki.factorio.com/Prototype/ProgrammableSpeaker>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(programmable_speaker)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct ProgrammableSpeaker {
     pub name: String,
+    #[use_self_forced]
     pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     pub entity: EntityBase,
+    #[use_self_forced]
     pub entity_with_health: EntityWithHealthBase,
+    #[use_self_forced]
     pub entity_with_owner: EntityWithOwnerBase,
     pub energy_source: EnergySource, // Must be electric
     pub energy_usage_per_tick: Energy,
     pub sprite: Sprite,
     pub maximum_polyphony: u32,
     pub instruments: Vec<Instrument>,
+    #[default(1_f32)]
     pub audible_distance_modifier: f32, // Default: 1
     pub circuit_wire_connection_point: Option<WireConnectionPoint>,
+    #[default(0_f64)]
     pub circuit_wire_max_distance: f64, // Default: 0
-    pub draw_copper_wires: bool,        // Default: true
-    pub draw_circuit_wires: bool,       // Default: true
+    #[default(true)]
+    pub draw_copper_wires: bool, // Default: true
+    #[default(true)]
+    pub draw_circuit_wires: bool, // Default: true
     pub circuit_connector_sprites: Option<CircuitConnectorSprites>,
 }
 
+impl ProgrammableSpeaker {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            self.maximum_polyphony == 0,
+            "ProgrammableSpeaker",
+            "`maximum_polyphony` must be greater than 0",
+        )
+    }
+
+    /// Instrument with given name
+    pub fn instrument(&self, name: &str) -> Option<&Instrument> {
+        self.instruments.iter().find(|i| i.name() == name)
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Pump>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(pump)]
 pub struct Pump {
     pub name: String,
@@ -3289,7 +6609,7 @@ pub struct Pump {
 }
 
 /// <https://wiki.factorio.com/Prototype/Radar>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(radar)]
 pub struct Radar {
     pub name: String,
@@ -3310,7 +6630,7 @@ pub struct Radar {
 
 /// <https://wiki.factorio.com/Prototype/CurvedRail>
 /// <https://wiki.factorio.com/Prototype/Rail>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(curved_rail)]
 pub struct CurvedRail {
     pub name: String,
@@ -3325,7 +6645,7 @@ pub struct CurvedRail {
 
 /// <https://wiki.factorio.com/Prototype/StraightRail>
 /// <https://wiki.factorio.com/Prototype/Rail>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(straight_rail)]
 pub struct StraightRail {
     pub name: String,
@@ -3343,7 +6663,7 @@ pub struct StraightRail {
 /// Rail signals must collide with each other
 /// <https://wiki.factorio.com/Prototype/RailChainSignal>
 /// <https://wiki.factorio.com/Prototype/RailSignalBase>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(rail_chain_signal)]
 pub struct RailChainSignal {
     pub name: String,
@@ -3374,7 +6694,7 @@ pub struct RailChainSignal {
 /// Rail signals must collide with each other
 /// <https://wiki.factorio.com/Prototype/RailSignal>
 /// <https://wiki.factorio.com/Prototype/RailSignalBase>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(rail_signal)]
 pub struct RailSignal {
     pub name: String,
@@ -3398,7 +6718,7 @@ pub struct RailSignal {
 }
 
 /// <https://wiki.factorio.com/Prototype/Reactor>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(reactor)]
 pub struct Reactor {
     pub name: String,
@@ -3427,7 +6747,7 @@ pub struct Reactor {
 }
 
 /// <https://wiki.factorio.com/Prototype/Roboport>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(roboport)]
 pub struct Roboport {
     pub name: String,
@@ -3480,41 +6800,91 @@ pub struct Roboport {
 }
 
 /// <https://wiki.factorio.com/Prototype/SimpleEntityWithOwner>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(simple_entity_with_owner)]
 pub struct SimpleEntityWithOwner {
     pub name: String,
+    #[use_self_forced]
     pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     pub entity: EntityBase,
+    #[use_self_forced]
     pub entity_with_health: EntityWithHealthBase,
+    #[use_self_forced]
     pub entity_with_owner: EntityWithOwnerBase,
-    pub render_layer: RenderLayer,             // default: "object"
-    pub secondary_draw_order: i8,              // Default: 0
-    pub random_animation_offset: bool,         // Default: false
-    pub random_variation_on_create: bool,      // Default: true
+    #[default(RenderLayer::Object)]
+    pub render_layer: RenderLayer, // default: "object"
+    #[default(0_i8)]
+    pub secondary_draw_order: i8, // Default: 0
+    #[default(false)]
+    pub random_animation_offset: bool, // Default: false
+    #[default(true)]
+    pub random_variation_on_create: bool, // Default: true
+    #[use_self_forced]
     pub visuals: SimpleEntityWithOwnerVisuals, // Either `picture`, `pictures` or `animations`
-    pub force_visibility: ForceCondition,      // Default: "all"
+    #[default(ForceCondition::All)]
+    pub force_visibility: ForceCondition, // Default: "all"
 }
 
 /// <https://wiki.factorio.com/Prototype/SimpleEntityWithForce>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(simple_entity_with_force)]
 pub struct SimpleEntityWithForce {
     pub name: String,
+    #[use_self_forced]
     pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     pub entity: EntityBase,
+    #[use_self_forced]
     pub entity_with_health: EntityWithHealthBase,
+    #[use_self_forced]
     pub entity_with_owner: EntityWithOwnerBase,
-    pub render_layer: RenderLayer,             // default: "object"
-    pub secondary_draw_order: i8,              // Default: 0
-    pub random_animation_offset: bool,         // Default: false
-    pub random_variation_on_create: bool,      // Default: true
+    #[default(RenderLayer::Object)]
+    pub render_layer: RenderLayer, // default: "object"
+    #[default(0_i8)]
+    pub secondary_draw_order: i8, // Default: 0
+    #[default(false)]
+    pub random_animation_offset: bool, // Default: false
+    #[default(true)]
+    pub random_variation_on_create: bool, // Default: true
+    // Same visuals as SimpleEntityWithOwner
+    #[use_self_forced]
     pub visuals: SimpleEntityWithOwnerVisuals, // Either `picture`, `pictures` or `animations`
-    pub force_visibility: ForceCondition,      // Default: "all"
+    #[default(ForceCondition::All)]
+    pub force_visibility: ForceCondition, // Default: "all"
+}
+
+#[test]
+fn simple_entity_with_force_load() {
+    let lua = Lua::new();
+    let mut data_table = DataTable::default();
+    let entity = lua
+        .load(
+            r#"
+                local picture = {filename = "__base__/graphics/entity/statue.png", size = 32}
+                return {
+                    type = "simple-entity-with-force", name = "statue",
+                    map_generator_bounding_box = {{-0.5, -0.5}, {0.5, 0.5}},
+                    repair_sound = {filename = "__core__/sound/silence-1sec.ogg"},
+                    integration_patch = {},
+                    picture = {north = picture, east = picture, south = picture, west = picture},
+                }
+            "#,
+        )
+        .eval()
+        .unwrap();
+    let entity = SimpleEntityWithForce::prototype_from_lua(entity, &lua, &mut data_table).unwrap();
+    assert_eq!(entity.render_layer, RenderLayer::Object);
+    assert!(entity.random_variation_on_create);
+    assert_eq!(entity.force_visibility, ForceCondition::All);
+    assert!(matches!(
+        entity.visuals,
+        SimpleEntityWithOwnerVisuals::Pictire(_)
+    ));
 }
 
 /// <https://wiki.factorio.com/Prototype/SolarPanel>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(solar_panel)]
 pub struct SolarPanel {
     pub name: String,
@@ -3529,29 +6899,51 @@ pub struct SolarPanel {
 }
 
 /// <https://wiki.factorio.com/Prototype/StorageTank>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(storage_tank)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct StorageTank {
     pub name: String,
+    #[use_self_forced]
     pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     pub entity: EntityBase,
+    #[use_self_forced]
     pub entity_with_health: EntityWithHealthBase,
+    #[use_self_forced]
     pub entity_with_owner: EntityWithOwnerBase,
     pub fluid_box: FluidBox,
     pub window_bounding_box: BoundingBox,
     pub pictures: StorageTankPictures,
-    pub flow_length_in_ticks: u32,      // Must be positive
-    pub two_direction_only: bool,       // Default: false
+    pub flow_length_in_ticks: u32, // Must be positive
+    #[default(false)]
+    pub two_direction_only: bool, // Default: false
+    #[default(0_f64)]
     pub circuit_wire_max_distance: f64, // Default: 0
-    pub draw_copper_wires: bool,        // Default: true
-    pub draw_circuit_wires: bool,       // Default: true
-    pub circuit_wire_connection_points: Vec<WireConnectionPoint>, // Mandatory if `circuit_wire_max_distance` > 0
-    pub circuit_connector_sprites: Vec<CircuitConnectorSprites>, // Mandatory if `circuit_wire_max_distance` > 0
-    pub scale_info_icons: bool,                                  // Default: true
+    #[default(true)]
+    pub draw_copper_wires: bool, // Default: true
+    #[default(true)]
+    pub draw_circuit_wires: bool, // Default: true
+    #[mandatory_if(circuit_wire_max_distance > 0.0)]
+    pub circuit_wire_connection_points: Option<Vec<WireConnectionPoint>>, // Mandatory if `circuit_wire_max_distance` > 0
+    #[mandatory_if(circuit_wire_max_distance > 0.0)]
+    pub circuit_connector_sprites: Option<Vec<CircuitConnectorSprites>>, // Mandatory if `circuit_wire_max_distance` > 0
+    #[default(true)]
+    pub scale_info_icons: bool, // Default: true
+}
+
+impl StorageTank {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            self.flow_length_in_ticks == 0,
+            "StorageTank",
+            "`flow_length_in_ticks` must be positive",
+        )
+    }
 }
 
 /// <https://wiki.factorio.com/Prototype/TrainStop>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(train_stop)]
 pub struct TrainStop {
     pub name: String,
@@ -3580,7 +6972,7 @@ pub struct TrainStop {
 
 // TODO: Clean up graphics properties
 /// <https://wiki.factorio.com/Prototype/TransportBeltConnectable>
-#[derive(Debug, Clone, Base)]
+#[derive(Debug, Clone, Base, ResourceWalk, HeapSize)]
 pub struct TransportBeltConnectableBase {
     pub speed: f64,
     pub animation_speed_coefficient: f64, // Default: 1
@@ -3588,14 +6980,14 @@ pub struct TransportBeltConnectableBase {
     pub belt_animation_set: TransportBeltConnectableGraphics,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ResourceWalk)]
 pub enum TransportBeltConnectableGraphics {
     AnimationSet(Box<BeltAnimationSet>),
     GraphicsSet(Box<BeltGraphicsSet>),
 }
 
 /// <https://wiki.factorio.com/Prototype/LinkedBelt>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(linked_belt)]
 pub struct LinkedBelt {
     pub name: String,
@@ -3612,7 +7004,7 @@ pub struct LinkedBelt {
 }
 
 /// <https://wiki.factorio.com/Prototype/Loader1x1>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(loader_1x1)]
 pub struct Loader1x1 {
     pub name: String,
@@ -3629,7 +7021,7 @@ pub struct Loader1x1 {
 }
 
 /// <https://wiki.factorio.com/Prototype/Loader1x2>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(loader)]
 pub struct Loader1x2 {
     pub name: String,
@@ -3646,7 +7038,7 @@ pub struct Loader1x2 {
 }
 
 /// <https://wiki.factorio.com/Prototype/Splitter>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(splitter)]
 pub struct Splitter {
     pub name: String,
@@ -3662,7 +7054,7 @@ pub struct Splitter {
 }
 
 /// <https://wiki.factorio.com/Prototype/TransportBelt>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(transport_belt)]
 pub struct TransportBelt {
     pub name: String,
@@ -3683,7 +7075,7 @@ pub struct TransportBelt {
 }
 
 /// <https://wiki.factorio.com/Prototype/UndergroundBelt>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, TransportBeltConnectable!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(underground_belt)]
 pub struct UndergroundBelt {
     pub name: String,
@@ -3699,34 +7091,45 @@ pub struct UndergroundBelt {
 }
 
 /// <https://wiki.factorio.com/Prototype/Turret>
-#[derive(Debug, Clone, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, ResourceWalk, HeapSize)]
 pub struct TurretBase {
     attack_parameters: AttackParameters,
-    folded_animation: RotatedAnimation4Way,
+    folded_animation: Box<RotatedAnimation4Way>,
     call_for_help_radius: f64,
     corpse: Option<String>,                        // Name of corpse entity
     attack_target_mask: Option<TriggerTargetMask>, // Default: all
     ignore_target_mask: Option<TriggerTargetMask>, // Default: no
-    shoot_in_prepare_state: bool,                  // Default: false
-    turret_has_direction: bool,                    // Default: false
-    random_animation_offset: bool,                 // Default: false
-    secondary_animation: bool,                     // Default: false
-    attack_from_start_frame: bool,                 // Default: false
-    allow_turning_when_starting_attack: bool,      // Default: false
-    base_picture_secondary_draw_order: u8,         // Default: 0
-    gun_animation_secondary_draw_order: u8,        // Default: 0
-    base_picture_render_layer: RenderLayer,        // Default: "lower-obejct"
-    gun_animation_render_layer: RenderLayer,       // Default: "object"
-    base_picture: Option<Animation4Way>,
-    preparing_animation: Option<RotatedAnimation4Way>,
-    prepared_animation: Option<RotatedAnimation4Way>,
-    prepared_alternative_animation: Option<RotatedAnimation4Way>,
-    starting_attack_animation: Option<RotatedAnimation4Way>,
-    attacking_animation: Option<RotatedAnimation4Way>,
-    energy_glow_animation: Option<RotatedAnimation4Way>,
-    ending_attack_animation: Option<RotatedAnimation4Way>,
-    folding_animation: Option<RotatedAnimation4Way>,
+    #[default(false)]
+    shoot_in_prepare_state: bool, // Default: false
+    #[default(false)]
+    turret_has_direction: bool, // Default: false
+    #[default(false)]
+    random_animation_offset: bool, // Default: false
+    #[default(false)]
+    secondary_animation: bool, // Default: false
+    #[default(false)]
+    attack_from_start_frame: bool, // Default: false
+    #[default(false)]
+    allow_turning_when_starting_attack: bool, // Default: false
+    #[default(0_u8)]
+    base_picture_secondary_draw_order: u8, // Default: 0
+    #[default(0_u8)]
+    gun_animation_secondary_draw_order: u8, // Default: 0
+    #[default(RenderLayer::LowerObject)]
+    base_picture_render_layer: RenderLayer, // Default: "lower-obejct"
+    #[default(RenderLayer::Object)]
+    gun_animation_render_layer: RenderLayer, // Default: "object"
+    base_picture: Option<Box<Animation4Way>>,
+    preparing_animation: Option<Box<RotatedAnimation4Way>>,
+    prepared_animation: Option<Box<RotatedAnimation4Way>>,
+    prepared_alternative_animation: Option<Box<RotatedAnimation4Way>>,
+    starting_attack_animation: Option<Box<RotatedAnimation4Way>>,
+    attacking_animation: Option<Box<RotatedAnimation4Way>>,
+    energy_glow_animation: Option<Box<RotatedAnimation4Way>>,
+    ending_attack_animation: Option<Box<RotatedAnimation4Way>>,
+    folding_animation: Option<Box<RotatedAnimation4Way>>,
     integration: Option<Sprite>,
+    #[default(0.0_f32)]
     glow_light_intensity: f32, // Default: 0
     starting_attack_sound: Option<Sound>,
     dying_sound: Option<Sound>,
@@ -3734,27 +7137,61 @@ pub struct TurretBase {
     folding_sound: Option<Sound>,
     prepared_sound: Option<Sound>,
     prepared_alternative_sound: Option<Sound>,
-    rotation_speed: f32,                       // Default: 1
-    preparing_speed: f32,                      // Default: 1
-    folded_speed: f32,                         // Default: 1
-    folded_speed_secondary: f32,               // Default: 1
-    prepared_speed: f32,                       // Default: 1
-    prepared_speed_secondary: f32,             // Default: 1
-    prepared_alternative_speed: f32,           // Default: 1
+    #[default(1.0_f32)]
+    rotation_speed: f32, // Default: 1
+    #[default(1.0_f32)]
+    preparing_speed: f32, // Default: 1
+    #[default(1.0_f32)]
+    folded_speed: f32, // Default: 1
+    #[default(1.0_f32)]
+    folded_speed_secondary: f32, // Default: 1
+    #[default(1.0_f32)]
+    prepared_speed: f32, // Default: 1
+    #[default(1.0_f32)]
+    prepared_speed_secondary: f32, // Default: 1
+    #[default(1.0_f32)]
+    prepared_alternative_speed: f32, // Default: 1
+    #[default(1.0_f32)]
     prepared_alternative_speed_secondary: f32, // Default: 1
-    prepared_alternative_chance: f32,          // Default: 0
-    starting_attack_speed: f32,                // Default: 1
-    attacking_speed: f32,                      // Default: 1
-    ending_attack_speed: f32,                  // Default: 1
-    folding_speed: f32,                        // Default: 1
-    prepare_range: f64,                        // Default: range defined in `attack_parameters`
-    alert_when_attacking: bool,                // Default: true
-    spawn_decorations_on_expansion: bool,      // Default: false,
+    #[default(0.0_f32)]
+    prepared_alternative_chance: f32, // Default: 0
+    #[default(1.0_f32)]
+    starting_attack_speed: f32, // Default: 1
+    #[default(1.0_f32)]
+    attacking_speed: f32, // Default: 1
+    #[default(1.0_f32)]
+    ending_attack_speed: f32, // Default: 1
+    #[default(1.0_f32)]
+    folding_speed: f32, // Default: 1
+    #[default(f64::from(attack_parameters.base().range.range()))]
+    prepare_range: f64, // Default: range defined in `attack_parameters`
+    #[default(true)]
+    alert_when_attacking: bool, // Default: true
+    #[default(false)]
+    spawn_decorations_on_expansion: bool, // Default: false,
     spawn_decoration: Option<Vec<CreateDecorativesTriggerEffectItem>>,
 }
 
+// Animations of turrets are boxed, otherwise every turret type would carry ten of them by value.
+// Optional lights, triggers, sounds and graphics of the largest entities are boxed for the same
+// reason, so on top of their base properties they only add a few pointers.
+#[test]
+fn prototype_sizes() {
+    use std::mem::size_of;
+    assert!(size_of::<TurretBase>() < 3 * size_of::<RotatedAnimation4Way>());
+    let entity = size_of::<PrototypeBaseSpec>()
+        + size_of::<EntityBase>()
+        + size_of::<EntityWithHealthBase>()
+        + size_of::<EntityWithOwnerBase>();
+    assert!(size_of::<RocketSilo>() < entity + size_of::<CraftingMachineBase>() + 1024);
+    assert!(size_of::<ArtilleryTurret>() < entity + 512);
+    assert!(
+        size_of::<FluidTurret>() < entity + size_of::<TurretBase>() + size_of::<FluidBox>() + 512
+    );
+}
+
 /// <https://wiki.factorio.com/Prototype/Turret>
-#[derive(Debug, Clone, Prototype, Turret!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, Turret!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(turret)]
 pub struct TurretPrototype {
     name: String,
@@ -3766,7 +7203,7 @@ pub struct TurretPrototype {
 }
 
 /// <https://wiki.factorio.com/Prototype/AmmoTurret>
-#[derive(Debug, Clone, Prototype, Turret!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, Turret!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(ammo_turret)]
 pub struct AmmoTurret {
     name: String,
@@ -3781,7 +7218,7 @@ pub struct AmmoTurret {
 }
 
 /// <https://wiki.factorio.com/Prototype/ElectricTurret>
-#[derive(Debug, Clone, Prototype, Turret!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, Turret!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(electric_turret)]
 pub struct ElectricTurret {
     name: String,
@@ -3793,39 +7230,60 @@ pub struct ElectricTurret {
     pub energy_source: EnergySource,
 }
 
-// `turret_has_direction` must = true
+// `turret_has_direction` must = true, see [DataTable::validate_fluid_turrets]
 /// <https://wiki.factorio.com/Prototype/FluidTurret>
-#[derive(Debug, Clone, Prototype, Turret!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, Turret!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(fluid_turret)]
 pub struct FluidTurret {
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     entity: EntityBase,
+    #[use_self_forced]
     entity_with_health: EntityWithHealthBase,
+    #[use_self_forced]
     entity_with_owner: EntityWithOwnerBase,
+    #[use_self_forced]
     turret: TurretBase,
     pub fluid_buffer_size: f32,
     pub fluid_buffer_input_flow: f32,
     pub activation_buffer_ratio: f32,
     pub fluid_box: FluidBox,
-    pub muzzle_light: Option<LightDefinition>,
-    pub enough_fuel_indicator_light: Option<LightDefinition>,
-    pub not_enough_fuel_indicator_light: Option<LightDefinition>,
+    pub muzzle_light: Option<Box<LightDefinition>>,
+    pub enough_fuel_indicator_light: Option<Box<LightDefinition>>,
+    pub not_enough_fuel_indicator_light: Option<Box<LightDefinition>>,
     pub muzzle_animation: Option<Animation>,
-    pub folded_muzzle_animation_shift: Option<AnimatedVector>,
-    pub preparing_muzzle_animation_shift: Option<AnimatedVector>,
-    pub prepared_muzzle_animation_shift: Option<AnimatedVector>,
-    pub starting_attack_muzzle_animation_shift: Option<AnimatedVector>,
-    pub attacking_muzzle_animation_shift: Option<AnimatedVector>,
-    pub ending_attack_muzzle_animation_shift: Option<AnimatedVector>,
-    pub folding_muzzle_animation_shift: Option<AnimatedVector>,
-    pub enough_fuel_indicator_picture: Option<Sprite4Way>,
-    pub not_enough_fuel_indicator_picture: Option<Sprite4Way>,
+    pub folded_muzzle_animation_shift: Option<Box<AnimatedVector>>,
+    pub preparing_muzzle_animation_shift: Option<Box<AnimatedVector>>,
+    pub prepared_muzzle_animation_shift: Option<Box<AnimatedVector>>,
+    pub starting_attack_muzzle_animation_shift: Option<Box<AnimatedVector>>,
+    pub attacking_muzzle_animation_shift: Option<Box<AnimatedVector>>,
+    pub ending_attack_muzzle_animation_shift: Option<Box<AnimatedVector>>,
+    pub folding_muzzle_animation_shift: Option<Box<AnimatedVector>>,
+    pub enough_fuel_indicator_picture: Option<Box<Sprite4Way>>,
+    pub not_enough_fuel_indicator_picture: Option<Box<Sprite4Way>>,
     pub out_of_ammo_alert_icon: Option<Sprite>,
 }
 
+impl FluidTurret {
+    /// Shift of the muzzle animation in given phase
+    pub fn muzzle_shift_for(&self, phase: TurretPhase) -> Option<&AnimatedVector> {
+        match phase {
+            TurretPhase::Folded => &self.folded_muzzle_animation_shift,
+            TurretPhase::Preparing => &self.preparing_muzzle_animation_shift,
+            TurretPhase::Prepared => &self.prepared_muzzle_animation_shift,
+            TurretPhase::StartingAttack => &self.starting_attack_muzzle_animation_shift,
+            TurretPhase::Attacking => &self.attacking_muzzle_animation_shift,
+            TurretPhase::EndingAttack => &self.ending_attack_muzzle_animation_shift,
+            TurretPhase::Folding => &self.folding_muzzle_animation_shift,
+        }
+        .as_deref()
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Unit>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(unit)]
 pub struct Unit {
     name: String,
@@ -3860,7 +7318,7 @@ pub struct Unit {
 }
 
 /// <https://wiki.factorio.com/Prototype/Vehicle>
-#[derive(Debug, Clone, Base)]
+#[derive(Debug, Clone, Base, ResourceWalk, HeapSize)]
 pub struct VehicleBase {
     weight: f64,         // Mus be positive
     braking_force: f64,  // Must be positive // braking_power is converted to this
@@ -3878,8 +7336,30 @@ pub struct VehicleBase {
     allow_passengers: bool, // Default: true
 }
 
+#[cfg(test)]
+impl VehicleBase {
+    fn stub() -> Self {
+        Self {
+            weight: 1000.0,
+            braking_force: 3.0,
+            friction_force: 0.5,
+            energy_per_hit_point: 5.0,
+            terrain_friction_modifier: 1.0,
+            sound_minimum_speed: 1.0 / 60.0,
+            sound_scaling_ratio: 1.0,
+            stop_trigger_speed: 0.0,
+            crash_trigger: None,
+            stop_trigger: None,
+            equipment_grid: None,
+            minimap_representation: None,
+            selected_minimap_representation: None,
+            allow_passengers: true,
+        }
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Car>
-#[derive(Debug, Clone, Prototype, Vehicle!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, Vehicle!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(car)]
 pub struct Car {
     name: String,
@@ -3911,7 +7391,7 @@ pub struct Car {
 }
 
 /// <https://wiki.factorio.com/Prototype/RollingStock>
-#[derive(Debug, Clone, Base)]
+#[derive(Debug, Clone, Base, ResourceWalk, HeapSize)]
 pub struct RollingStockBase {
     max_speed: f64,
     air_resistance: f64,
@@ -3932,7 +7412,7 @@ pub struct RollingStockBase {
 }
 
 /// <https://wiki.factorio.com/Prototype/ArtilleryWagon>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Vehicle!, RollingStock!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Vehicle!, RollingStock!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(artillery_wagon)]
 pub struct ArtilleryWagon {
     name: String,
@@ -3962,7 +7442,7 @@ pub struct ArtilleryWagon {
 }
 
 /// <https://wiki.factorio.com/Prototype/CargoWagon>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Vehicle!, RollingStock!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Vehicle!, RollingStock!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(cargo_wagon)]
 pub struct CargoWagon {
     name: String,
@@ -3976,7 +7456,7 @@ pub struct CargoWagon {
 }
 
 /// <https://wiki.factorio.com/Prototype/FluidWagon>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Vehicle!, RollingStock!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Vehicle!, RollingStock!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(fluid_wagon)]
 pub struct FluidWagon {
     name: String,
@@ -3991,7 +7471,7 @@ pub struct FluidWagon {
 }
 
 /// <https://wiki.factorio.com/Prototype/Locomotive>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Vehicle!, RollingStock!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Vehicle!, RollingStock!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(locomotive)]
 pub struct Locomotive {
     name: String,
@@ -4011,7 +7491,7 @@ pub struct Locomotive {
 }
 
 /// <https://wiki.factorio.com/Prototype/SpiderVehicle>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Vehicle!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, Vehicle!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(spider_vehicle)]
 pub struct SpiderVehicle {
     name: String,
@@ -4034,8 +7514,21 @@ pub struct SpiderVehicle {
     pub guns: Vec<String>,                    // (Names) Name of gun
 }
 
+impl SpiderVehicle {
+    /// SpiderLeg entities of `spider_engine`, in order of leg specifications. Legs that don't
+    /// exist are skipped, see [DataTable::validate_spidertron].
+    pub fn legs<'a>(&self, dt: &'a DataTable) -> Vec<&'a SpiderLeg> {
+        self.spider_engine
+            .legs()
+            .iter()
+            .filter_map(|spec| dt.spider_leg.get(spec.leg()))
+            .map(Arc::as_ref)
+            .collect()
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Wall>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, EntityWithOwner!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(wall)]
 pub struct Wall {
     name: String,
@@ -4065,7 +7558,7 @@ pub struct Wall {
 }
 
 /// <https://wiki.factorio.com/Prototype/Fish>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, DataTableAccessable, PrototypeFromLua)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(fish)]
 pub struct Fish {
     pub name: String,
@@ -4079,23 +7572,32 @@ pub struct Fish {
 }
 
 /// <https://wiki.factorio.com/Prototype/SimpleEntity>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(simple_entity)]
 pub struct SimpleEntity {
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     entity: EntityBase,
+    #[use_self_forced]
     entity_with_health: EntityWithHealthBase,
+    #[default(false)]
     pub count_as_rock_for_filtered_deconstruction: bool, // Default: false
-    pub render_layer: RenderLayer,                       // Default: "object"
-    pub secondary_draw_order: i8,                        // Default: 0
-    pub random_animation_offset: bool,                   // Default: false
-    pub random_variation_on_create: bool,                // Default: true
-    pub visuals: SimpleEntityVisuals,
+    #[default(RenderLayer::Object)]
+    pub render_layer: RenderLayer, // Default: "object"
+    #[default(0_i8)]
+    pub secondary_draw_order: i8, // Default: 0
+    #[default(false)]
+    pub random_animation_offset: bool, // Default: false
+    #[default(true)]
+    pub random_variation_on_create: bool, // Default: true
+    #[use_self_forced]
+    pub visuals: SimpleEntityVisuals, // Either `pictures`, `picture` or `animations`
 }
 
 /// <https://wiki.factorio.com/Prototype/SpiderLeg>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(spider_leg)]
 pub struct SpiderLeg {
     name: String,
@@ -4113,192 +7615,313 @@ pub struct SpiderLeg {
 }
 
 /// <https://wiki.factorio.com/Prototype/Tree>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, EntityWithHealth!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(tree)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct Tree {
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     entity: EntityBase,
+    // healing_per_tick defaults to 0.001666 for trees, see EntityWithHealthBase
+    #[use_self_forced]
     entity_with_health: EntityWithHealthBase,
     pub variation_weights: Option<Vec<f64>>,
+    #[default(0.5_f32)]
     pub darkness_of_burnt_tree: f32, // Default: 0.5
+    #[use_self_forced]
     pub visuals: TreeVisuals,
-    // healing_per_tick: default 0.001666
+}
+
+impl Tree {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        if let Some(weights) = &self.variation_weights {
+            let variation_count = self.visuals.variation_count();
+            if weights.len() != variation_count {
+                return Err(conversion_error(
+                    "table",
+                    "Tree",
+                    Some(format!(
+                        "`variation_weights` has {} values, but there are {} variations",
+                        weights.len(),
+                        variation_count
+                    )),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fields shared by [Explosion] and [FlameThrowerExplosion]
+/// <https://wiki.factorio.com/Prototype/Explosion>
+#[derive(Debug, Clone, PrototypeFromLua, Base, ResourceWalk, HeapSize)]
+pub struct ExplosionEffectBase {
+    pub animations: AnimationVariations,
+    pub sound: Option<Sound>,
+    #[mandatory_if(smoke_count > 0)]
+    pub smoke: Option<String>, // Name of trivial-smoke prototype // Mandatory if `smoke_count` > 0
+    #[default(1_f32)]
+    pub height: f32, // Default: 1
+    #[default(0_f32)]
+    pub smoke_slow_down_factor: f32, // Default: 0
+    #[default(0_u16)]
+    pub smoke_count: u16, // Default: 0
+    #[default(false)]
+    pub rotate: bool, // Default: false
+    #[default(false)]
+    pub beam: bool, // Default: false
+    #[default(false)]
+    pub correct_rotation: bool, // Default: false
+    #[default(false)]
+    pub scale_animation_speed: bool, // Default: false
+    #[default(0_u8)]
+    pub fade_in_duration: u8, // Default: 0
+    #[default(0_u8)]
+    pub fade_out_duration: u8, // Default: 0
+    #[default(RenderLayer::Explosion)]
+    pub render_layer: RenderLayer, // Default: "explosion"
+    #[default(0_u8)]
+    pub scale_in_duration: u8, // Default: 0
+    #[default(0_u8)]
+    pub scale_out_duration: u8, // Default: 0
+    #[default(1_f32)]
+    pub scale_end: f32, // Default: 1
+    #[default(0_f32)]
+    pub scale_increment_per_tick: f32, // Default: 0
+    #[default(0_f32)]
+    pub light_intensity_factor_initial: f32, // Default: 0
+    #[default(0_f32)]
+    pub light_intensity_factor_final: f32, // Default: 0
+    #[default(0.05_f32)]
+    pub light_size_factor_initial: f32, // Default: 0.05
+    #[default(0.1_f32)]
+    pub light_size_factor_final: f32, // Default: 0.1
+    pub light: Option<LightDefinition>,
+    #[default(0_f32)]
+    pub light_intensity_peak_start_progress: f32, // Default: 0
+    #[default(0.9_f32)]
+    pub light_intensity_peak_end_progress: f32, // Default: 0.9
+    #[default(0.1_f32)]
+    pub light_size_peak_start_progress: f32, // Default: 0.1
+    #[default(0.5_f32)]
+    pub light_size_peak_end_progress: f32, // Default: 0.5
+    #[default(1_f32)]
+    pub scale_initial: f32, // Default: 1
+    #[default(0_f32)]
+    pub scale_initial_deviation: f32, // Default: 0
+    #[default(1_f32)]
+    pub scale: f32, // Default: 1
+    #[default(0_f32)]
+    pub scale_deviation: f32, // Default: 0
 }
 
 /// <https://wiki.factorio.com/Prototype/Explosion>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, ExplosionEffect!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(explosion)]
 pub struct Explosion {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    entity: EntityBase,
-    animations: AnimationVariations,
-    sound: Option<Sound>,
-    smoke: Option<String>, // Name of trivial-smoke prototype // Mandatory if `smoke_count` > 0
-    height: f32,           // Default: 1
-    smoke_slow_down_factor: f32, // Default: 0
-    smoke_count: u16,      // Default: 0
-    rotate: bool,          // Default: false
-    beam: bool,            // Default: false
-    correct_rotation: bool, // Default: false
-    scale_animation_speed: bool, // Default: false
-    fade_in_duration: u8,  // Default: 0
-    fade_out_duration: u8, // Default: 0
-    render_layer: RenderLayer, // Default: "explosion"
-    scale_in_duration: u8, // Default: 0
-    scale_out_duration: u8, // Default: 0
-    scale_end: f32,        // Default: 1
-    scale_increment_per_tick: f32, // Default: 0
-    light_intensity_factor_initial: f32, // Default: 0
-    light_intensity_factor_final: f32, // Default: 0
-    light_size_factor_initial: f32, // Default: 0.05
-    light_size_factor_final: f32, // Default: 0.1
-    light: Option<LightDefinition>,
-    light_intensity_peak_start_progress: f32, // Default: 0
-    light_intensity_peak_end_progress: f32,   // Default: 0.9
-    light_size_peak_start_progress: f32,      // Default: 0.1
-    light_size_peak_end_progress: f32,        // Default: 0.5
-    scale_initial: f32,                       // Default: 1
-    scale_initial_deviation: f32,             // Default: 0
-    scale: f32,                               // Default: 1
-    scale_deviation: f32,                     // Default: 0
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
+    pub entity: EntityBase,
+    #[use_self_forced]
+    pub explosion_effect: ExplosionEffectBase,
 }
 
 /// <https://wiki.factorio.com/Prototype/FlameThrowerExplosion>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, ExplosionEffect!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(flame_thrower_explosion)]
 pub struct FlameThrowerExplosion {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    entity: EntityBase,
-    animations: AnimationVariations,
-    sound: Option<Sound>,
-    smoke: Option<String>, // Name of trivial-smoke prototype // Mandatory if `smoke_count` > 0
-    height: f32,           // Default: 1
-    smoke_slow_down_factor: f32, // Default: 0
-    smoke_count: u16,      // Default: 0
-    rotate: bool,          // Default: false
-    beam: bool,            // Default: false
-    correct_rotation: bool, // Default: false
-    scale_animation_speed: bool, // Default: false
-    fade_in_duration: u8,  // Default: 0
-    fade_out_duration: u8, // Default: 0
-    render_layer: RenderLayer, // Default: "explosion"
-    scale_in_duration: u8, // Default: 0
-    scale_out_duration: u8, // Default: 0
-    scale_end: f32,        // Default: 1
-    scale_increment_per_tick: f32, // Default: 0
-    light_intensity_factor_initial: f32, // Default: 0
-    light_intensity_factor_final: f32, // Default: 0
-    light_size_factor_initial: f32, // Default: 0.05
-    light_size_factor_final: f32, // Default: 0.1
-    light: Option<LightDefinition>,
-    light_intensity_peak_start_progress: f32, // Default: 0
-    light_intensity_peak_end_progress: f32,   // Default: 0.9
-    light_size_peak_start_progress: f32,      // Default: 0.1
-    light_size_peak_end_progress: f32,        // Default: 0.5
-    scale_initial: f32,                       // Default: 1
-    scale_initial_deviation: f32,             // Default: 0
-    scale: f32,                               // Default: 1
-    scale_deviation: f32,                     // Default: 0
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
+    pub entity: EntityBase,
+    #[use_self_forced]
+    pub explosion_effect: ExplosionEffectBase,
 }
 
 /// <https://wiki.factorio.com/Prototype/FireFlame>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(fire)]
 pub struct FireFlame {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    entity: EntityBase,
-    damage_per_tick: DamagePrototype,
-    spread_delay: u32,
-    spread_delay_deviation: u32,
-    render_layer: RenderLayer,           // Default: "object"
-    initial_render_layer: RenderLayer,   // Default: "object"
-    secondary_render_layer: RenderLayer, // Default: "object"
-    small_tree_fire_pictures: Option<AnimationVariations>,
-    pictures: Option<AnimationVariations>,
-    smoke_source_pictures: Option<AnimationVariations>,
-    secondary_pictures: Option<AnimationVariations>,
-    burnt_patch_pictures: Option<SpriteVariations>,
-    secondary_picture_fade_out_start: u32,    // Default: 0
-    secondary_picture_fade_out_duration: u32, // Default: 30
-    spawn_entity: Option<String>,             // Name of entity
-    smoke: Option<Vec<SmokeSource>>,
-    maximum_spread_count: u16,                      // Default: 200
-    initial_flame_count: u8,                        // Default: 0
-    uses_alternative_behavior: bool,                // Default: false
-    limit_overlapping_particles: bool,              // Default: false
-    tree_dying_factor: f32,                         // Default: 0
-    fade_in_duration: u32,                          // Default: 30
-    fade_out_duration: u32,                         // Default: 30
-    initial_lifetime: u32,                          // Default: 300
-    damage_multiplier_decrease_per_tick: f32,       // Default: 0
-    damage_multiplier_increase_per_added_fuel: f32, // Default: 0
-    maximum_damage_multiplier: f32,                 // default: 1
-    lifetime_increase_by: u32,                      // Default: 20
-    lifetime_increase_cooldown: u32,                // Default: 10
-    maximum_lifetime: u32,                          // Default: u32::MAX
-    add_fuel_cooldown: u32,                         // Default: 10
-    delay_between_initial_flames: u32,              // Default: 10
-    smoke_fade_in_duration: u32,                    // Default: 30
-    smoke_fade_out_duration: u32,                   // Default: 30
-    on_fuel_added_action: Option<Trigger>,
-    on_damage_tick_effect: Option<Trigger>,
-    light: Option<LightDefinition>,
-    particle_alpha_blend_duration: u16, // Default: 0
-    burnt_patch_lifetime: u32,          // Default: 1800
-    burnt_patch_alpha_default: f32,     // Default: 1
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
+    pub entity: EntityBase,
+    pub damage_per_tick: DamagePrototype,
+    pub spread_delay: u32,
+    pub spread_delay_deviation: u32,
+    #[default(RenderLayer::Object)]
+    pub render_layer: RenderLayer, // Default: "object"
+    #[default(RenderLayer::Object)]
+    pub initial_render_layer: RenderLayer, // Default: "object"
+    #[default(RenderLayer::Object)]
+    pub secondary_render_layer: RenderLayer, // Default: "object"
+    pub small_tree_fire_pictures: Option<AnimationVariations>,
+    pub pictures: Option<AnimationVariations>,
+    pub smoke_source_pictures: Option<AnimationVariations>,
+    pub secondary_pictures: Option<AnimationVariations>,
+    pub burnt_patch_pictures: Option<SpriteVariations>,
+    #[default(0_u32)]
+    pub secondary_picture_fade_out_start: u32, // Default: 0
+    #[default(30_u32)]
+    pub secondary_picture_fade_out_duration: u32, // Default: 30
+    pub spawn_entity: Option<String>, // Name of entity
+    pub smoke: Option<Vec<SmokeSource>>,
+    #[default(200_u16)]
+    pub maximum_spread_count: u16, // Default: 200
+    #[default(0_u8)]
+    pub initial_flame_count: u8, // Default: 0
+    #[default(false)]
+    pub uses_alternative_behavior: bool, // Default: false
+    #[default(false)]
+    pub limit_overlapping_particles: bool, // Default: false
+    #[default(0_f32)]
+    pub tree_dying_factor: f32, // Default: 0
+    #[default(30_u32)]
+    pub fade_in_duration: u32, // Default: 30
+    #[default(30_u32)]
+    pub fade_out_duration: u32, // Default: 30
+    #[default(300_u32)]
+    pub initial_lifetime: u32, // Default: 300
+    #[default(0_f32)]
+    pub damage_multiplier_decrease_per_tick: f32, // Default: 0
+    #[default(0_f32)]
+    pub damage_multiplier_increase_per_added_fuel: f32, // Default: 0
+    #[default(1_f32)]
+    pub maximum_damage_multiplier: f32, // default: 1
+    #[default(20_u32)]
+    pub lifetime_increase_by: u32, // Default: 20
+    #[default(10_u32)]
+    pub lifetime_increase_cooldown: u32, // Default: 10
+    #[default(u32::MAX)]
+    pub maximum_lifetime: u32, // Default: u32::MAX
+    #[default(10_u32)]
+    pub add_fuel_cooldown: u32, // Default: 10
+    #[default(10_u32)]
+    pub delay_between_initial_flames: u32, // Default: 10
+    #[default(30_u32)]
+    pub smoke_fade_in_duration: u32, // Default: 30
+    #[default(30_u32)]
+    pub smoke_fade_out_duration: u32, // Default: 30
+    pub on_fuel_added_action: Option<Trigger>,
+    pub on_damage_tick_effect: Option<Trigger>,
+    pub light: Option<LightDefinition>,
+    #[default(0_u16)]
+    pub particle_alpha_blend_duration: u16, // Default: 0
+    #[default(1800_u32)]
+    pub burnt_patch_lifetime: u32, // Default: 1800
+    #[default(1_f32)]
+    pub burnt_patch_alpha_default: f32, // Default: 1
     // Only loaded if `uses_alternative_behavior` is false
-    particle_alpha: f32,           // Default: 1
-    particle_alpha_deviation: f32, // Default: 0
-    flame_alpha: f32,              // Default: 1
-    flame_alpha_deviation: f32,    // Default: 0
+    #[default(1_f32)]
+    pub particle_alpha: f32, // Default: 1
+    #[default(0_f32)]
+    pub particle_alpha_deviation: f32, // Default: 0
+    #[default(1_f32)]
+    pub flame_alpha: f32, // Default: 1
+    #[default(0_f32)]
+    pub flame_alpha_deviation: f32, // Default: 0
     //
-    burnt_patch_alpha_variations: Option<Vec<FireFlameBurntPatchAlphaVariation>>,
+    pub burnt_patch_alpha_variations: Option<Vec<FireFlameBurntPatchAlphaVariation>>,
 }
 
 /// <https://wiki.factorio.com/Prototype/FluidStream>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(stream)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct FluidStream {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    entity: EntityBase,
-    particle_spawn_interval: u16,
-    particle_horizontal_speed: f64, // Must be higher than 0 // Must be greater than `particle_horizontal_speed_deviation`
-    particle_horizontal_speed_deviation: f64,
-    particle_vertical_acceleration: f64,
-    initial_action: Option<Trigger>,
-    action: Option<Trigger>,
-    special_neutral_target_damage: Option<DamagePrototype>,
-    width: f32,                        // Default: 0.5
-    particle_buffer_size: u32,         // Default: 20 // Must be less than 256 // So u8?
-    particle_spawn_timeout: u16,       // Default: 4 * `particle_spawn_interval`
-    particle_start_alpha: f32,         // Default: 1
-    particle_end_alpha: f32,           // Default: 1
-    particle_start_scale: f32,         // Default: 1
-    particle_alpha_per_part: f32,      // Default: 1
-    particle_scale_per_part: f32,      // Default: 1
-    particle_fade_out_threshold: f32,  // Defayklt: 1, // Between 0 and 1
-    particle_loop_exit_threshold: f32, // Default: 0 // Between 0 and 1
-    particle_loop_frame_count: u16,    // Default: 1 // If less than 1, force 1
-    particle_fade_out_duration: u16,   // Default: u16::MAX // If less than 1, force 1
-    spine_animation: Option<Animation>,
-    particle: Option<Animation>,
-    shadow: Option<Animation>,
-    smoke_sources: Option<Vec<SmokeSource>>,
-    progress_to_create_smoke: f32, // Default: 0.5
-    stream_light: Option<LightDefinition>,
-    ground_light: Option<LightDefinition>,
-    target_position_deviation: f64, // Default: 0
-    oriented_particle: bool,        // Default: false
-    shadow_scale_enabled: bool,     // Default: false
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
+    pub entity: EntityBase,
+    pub particle_spawn_interval: u16,
+    pub particle_horizontal_speed: f64, // Must be higher than 0 // Must be greater than `particle_horizontal_speed_deviation`
+    pub particle_horizontal_speed_deviation: f64,
+    pub particle_vertical_acceleration: f64,
+    pub initial_action: Option<Trigger>,
+    pub action: Option<Trigger>,
+    pub special_neutral_target_damage: Option<DamagePrototype>,
+    #[default(0.5_f32)]
+    pub width: f32, // Default: 0.5
+    #[default(20_u32)]
+    pub particle_buffer_size: u32, // Default: 20 // Must be less than 256 // So u8?
+    #[default(particle_spawn_interval.saturating_mul(4))]
+    pub particle_spawn_timeout: u16, // Default: 4 * `particle_spawn_interval`
+    #[default(1_f32)]
+    pub particle_start_alpha: f32, // Default: 1
+    #[default(1_f32)]
+    pub particle_end_alpha: f32, // Default: 1
+    #[default(1_f32)]
+    pub particle_start_scale: f32, // Default: 1
+    #[default(1_f32)]
+    pub particle_alpha_per_part: f32, // Default: 1
+    #[default(1_f32)]
+    pub particle_scale_per_part: f32, // Default: 1
+    #[default(1_f32)]
+    pub particle_fade_out_threshold: f32, // Defayklt: 1, // Between 0 and 1
+    #[default(0_f32)]
+    pub particle_loop_exit_threshold: f32, // Default: 0 // Between 0 and 1
+    #[default(1_u16)]
+    pub particle_loop_frame_count: u16, // Default: 1 // If less than 1, force 1
+    #[default(u16::MAX)]
+    pub particle_fade_out_duration: u16, // Default: u16::MAX // If less than 1, force 1
+    pub spine_animation: Option<Animation>,
+    pub particle: Option<Animation>,
+    pub shadow: Option<Animation>,
+    pub smoke_sources: Option<Vec<SmokeSource>>,
+    #[default(0.5_f32)]
+    pub progress_to_create_smoke: f32, // Default: 0.5
+    pub stream_light: Option<LightDefinition>,
+    pub ground_light: Option<LightDefinition>,
+    #[default(0_f64)]
+    pub target_position_deviation: f64, // Default: 0
+    #[default(false)]
+    pub oriented_particle: bool, // Default: false
+    #[default(false)]
+    pub shadow_scale_enabled: bool, // Default: false
+}
+
+impl FluidStream {
+    fn post_extr_fn(&mut self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            self.particle_horizontal_speed <= 0.0,
+            "FluidStream",
+            "`particle_horizontal_speed` must be higher than 0",
+        )?;
+        prot_from_lua_err(
+            self.particle_horizontal_speed <= self.particle_horizontal_speed_deviation,
+            "FluidStream",
+            "`particle_horizontal_speed` must be greater than `particle_horizontal_speed_deviation`",
+        )?;
+        prot_from_lua_err(
+            self.particle_buffer_size >= 256,
+            "FluidStream",
+            "`particle_buffer_size` must be less than 256",
+        )?;
+        prot_from_lua_err(
+            !(0.0..=1.0).contains(&self.particle_fade_out_threshold),
+            "FluidStream",
+            "`particle_fade_out_threshold` must be between 0 and 1",
+        )?;
+        prot_from_lua_err(
+            !(0.0..=1.0).contains(&self.particle_loop_exit_threshold),
+            "FluidStream",
+            "`particle_loop_exit_threshold` must be between 0 and 1",
+        )?;
+        self.particle_loop_frame_count = self.particle_loop_frame_count.max(1);
+        self.particle_fade_out_duration = self.particle_fade_out_duration.max(1);
+        Ok(())
+    }
 }
 
 /// <https://wiki.factorio.com/Prototype/FlyingText>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(flying_text)]
 pub struct FlyingText {
     name: String,
@@ -4310,17 +7933,19 @@ pub struct FlyingText {
 }
 
 /// <https://wiki.factorio.com/Prototype/HighlightBoxEntity>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(highlight_box)]
 pub struct HighlightBoxEntity {
     // Bruh
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     entity: EntityBase,
 }
 
 /// <https://wiki.factorio.com/Prototype/ItemEntity>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(item_entity)]
 pub struct ItemEntity {
     // Bruh
@@ -4330,7 +7955,7 @@ pub struct ItemEntity {
 }
 
 /// <https://wiki.factorio.com/Prototype/ItemRequestProxy>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(item_request_proxy)]
 pub struct ItemRequestProxy {
     name: String,
@@ -4341,7 +7966,7 @@ pub struct ItemRequestProxy {
 }
 
 /// <https://wiki.factorio.com/Prototype/ParticleSource>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(particle_source)]
 pub struct ParticleSource {
     name: String,
@@ -4368,7 +7993,7 @@ pub enum ParticleSourceParticleOrSmoke {
 }
 
 /// <https://wiki.factorio.com/Prototype/Projectile>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(projectile)]
 pub struct Projectile {
     name: String,
@@ -4397,7 +8022,7 @@ pub struct Projectile {
 }
 
 /// <https://wiki.factorio.com/Prototype/ResourceEntity>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(resource)]
 pub struct ResourceEntity {
     name: String,
@@ -4427,95 +8052,148 @@ pub struct ResourceEntity {
 }
 
 /// <https://wiki.factorio.com/Prototype/RocketSiloRocket>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(rocket_silo_rocket)]
 pub struct RocketSiloRocket {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    entity: EntityBase,
-    rocket_sprite: Sprite,
-    rocket_shadow_sprite: Sprite,
-    rocket_glare_overlay_sprite: Sprite,
-    rocket_smoke_bottom1_animation: Animation,
-    rocket_smoke_bottom2_animation: Animation,
-    rocket_smoke_top1_animation: Animation,
-    rocket_smoke_top2_animation: Animation,
-    rocket_smoke_top3_animation: Animation,
-    rocket_flame_animation: Animation,
-    rocket_flame_left_animation: Animation,
-    rocket_flame_right_animation: Animation,
-    rocket_rise_offset: Factorio2DVector,
-    rocket_flame_left_rotation: f32,
-    rocket_flame_right_rotation: f32,
-    rocket_render_layer_switch_distance: f64,
-    full_render_layer_switch_distance: f64,
-    rocket_launch_offset: Factorio2DVector,
-    effects_fade_in_start_distance: f64,
-    effects_fade_in_end_distance: f64,
-    shadow_fade_out_start_ratio: f64,
-    shadow_fade_out_end_ratio: f64,
-    rocket_visible_distance_from_center: f64,
-    rising_speed: f64,
-    engine_starting_speed: f64,
-    flying_speed: f64,
-    flying_acceleration: f64,
-    inventory_size: ItemStackIndex,
-    shadow_slave_entity: Option<String>, // Name of an entity
-    dying_explosion: Option<String>,     // Name of an entity
-    glow_light: Option<LightDefinition>,
-    rocket_initial_offset: Factorio2DVector,
-    rocket_above_wires_slice_offset_from_center: f64, // Default: -3
-    rocket_air_object_slice_offset_from_center: f64,  // Default: -5.5
-    flying_trigger: Option<TriggerEffect>,
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
+    pub entity: EntityBase,
+    pub rocket_sprite: Sprite,
+    pub rocket_shadow_sprite: Sprite,
+    pub rocket_glare_overlay_sprite: Sprite,
+    pub rocket_smoke_bottom1_animation: Animation,
+    pub rocket_smoke_bottom2_animation: Animation,
+    pub rocket_smoke_top1_animation: Animation,
+    pub rocket_smoke_top2_animation: Animation,
+    pub rocket_smoke_top3_animation: Animation,
+    pub rocket_flame_animation: Animation,
+    pub rocket_flame_left_animation: Animation,
+    pub rocket_flame_right_animation: Animation,
+    pub rocket_rise_offset: Factorio2DVector,
+    pub rocket_flame_left_rotation: f32,
+    pub rocket_flame_right_rotation: f32,
+    pub rocket_render_layer_switch_distance: f64,
+    pub full_render_layer_switch_distance: f64,
+    pub rocket_launch_offset: Factorio2DVector,
+    pub effects_fade_in_start_distance: f64,
+    pub effects_fade_in_end_distance: f64,
+    pub shadow_fade_out_start_ratio: f64,
+    pub shadow_fade_out_end_ratio: f64,
+    pub rocket_visible_distance_from_center: f64,
+    pub rising_speed: f64,
+    pub engine_starting_speed: f64,
+    pub flying_speed: f64,
+    pub flying_acceleration: f64,
+    pub inventory_size: ItemStackIndex,
+    pub shadow_slave_entity: Option<String>, // Name of an entity
+    pub dying_explosion: Option<String>,     // Name of an entity
+    pub glow_light: Option<LightDefinition>,
+    pub rocket_initial_offset: Factorio2DVector,
+    #[default(-3_f64)]
+    pub rocket_above_wires_slice_offset_from_center: f64, // Default: -3
+    #[default(-5.5_f64)]
+    pub rocket_air_object_slice_offset_from_center: f64, // Default: -5.5
+    pub flying_trigger: Option<TriggerEffect>,
 }
 
 /// <https://wiki.factorio.com/Prototype/RocketSiloRocketShadow>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(rocket_silo_rocket_shadow)]
 pub struct RocketSiloRocketShadow {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    entity: EntityBase,
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
+    pub entity: EntityBase,
 }
 
 /// <https://wiki.factorio.com/Prototype/SmokeWithTrigger>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(smoke_with_trigger)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct SmokeWithTrigger {
     // Collision box must be zero
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    entity: EntityBase,
-    animation: Animation,
-    cyclic: bool,         // Default: false
-    duration: u32,        // Default: 0 // May not be 0 if `cyclic` is true
-    spread_duration: u32, // Default: 0
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
+    pub entity: EntityBase,
+    pub animation: Animation,
+    #[default(false)]
+    pub cyclic: bool, // Default: false
+    #[default(0_u32)]
+    pub duration: u32, // Default: 0 // May not be 0 if `cyclic` is true
+    #[default(0_u32)]
+    pub spread_duration: u32, // Default: 0
     // `fade_in_duration` + `fade_away_duration` must be <= `duration`
-    fade_away_duration: u32,        // Default: 0
-    fade_in_duration: u32,          // Default: 0
-    start_scale: f64,               // Default: 1
-    end_scale: f64,                 // Default: 1
-    color: Color,                   // Default: (0.375, 0.375, 0.375, 0.375) [rgba]
-    affected_by_wind: bool,         // Default: true
-    show_when_smoke_off: bool,      // Default: false
-    render_layer: RenderLayer,      // Default: "smoke"
-    movement_slow_down_factor: f64, // Default: 0.995 // Must be [0; 1]
-    glow_fade_away_duration: u32,   // Default: `fade_away_duration`
-    glow_animation: Option<Animation>,
-    action: Option<Trigger>,
-    action_cooldown: u32,                // Default: 0
-    particle_count: u8,                  // Default: 1
-    particle_distance_scale_factor: f32, // Default: 0
-    spread_duration_variation: u32,      // Default: 0
-    particle_duration_variation: u32,    // Default: 0
-    particle_spread: Option<Factorio2DVector>,
-    particle_scale_factor: Option<Factorio2DVector>,
-    wave_distance: Option<Factorio2DVector>,
-    wave_speed: Option<Factorio2DVector>,
+    #[default(0_u32)]
+    pub fade_away_duration: u32, // Default: 0
+    #[default(0_u32)]
+    pub fade_in_duration: u32, // Default: 0
+    #[default(1_f64)]
+    pub start_scale: f64, // Default: 1
+    #[default(1_f64)]
+    pub end_scale: f64, // Default: 1
+    #[default(Color::SMOKE)]
+    pub color: Color, // Default: (0.375, 0.375, 0.375, 0.375) [rgba]
+    #[default(true)]
+    pub affected_by_wind: bool, // Default: true
+    #[default(false)]
+    pub show_when_smoke_off: bool, // Default: false
+    #[default(RenderLayer::Smoke)]
+    pub render_layer: RenderLayer, // Default: "smoke"
+    #[default(0.995_f64)]
+    pub movement_slow_down_factor: f64, // Default: 0.995 // Must be [0; 1]
+    #[default(fade_away_duration)]
+    pub glow_fade_away_duration: u32, // Default: `fade_away_duration`
+    pub glow_animation: Option<Animation>,
+    pub action: Option<Trigger>,
+    #[default(0_u32)]
+    pub action_cooldown: u32, // Default: 0
+    #[default(1_u8)]
+    pub particle_count: u8, // Default: 1
+    #[default(0_f32)]
+    pub particle_distance_scale_factor: f32, // Default: 0
+    #[default(0_u32)]
+    pub spread_duration_variation: u32, // Default: 0
+    #[default(0_u32)]
+    pub particle_duration_variation: u32, // Default: 0
+    pub particle_spread: Option<Factorio2DVector>,
+    pub particle_scale_factor: Option<Factorio2DVector>,
+    pub wave_distance: Option<Factorio2DVector>,
+    pub wave_speed: Option<Factorio2DVector>,
+}
+
+impl SmokeWithTrigger {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            self.entity.collision_box != BoundingBox::default(),
+            "SmokeWithTrigger",
+            "`collision_box` must be zero",
+        )?;
+        prot_from_lua_err(
+            self.cyclic && self.duration == 0,
+            "SmokeWithTrigger",
+            "`duration` can't be 0 if `cyclic` is true",
+        )?;
+        prot_from_lua_err(
+            self.fade_in_duration.saturating_add(self.fade_away_duration) > self.duration,
+            "SmokeWithTrigger",
+            "`fade_in_duration` + `fade_away_duration` must be <= `duration`",
+        )?;
+        prot_from_lua_err(
+            !(0.0..=1.0).contains(&self.movement_slow_down_factor),
+            "SmokeWithTrigger",
+            "`movement_slow_down_factor` must be between 0 and 1",
+        )?;
+        Ok(())
+    }
 }
 
 /// <https://wiki.factorio.com/Prototype/SpeechBubble>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(speech_bubble)]
 pub struct SpeechBubble {
     name: String,
@@ -4524,17 +8202,17 @@ pub struct SpeechBubble {
     style: String, // Needs a style of the type "speech_bubble_style", defined inside the gui styles.
     wrapper_flow_style: String, // Default: "flow_style" // Needs a style of the type "flow_style", defined inside the gui styles.
     y_offset: f64,              // Default: 0
-    fade_in_out_ticks: u32,     // Default: 60
+    fade_in_out_ticks: Ticks,   // Default: 60
 }
 
 /// <https://wiki.factorio.com/Prototype/Sticker>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(sticker)]
 pub struct Sticker {
     name: String,
     prototype_base: PrototypeBaseSpec,
     entity: EntityBase,
-    duration_in_ticks: u32, // Must be > 0
+    duration_in_ticks: Ticks, // Must be > 0
     animation: Option<Animation>,
     damage_interval: u32,               // Default: 1
     spread_fire_entity: Option<String>, // Name of an entity
@@ -4557,7 +8235,7 @@ pub struct Sticker {
 }
 
 /// <https://wiki.factorio.com/Prototype/TileGhost>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Entity!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(tile_ghost)]
 pub struct TileGhost {
     name: String,
@@ -4566,20 +8244,20 @@ pub struct TileGhost {
 }
 
 /// <https://wiki.factorio.com/Prototype/Equipment>
-#[derive(Debug, Clone, Base)]
+#[derive(Debug, Clone, Base, PrototypeFromLua, ResourceWalk, HeapSize)]
 pub struct EquipmentBase {
     sprite: Sprite,
     shape: EquipmentShape,
     categories: Vec<String>,         // (Names) Name of EquipmentCategory
     energy_source: EnergySource,     // Must be electric
-    take_result: String,             // Default: name of this prototype
-    background_color: Color, // Default: value of equipment_default_background_color in the utility constants
-    background_border_color: Color, // Default: value of equipment_default_background_border_color in the utility constants
-    grabbed_background_color: Color, // Default: value of equipment_default_grabbed_background_color in the utility constants
+    take_result: Option<String>,     // Default: name of this prototype
+    background_color: Option<Color>, // Default: value of equipment_default_background_color in the utility constants
+    background_border_color: Option<Color>, // Default: value of equipment_default_background_border_color in the utility constants
+    grabbed_background_color: Option<Color>, // Default: value of equipment_default_grabbed_background_color in the utility constants
 }
 
 /// <https://wiki.factorio.com/Prototype/ActiveDefenseEquipment>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(active_defense_equipment)]
 pub struct ActiveDefenseEquipment {
     name: String,
@@ -4590,7 +8268,7 @@ pub struct ActiveDefenseEquipment {
 }
 
 /// <https://wiki.factorio.com/Prototype/BatteryEquipment>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(battery_equipment)]
 pub struct BatteryEquipment {
     name: String,
@@ -4599,7 +8277,7 @@ pub struct BatteryEquipment {
 }
 
 /// <https://wiki.factorio.com/Prototype/BeltImmunityEquipment>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(belt_immunity_equipment)]
 pub struct BeltImmunityEquipment {
     name: String,
@@ -4609,7 +8287,7 @@ pub struct BeltImmunityEquipment {
 }
 
 /// <https://wiki.factorio.com/Prototype/EnergyShieldEquipment>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(energy_shield_equipment)]
 pub struct EnergyShieldEquipment {
     name: String,
@@ -4620,7 +8298,7 @@ pub struct EnergyShieldEquipment {
 }
 
 /// <https://wiki.factorio.com/Prototype/GeneratorEquipment>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(generator_equipment)]
 pub struct GeneratorEquipment {
     name: String,
@@ -4631,7 +8309,7 @@ pub struct GeneratorEquipment {
 }
 
 /// <https://wiki.factorio.com/Prototype/MovementBonusEquipment>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(movement_bonus_equipment)]
 pub struct MovementBonusEquipment {
     name: String,
@@ -4642,21 +8320,35 @@ pub struct MovementBonusEquipment {
 }
 
 /// <https://wiki.factorio.com/Prototype/NightVisionEquipment>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(night_vision_equipment)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct NightVisionEquipment {
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     equipment: EquipmentBase,
     energy_input: Energy,
     color_lookup: DaytimeColorLookupTable,
+    #[default(0.5_f32)]
     darkness_to_turn_on: f32, // Default: 0.5 // Must be >= 0 and <= 1
     activate_sound: Option<Sound>,
     deactivate_sound: Option<Sound>,
 }
 
+impl NightVisionEquipment {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(
+            !(0.0..=1.0).contains(&self.darkness_to_turn_on),
+            "NightVisionEquipment",
+            "`darkness_to_turn_on` must be in range [0, 1]",
+        )
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/RoboportEquipment>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(roboport_equipment)]
 pub struct RoboportEquipment {
     name: String,
@@ -4686,7 +8378,7 @@ pub struct RoboportEquipment {
 }
 
 /// <https://wiki.factorio.com/Prototype/SolarPanelEquipment>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Equipment!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(solar_panel_equipment)]
 pub struct SolarPanelEquipment {
     name: String,
@@ -4696,15 +8388,16 @@ pub struct SolarPanelEquipment {
 }
 
 /// <https://wiki.factorio.com/Prototype/EquipmentCategory>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(equipment_category)]
 pub struct EquipmentCategory {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
 }
 
 /// <https://wiki.factorio.com/Prototype/EquipmentGrid>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(equipment_grid)]
 pub struct EquipmentGrid {
     name: String,
@@ -4716,7 +8409,7 @@ pub struct EquipmentGrid {
 }
 
 /// <https://wiki.factorio.com/Prototype/Fluid>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(fluid)]
 pub struct Fluid {
     name: String,
@@ -4736,15 +8429,16 @@ pub struct Fluid {
 }
 
 /// <https://wiki.factorio.com/Prototype/FuelCategory>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(fuel_category)]
 pub struct FuelCategory {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
 }
 
 /// <https://wiki.factorio.com/Prototype/GuiStyle>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(gui_style)]
 pub struct GuiStyle {
     name: String,
@@ -4759,24 +8453,39 @@ pub struct GuiStyle {
 }
 
 /// <https://wiki.factorio.com/Prototype/Item>
-#[derive(Debug, Clone, Base)]
+#[derive(Debug, Clone, PrototypeFromLua, Base, ResourceWalk, HeapSize)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct ItemBase {
-    icon: IconSpecification,
-    stack_size: u32,                    // Must be 1 when "not-stackable" flag is set
-    place_result: String,               // Default: "" // Name of Entity
+    #[use_self_forced]
+    #[loaded_if(prot_table.contains_key("icon")? || prot_table.contains_key("icons")?)]
+    icon: Option<IconSpecification>, // Mandatory unless `place_result` is set, icon of the entity is used then
+    stack_size: u32, // Must be 1 when "not-stackable" flag is set
+    #[default("")]
+    place_result: String, // Default: "" // Name of Entity
+    #[default("")]
     placed_as_equipment_result: String, // Default: ""
-    subgroup: String,                   // Default: "other" // Empty text is not allowed
+    #[default("other")]
+    subgroup: String, // Default: "other" // Empty text is not allowed
+    #[default("")]
     fuel_category: String, // Default: "" // Must exist when fuel_value is defined // Name of FuelCategory
-    burnt_result: String,  // Default: "" // Name of Item
+    #[default("")]
+    burnt_result: String, // Default: "" // Name of Item
     place_as_tile: Option<PlaceAsTile>,
     pictures: Option<SpriteVariations>, // 16 max
     flags: Option<ItemPrototypeFlags>,
-    default_request_amount: u32,       // Default: `stack_size`
-    wire_count: u32,                   // Default: 0
+    #[default(stack_size)]
+    default_request_amount: u32, // Default: `stack_size`
+    #[default(0_u32)]
+    wire_count: u32, // Default: 0
+    #[default(Energy(0.0))]
     fuel_value: Energy, // Default: "0J" // Mandatory for: `fuel_acceleration_multiplier`, `fuel_top_speed_multiplier`, `fuel_emissions_multiplier`, `fuel_glow_color`
+    #[default(1.0_f64)]
     fuel_acceleration_multiplier: f64, // Default: 1.0
+    #[default(1.0_f64)]
     fuel_top_speed_multiplier: f64, // Default: 1.0
+    #[default(1.0_f64)]
     fuel_emissions_multiplier: f64, // Default: 1.0
+    #[default(Color::BLACK)]
     fuel_glow_color: Color, // Default: {r=0, g=0, b=0, a=1}
     open_sound: Option<Sound>,
     close_sound: Option<Sound>,
@@ -4785,8 +8494,61 @@ pub struct ItemBase {
     rocket_launch_product: Option<ItemProductPrototype>,
 }
 
+impl ItemBase {
+    /// Icon of the item, or icon of the entity from `place_result` if the item doesn't have one
+    pub fn effective_icon<'a>(&'a self, dt: &'a DataTable) -> Option<&'a IconSpecification> {
+        self.effective_icon_from(&dt.entity_icons())
+    }
+
+    /// Same as [ItemBase::effective_icon], with entity icons from [DataTable::entity_icons]
+    /// looked up once for all items
+    fn effective_icon_from<'a>(
+        &'a self,
+        entity_icons: &BTreeMap<&str, &'a IconSpecification>,
+    ) -> Option<&'a IconSpecification> {
+        self.icon
+            .as_ref()
+            .or_else(|| entity_icons.get(self.place_result.as_str()).copied())
+    }
+
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        if let Some(pictures) = &self.pictures {
+            pictures.require_variation_count(1, 16, "Item", "pictures")?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn item_icon_from_place_result() {
+    let lua = Lua::new();
+    let mut data_table = DataTable::default();
+    let entity = lua
+        .load(
+            r#"{
+                type = "simple-entity", name = "rock",
+                icon = "__base__/graphics/icons/rock.png", icon_size = 64,
+                picture = {filename = "__base__/graphics/entity/rock.png", size = 32},
+            }"#,
+        )
+        .eval()
+        .unwrap();
+    let entity = SimpleEntity::prototype_from_lua(entity, &lua, &mut data_table).unwrap();
+    data_table.extend(entity).unwrap();
+    let item = |source: &str| {
+        let value = lua.load(source).eval().unwrap();
+        ItemBase::prototype_from_lua(value, &lua, &mut DataTable::default()).unwrap()
+    };
+    // No `icon` or `icons` is not an error, the icon of `place_result` is used instead
+    let placed = item(r#"{type = "item", name = "rock", stack_size = 50, place_result = "rock"}"#);
+    assert!(placed.icon.is_none());
+    assert!(placed.effective_icon(&data_table).is_some());
+    let unplaced = item(r#"{type = "item", name = "pebble", stack_size = 50}"#);
+    assert!(unplaced.effective_icon(&data_table).is_none());
+}
+
 /// <https://wiki.factorio.com/Prototype/Item>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(item)]
 pub struct ItemPrototype {
     name: String,
@@ -4795,7 +8557,7 @@ pub struct ItemPrototype {
 }
 
 /// <https://wiki.factorio.com/Prototype/AmmoItem>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(ammo)]
 pub struct AmmoItem {
     name: String,
@@ -4807,7 +8569,7 @@ pub struct AmmoItem {
 }
 
 /// <https://wiki.factorio.com/Prototype/Capsule>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(capsule)]
 pub struct Capsule {
     name: String,
@@ -4818,7 +8580,7 @@ pub struct Capsule {
 }
 
 /// <https://wiki.factorio.com/Prototype/Gun>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(gun)]
 pub struct Gun {
     name: String,
@@ -4828,7 +8590,7 @@ pub struct Gun {
 }
 
 /// <https://wiki.factorio.com/Prototype/ItemWithEntityData>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(item_with_entity_data)]
 pub struct ItemWithEntityData {
     name: String,
@@ -4839,7 +8601,7 @@ pub struct ItemWithEntityData {
 }
 
 /// <https://wiki.factorio.com/Prototype/ItemWithLabel>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(item_with_label)]
 pub struct ItemWithLabel {
     name: String,
@@ -4850,7 +8612,7 @@ pub struct ItemWithLabel {
 }
 
 /// <https://wiki.factorio.com/Prototype/ItemWithInventory>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(item_with_inventory)]
 pub struct ItemWithInventory {
     // Stack size must be 1
@@ -4870,7 +8632,7 @@ pub struct ItemWithInventory {
 }
 
 /// <https://wiki.factorio.com/Prototype/BlueprintBook>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(blueprint_book)]
 pub struct BlueprintBook {
     // Stack size must be 1
@@ -4890,7 +8652,7 @@ pub struct BlueprintBook {
 }
 
 /// <https://wiki.factorio.com/Prototype/ItemWithTags>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(item_with_tags)]
 pub struct ItemWithTags {
     name: String,
@@ -4901,7 +8663,7 @@ pub struct ItemWithTags {
 }
 
 /// <https://wiki.factorio.com/Prototype/SelectionTool>
-#[derive(Debug, Clone, Base)]
+#[derive(Debug, Clone, Base, PrototypeFromLua, HeapSize)]
 pub struct SelectionToolBase {
     selection_color: Color,
     alt_selection_color: Color,
@@ -4909,44 +8671,96 @@ pub struct SelectionToolBase {
     alt_selection_mode: SelectionMode,
     selection_cursor_box_type: CursorBoxType,
     alt_selection_cursor_box_type: CursorBoxType,
+    #[default(selection_color.clone())]
     reverse_selection_color: Color, // Default: Value of `selection_color`
+    #[default(selection_color.clone())]
     selection_count_button_color: Color, // Default: Value of `selection_color`
+    #[default(alt_selection_color.clone())]
     alt_selection_count_button_color: Color, // Default: Value of `alt_selection_color`
+    #[default(reverse_selection_color.clone())]
     reverse_selection_count_button_color: Color, // Default: Value of `reverse_selection_color`
-    chart_selection_color: Color,   // Default: Value of `selection_color`
+    #[default(selection_color.clone())]
+    chart_selection_color: Color, // Default: Value of `selection_color`
+    #[default(alt_selection_color.clone())]
     chart_alt_selection_color: Color, // Default: Value of alt_selection_color
+    #[default(reverse_selection_color.clone())]
     chart_reverse_selection_color: Color, // Default: Value of `reverse_selection_color`
+    #[default(selection_mode)]
     reverse_selection_mode: SelectionMode, // Default: Value of `selection_mode`
+    #[default(selection_cursor_box_type)]
     reverse_selection_cursor_box_type: CursorBoxType, // Default: Value of `selection_cursor_box_type`
-    always_include_tiles: bool,                       // Default: false
+    #[default(false)]
+    always_include_tiles: bool, // Default: false
+    #[default("selection-tool-cursor")]
     mouse_cursor: String, // Default: "selection-tool-cursor" // Name of Prototype/MouseCursor
     entity_filters: Option<Vec<String>>, // (Names) Name of Entity
     alt_entity_filters: Option<Vec<String>>, // (Names) Name of Entity
     entity_type_filters: Option<Vec<String>>, // (Names) Name of Entity type
     alt_entity_type_filters: Option<Vec<String>>, // (Names) Name of Entity type
-    tile_filters: Option<Vec<String>>, // (Names) Name of a Tile
+    tile_filters: Option<Vec<String>>,   // (Names) Name of a Tile
     alt_tile_filters: Option<Vec<String>>, // (Names) Name of a Tile
+    #[default(FilterMode::Whitelist)]
     entity_filter_mode: FilterMode, // Default: "whitelist"
+    #[default(FilterMode::Whitelist)]
     alt_entity_filter_mode: FilterMode, // Default: "whitelist"
+    #[default(FilterMode::Whitelist)]
     tile_filter_mode: FilterMode, // Default: "whitelist"
+    #[default(FilterMode::Whitelist)]
     alt_tile_filter_mode: FilterMode, // Default: "whitelist"
 }
 
+impl SelectionToolBase {
+    /// Override properties that are ignored by blueprint, copy-paste, deconstruction and upgrade
+    /// items with the values the game uses. `modes` are forced `selection_mode` and
+    /// `alt_selection_mode`, if the item has them.
+    fn force_ignored(&mut self, modes: Option<(SelectionMode, SelectionMode)>) {
+        if let Some((selection_mode, alt_selection_mode)) = modes {
+            self.selection_mode = selection_mode;
+            self.alt_selection_mode = alt_selection_mode;
+        }
+        self.always_include_tiles = false;
+        self.entity_filters = None;
+        self.entity_type_filters = None;
+        self.tile_filters = None;
+        self.entity_filter_mode = FilterMode::Whitelist;
+        self.tile_filter_mode = FilterMode::Whitelist;
+        self.alt_entity_filters = None;
+        self.alt_entity_type_filters = None;
+        self.alt_tile_filters = None;
+        self.alt_entity_filter_mode = FilterMode::Whitelist;
+        self.alt_tile_filter_mode = FilterMode::Whitelist;
+    }
+}
+
+/// Check shared by items which can't be stacked
+fn check_single_stack(item: &ItemBase, type_name: &'static str) -> LuaResult<()> {
+    prot_from_lua_err(
+        item.stack_size != 1,
+        type_name,
+        format!("`stack_size` must be 1, got {}", item.stack_size),
+    )
+}
+
 /// <https://wiki.factorio.com/Prototype/SelectionTool>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, SelectionTool!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, SelectionTool!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(selection_tool)]
 pub struct SelectionToolPrototype {
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     item: ItemBase,
-    default_label_color: Color,         // Default: default item text color
+    default_label_color: Option<Color>, // Default: default item text color
+    #[default(false)]
     draw_label_for_cursor_render: bool, // Default: false
+    #[use_self_forced]
     selection_tool: SelectionToolBase,
 }
 
 /// <https://wiki.factorio.com/Prototype/BlueprintItem>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, SelectionTool!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, SelectionTool!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(blueprint)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct BlueprintItem {
     // Stack size must be 1
     // Ignored/forced properties:
@@ -4964,16 +8778,30 @@ pub struct BlueprintItem {
     // alt_entity_filter_mode
     // alt_tile_filter_mode
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     item: ItemBase,
-    default_label_color: Color,         // Default: default item text color
+    default_label_color: Option<Color>, // Default: default item text color
+    #[default(false)]
     draw_label_for_cursor_render: bool, // Default: false
+    #[use_self_forced]
     selection_tool: SelectionToolBase,
 }
 
+impl BlueprintItem {
+    fn post_extr_fn(&mut self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        check_single_stack(&self.item, "BlueprintItem")?;
+        self.selection_tool
+            .force_ignored(Some((SelectionMode::BLUEPRINT, SelectionMode::BLUEPRINT)));
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/CopyPasteTool>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, SelectionTool!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, SelectionTool!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(copy_paste_tool)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct CopyPasteTool {
     // Stack size must be 1
     // Ignored/forced properties:
@@ -4989,17 +8817,31 @@ pub struct CopyPasteTool {
     // alt_entity_filter_mode
     // alt_tile_filter_mode
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     item: ItemBase,
-    default_label_color: Color,         // Default: default item text color
+    default_label_color: Option<Color>, // Default: default item text color
+    #[default(false)]
     draw_label_for_cursor_render: bool, // Default: false
+    #[use_self_forced]
     selection_tool: SelectionToolBase,
+    #[default(false)]
     cuts: bool, // Default: false
 }
 
+impl CopyPasteTool {
+    fn post_extr_fn(&mut self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        check_single_stack(&self.item, "CopyPasteTool")?;
+        self.selection_tool.force_ignored(None);
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/DeconstructionItem>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, SelectionTool!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, SelectionTool!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(deconstruction_item)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct DeconstructionItem {
     // Stack size must be 1
     // Ignored/forced properties:
@@ -5017,18 +8859,41 @@ pub struct DeconstructionItem {
     // alt_entity_filter_mode
     // alt_tile_filter_mode
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     item: ItemBase,
-    default_label_color: Color,         // Default: default item text color
+    default_label_color: Option<Color>, // Default: default item text color
+    #[default(false)]
     draw_label_for_cursor_render: bool, // Default: false
+    #[use_self_forced]
     selection_tool: SelectionToolBase,
+    #[default(0_u16)]
     entity_filter_count: ItemStackIndex, // Default: 0 // Can't be > 255 // So u8? Kappa
-    tile_filter_count: ItemStackIndex,   // Default: 0 // Can't be > 255
+    #[default(0_u16)]
+    tile_filter_count: ItemStackIndex, // Default: 0 // Can't be > 255
+}
+
+impl DeconstructionItem {
+    fn post_extr_fn(&mut self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        check_single_stack(&self.item, "DeconstructionItem")?;
+        prot_from_lua_err(
+            self.entity_filter_count > 255 || self.tile_filter_count > 255,
+            "DeconstructionItem",
+            "`entity_filter_count` and `tile_filter_count` can't be larger than 255",
+        )?;
+        self.selection_tool.force_ignored(Some((
+            SelectionMode::DECONSTRUCT,
+            SelectionMode::CANCEL_DECONSTRUCT,
+        )));
+        Ok(())
+    }
 }
 
 /// <https://wiki.factorio.com/Prototype/UpgradeItem>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, SelectionTool!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, SelectionTool!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(upgrade_item)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct UpgradeItem {
     // Stack size must be 1
     // Ignored/forced properties:
@@ -5046,16 +8911,37 @@ pub struct UpgradeItem {
     // alt_entity_filter_mode
     // alt_tile_filter_mode
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     item: ItemBase,
-    default_label_color: Color,         // Default: default item text color
+    default_label_color: Option<Color>, // Default: default item text color
+    #[default(false)]
     draw_label_for_cursor_render: bool, // Default: false
+    #[use_self_forced]
     selection_tool: SelectionToolBase,
+    #[default(0_u16)]
     mapper_count: ItemStackIndex, // Default: 0 // Can't be > 255
 }
 
+impl UpgradeItem {
+    fn post_extr_fn(&mut self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        check_single_stack(&self.item, "UpgradeItem")?;
+        prot_from_lua_err(
+            self.mapper_count > 255,
+            "UpgradeItem",
+            "`mapper_count` can't be larger than 255",
+        )?;
+        self.selection_tool.force_ignored(Some((
+            SelectionMode::UPGRADE,
+            SelectionMode::CANCEL_UPGRADE,
+        )));
+        Ok(())
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/Module>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(module)]
 pub struct Module {
     name: String,
@@ -5073,7 +8959,7 @@ pub struct Module {
 }
 
 /// <https://wiki.factorio.com/Prototype/RailPlanner>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(rail_planner)]
 pub struct RailPlanner {
     name: String,
@@ -5084,7 +8970,7 @@ pub struct RailPlanner {
 }
 
 /// <https://wiki.factorio.com/Prototype/SpidertronRemote>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(spidertron_remote)]
 pub struct SpidertronRemote {
     name: String,
@@ -5093,79 +8979,162 @@ pub struct SpidertronRemote {
     icon_color_indicator_mask: IconSpecification,
 }
 
+/// Durability rules shared by [Tool], [Armor] and [RepairTool]: `durability` is mandatory and
+/// must be positive unless the tool is `infinite`, and durability description locale keys may
+/// not be longer than 200 characters.
+fn check_durability(
+    type_name: &'static str,
+    durability: Option<f64>,
+    infinite: bool,
+    description_key: &str,
+    description_value: &str,
+) -> LuaResult<()> {
+    let err = |message: &str| conversion_error("table", type_name, Some(message.into()));
+    match durability {
+        Some(durability) if durability <= 0.0 => return Err(err("`durability` must be positive")),
+        None if !infinite => return Err(err("`durability` is mandatory when `infinite` is false")),
+        _ => {}
+    }
+    if description_key.len() > 200 || description_value.len() > 200 {
+        return Err(err(
+            "`durability_description_key` and `durability_description_value` may not be longer than 200 characters",
+        ));
+    }
+    Ok(())
+}
+
 /// <https://wiki.factorio.com/Prototype/Tool>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(tool)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct Tool {
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     item: ItemBase,
-    durability: Option<f64>, // Must be positive // Mandatory if `infinite` = false, ignored if true
+    durability: Option<f64>, // Must be positive // Mandatory if `infinite` is false
+    #[default("description.durability-key")]
     durability_description_key: String, // Default: "description.durability-key" // May not be longer than 200 characters
+    #[default("description.durability-value")]
     durability_description_value: String, // Default: "description.durability-value" // May not be longer than 200 characters
-    infinite: bool,                       // Default: false // If false, `durability` must be set
+    #[default(false)]
+    infinite: bool, // Default: false
+}
+
+impl Tool {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        check_durability(
+            "Tool",
+            self.durability,
+            self.infinite,
+            &self.durability_description_key,
+            &self.durability_description_value,
+        )
+    }
 }
 
 /// <https://wiki.factorio.com/Prototype/Armor>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(armor)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct Armor {
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     item: ItemBase,
-    durability: f64, // Must be positive // Mandatory if `infinite` is false
+    durability: Option<f64>, // Must be positive // Mandatory if `infinite` is false
+    #[default("description.durability-key")]
     durability_description_key: String, // Default: "description.durability-key" // May not be longer than 200 characters
+    #[default("description.durability-value")]
     durability_description_value: String, // Default: "description.durability-value" // May not be longer than 200 characters
-    infinite: bool,                       // Default: true
-    equipment_grid: Option<String>,       // Name of EquipmentGrid
+    #[default(true)]
+    infinite: bool, // Default: true
+    equipment_grid: Option<String>, // Name of EquipmentGrid
     resistances: Option<Resistances>,
     inventory_size_bonus: Option<ItemStackIndex>,
 }
 
+impl Armor {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        check_durability(
+            "Armor",
+            self.durability,
+            self.infinite,
+            &self.durability_description_key,
+            &self.durability_description_value,
+        )
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/RepairTool>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, Item!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(repair_tool)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct RepairTool {
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
     item: ItemBase,
-    durability: f64, // Must be positive // Mandatory if `infinite` is false
+    durability: Option<f64>, // Must be positive // Mandatory if `infinite` is false
+    #[default("description.durability-key")]
     durability_description_key: String, // Default: "description.durability-key" // May not be longer than 200 characters
+    #[default("description.durability-value")]
     durability_description_value: String, // Default: "description.durability-value" // May not be longer than 200 characters
-    infinite: bool,                       // Default: true
+    #[default(true)]
+    infinite: bool, // Default: true
     speed: f32,
     repair_result: Option<Trigger>,
 }
 
+impl RepairTool {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        check_durability(
+            "RepairTool",
+            self.durability,
+            self.infinite,
+            &self.durability_description_key,
+            &self.durability_description_value,
+        )
+    }
+}
+
 /// <https://wiki.factorio.com/Prototype/ItemGroup>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(item_group)]
 pub struct ItemGroup {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    icon: IconSpecification,
-    order_in_recipe: String, // Default: `order`
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    #[use_self_forced]
+    pub icon: IconSpecification,
+    #[default(prototype_base.order.clone())]
+    pub order_in_recipe: String, // Default: `order`
 }
 
 /// <https://wiki.factorio.com/Prototype/ItemSubGroup>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(item_subgroup)]
 pub struct ItemSubGroup {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    group: String, // Name of ItemGroup
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    pub group: String, // Name of ItemGroup
 }
 
 /// <https://wiki.factorio.com/Prototype/ModuleCategory>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(module_category)]
 pub struct ModuleCategory {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
 }
 
 /// <https://wiki.factorio.com/Prototype/NamedNoiseExpression>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(noise_expression)]
 pub struct NamedNoiseExpression {
     name: String,
@@ -5175,7 +9144,7 @@ pub struct NamedNoiseExpression {
 }
 
 /// <https://wiki.factorio.com/Prototype/NoiseLayer>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(noise_layer)]
 pub struct NoiseLayer {
     name: String,
@@ -5183,30 +9152,61 @@ pub struct NoiseLayer {
 }
 
 /// <https://wiki.factorio.com/Prototype/Particle>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(optimized_particle)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct Particle {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    pictures: AnimationVariations,
-    life_time: u16, // Can't be 1
-    shadows: Option<AnimationVariations>,
-    draw_shadow_when_on_ground: bool, // Default: true
-    regular_trigger_effect: Option<TriggerEffect>,
-    ended_in_water_trigger_effect: Option<TriggerEffect>,
-    ended_on_ground_trigger_effect: Option<TriggerEffect>,
-    render_layer: RenderLayer,                // Default: "object"
-    render_layer_when_on_ground: RenderLayer, // Default: "lower-object"
-    regular_trigger_effect_frequency: u32,    // Default: 0 // Can't be 1
-    movement_modifier_when_on_ground: f64,    // Default: 0.8
-    movement_modifier: f64,                   // Default: 1
-    vertical_acceleration: f32,               // Default: -0.004 // Has to be >= -0.01 and <= 0.01
-    mining_particle_frame_speed: f32,         // Default: 0
-    fade_away_duration: u16, // Degault: `life-time`, capped to 60. If equals to 0, silently(?) changed to 1
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    pub pictures: AnimationVariations,
+    pub life_time: u16, // Can't be 1
+    pub shadows: Option<AnimationVariations>,
+    #[default(true)]
+    pub draw_shadow_when_on_ground: bool, // Default: true
+    pub regular_trigger_effect: Option<TriggerEffect>,
+    pub ended_in_water_trigger_effect: Option<TriggerEffect>,
+    pub ended_on_ground_trigger_effect: Option<TriggerEffect>,
+    #[default(RenderLayer::Object)]
+    pub render_layer: RenderLayer, // Default: "object"
+    #[default(RenderLayer::LowerObject)]
+    pub render_layer_when_on_ground: RenderLayer, // Default: "lower-object"
+    #[default(0_u32)]
+    pub regular_trigger_effect_frequency: u32, // Default: 0 // Can't be 1
+    #[default(0.8_f64)]
+    pub movement_modifier_when_on_ground: f64, // Default: 0.8
+    #[default(1_f64)]
+    pub movement_modifier: f64, // Default: 1
+    #[default(-0.004_f32)]
+    pub vertical_acceleration: f32, // Default: -0.004 // Has to be >= -0.01 and <= 0.01
+    #[default(0_f32)]
+    pub mining_particle_frame_speed: f32, // Default: 0
+    #[default(life_time.min(60))]
+    pub fade_away_duration: u16, // Degault: `life-time`, capped to 60. If equals to 0, silently(?) changed to 1
+}
+
+impl Particle {
+    fn post_extr_fn(&mut self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(self.life_time == 1, "Particle", "`life_time` can't be 1")?;
+        prot_from_lua_err(
+            self.regular_trigger_effect_frequency == 1,
+            "Particle",
+            "`regular_trigger_effect_frequency` can't be 1",
+        )?;
+        prot_from_lua_err(
+            !(-0.01..=0.01).contains(&self.vertical_acceleration),
+            "Particle",
+            "`vertical_acceleration` must be >= -0.01 and <= 0.01",
+        )?;
+        if self.fade_away_duration == 0 {
+            self.fade_away_duration = 1
+        }
+        Ok(())
+    }
 }
 
 /// <https://wiki.factorio.com/Prototype/Recipe>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(recipe)]
 pub struct Recipe {
     // recipe with category named "crafting" cannot have fluid ingredients or products
@@ -5216,31 +9216,56 @@ pub struct Recipe {
     subgroup: String, // Default: subgroup of product (if only 1) or main_product if multiple. Required either.
     icon: IconSpecification, // Mandatory for recipe with multiple products and no main_product. Defaults to icon of `main_product` or index 1
     crafting_machine_tint: RecipeTint, // Defaults to all zeros
-    // If one of the difficulties is defined, and the other is set to false, `enabled = false` in other and
-    // copies over protperties from the first difficulty. If one difficulty is not defined (set to nil), it uses other
-    // difficulty's properties. If RecipeData defined directly in table body (if there are no `normal` and `expensive`), set only `normal
-    normal: RecipeData,
-    expensive: Option<RecipeData>,
+    difficulty: DifficultyPair<RecipeData>,
+}
+
+#[cfg(test)]
+impl Recipe {
+    fn stub(
+        name: &str,
+        ingredients: &[(ProductType, f64)],
+        results: &[(ProductType, f64)],
+    ) -> Self {
+        Self {
+            name: name.into(),
+            prototype_base: PrototypeBaseSpec {
+                localised_description: None,
+                localised_name: None,
+                order: String::new(),
+            },
+            category: "crafting".into(),
+            subgroup: "intermediate-product".into(),
+            icon: base_game_stubs::stub_icon(),
+            crafting_machine_tint: RecipeTint::ZERO,
+            difficulty: DifficultyPair::new(
+                DifficultyValue::Defined(RecipeData::stub(ingredients, results)),
+                DifficultyValue::Undefined,
+            )
+            .unwrap(),
+        }
+    }
 }
 
 /// <https://wiki.factorio.com/Prototype/RecipeCategory>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(recipe_category)]
 pub struct RecipeCategory {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
 }
 
 /// <https://wiki.factorio.com/Prototype/ResourceCategory>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(resource_category)]
 pub struct ResourceCategory {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
 }
 
 /// <https://wiki.factorio.com/Prototype/Shortcut>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(shortcut)]
 pub struct Shortcut {
     name: String,
@@ -5258,49 +9283,72 @@ pub struct Shortcut {
 }
 
 /// <https://wiki.factorio.com/Prototype/Technology>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(technology)]
 pub struct Technology {
     name: String,
     prototype_base: PrototypeBaseSpec,
     icon: IconSpecification,
-    // Same deal as with Recipe prototype
-    normal: TechnologyData,
-    expensive: Option<TechnologyData>,
+    difficulty: DifficultyPair<TechnologyData>,
+}
+
+impl Technology {
+    /// Names of recipes unlocked in `normal` difficulty
+    pub fn unlocked_recipes(&self) -> Vec<&str> {
+        self.difficulty.normal().unlocked_recipes()
+    }
+
+    /// Effects of researching this technology in `normal` difficulty
+    pub fn modifiers(&self) -> &[ModifierPrototype] {
+        self.difficulty.normal().effects()
+    }
 }
 
 /// <https://wiki.factorio.com/Prototype/Tile>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(tile)]
 pub struct Tile {
     // 255 instances max
     name: String,
+    #[use_self_forced]
     prototype_base: PrototypeBaseSpec,
     collision_mask: CollisionMask,
     layer: u8,
     variants: MainTileTransitions,
     map_color: Color,
     pollution_absorption_per_second: f64,
-    transition_overlay_layer_offset: u8,         // Default: 0
-    layer_group: LayerGroup, // Default: "water" if `draw_in_water_layer` else "ground"
+    #[default(0_u8)]
+    transition_overlay_layer_offset: u8, // Default: 0
+    #[default(false)]
     draw_in_water_layer: bool, // Default: false
+    #[default(if draw_in_water_layer { LayerGroup::Water } else { LayerGroup::Ground })]
+    layer_group: LayerGroup, // Default: "water" if `draw_in_water_layer` else "ground"
     transition_merges_with_tile: Option<String>, // Name of a tile
-    effect_color: Color,     // Default: all 1
-    tint: Color,             // Default: all 1
+    #[default(Color::WHITE)]
+    effect_color: Color, // Default: all 1
+    #[default(Color::WHITE)]
+    tint: Color,     // Default: all 1
     walking_sound: Option<Sound>,
     build_sound: Option<TileBuildSound>,
     mined_sound: Option<Sound>,
-    walking_speed_modifier: f64,            // Default: 1
-    vehicle_friction_modifier: f64,         // Default: 1
-    decorative_removal_probability: f32,    // Default: 0
+    #[default(1_f64)]
+    walking_speed_modifier: f64, // Default: 1
+    #[default(1_f64)]
+    vehicle_friction_modifier: f64, // Default: 1
+    #[default(0_f32)]
+    decorative_removal_probability: f32, // Default: 0
     allowed_neighbors: Option<Vec<String>>, // (Names) Name of Tile
-    needs_correction: bool,                 // Default: false
+    #[default(false)]
+    needs_correction: bool, // Default: false
     minable: Option<MinableProperties>,
     next_direction: Option<String>, // Name of a Tile
+    #[default(true)]
     can_be_part_of_blueprint: bool, // Default: true
     effect: Option<String>,         // Name of TileEffect
     trigger_effect: Option<TriggerEffect>,
     scorch_mark_color: Option<Color>,
+    #[use_self_forced]
+    #[loaded_if(prot_table.contains_key("icon")? || prot_table.contains_key("icons")?)]
     icon: Option<IconSpecification>,
     effect_color_secondary: Option<Color>,
     effect_is_opaque: Option<bool>, // Why is there no default
@@ -5308,11 +9356,111 @@ pub struct Tile {
     transitions_between_transitions: Option<Vec<BetweenTileTransitions>>,
     autoplace: Option<AutoplaceSpecification>,
     placeable_by: Option<Vec<ItemToPlace>>,
+    #[default(false)]
     check_collision_with_entities: bool, // Default: false
 }
 
+#[test]
+fn tile_load() {
+    let lua = Lua::new();
+    let mut data_table = DataTable::default();
+    let tile = lua
+        .load(
+            r#"{
+                type = "tile", name = "deepwater",
+                collision_mask = {"water-tile", "resource-layer"},
+                layer = 3,
+                draw_in_water_layer = true,
+                variants = {
+                    empty_transitions = true,
+                    layer = 3,
+                    main = {{picture = "__base__/graphics/terrain/deepwater1.png", count = 8, size = 1}},
+                },
+                map_color = {r = 0.15, g = 0.25, b = 0.5},
+                pollution_absorption_per_second = 0.0001,
+                allowed_neighbors = {"water"},
+            }"#,
+        )
+        .eval()
+        .unwrap();
+    let tile = Tile::prototype_from_lua(tile, &lua, &mut data_table).unwrap();
+    assert_eq!(tile.layer_group, LayerGroup::Water);
+    assert_eq!(tile.tint, Color::WHITE);
+    assert!(tile.can_be_part_of_blueprint);
+    assert!(data_table
+        .resource_records
+        .iter()
+        .any(|r| r.path == "__base__/graphics/terrain/deepwater1.png"));
+    data_table.extend(tile).unwrap();
+    // `water` isn't loaded
+    assert!(data_table.validate_tile_references().is_err());
+}
+
+#[test]
+fn can_place_on_tiles() {
+    let lua = Lua::new();
+    let mut data_table = DataTable::default();
+    for (name, collision_mask) in [
+        ("grass-1", r#"{"ground-tile"}"#),
+        (
+            "water",
+            r#"{"water-tile", "item-layer", "resource-layer", "player-layer", "doodad-layer"}"#,
+        ),
+    ] {
+        let tile = lua
+            .load(&format!(
+                r#"{{
+                    type = "tile", name = "{name}", collision_mask = {collision_mask}, layer = 1,
+                    variants = {{
+                        empty_transitions = true,
+                        layer = 1,
+                        main = {{{{picture = "__base__/graphics/terrain/{name}.png", count = 1, size = 1}}}},
+                    }},
+                    map_color = {{r = 0, g = 0, b = 0}},
+                    pollution_absorption_per_second = 0,
+                }}"#
+            ))
+            .eval()
+            .unwrap();
+        let tile = Tile::prototype_from_lua(tile, &lua, &mut data_table).unwrap();
+        data_table.extend(tile).unwrap();
+    }
+    // Stub furnace has the default collision mask of buildings, which includes "water-tile"
+    data_table.add_stubs("furnace stone-furnace").unwrap();
+    let offshore_pump = lua
+        .load(
+            r#"
+                local picture = {filename = "__base__/graphics/entity/offshore-pump.png", size = 1}
+                return {
+                    type = "offshore-pump", name = "offshore-pump",
+                    collision_mask = {"object-layer", "train-layer"},
+                    map_generator_bounding_box = {{-0.5, -0.5}, {0.5, 0.5}},
+                    repair_sound = {filename = "__core__/sound/silence-1sec.ogg"},
+                    integration_patch = {},
+                    fluid = "water",
+                    pumping_speed = 20,
+                    fluid_box = {
+                        pipe_connections = {{positions = {{0, 1}, {-1, 0}, {0, -1}, {1, 0}}}},
+                        secondary_draw_orders = {north = -1},
+                    },
+                    picture = {north = picture, east = picture, south = picture, west = picture},
+                }
+            "#,
+        )
+        .eval()
+        .unwrap();
+    let offshore_pump =
+        OffshorePump::prototype_from_lua(offshore_pump, &lua, &mut data_table).unwrap();
+    data_table.extend(offshore_pump).unwrap();
+    assert!(data_table.can_place_on("stone-furnace", "grass-1"));
+    assert!(!data_table.can_place_on("stone-furnace", "water"));
+    assert!(data_table.can_place_on("offshore-pump", "water"));
+    assert!(!data_table.can_place_on("assembling-machine-1", "grass-1"));
+    assert!(!data_table.can_place_on("stone-furnace", "sand-1"));
+}
+
 /// <https://wiki.factorio.com/Prototype/TipsAndTricksItem>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(tips_and_tricks_item)]
 pub struct TipsAndTricksItem {
     name: String,
@@ -5331,30 +9479,60 @@ pub struct TipsAndTricksItem {
 }
 
 /// <https://wiki.factorio.com/Prototype/TrivialSmoke>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, PrototypeFromLua, ResourceWalk, HeapSize)]
 #[data_table(trivial_smoke)]
+#[post_extr_fn(Self::post_extr_fn)]
 pub struct TrivialSmoke {
-    name: String,
-    prototype_base: PrototypeBaseSpec,
-    animation: Animation,
-    duration: u32, // Can't be 0
-    glow_animation: Option<Animation>,
-    color: Color,                   // Default: all 0.375
-    start_scale: f64,               // Default: 1
-    movement_slow_down_factor: f64, // Default: 0.995 // Between 1 and 0 (inclusive both sides)
-    spread_duration: u32,           // Default: 0
+    pub name: String,
+    #[use_self_forced]
+    pub prototype_base: PrototypeBaseSpec,
+    pub animation: Animation,
+    pub duration: u32, // Can't be 0
+    pub glow_animation: Option<Animation>,
+    #[default(Color::SMOKE)]
+    pub color: Color, // Default: all 0.375
+    #[default(1_f64)]
+    pub start_scale: f64, // Default: 1
+    #[default(0.995_f64)]
+    pub movement_slow_down_factor: f64, // Default: 0.995 // Between 1 and 0 (inclusive both sides)
+    #[default(0_u32)]
+    pub spread_duration: u32, // Default: 0
     // `fade_in_duration` + `fade_away_duration` must be <= `duration`
-    fade_away_duration: u32,      // Default: 0
-    fade_in_duration: u32,        // Default: 0
-    glow_fade_away_duration: u32, // Default: `fade_away_duration`
-    cyclic: bool,                 // Default: false
-    affected_by_wind: bool,       // Default: true
-    show_when_smoke_off: bool,    // Default: false
-    render_layer: RenderLayer,    // Default: "smoke"
+    #[default(0_u32)]
+    pub fade_away_duration: u32, // Default: 0
+    #[default(0_u32)]
+    pub fade_in_duration: u32, // Default: 0
+    #[default(fade_away_duration)]
+    pub glow_fade_away_duration: u32, // Default: `fade_away_duration`
+    #[default(false)]
+    pub cyclic: bool, // Default: false
+    #[default(true)]
+    pub affected_by_wind: bool, // Default: true
+    #[default(false)]
+    pub show_when_smoke_off: bool, // Default: false
+    #[default(RenderLayer::Smoke)]
+    pub render_layer: RenderLayer, // Default: "smoke"
+}
+
+impl TrivialSmoke {
+    fn post_extr_fn(&self, _lua: &Lua, _data_table: &DataTable) -> LuaResult<()> {
+        prot_from_lua_err(self.duration == 0, "TrivialSmoke", "`duration` can't be 0")?;
+        prot_from_lua_err(
+            !(0.0..=1.0).contains(&self.movement_slow_down_factor),
+            "TrivialSmoke",
+            "`movement_slow_down_factor` must be between 0 and 1",
+        )?;
+        prot_from_lua_err(
+            self.fade_in_duration.saturating_add(self.fade_away_duration) > self.duration,
+            "TrivialSmoke",
+            "`fade_in_duration` + `fade_away_duration` must be <= `duration`",
+        )?;
+        Ok(())
+    }
 }
 
 /// <https://wiki.factorio.com/Prototype/Tutorial>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(tutorial)]
 pub struct Tutorial {
     name: String,
@@ -5364,7 +9542,7 @@ pub struct Tutorial {
 }
 
 /// <https://wiki.factorio.com/Prototype/VirtualSignal>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, ResourceWalk, HeapSize)]
 #[data_table(virtual_signal)]
 pub struct VirtualSignal {
     name: String,
@@ -5385,8 +9563,192 @@ pub enum PrototypesErr {
     PrototypeNotFound(String),
     #[error("Field {0} is required")]
     FieldRequired(String),
+    #[error("Order string {0:?} contains non-printable characters")]
+    InvalidOrderString(String),
+    #[error("Recipes form a cycle: {}", .0.join(", "))]
+    RecipeCycle(Vec<String>),
+    #[error("Entity \"{name}\" can't have \"{next_upgrade}\" as `next_upgrade`: {reason}")]
+    InvalidNextUpgrade {
+        ty: PrototypeType,
+        name: String,
+        next_upgrade: String,
+        reason: &'static str,
+    },
+    #[error("Entities form an upgrade cycle: {}", .0.join(", "))]
+    UpgradeCycle(Vec<String>),
+    #[error("Tips and tricks items form a dependency cycle: {}", .0.join(", "))]
+    TipDependencyCycle(Vec<String>),
+    #[error("`{field}` of data.raw[\"{}\"][\"{name}\"] {message}", .ty.as_ref())]
+    InvalidFieldValue {
+        ty: PrototypeType,
+        name: String,
+        field: &'static str,
+        message: &'static str,
+    },
+    #[error("Prototype data.raw[\"{}\"][\"{name}\"] can't be defined, the only allowed name is \"{expected}\"", .ty.as_ref())]
+    InvalidSingletonName {
+        ty: PrototypeType,
+        name: String,
+        expected: &'static str,
+    },
+    #[error("Prototype data.raw[\"{category}\"][\"{name}\"] has unknown type \"{ty}\"")]
+    UnknownType {
+        category: String,
+        name: String,
+        ty: String,
+    },
+    #[error("Prototype data.raw[\"{category}\"][\"{name}\"] has no `type`")]
+    MissingType { category: String, name: String },
+    #[error("Prototype data.raw[\"{}\"][\"{name}\"] has the same name as data.raw[\"{}\"][\"{name}\"]", .ty.as_ref(), .existing.as_ref())]
+    DuplicateName {
+        ty: PrototypeType,
+        name: String,
+        existing: PrototypeType,
+    },
+    #[error("Stub of {} \"{name}\" can't be loaded: {error}", .ty.as_ref())]
+    InvalidStub {
+        ty: PrototypeType,
+        name: String,
+        error: mlua::Error,
+    },
+    #[error("`{field}` of data.raw[\"{}\"][\"{name}\"] references \"{target}\", which doesn't exist", .ty.as_ref())]
+    MissingReference {
+        ty: PrototypeType,
+        name: String,
+        field: &'static str,
+        target: String,
+    },
     #[error("`DataTableAccessable::find()` is not available on Abstract types, use `DataTableAccessable::find_cloned()`")]
     AbstractFind,
     #[error("`DataTableAccessable::extend()` is not available for Abstract types")]
     AbstractExtend,
 }
+
+impl PrototypesErr {
+    /// Type and name of the prototype this error belongs to, if it belongs to a single one
+    pub fn owner(&self) -> Option<(PrototypeType, &str)> {
+        match self {
+            Self::InvalidNextUpgrade { ty, name, .. }
+            | Self::InvalidFieldValue { ty, name, .. }
+            | Self::InvalidSingletonName { ty, name, .. }
+            | Self::DuplicateName { ty, name, .. }
+            | Self::MissingReference { ty, name, .. } => Some((*ty, name.as_str())),
+            _ => None,
+        }
+    }
+
+    /// [InvalidFieldValue](Self::InvalidFieldValue) of `field` of `owner`
+    pub fn invalid_field(
+        owner: &dyn Prototype,
+        field: &'static str,
+        message: &'static str,
+    ) -> Self {
+        Self::InvalidFieldValue {
+            ty: owner.prototype_type(),
+            name: owner.name().clone(),
+            field,
+            message,
+        }
+    }
+
+    /// [MissingReference](Self::MissingReference) from `field` of `owner` to `target`
+    pub fn missing_reference(owner: &dyn Prototype, field: &'static str, target: &str) -> Self {
+        Self::MissingReference {
+            ty: owner.prototype_type(),
+            name: owner.name().clone(),
+            field,
+            target: target.into(),
+        }
+    }
+}
+
+/// `Ok` if no errors were collected by a validation pass
+pub(crate) fn collect_errors<E>(errors: Vec<E>) -> Result<(), Vec<E>> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Passes run by [DataTable::validate_all]
+pub(crate) fn validation_passes() -> Vec<fn(&DataTable) -> Vec<PrototypesErr>> {
+    vec![
+        |dt| dt.validate_references().err().unwrap_or_default(),
+        |dt| {
+            dt.validate_trivial_smoke_references()
+                .err()
+                .unwrap_or_default()
+        },
+        |dt| dt.validate_fluid_box_filters().err().unwrap_or_default(),
+        |dt| dt.validate_fluid_temperatures().err().unwrap_or_default(),
+        |dt| dt.validate_resource_categories().err().unwrap_or_default(),
+        |dt| dt.validate_spawner_units().err().unwrap_or_default(),
+        |dt| dt.validate_autoplace_controls().err().unwrap_or_default(),
+        |dt| dt.validate_mining_results().err().unwrap_or_default(),
+        |dt| dt.validate_subgroups_and_groups().err().unwrap_or_default(),
+        |dt| dt.validate_rocket_silos().err().unwrap_or_default(),
+        |dt| dt.validate_module_limitations().err().unwrap_or_default(),
+        |dt| dt.validate_singletons().err().unwrap_or_default(),
+        |dt| {
+            dt.validate_next_upgrade_constraints()
+                .err()
+                .unwrap_or_default()
+        },
+        |dt| {
+            dt.validate_items_have_icons_or_entities()
+                .err()
+                .unwrap_or_default()
+        },
+        |dt| dt.validate_fuel_categories().err().unwrap_or_default(),
+        |dt| dt.validate_default_signals().err().unwrap_or_default(),
+        |dt| dt.validate_tile_references().err().unwrap_or_default(),
+        |dt| dt.validate_technology_effects().err().unwrap_or_default(),
+        |dt| dt.validate_science_packs().err().unwrap_or_default(),
+        |dt| dt.validate_cliff_explosives().err().unwrap_or_default(),
+        |dt| dt.validate_render_layers().err().unwrap_or_default(),
+        |dt| {
+            dt.validate_map_color_requirements()
+                .err()
+                .unwrap_or_default()
+        },
+        |dt| dt.validate_fluid_turrets().err().unwrap_or_default(),
+        |dt| dt.validate_icon_layers().err().unwrap_or_default(),
+        |dt| {
+            dt.validate_custom_input_controls()
+                .err()
+                .unwrap_or_default()
+        },
+        |dt| {
+            dt.validate_achievement_references()
+                .err()
+                .unwrap_or_default()
+        },
+        |dt| dt.validate_tips_and_tricks().err().unwrap_or_default(),
+        |dt| dt.validate_spidertron().err().unwrap_or_default(),
+        |dt| {
+            dt.validate_radius_visualizations()
+                .err()
+                .unwrap_or_default()
+        },
+        |dt| {
+            dt.validate_circuit_connector_sprites()
+                .err()
+                .unwrap_or_default()
+        },
+        |dt| dt.validate_character_references().err().unwrap_or_default(),
+    ]
+}
+
+/// Errors of [validation_passes], in pass order, merged and sorted as described in
+/// [DataTable::validate_all]
+pub(crate) fn merge_validation_errors(
+    results: Vec<Vec<PrototypesErr>>,
+) -> Result<(), Vec<PrototypesErr>> {
+    let mut errors: Vec<PrototypesErr> = results.into_iter().flatten().collect();
+    errors.sort_by(|a, b| {
+        let (a, b) = (a.owner(), b.owner());
+        a.is_none().cmp(&b.is_none()).then(a.cmp(&b))
+    });
+    collect_errors(errors)
+}
@@ -0,0 +1,88 @@
+//! Lua types and helpers that hide the parts of the mlua API that changed after factorio-mlua
+//! 0.8, where Lua values borrow the `Lua` state for `'lua`. In mlua 0.10 and later they don't,
+//! `Table::get` has no key type parameter and `ToLua` is `IntoLua`.
+//!
+//! [PrototypeFromLua](crate::PrototypeFromLua) impls and derives written against this module
+//! keep their signatures when the crate moves to a newer mlua, only this module changes. Both
+//! can't be supported side by side: factorio-mlua and mlua both link Lua, so cargo doesn't allow
+//! them in one dependency graph.
+
+pub use mlua::prelude::{Lua, LuaError, LuaResult};
+
+pub type LuaValue<'lua> = mlua::Value<'lua>;
+pub type LuaTable<'lua> = mlua::Table<'lua>;
+pub type LuaFunction<'lua> = mlua::Function<'lua>;
+
+/// `FromLua<'lua>` with mlua 0.8, `FromLua` with mlua 0.10
+pub trait FromLuaCompat<'lua>: mlua::FromLua<'lua> {}
+impl<'lua, T: mlua::FromLua<'lua>> FromLuaCompat<'lua> for T {}
+
+/// `ToLua<'lua>` with mlua 0.8, `IntoLua` with mlua 0.10
+pub trait IntoLuaCompat<'lua>: mlua::ToLua<'lua> {}
+impl<'lua, T: mlua::ToLua<'lua>> IntoLuaCompat<'lua> for T {}
+
+/// `Table::get`, which takes the key type as a generic parameter only with mlua 0.8, so calls
+/// to it with turbofish don't build with later versions
+pub trait TableGetCompat<'lua> {
+    fn get_as<V: FromLuaCompat<'lua>>(&self, key: impl IntoLuaCompat<'lua>) -> LuaResult<V>;
+}
+
+impl<'lua> TableGetCompat<'lua> for LuaTable<'lua> {
+    #[inline]
+    fn get_as<V: FromLuaCompat<'lua>>(&self, key: impl IntoLuaCompat<'lua>) -> LuaResult<V> {
+        self.get(key)
+    }
+}
+
+/// `FromLuaConversionError`, whose `to` is a `&'static str` with mlua 0.8 and a `String`
+/// with mlua 0.10
+#[allow(clippy::useless_conversion)]
+pub fn conversion_error(from: &'static str, to: &'static str, message: Option<String>) -> LuaError {
+    LuaError::FromLuaConversionError {
+        from,
+        to: to.into(),
+        message,
+    }
+}
+
+/// `FromLua` impl for `$ty`. `$body` converts `$value`, a [LuaValue], with `$lua`.
+macro_rules! impl_from_lua {
+    ($ty:ty, |$value:ident, $lua:ident| $body:block) => {
+        impl<'lua> mlua::FromLua<'lua> for $ty {
+            fn from_lua($value: mlua::Value<'lua>, $lua: &'lua mlua::Lua) -> mlua::Result<Self> $body
+        }
+    };
+}
+pub(crate) use impl_from_lua;
+
+/// `ToLua` impl for `$ty`, `IntoLua` with mlua 0.10. `$body` converts `$self` with `$lua` to a
+/// [LuaValue].
+macro_rules! impl_into_lua {
+    ($ty:ty, |$self:ident, $lua:ident| $body:block) => {
+        impl<'lua> mlua::ToLua<'lua> for $ty {
+            fn to_lua($self, $lua: &'lua mlua::Lua) -> mlua::Result<mlua::Value<'lua>> $body
+        }
+    };
+}
+pub(crate) use impl_into_lua;
+
+#[test]
+fn table_get_compat() {
+    let lua = Lua::new();
+    let table: LuaTable = lua.load(r#"return {1, name = "chest"}"#).eval().unwrap();
+    assert_eq!(table.get_as::<String>("name").unwrap(), "chest");
+    assert_eq!(table.get_as::<Option<u8>>(1).unwrap(), Some(1));
+    let missing: Option<LuaValue> = table.get_as("missing").unwrap();
+    assert!(missing.is_none());
+    let err = table.get_as::<u8>("name").unwrap_err();
+    assert!(matches!(err, LuaError::FromLuaConversionError { .. }));
+}
+
+#[test]
+fn conversion_error_message() {
+    let err = conversion_error("string", "f64", Some("expected number".into()));
+    assert_eq!(
+        err.to_string(),
+        "error converting Lua string to f64 (expected number)"
+    );
+}
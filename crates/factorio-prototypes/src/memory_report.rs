@@ -0,0 +1,160 @@
+use super::{data_table_categories, DataTable, PrototypeCategory, ResourceRecord};
+use std::collections::BTreeMap;
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// Coarse estimate of memory used by a [DataTable], see [DataTable::memory_report].
+///
+/// Sizes of prototype categories include the hash table storage, names of prototypes and heap
+/// allocations made by prototypes, as estimated by [HeapSize]. Fields of types that don't
+/// implement it count as 0, so real usage is higher.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryReport {
+    /// Prototype categories, by name of [DataTable] field
+    pub categories: BTreeMap<&'static str, CategoryMemory>,
+    /// Bytes used by registered resource records
+    pub resource_records: usize,
+}
+
+/// Memory estimate of a single prototype category
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CategoryMemory {
+    /// Amount of prototypes
+    pub count: usize,
+    /// Size of single prototype struct
+    pub prototype_size: usize,
+    /// Estimated bytes used by the category
+    pub bytes: usize,
+}
+
+/// Estimate of heap memory owned by a value. Prototypes derive it, see [MemoryReport].
+pub trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+/// Field sizes of derived [HeapSize], picked by autoref specialization like in
+/// [ResourceWalk](crate::ResourceWalk) derive
+pub(crate) mod heap_size {
+    use super::HeapSize;
+
+    pub struct Field<'a, T>(pub &'a T);
+
+    pub trait Measure {
+        fn field_heap_size(&self) -> usize;
+    }
+
+    impl<T: HeapSize> Measure for Field<'_, T> {
+        fn field_heap_size(&self) -> usize {
+            self.0.heap_size()
+        }
+    }
+
+    // Only picked when `Measure` is not implemented, since it needs one more autoref
+    pub trait Skip {
+        fn field_heap_size(&self) -> usize {
+            0
+        }
+    }
+
+    impl<T> Skip for &Field<'_, T> {}
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        size_of::<T>() + T::heap_size(self)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl HeapSize for ResourceRecord {
+    fn heap_size(&self) -> usize {
+        self.path.heap_size()
+    }
+}
+
+impl<T: HeapSize> HeapSize for PrototypeCategory<T> {
+    fn heap_size(&self) -> usize {
+        // Each prototype is in its own `Arc` allocation, after the strong and weak counts
+        self.capacity() * (size_of::<String>() + size_of::<Arc<T>>())
+            + self.keys().map(String::heap_size).sum::<usize>()
+            + self.len() * (2 * size_of::<usize>() + size_of::<T>())
+            + self.values().map(|p| T::heap_size(p)).sum::<usize>()
+    }
+}
+
+impl CategoryMemory {
+    fn new<T: HeapSize>(category: &PrototypeCategory<T>) -> Self {
+        Self {
+            count: category.len(),
+            prototype_size: size_of::<T>(),
+            bytes: category.heap_size(),
+        }
+    }
+}
+
+impl MemoryReport {
+    pub(crate) fn new(data_table: &DataTable) -> Self {
+        let mut categories = BTreeMap::new();
+        macro_rules! add_categories {
+            ($($group:ident { $($category:ident: $prototype:ty,)* })*) => {
+                $($( categories.insert(stringify!($category), CategoryMemory::new(&data_table.$category)); )*)*
+            };
+        }
+        data_table_categories!(add_categories);
+        Self {
+            categories,
+            resource_records: data_table.resource_records.heap_size(),
+        }
+    }
+
+    /// Estimated total bytes, including size of [DataTable] itself
+    pub fn total(&self) -> usize {
+        size_of::<DataTable>()
+            + self.resource_records
+            + self.categories.values().map(|c| c.bytes).sum::<usize>()
+    }
+
+    /// Categories sorted by estimated size, largest first
+    pub fn largest(&self) -> Vec<(&'static str, CategoryMemory)> {
+        let mut result: Vec<(&'static str, CategoryMemory)> =
+            self.categories.iter().map(|(k, v)| (*k, *v)).collect();
+        result.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes));
+        result
+    }
+}
+
+#[test]
+fn memory_report_counts_prototypes() {
+    let mut data_table = DataTable::default();
+    let empty = data_table.memory_report().categories["item_group"];
+    data_table
+        .add_stubs("item-group intermediate-products")
+        .unwrap();
+    let report = data_table.memory_report();
+    let item_group = report.categories["item_group"];
+    assert_eq!(item_group.count, 1);
+    assert_eq!(item_group.prototype_size, size_of::<super::ItemGroup>());
+    // Stored prototype, name key and heap allocations of the prototype itself
+    assert!(
+        item_group.bytes
+            > empty.bytes + size_of::<super::ItemGroup>() + "intermediate-products".len() * 2
+    );
+    assert_eq!(report.largest()[0].0, "item_group");
+}
@@ -2,7 +2,20 @@ use serde::{Deserialize, Serialize};
 use strum_macros::{AsRefStr, EnumString};
 
 /// Enum for all Prototype types available in the game
-#[derive(Debug, Hash, Eq, PartialEq, Clone, Copy, EnumString, AsRefStr, Deserialize, Serialize)]
+#[derive(
+    Debug,
+    Hash,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Clone,
+    Copy,
+    EnumString,
+    AsRefStr,
+    Deserialize,
+    Serialize,
+)]
 #[strum(serialize_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum PrototypeType {
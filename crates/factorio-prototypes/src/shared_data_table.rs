@@ -0,0 +1,130 @@
+use super::{
+    DataTable, DataTableAccessable, PrototypeReference, PrototypeReferenceValidate, PrototypesErr,
+};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Frozen, read-only [DataTable] that can be cheaply cloned and shared between threads.
+///
+/// [DataTable] stays the loading type: prototypes are added and references are tracked through
+/// `&mut DataTable`. References are tracked with `Arc`, so [DataTable] is `Send` and `Sync` as
+/// well and converting doesn't rebuild anything. Once loading and validation are done, convert
+/// it with [DataTable::into_shared] and hand out clones of this to readers. All read-only
+/// [DataTable] methods are available through [Deref].
+#[derive(Debug, Clone)]
+pub struct SharedDataTable(Arc<DataTable>);
+
+impl SharedDataTable {
+    pub fn new(data_table: DataTable) -> Self {
+        Self(Arc::new(data_table))
+    }
+}
+
+impl Deref for SharedDataTable {
+    type Target = DataTable;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<DataTable> for SharedDataTable {
+    fn from(data_table: DataTable) -> Self {
+        data_table.into_shared()
+    }
+}
+
+/// [SharedDataTable] in which all tracked references were validated and linked to their
+/// prototypes, see [DataTable::freeze].
+///
+/// Linked references resolve with [get](Self::get) without a lookup. References created outside
+/// of the table are not known to it, they are linked on first use.
+#[derive(Debug, Clone)]
+pub struct FrozenDataTable(SharedDataTable);
+
+impl FrozenDataTable {
+    pub(crate) fn new(data_table: DataTable) -> Self {
+        Self(data_table.into_shared())
+    }
+
+    /// Prototype the reference points to. References tracked by the [DataTable], meaning created
+    /// with [DataTable::new_reference] or parsed from Lua, were linked when freezing and always
+    /// resolve. Other references are linked to this table by the first call, which errors if the
+    /// reference is invalid. References that are already linked are left as they are.
+    pub fn get<'a, T: DataTableAccessable + 'static>(
+        &self,
+        reference: &'a PrototypeReference<T>,
+    ) -> Result<&'a T, PrototypesErr> {
+        if let Some(prototype) = reference.linked() {
+            return Ok(prototype);
+        }
+        reference.link(&self.0)?;
+        reference
+            .linked()
+            .ok_or_else(|| PrototypesErr::PrototypeNotFound(reference.name.clone()))
+    }
+
+    /// Shareable table, without the guarantee of validated references
+    pub fn shared(&self) -> &SharedDataTable {
+        &self.0
+    }
+}
+
+impl Deref for FrozenDataTable {
+    type Target = SharedDataTable;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[test]
+fn shared_data_table_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<DataTable>();
+    assert_send_sync::<SharedDataTable>();
+    assert_send_sync::<FrozenDataTable>()
+}
+
+#[test]
+fn frozen_data_table_get() {
+    use super::{AbstractEntity, ItemGroup, PrototypeType};
+    let mut data_table = DataTable::default();
+    data_table
+        .add_stubs("item-group intermediate-products\nfurnace stone-furnace")
+        .unwrap();
+    let tracked = data_table.new_reference::<ItemGroup>("intermediate-products".into());
+    let tracked_again = data_table.new_reference::<ItemGroup>("intermediate-products".into());
+    let entity = data_table.new_reference::<AbstractEntity>("stone-furnace".into());
+    let frozen = data_table.freeze().unwrap();
+    let group = frozen.get(&tracked).unwrap();
+    assert_eq!(group.name, "intermediate-products");
+    // Links share the prototype stored in the table
+    assert!(std::ptr::eq(group, frozen.get(&tracked_again).unwrap()));
+    assert!(std::ptr::eq(
+        group,
+        frozen.find::<ItemGroup>("intermediate-products").unwrap()
+    ));
+    assert_eq!(
+        frozen.get(&entity).unwrap().prototype_type,
+        PrototypeType::Furnace
+    );
+    let untracked = PrototypeReference::<ItemGroup>::new("intermediate-products".into());
+    assert!(untracked.linked().is_none());
+    assert!(std::ptr::eq(frozen.get(&untracked).unwrap(), group));
+    assert!(untracked.linked().is_some());
+    let missing = PrototypeReference::<ItemGroup>::new("missing".into());
+    assert!(matches!(
+        frozen.get(&missing),
+        Err(PrototypesErr::PrototypeNotFound(name)) if name == "missing"
+    ));
+    assert!(missing.linked().is_none());
+}
+
+#[test]
+fn freeze_reports_invalid_references() {
+    use super::ItemGroup;
+    let mut data_table = DataTable::default();
+    let _reference = data_table.new_reference::<ItemGroup>("missing".into());
+    assert!(data_table.freeze().is_err());
+}
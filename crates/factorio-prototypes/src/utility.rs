@@ -1,6 +1,6 @@
 use std::{collections::HashMap, fmt::Display};
 
-use factorio_prototypes_macros::DataTableAccessable;
+use factorio_prototypes_macros::{DataTableAccessable, HeapSize, ResourceWalk};
 use serde::Deserialize;
 use thiserror::Error;
 
@@ -13,7 +13,7 @@ use super::{
 };
 
 /// <https://wiki.factorio.com/Prototype/UtilityConstants>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, Deserialize)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, Deserialize, ResourceWalk, HeapSize)]
 #[data_table(utility_constants)]
 #[serde(try_from = "UtilityConstantsIntermediate")]
 pub struct UtilityConstants {
@@ -724,7 +724,7 @@ impl TryFrom<UtilityConstantsEntityRendererSearchBoxLimitsIntermediate>
 }
 
 /// <https://wiki.factorio.com/Prototype/UtilitySounds>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, Deserialize)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, Deserialize, ResourceWalk, HeapSize)]
 #[data_table(utility_sounds)]
 pub struct UtilitySounds {
     pub name: String,
@@ -794,7 +794,7 @@ pub struct UtilitySounds {
 }
 
 /// <https://wiki.factorio.com/Prototype/UtilitySprites>
-#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, Deserialize)]
+#[derive(Debug, Clone, Prototype, PrototypeBase!, DataTableAccessable, Deserialize, ResourceWalk, HeapSize)]
 #[data_table(utility_sprites)]
 pub struct UtilitySprites {
     // Only one instance allowed
@@ -1175,7 +1175,7 @@ pub struct UtilitySprites {
     pub character_logistic_requests_modifier_constant: Option<Sprite>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ResourceWalk)]
 pub struct UtilitySpritesCursorBox {
     pub regular: Vec<BoxSpecification>,
     pub not_allowed: Vec<BoxSpecification>,